@@ -12,7 +12,11 @@ use clap::Parser;
 use client::{Client, ClientBuilder};
 use common::utils::hex_str_to_bytes;
 use config::{CliConfig, Config};
-use consensus::database::FileDB;
+#[cfg(feature = "postgres")]
+use consensus::database::postgres::PostgresDB;
+#[cfg(feature = "sled")]
+use consensus::database::sled::SledDB;
+use consensus::database::{Database, FileDB};
 use dirs::home_dir;
 use eyre::Result;
 use futures::executor::block_on;
@@ -36,7 +40,43 @@ async fn main() -> Result<()> {
     tracing::subscriber::set_global_default(subscriber).expect("subscriber set failed");
 
     let config = get_config();
-    let mut client = match ClientBuilder::new().config(config).build::<FileDB>() {
+    if let Err(problems) = config.validate() {
+        for problem in problems {
+            error!(target: "helios::runner", "invalid config: {problem}");
+        }
+        exit(1);
+    }
+
+    // A database url without a database type is the one of a PostgreSQL database.
+    let database_type = match config.database_type.as_deref() {
+        None if config.database_url.is_some() => "postgres",
+        database_type => database_type.unwrap_or("file"),
+    };
+    match database_type {
+        "file" => run::<FileDB>(config).await,
+        #[cfg(feature = "sled")]
+        "sled" => run::<SledDB>(config).await,
+        #[cfg(feature = "postgres")]
+        "postgres" => run::<PostgresDB>(config).await,
+        #[cfg(not(feature = "sled"))]
+        "sled" => {
+            error!(target: "helios::runner", "the sled database requires the sled feature");
+            exit(1);
+        }
+        #[cfg(not(feature = "postgres"))]
+        "postgres" => {
+            error!(target: "helios::runner", "the postgres database requires the postgres feature");
+            exit(1);
+        }
+        _ => {
+            error!(target: "helios::runner", "unknown database type {database_type}");
+            exit(1);
+        }
+    }
+}
+
+async fn run<DB: Database>(config: Config) -> Result<()> {
+    let mut client = match ClientBuilder::new().config(config).build::<DB>() {
         Ok(client) => client,
         Err(err) => {
             error!(target: "helios::runner", error = %err);
@@ -49,12 +89,49 @@ async fn main() -> Result<()> {
         exit(1);
     }
 
+    let client = Arc::new(client);
+    #[cfg(unix)]
+    register_reload_handler(client.clone());
     register_shutdown_handler(client);
     std::future::pending().await
 }
 
-fn register_shutdown_handler(client: Client<FileDB>) {
-    let client = Arc::new(client);
+/// Reloads the consensus RPC and checkpoint fallback settings from the config file, the
+/// environment and the command line arguments on SIGHUP, without restarting. An invalid config
+/// is reported and the current one is kept.
+#[cfg(unix)]
+fn register_reload_handler<DB: Database>(client: Arc<Client<DB>>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup()).expect("could not register reload handler");
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!(target: "helios::runner", "reloading config");
+
+            let cli = Cli::parse();
+            let config =
+                match Config::try_from_file(&config_path(), &cli.network, &cli.as_cli_config()) {
+                    Ok(config) => config,
+                    Err(err) => {
+                        error!(target: "helios::runner", error = %err, "cannot reload config");
+                        continue;
+                    }
+                };
+            if let Err(problems) = config.validate() {
+                for problem in problems {
+                    error!(target: "helios::runner", "invalid config: {problem}");
+                }
+                continue;
+            }
+
+            if let Err(err) = client.reload(&config).await {
+                error!(target: "helios::runner", error = %err, "cannot reload config");
+            }
+        }
+    });
+}
+
+fn register_shutdown_handler<DB: Database>(client: Arc<Client<DB>>) {
     let shutdown_counter = Arc::new(Mutex::new(0));
 
     ctrlc::set_handler(move || {
@@ -88,13 +165,17 @@ fn register_shutdown_handler(client: Client<FileDB>) {
 fn get_config() -> Config {
     let cli = Cli::parse();
 
-    let config_path = home_dir().unwrap().join(".helios/helios.toml");
+    let config_path = config_path();
 
     let cli_config = cli.as_cli_config();
 
     Config::from_file(&config_path, &cli.network, &cli_config)
 }
 
+fn config_path() -> PathBuf {
+    home_dir().unwrap().join(".helios/helios.toml")
+}
+
 #[derive(Parser)]
 #[clap(version, about)]
 /// Helios is a fast, secure, and portable light client for Ethereum
@@ -119,6 +200,40 @@ struct Cli {
     load_external_fallback: bool,
     #[clap(short = 's', long, env)]
     strict_checkpoint_age: bool,
+    #[clap(long, env)]
+    helios_rpc: bool,
+    #[clap(long, env)]
+    grpc_port: Option<u16>,
+    #[clap(long, env)]
+    light_client_server_port: Option<u16>,
+    #[clap(long, env, value_delimiter = ',')]
+    broadcast_rpcs: Option<Vec<String>>,
+    #[clap(long, env)]
+    sync_from_genesis: bool,
+    #[clap(long, env)]
+    update_archive: Option<PathBuf>,
+    #[clap(long, env, value_delimiter = ',')]
+    checkpoint_services: Option<Vec<String>>,
+    #[clap(long, env)]
+    checkpoint_quorum: Option<usize>,
+    #[clap(long, env)]
+    stale_checkpoint_age: Option<u64>,
+    #[clap(long, env)]
+    encrypt_database: bool,
+    #[clap(long, env)]
+    database_type: Option<String>,
+    #[clap(long, env)]
+    database_url: Option<String>,
+    #[clap(long, env)]
+    min_optimistic_participation: Option<f64>,
+    #[clap(long, env)]
+    strict_finality: bool,
+    #[clap(long, env)]
+    verify_transactions_root: bool,
+    #[clap(long, env, value_delimiter = ',')]
+    quorum_rpcs: Option<Vec<String>>,
+    #[clap(long, env)]
+    rpc_quorum: Option<usize>,
 }
 
 impl Cli {
@@ -141,6 +256,23 @@ impl Cli {
             fallback: self.fallback.clone(),
             load_external_fallback: true_or_none(self.load_external_fallback),
             strict_checkpoint_age: true_or_none(self.strict_checkpoint_age),
+            helios_rpc: true_or_none(self.helios_rpc),
+            grpc_port: self.grpc_port,
+            light_client_server_port: self.light_client_server_port,
+            broadcast_rpcs: self.broadcast_rpcs.clone(),
+            sync_from_genesis: true_or_none(self.sync_from_genesis),
+            update_archive: self.update_archive.clone(),
+            checkpoint_services: self.checkpoint_services.clone(),
+            checkpoint_quorum: self.checkpoint_quorum,
+            stale_checkpoint_age: self.stale_checkpoint_age,
+            encrypt_database: true_or_none(self.encrypt_database),
+            database_type: self.database_type.clone(),
+            database_url: self.database_url.clone(),
+            min_optimistic_participation: self.min_optimistic_participation,
+            strict_finality: true_or_none(self.strict_finality),
+            verify_transactions_root: true_or_none(self.verify_transactions_root),
+            quorum_rpcs: self.quorum_rpcs.clone(),
+            rpc_quorum: self.rpc_quorum,
         }
     }
 }
@@ -0,0 +1,277 @@
+//! `helios-proof` syncs the light client to finality and produces account and storage proofs
+//! that were verified against the finalized state root, so that dWallet integrations can be
+//! driven from scripts.
+
+use std::{fs, path::PathBuf, process::exit, str::FromStr};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use client::ClientBuilder;
+use common::{types::BlockTag, utils::hex_str_to_bytes};
+use config::{CliConfig, Config};
+use consensus::{
+    database::FileDB,
+    types::encoding::{decode_versioned, encode_versioned},
+};
+use dirs::home_dir;
+use ethers::types::{Address, H256};
+use execution::{compact::CompactProof, types::ProofBundle};
+use eyre::Result;
+use tracing::error;
+use tracing_subscriber::{
+    filter::{EnvFilter, LevelFilter},
+    FmtSubscriber,
+};
+
+#[tokio::main]
+async fn main() {
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .from_env()
+        .expect("invalid env filter");
+
+    let subscriber = FmtSubscriber::builder()
+        .with_env_filter(env_filter)
+        .with_writer(std::io::stderr)
+        .finish();
+
+    tracing::subscriber::set_global_default(subscriber).expect("subscriber set failed");
+
+    let cli = Cli::parse();
+    if let Err(err) = run(cli).await {
+        error!(target: "helios::proof", error = %err);
+        exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    match &cli.command {
+        Command::Sync => {
+            let client = start_client(&cli).await?;
+            let block = client
+                .get_block_by_number(BlockTag::Finalized, false)
+                .await?
+                .ok_or(eyre::eyre!("no finalized block"))?;
+
+            let output = serde_json::json!({
+                "blockNumber": block.number.as_u64(),
+                "blockHash": block.hash,
+                "stateRoot": block.state_root,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        Command::Prove {
+            address,
+            slots,
+            format,
+            output,
+        } => {
+            let client = start_client(&cli).await?;
+            let bundle = client
+                .get_proof_bundle(address, slots, BlockTag::Finalized)
+                .await?;
+
+            let bytes = match format {
+                Format::Json => serde_json::to_vec_pretty(&bundle)?,
+                Format::Bcs => encode_versioned(&bundle)?,
+                Format::Compact => CompactProof::from(&bundle).encode(),
+            };
+
+            match output {
+                Some(path) => fs::write(path, bytes)?,
+                None if *format == Format::Json => println!("{}", String::from_utf8(bytes)?),
+                None => println!("{}", hex::encode(bytes)),
+            }
+        }
+        Command::Verify {
+            input,
+            format,
+            state_root,
+        } => {
+            let bytes = fs::read(input)?;
+            let (block_number, address) = verify_proof(&bytes, *format, *state_root)?;
+            println!("valid proof for {address:?} at block {block_number}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies a proof encoded with `format`, and that it is for `state_root` if given. Returns the
+/// block number of the proof and the first proven address.
+fn verify_proof(bytes: &[u8], format: Format, state_root: Option<H256>) -> Result<(u64, Address)> {
+    let (block_number, proof_state_root, address) = match format {
+        Format::Json | Format::Bcs => {
+            let bundle: ProofBundle = match format {
+                Format::Json => serde_json::from_slice(bytes)?,
+                _ => decode_versioned(bytes)?,
+            };
+            bundle.verify()?;
+            (bundle.block_number, bundle.state_root, bundle.address)
+        }
+        Format::Compact => {
+            let proof = CompactProof::decode(bytes)?;
+            proof.verify()?;
+            let address = proof
+                .accounts
+                .first()
+                .map(|account| account.address)
+                .ok_or(eyre::eyre!("empty proof"))?;
+            (proof.block_number, proof.state_root, address)
+        }
+    };
+
+    if let Some(state_root) = state_root {
+        if state_root != proof_state_root {
+            eyre::bail!(
+                "the proof is for state root {:?}, expected {:?}",
+                proof_state_root,
+                state_root
+            );
+        }
+    }
+
+    Ok((block_number, address))
+}
+
+async fn start_client(cli: &Cli) -> Result<client::Client<FileDB>> {
+    let mut client = ClientBuilder::new().config(get_config(cli)).build()?;
+    client.start().await?;
+    client.wait_synced().await;
+
+    Ok(client)
+}
+
+fn get_config(cli: &Cli) -> Config {
+    let config_path = home_dir().unwrap().join(".helios/helios.toml");
+
+    let cli_config = CliConfig {
+        checkpoint: cli
+            .checkpoint
+            .as_ref()
+            .map(|c| hex_str_to_bytes(c).expect("invalid checkpoint")),
+        execution_rpc: cli.execution_rpc.clone(),
+        consensus_rpc: cli.consensus_rpc.clone(),
+        data_dir: cli
+            .data_dir
+            .as_ref()
+            .map(|s| PathBuf::from_str(s).expect("cannot find data dir")),
+        ..Default::default()
+    };
+
+    let mut config = Config::from_file(&config_path, &cli.network, &cli_config);
+    // The proofs are only written to the output, the JSON-RPC server is not needed.
+    config.rpc_bind_ip = None;
+    config.rpc_port = None;
+
+    config
+}
+
+#[derive(Parser)]
+#[clap(version, about)]
+/// Generate and verify account and storage proofs against the finalized Ethereum state
+struct Cli {
+    #[clap(short, long, default_value = "mainnet")]
+    network: String,
+    #[clap(short = 'w', long, env)]
+    checkpoint: Option<String>,
+    #[clap(short, long, env)]
+    execution_rpc: Option<String>,
+    #[clap(short, long, env)]
+    consensus_rpc: Option<String>,
+    #[clap(short, long, env)]
+    data_dir: Option<String>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Sync to finality and print the finalized block
+    Sync,
+    /// Sync to finality and emit the proof of an account and its storage slots
+    Prove {
+        #[clap(short, long)]
+        address: Address,
+        #[clap(short, long = "slot")]
+        slots: Vec<H256>,
+        #[clap(short, long, value_enum, default_value = "json")]
+        format: Format,
+        /// Write the proof to a file instead of stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Verify a previously produced proof, without any network access
+    Verify {
+        #[clap(short, long)]
+        input: PathBuf,
+        #[clap(short, long, value_enum, default_value = "json")]
+        format: Format,
+        /// The trusted state root the proof must be for
+        #[clap(short, long)]
+        state_root: Option<H256>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Json,
+    /// Versioned BCS, as consumed by dWallet
+    Bcs,
+    /// Fixed width fields and deduplicated trie nodes, for verification in Move
+    Compact,
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{EIP1186ProofResponse, U256};
+
+    use super::*;
+
+    fn bundle() -> ProofBundle {
+        let proof = fs::read_to_string("../execution/testdata/proof.json").unwrap();
+        let proof: EIP1186ProofResponse = serde_json::from_str(&proof).unwrap();
+
+        ProofBundle {
+            block_number: 100,
+            block_hash: H256::repeat_byte(1),
+            state_root: H256::from_str(
+                "0xaa02f5db2ee75e3da400d10f3c30e894b6016ce8a2501680380a907b6674ce0d",
+            )
+            .unwrap(),
+            address: proof.address,
+            slots: Vec::new(),
+            proof,
+        }
+    }
+
+    fn encode(bundle: &ProofBundle, format: Format) -> Vec<u8> {
+        match format {
+            Format::Json => serde_json::to_vec_pretty(bundle).unwrap(),
+            Format::Bcs => encode_versioned(bundle).unwrap(),
+            Format::Compact => CompactProof::from(bundle).encode(),
+        }
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let bundle = bundle();
+        for format in [Format::Json, Format::Bcs, Format::Compact] {
+            let bytes = encode(&bundle, format);
+            assert_eq!(
+                (100, bundle.address),
+                verify_proof(&bytes, format, Some(bundle.state_root)).unwrap()
+            );
+            assert!(verify_proof(&bytes, format, Some(H256::zero())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_tampered_proof() {
+        let mut bundle = bundle();
+        bundle.proof.balance += U256::one();
+        for format in [Format::Json, Format::Bcs, Format::Compact] {
+            let bytes = encode(&bundle, format);
+            assert!(verify_proof(&bytes, format, None).is_err());
+        }
+    }
+}
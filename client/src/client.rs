@@ -5,17 +5,31 @@ use std::path::PathBuf;
 use std::{sync::Arc, time::Duration};
 
 use common::types::{Block, BlockTag};
-use config::{networks::Network, Config};
+use config::{networks::Network, BackupConfig, Config, DatabaseKey, RetentionConfig};
 use consensus::database::Database;
 use ethers::{
     prelude::{Address, EIP1186ProofResponse, U256},
-    types::{Filter, Log, SyncingStatus, Transaction, TransactionReceipt, H256},
+    types::{FeeHistory, Filter, Log, SyncingStatus, Transaction, TransactionReceipt, H256},
+};
+use execution::{
+    broadcast::BroadcastEvent,
+    ens::EnsResolverLayout,
+    quorum::QuorumChecked,
+    types::{
+        AccountProofs, BeaconRootProof, BlockReceiptsProof, CallOpts, CodeProof, Erc20Balance,
+        ProofBundle, ProvenNonce, ProvenStorage, TransactionCounts,
+    },
+    watcher::{WatchEvent, WatchTarget},
 };
-use execution::types::CallOpts;
 use eyre::{eyre, Result};
+use tokio::sync::mpsc;
 use tracing::{info, warn};
 use zduny_wasm_timer::Delay;
 
+#[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+use crate::grpc::Grpc;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::light_client_server::LightClientServer;
 use crate::node::Node;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::rpc::Rpc;
@@ -36,6 +50,29 @@ pub struct ClientBuilder {
     fallback: Option<String>,
     load_external_fallback: bool,
     strict_checkpoint_age: bool,
+    helios_rpc: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    grpc_port: Option<u16>,
+    #[cfg(not(target_arch = "wasm32"))]
+    light_client_server_port: Option<u16>,
+    broadcast_rpcs: Vec<String>,
+    sync_from_genesis: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    update_archive: Option<PathBuf>,
+    checkpoint_services: Vec<String>,
+    checkpoint_quorum: Option<usize>,
+    stale_checkpoint_age: Option<u64>,
+    encrypt_database: bool,
+    database_key: Option<DatabaseKey>,
+    retention: Option<RetentionConfig>,
+    backup: Option<BackupConfig>,
+    database_url: Option<String>,
+    database_snapshot: Option<Vec<u8>>,
+    min_optimistic_participation: Option<f64>,
+    strict_finality: bool,
+    verify_transactions_root: bool,
+    quorum_rpcs: Vec<String>,
+    rpc_quorum: Option<usize>,
 }
 
 impl ClientBuilder {
@@ -103,6 +140,152 @@ impl ClientBuilder {
         self
     }
 
+    /// Serves the verified data of the node over gRPC on `port`, when built with the `grpc`
+    /// feature.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn grpc_port(mut self, port: u16) -> Self {
+        self.grpc_port = Some(port);
+        self
+    }
+
+    /// Serves the verified light client data on `port` with the light client endpoints of the
+    /// beacon API.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn light_client_server_port(mut self, port: u16) -> Self {
+        self.light_client_server_port = Some(port);
+        self
+    }
+
+    /// Serves the verified data of the `helios` namespace along with the `eth` namespace.
+    pub fn helios_rpc(mut self) -> Self {
+        self.helios_rpc = true;
+        self
+    }
+
+    /// Broadcasts the transactions sent with [`Client::send_and_confirm_transaction`] to `rpcs`
+    /// too.
+    pub fn broadcast_rpcs(mut self, rpcs: &[&str]) -> Self {
+        self.broadcast_rpcs = rpcs.iter().map(|rpc| rpc.to_string()).collect();
+        self
+    }
+
+    /// Bootstraps from the genesis block when no checkpoint is given or saved, for devnets.
+    pub fn sync_from_genesis(mut self) -> Self {
+        self.sync_from_genesis = true;
+        self
+    }
+
+    /// Imports the light client updates of the archive at `path`, a directory or an era file,
+    /// before fetching the remaining ones from the consensus RPC.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn update_archive(mut self, path: PathBuf) -> Self {
+        self.update_archive = Some(path);
+        self
+    }
+
+    /// Fetches the checkpoint from the checkpoint sync services at `endpoints` when none is given
+    /// or saved, or when syncing fails, and only uses one they agree on.
+    pub fn checkpoint_services(mut self, endpoints: &[&str]) -> Self {
+        self.checkpoint_services = endpoints
+            .iter()
+            .map(|endpoint| endpoint.to_string())
+            .collect();
+        self
+    }
+
+    /// Sets how many of the checkpoint services must agree on a checkpoint, all of them by
+    /// default.
+    pub fn checkpoint_quorum(mut self, quorum: usize) -> Self {
+        self.checkpoint_quorum = Some(quorum);
+        self
+    }
+
+    /// Bootstraps from a fresh checkpoint of the fallbacks instead of the saved one when the
+    /// saved one is more than `age` seconds old.
+    pub fn stale_checkpoint_age(mut self, age: u64) -> Self {
+        self.stale_checkpoint_age = Some(age);
+        self
+    }
+
+    /// Encrypts the contents of the database with the hex key of the `HELIOS_DATABASE_KEY`
+    /// environment variable.
+    pub fn encrypt_database(mut self) -> Self {
+        self.encrypt_database = true;
+        self
+    }
+
+    /// Encrypts the contents of the database with the key returned by `key`, e.g. from a KMS.
+    pub fn database_key(
+        mut self,
+        key: impl Fn() -> Result<[u8; 32]> + Send + Sync + 'static,
+    ) -> Self {
+        self.encrypt_database = true;
+        self.database_key = Some(DatabaseKey::new(key));
+        self
+    }
+
+    /// Prunes the verified data and the database of a long-running client, see
+    /// [`RetentionConfig`].
+    pub fn retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Backs the verified state up to an object store, and restores it on startup, see
+    /// [`BackupConfig`].
+    pub fn backup(mut self, backup: BackupConfig) -> Self {
+        self.backup = Some(backup);
+        self
+    }
+
+    /// Keeps the checkpoints, the verified headers and the conflict evidence in the PostgreSQL
+    /// database of the connection string `url`, with the `PostgresDB` database of the `postgres`
+    /// feature of the consensus crate.
+    pub fn database_url(mut self, url: &str) -> Self {
+        self.database_url = Some(url.to_string());
+        self
+    }
+
+    /// Starts the `MemoryDB` database of the consensus crate from the contents it exported in a
+    /// previous run, see [`Client::database`].
+    pub fn database_snapshot(mut self, snapshot: Vec<u8>) -> Self {
+        self.database_snapshot = Some(snapshot);
+        self
+    }
+
+    /// Rejects the optimistic updates of which less than `participation` of the sync committee,
+    /// between 0 and 1, signed, see [`Config::min_optimistic_participation`].
+    pub fn min_optimistic_participation(mut self, participation: f64) -> Self {
+        self.min_optimistic_participation = Some(participation);
+        self
+    }
+
+    /// Only advances on finality-proven updates, see [`Config::strict_finality`].
+    pub fn strict_finality(mut self) -> Self {
+        self.strict_finality = true;
+        self
+    }
+
+    /// Checks the transactions root of the execution RPC blocks against the one rebuilt from the
+    /// payloads, see [`Config::verify_transactions_root`].
+    pub fn verify_transactions_root(mut self) -> Self {
+        self.verify_transactions_root = true;
+        self
+    }
+
+    /// Queries the unproven data from `rpcs` too, see [`Config::quorum_rpcs`].
+    pub fn quorum_rpcs(mut self, rpcs: &[&str]) -> Self {
+        self.quorum_rpcs = rpcs.iter().map(|rpc| rpc.to_string()).collect();
+        self
+    }
+
+    /// Only returns the unproven data that `quorum` execution RPCs agree on, see
+    /// [`Config::rpc_quorum`].
+    pub fn rpc_quorum(mut self, quorum: usize) -> Self {
+        self.rpc_quorum = Some(quorum);
+        self
+    }
+
     pub fn build<DB: Database>(self) -> Result<Client<DB>> {
         let base_config = if let Some(network) = self.network {
             network.to_base_config()
@@ -191,6 +374,172 @@ impl ClientBuilder {
             self.strict_checkpoint_age
         };
 
+        #[cfg(not(target_arch = "wasm32"))]
+        let grpc_port = if self.grpc_port.is_some() {
+            self.grpc_port
+        } else if let Some(config) = &self.config {
+            config.grpc_port
+        } else {
+            None
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let light_client_server_port = if self.light_client_server_port.is_some() {
+            self.light_client_server_port
+        } else if let Some(config) = &self.config {
+            config.light_client_server_port
+        } else {
+            None
+        };
+
+        let helios_rpc = if let Some(config) = &self.config {
+            self.helios_rpc || config.helios_rpc
+        } else {
+            self.helios_rpc
+        };
+
+        let broadcast_rpcs = if !self.broadcast_rpcs.is_empty() {
+            self.broadcast_rpcs
+        } else if let Some(config) = &self.config {
+            config.broadcast_rpcs.clone()
+        } else {
+            Vec::new()
+        };
+
+        let sync_from_genesis = if let Some(config) = &self.config {
+            self.sync_from_genesis || config.sync_from_genesis
+        } else {
+            self.sync_from_genesis
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let update_archive = if self.update_archive.is_some() {
+            self.update_archive
+        } else if let Some(config) = &self.config {
+            config.update_archive.clone()
+        } else {
+            None
+        };
+
+        let checkpoint_services = if !self.checkpoint_services.is_empty() {
+            self.checkpoint_services
+        } else if let Some(config) = &self.config {
+            config.checkpoint_services.clone()
+        } else {
+            Vec::new()
+        };
+
+        let checkpoint_quorum = if self.checkpoint_quorum.is_some() {
+            self.checkpoint_quorum
+        } else if let Some(config) = &self.config {
+            config.checkpoint_quorum
+        } else {
+            None
+        };
+
+        let stale_checkpoint_age = if self.stale_checkpoint_age.is_some() {
+            self.stale_checkpoint_age
+        } else if let Some(config) = &self.config {
+            config.stale_checkpoint_age
+        } else {
+            None
+        };
+
+        let encrypt_database = if let Some(config) = &self.config {
+            self.encrypt_database || config.encrypt_database
+        } else {
+            self.encrypt_database
+        };
+
+        let database_key = if self.database_key.is_some() {
+            self.database_key
+        } else if let Some(config) = &self.config {
+            config.database_key.clone()
+        } else {
+            None
+        };
+
+        let retention = if let Some(retention) = self.retention {
+            retention
+        } else if let Some(config) = &self.config {
+            config.retention.clone()
+        } else {
+            Default::default()
+        };
+
+        // The database is the one `build` is called with, the type is kept for the databases
+        // that pick their storage from it.
+        let database_type = self
+            .config
+            .as_ref()
+            .and_then(|config| config.database_type.clone());
+
+        let sync_health = self
+            .config
+            .as_ref()
+            .map(|config| config.sync_health.clone())
+            .unwrap_or_default();
+
+        let backup = if self.backup.is_some() {
+            self.backup
+        } else if let Some(config) = &self.config {
+            config.backup.clone()
+        } else {
+            None
+        };
+
+        let database_url = if self.database_url.is_some() {
+            self.database_url
+        } else if let Some(config) = &self.config {
+            config.database_url.clone()
+        } else {
+            None
+        };
+
+        let database_snapshot = if self.database_snapshot.is_some() {
+            self.database_snapshot
+        } else if let Some(config) = &self.config {
+            config.database_snapshot.clone()
+        } else {
+            None
+        };
+
+        let min_optimistic_participation = if self.min_optimistic_participation.is_some() {
+            self.min_optimistic_participation
+        } else if let Some(config) = &self.config {
+            config.min_optimistic_participation
+        } else {
+            None
+        };
+
+        let strict_finality = if let Some(config) = &self.config {
+            self.strict_finality || config.strict_finality
+        } else {
+            self.strict_finality
+        };
+
+        let verify_transactions_root = if let Some(config) = &self.config {
+            self.verify_transactions_root || config.verify_transactions_root
+        } else {
+            self.verify_transactions_root
+        };
+
+        let quorum_rpcs = if !self.quorum_rpcs.is_empty() {
+            self.quorum_rpcs
+        } else if let Some(config) = &self.config {
+            config.quorum_rpcs.clone()
+        } else {
+            Vec::new()
+        };
+
+        let rpc_quorum = if self.rpc_quorum.is_some() {
+            self.rpc_quorum
+        } else if let Some(config) = &self.config {
+            config.rpc_quorum
+        } else {
+            None
+        };
+
         let config = Config {
             consensus_rpc,
             execution_rpc,
@@ -214,7 +563,51 @@ impl ClientBuilder {
             fallback,
             load_external_fallback,
             strict_checkpoint_age,
-            database_type: None,
+            database_type,
+            force_update_timeout: None,
+            sync_concurrency: None,
+            rpc_retry: Default::default(),
+            rpc_rate_limits: Default::default(),
+            rpc_credentials: Default::default(),
+            rpc_tls: Default::default(),
+            rpc_proxy: None,
+            rpc_proxies: Default::default(),
+            rpc_record_dir: None,
+            gossip_peers: Vec::new(),
+            fork_overrides: Default::default(),
+            rpc_unknown_forks: false,
+            slot_clock: Default::default(),
+            advance: Default::default(),
+            retention,
+            backup,
+            sync_health,
+            helios_rpc,
+            #[cfg(not(target_arch = "wasm32"))]
+            grpc_port,
+            #[cfg(target_arch = "wasm32")]
+            grpc_port: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            light_client_server_port,
+            #[cfg(target_arch = "wasm32")]
+            light_client_server_port: None,
+            broadcast_rpcs,
+            sync_from_genesis,
+            #[cfg(not(target_arch = "wasm32"))]
+            update_archive,
+            #[cfg(target_arch = "wasm32")]
+            update_archive: None,
+            checkpoint_services,
+            checkpoint_quorum,
+            stale_checkpoint_age,
+            encrypt_database,
+            database_key,
+            database_url,
+            database_snapshot,
+            min_optimistic_participation,
+            strict_finality,
+            verify_transactions_root,
+            quorum_rpcs,
+            rpc_quorum,
         };
 
         Client::<DB>::new(config)
@@ -225,6 +618,10 @@ pub struct Client<DB: Database> {
     node: Arc<Node<DB>>,
     #[cfg(not(target_arch = "wasm32"))]
     rpc: Option<Rpc<DB>>,
+    #[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+    grpc: Option<Grpc<DB>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    light_client_server: Option<LightClientServer<DB>>,
 }
 
 impl<DB: Database> Client<DB> {
@@ -241,10 +638,29 @@ impl<DB: Database> Client<DB> {
             rpc = Some(Rpc::new(node.clone(), config.rpc_bind_ip, config.rpc_port));
         }
 
+        #[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+        let grpc = config
+            .grpc_port
+            .map(|port| Grpc::new(node.clone(), config.rpc_bind_ip, port));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let light_client_server = config
+            .light_client_server_port
+            .map(|port| LightClientServer::new(node.clone(), config.rpc_bind_ip, port));
+
+        #[cfg(not(all(feature = "grpc", not(target_arch = "wasm32"))))]
+        if config.grpc_port.is_some() {
+            warn!(target: "helios::client", "the grpc port is ignored without the grpc feature");
+        }
+
         Ok(Client {
             node,
             #[cfg(not(target_arch = "wasm32"))]
             rpc,
+            #[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+            grpc,
+            #[cfg(not(target_arch = "wasm32"))]
+            light_client_server,
         })
     }
 
@@ -254,16 +670,44 @@ impl<DB: Database> Client<DB> {
             rpc.start().await?;
         }
 
+        #[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+        if let Some(grpc) = &self.grpc {
+            grpc.start();
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(light_client_server) = &self.light_client_server {
+            light_client_server.start().await?;
+        }
+
         Ok(())
     }
 
     pub async fn shutdown(&self) {
         info!(target: "helios::client","Shutting down");
-        if let Err(err) = self.node.consensus.shutdown() {
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(rpc) = &self.rpc {
+            rpc.stop();
+        }
+
+        if let Err(err) = self.node.consensus.shutdown().await {
             warn!(target: "helios::client", error = %err, "the graceful shutdown failed");
         }
     }
 
+    /// Returns the database of the client, which has the last checkpoint once the client was
+    /// shut down.
+    pub fn database(&self) -> &DB {
+        self.node.consensus.database()
+    }
+
+    /// Applies the consensus RPC and checkpoint fallback settings of `config` without restarting
+    /// the client, see [`consensus::ConsensusClient::reload`]. The other settings are kept.
+    pub async fn reload(&self, config: &Config) -> Result<()> {
+        self.node.consensus.reload(config).await
+    }
+
     pub async fn call(&self, opts: &CallOpts, block: BlockTag) -> Result<Vec<u8>> {
         self.node.call(opts, block).await.map_err(|err| err.into())
     }
@@ -311,10 +755,90 @@ impl<DB: Database> Client<DB> {
         self.node.get_storage_at(address, slot, block).await
     }
 
+    /// Same as [`Client::get_storage_at`] at the finalized block, but also returns the proof of
+    /// the value, to check it offline.
+    pub async fn get_proven_storage_at(
+        &self,
+        address: &Address,
+        slot: H256,
+    ) -> Result<ProvenStorage> {
+        self.node.get_proven_storage_at(address, slot).await
+    }
+
+    pub async fn get_proof_bundle(
+        &self,
+        address: &Address,
+        slots: &[H256],
+        block: BlockTag,
+    ) -> Result<ProofBundle> {
+        self.node.get_proof_bundle(address, slots, block).await
+    }
+
+    /// Returns the code of `address` at `block`, with a proof that it hashes to the code hash of
+    /// the account, to check the code offline.
+    pub async fn get_code_proof(&self, address: &Address, block: BlockTag) -> Result<CodeProof> {
+        self.node.get_code_proof(address, block).await
+    }
+
+    /// Returns the nonce of `address` at the finalized block, with its proof, to build a
+    /// transaction from a proven nonce.
+    pub async fn get_proven_nonce(&self, address: &Address) -> Result<ProvenNonce> {
+        self.node.get_proven_nonce(address).await
+    }
+
+    pub async fn get_beacon_root_proof(&self) -> Result<BeaconRootProof> {
+        self.node.get_beacon_root_proof().await
+    }
+
+    pub async fn create_account_proofs(&self, addresses: &[Address]) -> Result<AccountProofs> {
+        self.node.create_account_proofs(addresses).await
+    }
+
+    /// Returns the nonces of `addresses` at the finalized block, with their proofs, to track many
+    /// addresses with a single finalized state root.
+    pub async fn get_transaction_counts(&self, addresses: &[Address]) -> Result<TransactionCounts> {
+        self.node.get_transaction_counts(addresses).await
+    }
+
+    pub async fn get_erc20_balance(
+        &self,
+        token: &Address,
+        holder: &Address,
+        balances_slot: u64,
+    ) -> Result<Erc20Balance> {
+        self.node
+            .get_erc20_balance(token, holder, balances_slot)
+            .await
+    }
+
+    pub async fn resolve_ens(
+        &self,
+        name: &str,
+        layout: &EnsResolverLayout,
+    ) -> Result<Option<Address>> {
+        self.node.resolve_ens(name, layout).await
+    }
+
+    /// Watches accounts and storage slots. Every change between two finalized blocks is sent to
+    /// the returned channel together with the proof of the new value.
+    pub fn watch(&self, targets: Vec<WatchTarget>) -> mpsc::Receiver<WatchEvent> {
+        self.node.watch(targets)
+    }
+
     pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<H256> {
         self.node.send_raw_transaction(bytes).await
     }
 
+    /// Broadcasts a transaction to the execution RPC and to the `broadcast_rpcs`, and returns its
+    /// receipt once it is proven against a finalized block. The progress is sent to `events`.
+    pub async fn send_and_confirm_transaction(
+        &self,
+        bytes: &[u8],
+        events: Option<mpsc::Sender<BroadcastEvent>>,
+    ) -> Result<TransactionReceipt> {
+        self.node.send_and_confirm_transaction(bytes, events).await
+    }
+
     pub async fn get_transaction_receipt(
         &self,
         tx_hash: &H256,
@@ -326,6 +850,19 @@ impl<DB: Database> Client<DB> {
         self.node.get_transaction_by_hash(tx_hash).await
     }
 
+    /// Returns the transaction `tx_hash` once its inclusion in a finalized block is proven, unlike
+    /// [`Self::get_transaction_by_hash`], which also returns the transactions of the optimistic
+    /// blocks.
+    pub async fn get_verified_transaction_by_hash(&self, tx_hash: &H256) -> Result<Transaction> {
+        self.node.get_verified_transaction_by_hash(tx_hash).await
+    }
+
+    /// Returns the receipts of every transaction of the finalized block `tag` at once, with the
+    /// header of the block and the proof of each receipt, see [`BlockReceiptsProof::verify`].
+    pub async fn get_verified_block_receipts(&self, tag: BlockTag) -> Result<BlockReceiptsProof> {
+        self.node.get_verified_block_receipts(tag).await
+    }
+
     pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
         self.node.get_logs(filter).await
     }
@@ -358,6 +895,36 @@ impl<DB: Database> Client<DB> {
         self.node.get_priority_fee()
     }
 
+    /// Returns the nonce of `address` in the pending state, which the execution RPCs agree on but
+    /// is not verified, see [`Config::quorum_rpcs`].
+    pub async fn get_pending_nonce(&self, address: &Address) -> Result<QuorumChecked<u64>> {
+        self.node.get_pending_nonce(address).await
+    }
+
+    /// Returns the gas used by the call `opts` against the pending state, which the execution RPCs
+    /// agree on but is not verified, see [`Config::quorum_rpcs`].
+    pub async fn estimate_pending_gas(&self, opts: &CallOpts) -> Result<QuorumChecked<u64>> {
+        self.node.estimate_pending_gas(opts).await
+    }
+
+    /// Returns the priority fee suggested by the execution RPCs, once they agree on it, see
+    /// [`Config::quorum_rpcs`].
+    pub async fn get_quorum_priority_fee(&self) -> Result<QuorumChecked<U256>> {
+        self.node.get_quorum_priority_fee().await
+    }
+
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        last_block: BlockTag,
+    ) -> Result<FeeHistory> {
+        self.node.get_fee_history(block_count, last_block).await
+    }
+
+    pub async fn suggest_base_fee(&self) -> Result<U256> {
+        self.node.suggest_base_fee().await
+    }
+
     pub async fn get_block_number(&self) -> Result<U256> {
         self.node.get_block_number().await
     }
@@ -374,6 +941,19 @@ impl<DB: Database> Client<DB> {
         self.node.get_block_by_hash(hash, full_tx).await
     }
 
+    /// Returns the finalized block `block`, cross-checked against the block of the execution RPC.
+    pub async fn get_verified_block_by_number(
+        &self,
+        block: BlockTag,
+        full_tx: bool,
+    ) -> Result<Block> {
+        self.node.get_verified_block_by_number(block, full_tx).await
+    }
+
+    pub async fn get_verified_block_by_hash(&self, hash: &H256, full_tx: bool) -> Result<Block> {
+        self.node.get_verified_block_by_hash(hash, full_tx).await
+    }
+
     pub async fn get_transaction_by_block_hash_and_index(
         &self,
         block_hash: &H256,
@@ -0,0 +1,313 @@
+//! A gRPC service serving the verified data of the node, see `proto/helios.proto`. It serves the
+//! same data as the `helios` namespace of the JSON-RPC server.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+};
+
+use common::types::BlockTag;
+use consensus::{
+    constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES,
+    database::Database,
+    light_client_data::LightClientData,
+    types::{
+        encoding::{encode_versioned, FinalityUpdateSerde},
+        FinalityUpdate, FinalizedHeader as VerifiedFinalizedHeader,
+    },
+};
+use ethers::types::{Address, H256, U256};
+use execution::types::ProofBundle;
+use eyre::Result;
+use futures::{Stream, StreamExt};
+use serde::{de::value::StrDeserializer, Deserialize};
+use tokio_stream::wrappers::BroadcastStream;
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{error, info};
+
+use crate::node::Node;
+
+pub mod proto {
+    tonic::include_proto!("helios.v1");
+}
+
+use proto::helios_server::{Helios, HeliosServer};
+
+pub struct Grpc<DB: Database> {
+    node: Arc<Node<DB>>,
+    address: SocketAddr,
+}
+
+impl<DB: Database> Grpc<DB> {
+    pub fn new(node: Arc<Node<DB>>, ip: Option<IpAddr>, port: u16) -> Self {
+        let address = SocketAddr::new(ip.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)), port);
+        Grpc { node, address }
+    }
+
+    /// Starts the server in the background. It stops along with the consensus client.
+    pub fn start(&self) -> SocketAddr {
+        let service = HeliosService {
+            node: self.node.clone(),
+        };
+        let address = self.address;
+        let cancel = self.node.consensus.cancellation_token();
+
+        tokio::spawn(async move {
+            let res = Server::builder()
+                .add_service(HeliosServer::new(service))
+                .serve_with_shutdown(address, cancel.cancelled())
+                .await;
+
+            if let Err(err) = res {
+                error!(target: "helios::grpc", error = %err, "grpc server failed");
+            }
+        });
+
+        info!(target: "helios::grpc", "grpc server started at {}", address);
+
+        address
+    }
+}
+
+struct HeliosService<DB: Database> {
+    node: Arc<Node<DB>>,
+}
+
+type FinalizedHeaderStream =
+    Pin<Box<dyn Stream<Item = Result<proto::FinalizedHeader, Status>> + Send + 'static>>;
+
+type FinalityUpdateStream =
+    Pin<Box<dyn Stream<Item = Result<proto::FinalityUpdateBcs, Status>> + Send + 'static>>;
+
+#[tonic::async_trait]
+impl<DB: Database> Helios for HeliosService<DB> {
+    type StreamFinalizedHeadersStream = FinalizedHeaderStream;
+    type StreamFinalityUpdatesStream = FinalityUpdateStream;
+
+    async fn stream_finalized_headers(
+        &self,
+        _request: Request<proto::StreamFinalizedHeadersRequest>,
+    ) -> Result<Response<Self::StreamFinalizedHeadersStream>, Status> {
+        // A lagging subscriber misses the oldest headers, which are superseded by the next ones.
+        let headers = BroadcastStream::new(self.node.consensus.subscribe_finalized())
+            .filter_map(|header| async move { header.ok().map(|header| Ok(header.into())) });
+
+        Ok(Response::new(Box::pin(headers)))
+    }
+
+    async fn stream_finality_updates(
+        &self,
+        _request: Request<proto::StreamFinalityUpdatesRequest>,
+    ) -> Result<Response<Self::StreamFinalityUpdatesStream>, Status> {
+        let data = self.node.consensus.light_client_data();
+        Ok(Response::new(finality_updates(&data)))
+    }
+
+    async fn get_finalized_state_root(
+        &self,
+        _request: Request<proto::GetFinalizedStateRootRequest>,
+    ) -> Result<Response<proto::FinalizedStateRoot>, Status> {
+        let block = self
+            .node
+            .get_block_by_number(BlockTag::Finalized, false)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::unavailable("no finalized block"))?;
+
+        Ok(Response::new(proto::FinalizedStateRoot {
+            block_number: block.number.as_u64(),
+            block_hash: block.hash.as_bytes().to_vec(),
+            state_root: block.state_root.as_bytes().to_vec(),
+        }))
+    }
+
+    async fn get_account_proof(
+        &self,
+        request: Request<proto::GetAccountProofRequest>,
+    ) -> Result<Response<proto::AccountProof>, Status> {
+        let request = request.into_inner();
+        if request.address.len() != Address::len_bytes() {
+            return Err(Status::invalid_argument("invalid address"));
+        }
+        let address = Address::from_slice(&request.address);
+
+        let slots = request
+            .slots
+            .iter()
+            .map(|slot| {
+                if slot.len() != H256::len_bytes() {
+                    return Err(Status::invalid_argument("invalid storage slot"));
+                }
+                Ok(H256::from_slice(slot))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let block = parse_block_tag(&request.block)?;
+        let bundle = self
+            .node
+            .get_proof_bundle(&address, &slots, block)
+            .await
+            .map_err(internal)?;
+
+        Ok(Response::new(bundle.into()))
+    }
+
+    async fn get_updates_bcs(
+        &self,
+        request: Request<proto::GetUpdatesBcsRequest>,
+    ) -> Result<Response<proto::UpdatesBcs>, Status> {
+        let request = request.into_inner();
+        if request.count > MAX_REQUEST_LIGHT_CLIENT_UPDATES {
+            return Err(Status::invalid_argument(format!(
+                "at most {MAX_REQUEST_LIGHT_CLIENT_UPDATES} updates can be requested"
+            )));
+        }
+
+        let updates = self
+            .node
+            .get_updates_bcs(request.start_period, request.count)
+            .await
+            .map_err(internal)?;
+
+        Ok(Response::new(proto::UpdatesBcs { updates }))
+    }
+}
+
+/// Streams the finality updates recorded in `data` from now on. A lagging subscriber misses the
+/// oldest updates, which are superseded by the next ones.
+fn finality_updates(data: &LightClientData) -> FinalityUpdateStream {
+    let updates =
+        BroadcastStream::new(data.subscribe_finality_updates()).filter_map(|update| async move {
+            let update = update.ok()?;
+            Some(proto::FinalityUpdateBcs::try_from(&update).map_err(internal))
+        });
+
+    Box::pin(updates)
+}
+
+/// Parses a block tag as in the JSON-RPC API. The finalized block is proven by default.
+fn parse_block_tag(block: &str) -> Result<BlockTag, Status> {
+    if block.is_empty() {
+        return Ok(BlockTag::Finalized);
+    }
+
+    BlockTag::deserialize(StrDeserializer::<serde::de::value::Error>::new(block))
+        .map_err(|err| Status::invalid_argument(err.to_string()))
+}
+
+fn internal(err: eyre::Report) -> Status {
+    Status::internal(err.to_string())
+}
+
+fn u256_bytes(value: &U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
+
+impl From<VerifiedFinalizedHeader> for proto::FinalizedHeader {
+    fn from(header: VerifiedFinalizedHeader) -> Self {
+        Self {
+            slot: header.slot,
+            block_root: header.block_root.to_vec(),
+            header: Some(proto::BeaconBlockHeader {
+                slot: header.header.slot.as_u64(),
+                proposer_index: header.header.proposer_index.as_u64(),
+                parent_root: header.header.parent_root.to_vec(),
+                state_root: header.header.state_root.to_vec(),
+                body_root: header.header.body_root.to_vec(),
+            }),
+            execution_block_hash: header.execution_block_hash.map(|hash| hash.to_vec()),
+            execution_state_root: header.execution_state_root.map(|root| root.to_vec()),
+        }
+    }
+}
+
+impl TryFrom<&FinalityUpdate> for proto::FinalityUpdateBcs {
+    type Error = eyre::Report;
+
+    fn try_from(update: &FinalityUpdate) -> Result<Self> {
+        Ok(Self {
+            finalized_slot: update.finalized_header.slot.as_u64(),
+            update: encode_versioned(&FinalityUpdateSerde::from(update))?,
+        })
+    }
+}
+
+impl From<ProofBundle> for proto::AccountProof {
+    fn from(bundle: ProofBundle) -> Self {
+        let proof = bundle.proof;
+
+        Self {
+            block_number: bundle.block_number,
+            state_root: bundle.state_root.as_bytes().to_vec(),
+            address: bundle.address.as_bytes().to_vec(),
+            balance: u256_bytes(&proof.balance),
+            nonce: proof.nonce.as_u64(),
+            code_hash: proof.code_hash.as_bytes().to_vec(),
+            storage_hash: proof.storage_hash.as_bytes().to_vec(),
+            account_proof: proof
+                .account_proof
+                .iter()
+                .map(|node| node.to_vec())
+                .collect(),
+            storage_proof: proof
+                .storage_proof
+                .iter()
+                .map(|storage_proof| proto::StorageProof {
+                    key: u256_bytes(&storage_proof.key),
+                    value: u256_bytes(&storage_proof.value),
+                    proof: storage_proof
+                        .proof
+                        .iter()
+                        .map(|node| node.to_vec())
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use consensus::types::{encoding::decode_versioned, GenericUpdate, Header};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_finality_updates() {
+        let data = LightClientData::default();
+        let mut updates = finality_updates(&data);
+
+        let update = FinalityUpdate {
+            attested_header: Header {
+                slot: 8256.into(),
+                ..Default::default()
+            },
+            finalized_header: Header {
+                slot: 8192.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        data.record_update(&GenericUpdate::from(&update), true);
+
+        let streamed = updates.next().await.unwrap().unwrap();
+        assert_eq!(8192, streamed.finalized_slot);
+
+        let decoded: FinalityUpdate = decode_versioned::<FinalityUpdateSerde>(&streamed.update)
+            .unwrap()
+            .into();
+        assert_eq!(8256, decoded.attested_header.slot.as_u64());
+        assert_eq!(8192, decoded.finalized_header.slot.as_u64());
+    }
+
+    #[test]
+    fn test_parse_block_tag() {
+        assert!(matches!(parse_block_tag(""), Ok(BlockTag::Finalized)));
+        assert!(matches!(parse_block_tag("latest"), Ok(BlockTag::Latest)));
+        assert!(matches!(parse_block_tag("0x10"), Ok(BlockTag::Number(16))));
+        assert!(parse_block_tag("earliest").is_err());
+    }
+}
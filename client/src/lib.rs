@@ -6,4 +6,10 @@ pub mod errors;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod rpc;
 
+#[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+pub mod grpc;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod light_client_server;
+
 pub mod node;
@@ -0,0 +1,454 @@
+//! Serves the light client data verified by the node with the `/eth/v1/beacon/light_client/*`
+//! endpoints of the beacon API, so that other light clients can sync from it.
+//!
+//! Only the beacon part of the light client headers is kept by the node. From Capella on, the
+//! light client headers also hold the execution payload header and its branch, so the data of
+//! those forks is refused with `501 Not Implemented` rather than served without them.
+//!
+//! The responses carry strong `ETag` and `Cache-Control` headers, so that a fleet of light clients
+//! can share one instance, and its upstream consensus RPC, behind a caching proxy. The bootstraps
+//! never change. The updates of past periods are cached for [`PAST_PERIOD_MAX_AGE`] and then
+//! revalidated with their `ETag`, as a better update of the period may still be verified. The
+//! other responses are cached until the next slot.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use common::utils::hex_str_to_bytes;
+use config::Config;
+use consensus::{
+    clock::{slot_to_sync_committee_period, SlotClock},
+    constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES,
+    database::Database,
+    light_client_data::LightClientData,
+};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::{error, info};
+
+use crate::node::Node;
+
+/// How long, in seconds, the updates of past periods may be cached for.
+pub const PAST_PERIOD_MAX_AGE: u64 = 60 * 60;
+
+/// The forks of which the light client headers are only the beacon header.
+const BEACON_HEADER_FORKS: &[&str] = &["phase0", "altair", "bellatrix"];
+
+pub struct LightClientServer<DB: Database> {
+    node: Arc<Node<DB>>,
+    address: SocketAddr,
+}
+
+impl<DB: Database> LightClientServer<DB> {
+    pub fn new(node: Arc<Node<DB>>, ip: Option<IpAddr>, port: u16) -> Self {
+        let address = SocketAddr::new(ip.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)), port);
+        LightClientServer { node, address }
+    }
+
+    /// Starts the server in the background. It stops along with the consensus client.
+    pub async fn start(&self) -> Result<SocketAddr> {
+        let state = ServerState {
+            data: self.node.consensus.light_client_data(),
+            config: self.node.config.clone(),
+            clock: SlotClock::from_config(&self.node.config),
+        };
+
+        let router = Router::new()
+            .route(
+                "/eth/v1/beacon/light_client/bootstrap/:block_root",
+                get(get_bootstrap),
+            )
+            .route("/eth/v1/beacon/light_client/updates", get(get_updates))
+            .route(
+                "/eth/v1/beacon/light_client/finality_update",
+                get(get_finality_update),
+            )
+            .route(
+                "/eth/v1/beacon/light_client/optimistic_update",
+                get(get_optimistic_update),
+            )
+            .with_state(state);
+
+        let listener = tokio::net::TcpListener::bind(self.address).await?;
+        let address = listener.local_addr()?;
+        let cancel = self.node.consensus.cancellation_token();
+
+        tokio::spawn(async move {
+            let res = axum::serve(listener, router)
+                .with_graceful_shutdown(async move { cancel.cancelled().await })
+                .await;
+
+            if let Err(err) = res {
+                error!(target: "helios::light_client_server", error = %err, "light client server failed");
+            }
+        });
+
+        info!(target: "helios::light_client_server", "light client server started at {}", address);
+
+        Ok(address)
+    }
+}
+
+#[derive(Clone)]
+struct ServerState {
+    data: LightClientData,
+    config: Arc<Config>,
+    clock: SlotClock,
+}
+
+#[derive(Deserialize)]
+struct UpdatesQuery {
+    start_period: u64,
+    count: u64,
+}
+
+/// A response of the beacon API, with the fork of the data.
+#[derive(Serialize)]
+struct Versioned {
+    version: &'static str,
+    data: Value,
+}
+
+/// How long a response may be cached for.
+enum Caching {
+    /// The data never changes, e.g. the bootstrap of a block root.
+    Immutable,
+    /// The data rarely changes, e.g. the best update of a past period.
+    PastPeriod,
+    /// The data may change from the start of the next slot.
+    UntilNextSlot,
+}
+
+async fn get_bootstrap(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Path(block_root): Path<String>,
+) -> Result<Response, ApiError> {
+    let block_root =
+        hex_str_to_bytes(&block_root).map_err(|_| ApiError::bad_request("invalid block root"))?;
+    let bootstrap = state
+        .data
+        .bootstrap(&block_root)
+        .ok_or_else(|| ApiError::not_found("no verified bootstrap for the block root"))?;
+
+    let slot = bootstrap.header.slot.as_u64();
+    let data = wrap_headers(serde_json::to_value(bootstrap)?, &["header"]);
+    cached(
+        &state,
+        &headers,
+        Caching::Immutable,
+        &versioned(&state.config, slot, data)?,
+    )
+}
+
+async fn get_updates(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Query(query): Query<UpdatesQuery>,
+) -> Result<Response, ApiError> {
+    if query.count > MAX_REQUEST_LIGHT_CLIENT_UPDATES {
+        return Err(ApiError::bad_request(&format!(
+            "at most {MAX_REQUEST_LIGHT_CLIENT_UPDATES} updates can be requested"
+        )));
+    }
+
+    let updates = state
+        .data
+        .updates(query.start_period, query.count)
+        .into_iter()
+        .map(|update| {
+            let slot = update.attested_header.slot.as_u64();
+            let data = wrap_headers(
+                serde_json::to_value(update)?,
+                &["attested_header", "finalized_header"],
+            );
+            versioned(&state.config, slot, data)
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    // The updates of past periods rarely change, but a better or a missing one may still be
+    // verified later.
+    let current_period = slot_to_sync_committee_period(&state.config, state.clock.current_slot());
+    let caching = if updates.len() as u64 == query.count
+        && query.start_period.saturating_add(query.count) <= current_period
+    {
+        Caching::PastPeriod
+    } else {
+        Caching::UntilNextSlot
+    };
+
+    cached(&state, &headers, caching, &updates)
+}
+
+async fn get_finality_update(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let update = state
+        .data
+        .finality_update()
+        .ok_or_else(|| ApiError::not_found("no verified finality update"))?;
+
+    let slot = update.attested_header.slot.as_u64();
+    let data = wrap_headers(
+        serde_json::to_value(update)?,
+        &["attested_header", "finalized_header"],
+    );
+    cached(
+        &state,
+        &headers,
+        Caching::UntilNextSlot,
+        &versioned(&state.config, slot, data)?,
+    )
+}
+
+async fn get_optimistic_update(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let update = state
+        .data
+        .optimistic_update()
+        .ok_or_else(|| ApiError::not_found("no verified optimistic update"))?;
+
+    let slot = update.attested_header.slot.as_u64();
+    let data = wrap_headers(serde_json::to_value(update)?, &["attested_header"]);
+    cached(
+        &state,
+        &headers,
+        Caching::UntilNextSlot,
+        &versioned(&state.config, slot, data)?,
+    )
+}
+
+/// Fails from Capella on, as the light client headers of `data` have no execution header.
+fn versioned(config: &Config, slot: u64, data: Value) -> Result<Versioned, ApiError> {
+    let version = config.fork_name(slot);
+    if !BEACON_HEADER_FORKS.contains(&version) {
+        return Err(ApiError::not_implemented(&format!(
+            "the light client headers of {version} hold execution headers, which are not kept"
+        )));
+    }
+
+    Ok(Versioned { version, data })
+}
+
+/// Serializes `body` with its `ETag` and `Cache-Control` headers. A request whose `If-None-Match`
+/// header has the `ETag` of the body gets an empty `304 Not Modified` response.
+fn cached<T: Serialize>(
+    state: &ServerState,
+    headers: &HeaderMap,
+    caching: Caching,
+    body: &T,
+) -> Result<Response, ApiError> {
+    let body = serde_json::to_vec(body)?;
+    let etag = format!("\"{}\"", hex::encode(Sha256::digest(&body)));
+    let cache_control = match caching {
+        Caching::Immutable => "public, max-age=31536000, immutable".to_string(),
+        Caching::PastPeriod => format!("public, max-age={PAST_PERIOD_MAX_AGE}"),
+        Caching::UntilNextSlot => format!(
+            "public, max-age={}",
+            state.clock.duration_until_next_slot().as_secs()
+        ),
+    };
+
+    let not_modified = headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag == etag);
+    let response_headers = [(ETAG, etag), (CACHE_CONTROL, cache_control)];
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    Ok((
+        response_headers,
+        [(CONTENT_TYPE, "application/json".to_string())],
+        body,
+    )
+        .into_response())
+}
+
+/// Wraps the beacon headers of `fields` into light client headers, i.e. `{"beacon": header}`.
+fn wrap_headers(mut data: Value, fields: &[&str]) -> Value {
+    for field in fields {
+        if let Some(header) = data.get_mut(*field) {
+            *header = json!({ "beacon": header.take() });
+        }
+    }
+
+    data
+}
+
+/// An error response of the beacon API.
+#[derive(Debug)]
+struct ApiError {
+    code: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: &str) -> Self {
+        Self {
+            code: StatusCode::BAD_REQUEST,
+            message: message.to_string(),
+        }
+    }
+
+    fn not_found(message: &str) -> Self {
+        Self {
+            code: StatusCode::NOT_FOUND,
+            message: message.to_string(),
+        }
+    }
+
+    fn not_implemented(message: &str) -> Self {
+        Self {
+            code: StatusCode::NOT_IMPLEMENTED,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        Self {
+            code: StatusCode::INTERNAL_SERVER_ERROR,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({ "code": self.code.as_u16(), "message": self.message });
+        (self.code, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use config::networks;
+    use consensus::types::{GenericUpdate, Header, Update};
+
+    use super::*;
+
+    fn server_state() -> ServerState {
+        let base_config = networks::mainnet();
+        let config = Config {
+            chain: base_config.chain,
+            forks: base_config.forks,
+            ..Default::default()
+        };
+
+        ServerState {
+            data: LightClientData::default(),
+            clock: SlotClock::from_config(&config),
+            config: Arc::new(config),
+        }
+    }
+
+    fn record_update(data: &LightClientData, period: u64) {
+        let update = Update {
+            attested_header: Header {
+                slot: (period * 8192).into(),
+                ..Default::default()
+            },
+            signature_slot: (period * 8192 + 1).into(),
+            ..Default::default()
+        };
+        data.record_update(&GenericUpdate::from(&update), true);
+    }
+
+    async fn updates(state: &ServerState, headers: HeaderMap, count: u64) -> Response {
+        let query = UpdatesQuery {
+            start_period: 1,
+            count,
+        };
+        get_updates(State(state.clone()), headers, Query(query))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_updates_cached() {
+        let state = server_state();
+        record_update(&state.data, 1);
+
+        let response = updates(&state, HeaderMap::new(), 1).await;
+        assert_eq!(StatusCode::OK, response.status());
+        // A better update of the past period may still be verified.
+        assert_eq!(
+            format!("public, max-age={PAST_PERIOD_MAX_AGE}"),
+            response.headers()[CACHE_CONTROL]
+        );
+        let etag = response.headers()[ETAG].clone();
+
+        // A missing update may be verified in the next slots.
+        let response = updates(&state, HeaderMap::new(), 2).await;
+        let cache_control = response.headers()[CACHE_CONTROL].to_str().unwrap();
+        assert_ne!(
+            format!("public, max-age={PAST_PERIOD_MAX_AGE}"),
+            cache_control
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, etag.clone());
+        let response = updates(&state, headers, 1).await;
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+        assert_eq!(etag, response.headers()[ETAG]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static("\"other\""));
+        let response = updates(&state, headers, 1).await;
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[tokio::test]
+    async fn test_get_updates_errors() {
+        let state = server_state();
+        let query = UpdatesQuery {
+            start_period: 1,
+            count: MAX_REQUEST_LIGHT_CLIENT_UPDATES + 1,
+        };
+        let err = get_updates(State(state.clone()), HeaderMap::new(), Query(query))
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, err.code);
+
+        let err = get_finality_update(State(state), HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::NOT_FOUND, err.code);
+    }
+
+    #[tokio::test]
+    async fn test_capella_refused() {
+        let state = server_state();
+        // The period of the Capella fork of mainnet.
+        record_update(&state.data, 758);
+
+        let err = get_optimistic_update(State(state), HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::NOT_IMPLEMENTED, err.code);
+    }
+}
@@ -2,13 +2,32 @@ use std::sync::Arc;
 
 use common::types::{Block, BlockTag};
 use config::Config;
-use consensus::{database::Database, rpc::nimbus_rpc::NimbusRpc, ConsensusClient};
+use consensus::{
+    database::Database, rpc::nimbus_rpc::NimbusRpc, types::encoding::UpdatesResponse,
+    ConsensusClient,
+};
 use ethers::{
     prelude::{Address, EIP1186ProofResponse, U256},
-    types::{Filter, Log, SyncProgress, SyncingStatus, Transaction, TransactionReceipt, H256},
+    types::{
+        FeeHistory, Filter, Log, SyncProgress, SyncingStatus, Transaction, TransactionReceipt, H256,
+    },
+};
+use execution::{
+    broadcast::{BroadcastEvent, Broadcaster},
+    ens::EnsResolverLayout,
+    evm::Evm,
+    quorum::{QuorumChecked, QuorumRpc},
+    rpc::http_rpc::HttpRpc,
+    state::State,
+    types::{
+        AccountProofs, BeaconRootProof, BlockReceiptsProof, CallOpts, CodeProof, Erc20Balance,
+        ProofBundle, ProvenNonce, ProvenStorage, TransactionCounts,
+    },
+    watcher::{WatchEvent, WatchTarget, Watcher},
+    ExecutionClient,
 };
-use execution::{evm::Evm, rpc::http_rpc::HttpRpc, state::State, types::CallOpts, ExecutionClient};
 use eyre::{eyre, Result};
+use tokio::sync::{mpsc, watch};
 use zduny_wasm_timer::{SystemTime, UNIX_EPOCH};
 
 use crate::errors::NodeError;
@@ -18,6 +37,9 @@ pub struct Node<DB: Database> {
     pub execution: Arc<ExecutionClient<HttpRpc>>,
     pub config: Arc<Config>,
     pub history_size: usize,
+    finalized_block_recv: watch::Receiver<Option<Block>>,
+    /// The execution RPC and the `quorum_rpcs`, for the data that no proof covers.
+    quorum_rpc: QuorumRpc<HttpRpc>,
 }
 
 impl<DB: Database> Node<DB> {
@@ -31,17 +53,21 @@ impl<DB: Database> Node<DB> {
         let block_recv = consensus.block_recv.take().unwrap();
         let finalized_block_recv = consensus.finalized_block_recv.take().unwrap();
 
-        let state = State::new(block_recv, finalized_block_recv, 256);
+        let state = State::new(block_recv, finalized_block_recv.clone(), 256);
         let execution = Arc::new(
             ExecutionClient::new(execution_rpc, state)
                 .map_err(NodeError::ExecutionClientCreationError)?,
         );
+        let quorum_rpc = QuorumRpc::new(&execution, &config.quorum_rpcs, config.rpc_quorum)
+            .map_err(NodeError::ExecutionClientCreationError)?;
 
         Ok(Node {
             consensus,
             execution,
             config,
             history_size: 64,
+            finalized_block_recv,
+            quorum_rpc,
         })
     }
 
@@ -129,10 +155,124 @@ impl<DB: Database> Node<DB> {
         }
     }
 
+    /// Returns the proof of `address` and `slots` at the block `tag`, verified against the state
+    /// root of the block.
+    pub async fn get_proof_bundle(
+        &self,
+        address: &Address,
+        slots: &[H256],
+        tag: BlockTag,
+    ) -> Result<ProofBundle> {
+        self.check_blocktag_age(&tag).await?;
+
+        let block = self.execution.get_block(tag, false).await?;
+        self.execution
+            .get_proof_bundle(address, slots, &block)
+            .await
+    }
+
+    /// Returns the value of `slot` of `address` at the finalized block, with its proof against
+    /// the state root of the block.
+    pub async fn get_proven_storage_at(
+        &self,
+        address: &Address,
+        slot: H256,
+    ) -> Result<ProvenStorage> {
+        self.execution.get_proven_storage(address, slot).await
+    }
+
+    /// Returns the code of `address` at `tag`, with the proof of the account against the state
+    /// root of the block.
+    pub async fn get_code_proof(&self, address: &Address, tag: BlockTag) -> Result<CodeProof> {
+        self.check_blocktag_age(&tag).await?;
+
+        let block = self.execution.get_block(tag, false).await?;
+        self.execution.get_code_proof(address, &block).await
+    }
+
+    /// Returns the nonce of `address` at the finalized block, with its proof against the state
+    /// root of the block.
+    pub async fn get_proven_nonce(&self, address: &Address) -> Result<ProvenNonce> {
+        self.execution.get_proven_nonce(address).await
+    }
+
+    /// Returns the light client updates of the `count` periods from `start_period`, with the
+    /// latest finality and optimistic updates, encoded with
+    /// [`UpdatesResponse::serialize_to_bytes`].
+    pub async fn get_updates_bcs(&self, start_period: u64, count: u64) -> Result<Vec<u8>> {
+        let updates = self.consensus.get_updates(start_period, count).await?;
+        UpdatesResponse::from(&updates).serialize_to_bytes()
+    }
+
+    /// Returns the proof that the beacon roots contract stores the parent beacon block root of the
+    /// finalized block.
+    pub async fn get_beacon_root_proof(&self) -> Result<BeaconRootProof> {
+        self.execution.get_beacon_root_proof().await
+    }
+
+    /// Returns the proofs of `addresses`, verified against the finalized state root.
+    pub async fn create_account_proofs(&self, addresses: &[Address]) -> Result<AccountProofs> {
+        self.execution.create_account_proofs(addresses).await
+    }
+
+    /// Returns the nonces of `addresses`, proven against the finalized state root.
+    pub async fn get_transaction_counts(&self, addresses: &[Address]) -> Result<TransactionCounts> {
+        self.execution.get_transaction_counts(addresses).await
+    }
+
+    /// Returns the ERC-20 balance of `holder` in `token`, proven against the finalized state root.
+    pub async fn get_erc20_balance(
+        &self,
+        token: &Address,
+        holder: &Address,
+        balances_slot: u64,
+    ) -> Result<Erc20Balance> {
+        self.execution
+            .get_erc20_balance(token, holder, balances_slot)
+            .await
+    }
+
+    /// Resolves the ENS `name` from storage proven against the finalized state root.
+    pub async fn resolve_ens(
+        &self,
+        name: &str,
+        layout: &EnsResolverLayout,
+    ) -> Result<Option<Address>> {
+        self.execution.resolve_ens(name, layout).await
+    }
+
+    /// Starts watching `targets`, which are proven again at every new finalized block, until the
+    /// client is shut down.
+    pub fn watch(&self, targets: Vec<WatchTarget>) -> mpsc::Receiver<WatchEvent> {
+        Watcher::new(self.execution.clone(), targets).spawn_with_cancellation(
+            self.finalized_block_recv.clone(),
+            self.consensus.cancellation_token(),
+        )
+    }
+
     pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<H256> {
         self.execution.send_raw_transaction(bytes).await
     }
 
+    /// Broadcasts a transaction to the execution RPC and to the `broadcast_rpcs`, and returns its
+    /// receipt once it is proven against a finalized block, or fails once the client is shut
+    /// down.
+    pub async fn send_and_confirm_transaction(
+        &self,
+        bytes: &[u8],
+        events: Option<mpsc::Sender<BroadcastEvent>>,
+    ) -> Result<TransactionReceipt> {
+        let broadcaster = Broadcaster::new(self.execution.clone(), &self.config.broadcast_rpcs)?;
+        broadcaster
+            .send_raw_transaction(
+                bytes,
+                self.finalized_block_recv.clone(),
+                events,
+                self.consensus.cancellation_token(),
+            )
+            .await
+    }
+
     pub async fn get_transaction_receipt(
         &self,
         tx_hash: &H256,
@@ -144,6 +284,19 @@ impl<DB: Database> Node<DB> {
         self.execution.get_transaction(*tx_hash).await
     }
 
+    /// Returns the transaction `tx_hash` of a finalized block, proven to be in the transactions
+    /// trie of the block. Fails for the transactions that are not finalized yet.
+    pub async fn get_verified_transaction_by_hash(&self, tx_hash: &H256) -> Result<Transaction> {
+        self.execution.get_verified_transaction(*tx_hash).await
+    }
+
+    /// Returns the receipts of every transaction of the finalized block `tag`, proven against the
+    /// receipts root of the block.
+    pub async fn get_verified_block_receipts(&self, tag: BlockTag) -> Result<BlockReceiptsProof> {
+        self.check_blocktag_age(&tag).await?;
+        self.execution.get_verified_block_receipts(tag).await
+    }
+
     pub async fn get_transaction_by_block_hash_and_index(
         &self,
         hash: &H256,
@@ -194,6 +347,24 @@ impl<DB: Database> Node<DB> {
         Ok(tip)
     }
 
+    /// Returns the nonce of `address` in the pending state, once the execution RPC and the
+    /// `quorum_rpcs` agree on it. It is not verified.
+    pub async fn get_pending_nonce(&self, address: &Address) -> Result<QuorumChecked<u64>> {
+        self.quorum_rpc.get_pending_nonce(address).await
+    }
+
+    /// Returns the gas used by the call `opts` against the pending state, once the execution RPC
+    /// and the `quorum_rpcs` agree on it. It is not verified, unlike [`Self::estimate_gas`].
+    pub async fn estimate_pending_gas(&self, opts: &CallOpts) -> Result<QuorumChecked<u64>> {
+        self.quorum_rpc.estimate_gas(opts).await
+    }
+
+    /// Returns the priority fee suggested by the execution RPC and the `quorum_rpcs`, once they
+    /// agree on it. It is not verified.
+    pub async fn get_quorum_priority_fee(&self) -> Result<QuorumChecked<U256>> {
+        self.quorum_rpc.max_priority_fee_per_gas().await
+    }
+
     pub async fn get_block_number(&self) -> Result<U256> {
         self.check_head_age().await?;
 
@@ -219,6 +390,40 @@ impl<DB: Database> Node<DB> {
         }
     }
 
+    /// Returns the finalized block `tag`, cross-checked against the block of the execution RPC.
+    pub async fn get_verified_block_by_number(
+        &self,
+        tag: BlockTag,
+        full_tx: bool,
+    ) -> Result<Block> {
+        self.execution.get_verified_block(tag, full_tx).await
+    }
+
+    pub async fn get_verified_block_by_hash(&self, hash: &H256, full_tx: bool) -> Result<Block> {
+        self.execution
+            .get_verified_block_by_hash(*hash, full_tx)
+            .await
+    }
+
+    /// Returns the fee history of the verified blocks up to `last_block`, see
+    /// [`ExecutionClient::get_fee_history`].
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        last_block: BlockTag,
+    ) -> Result<FeeHistory> {
+        self.check_blocktag_age(&last_block).await?;
+        self.execution
+            .get_fee_history(block_count, last_block)
+            .await
+    }
+
+    /// Returns the base fee of the next block, derived from the latest verified block.
+    pub async fn suggest_base_fee(&self) -> Result<U256> {
+        self.check_head_age().await?;
+        self.execution.suggest_base_fee().await
+    }
+
     pub fn chain_id(&self) -> u64 {
         self.config.chain.chain_id
     }
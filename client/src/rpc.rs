@@ -9,18 +9,24 @@ use common::{
     types::{Block, BlockTag},
     utils::{hex_str_to_bytes, u64_to_hex_string},
 };
-use consensus::database::Database;
+use consensus::{constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES, database::Database};
 use ethers::{
     abi::AbiEncode,
-    types::{Address, Filter, Log, SyncingStatus, Transaction, TransactionReceipt, H256, U256},
+    types::{
+        Address, Filter, Log, SyncingStatus, Transaction, TransactionReceipt, H256, U256, U64,
+    },
+};
+use execution::{
+    quorum::QuorumChecked,
+    types::{BlockReceiptsProof, CallOpts, ProofBundle},
 };
-use execution::types::CallOpts;
 use eyre::Result;
 use jsonrpsee::{
     core::{async_trait, server::Methods, Error},
     proc_macros::rpc,
     server::{ServerBuilder, ServerHandle},
 };
+use serde::Serialize;
 use tracing::info;
 
 use crate::{errors::NodeError, node::Node};
@@ -57,6 +63,14 @@ impl<DB: Database> Rpc<DB> {
 
         Ok(addr)
     }
+
+    /// Stops the server, if it was started. The requests in progress are completed.
+    pub fn stop(&self) {
+        if let Some(handle) = &self.handle {
+            // The server may have stopped on its own.
+            _ = handle.stop();
+        }
+    }
 }
 
 #[rpc(server, namespace = "eth")]
@@ -138,6 +152,54 @@ trait NetRpc {
     async fn version(&self) -> Result<String, Error>;
 }
 
+/// The verified data served to the services that cannot embed the light client. The server only
+/// serves these methods when `helios_rpc` is set in the configuration.
+#[rpc(server, namespace = "helios")]
+trait HeliosRpc {
+    #[method(name = "getFinalizedStateRoot")]
+    async fn get_finalized_state_root(&self) -> Result<FinalizedStateRoot, Error>;
+    #[method(name = "getAccountProof")]
+    async fn get_account_proof(
+        &self,
+        address: &str,
+        slots: Vec<H256>,
+        block: BlockTag,
+    ) -> Result<ProofBundle, Error>;
+    #[method(name = "getUpdatesBcs")]
+    async fn get_updates_bcs(&self, start_period: u64, count: u64) -> Result<String, Error>;
+    #[method(name = "getVerifiedBlockByNumber")]
+    async fn get_verified_block_by_number(
+        &self,
+        block: BlockTag,
+        full_tx: bool,
+    ) -> Result<Block, Error>;
+    #[method(name = "getVerifiedBlockByHash")]
+    async fn get_verified_block_by_hash(&self, hash: H256, full_tx: bool) -> Result<Block, Error>;
+    #[method(name = "getVerifiedTransactionByHash")]
+    async fn get_verified_transaction_by_hash(&self, hash: H256) -> Result<Transaction, Error>;
+    #[method(name = "getVerifiedBlockReceipts")]
+    async fn get_verified_block_receipts(
+        &self,
+        block: BlockTag,
+    ) -> Result<BlockReceiptsProof, Error>;
+    #[method(name = "getPendingNonce")]
+    async fn get_pending_nonce(&self, address: &str) -> Result<QuorumChecked<u64>, Error>;
+    #[method(name = "estimatePendingGas")]
+    async fn estimate_pending_gas(&self, opts: CallOpts) -> Result<QuorumChecked<u64>, Error>;
+    #[method(name = "getQuorumPriorityFee")]
+    async fn get_quorum_priority_fee(&self) -> Result<QuorumChecked<U256>, Error>;
+}
+
+/// The state root of the finalized execution block, whose payload is verified against the
+/// finalized beacon header.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalizedStateRoot {
+    pub block_number: U64,
+    pub block_hash: H256,
+    pub state_root: H256,
+}
+
 #[derive(Clone)]
 struct RpcInner<DB: Database> {
     node: Arc<Node<DB>>,
@@ -317,17 +379,97 @@ impl<DB: Database> NetRpcServer for RpcInner<DB> {
     }
 }
 
+#[async_trait]
+impl<DB: Database> HeliosRpcServer for RpcInner<DB> {
+    async fn get_finalized_state_root(&self) -> Result<FinalizedStateRoot, Error> {
+        let block = convert_err(
+            self.node
+                .get_block_by_number(BlockTag::Finalized, false)
+                .await,
+        )?
+        .ok_or_else(|| Error::Custom("no finalized block".to_string()))?;
+
+        Ok(FinalizedStateRoot {
+            block_number: block.number,
+            block_hash: block.hash,
+            state_root: block.state_root,
+        })
+    }
+
+    async fn get_account_proof(
+        &self,
+        address: &str,
+        slots: Vec<H256>,
+        block: BlockTag,
+    ) -> Result<ProofBundle, Error> {
+        let address = convert_err(Address::from_str(address))?;
+        convert_err(self.node.get_proof_bundle(&address, &slots, block).await)
+    }
+
+    async fn get_updates_bcs(&self, start_period: u64, count: u64) -> Result<String, Error> {
+        if count > MAX_REQUEST_LIGHT_CLIENT_UPDATES {
+            return Err(Error::Custom(format!(
+                "at most {MAX_REQUEST_LIGHT_CLIENT_UPDATES} updates can be requested"
+            )));
+        }
+
+        let bytes = convert_err(self.node.get_updates_bcs(start_period, count).await)?;
+        Ok(format!("0x{}", hex::encode(bytes)))
+    }
+
+    async fn get_verified_block_by_number(
+        &self,
+        block: BlockTag,
+        full_tx: bool,
+    ) -> Result<Block, Error> {
+        convert_err(self.node.get_verified_block_by_number(block, full_tx).await)
+    }
+
+    async fn get_verified_block_by_hash(&self, hash: H256, full_tx: bool) -> Result<Block, Error> {
+        convert_err(self.node.get_verified_block_by_hash(&hash, full_tx).await)
+    }
+
+    async fn get_verified_transaction_by_hash(&self, hash: H256) -> Result<Transaction, Error> {
+        convert_err(self.node.get_verified_transaction_by_hash(&hash).await)
+    }
+
+    async fn get_verified_block_receipts(
+        &self,
+        block: BlockTag,
+    ) -> Result<BlockReceiptsProof, Error> {
+        convert_err(self.node.get_verified_block_receipts(block).await)
+    }
+
+    async fn get_pending_nonce(&self, address: &str) -> Result<QuorumChecked<u64>, Error> {
+        let address = convert_err(Address::from_str(address))?;
+        convert_err(self.node.get_pending_nonce(&address).await)
+    }
+
+    async fn estimate_pending_gas(&self, opts: CallOpts) -> Result<QuorumChecked<u64>, Error> {
+        convert_err(self.node.estimate_pending_gas(&opts).await)
+    }
+
+    async fn get_quorum_priority_fee(&self) -> Result<QuorumChecked<U256>, Error> {
+        convert_err(self.node.get_quorum_priority_fee().await)
+    }
+}
+
 async fn start<DB: Database>(rpc: RpcInner<DB>) -> Result<(ServerHandle, SocketAddr)> {
     let server = ServerBuilder::default().build(rpc.address).await?;
     let addr = server.local_addr()?;
 
     let mut methods = Methods::new();
     let eth_methods: Methods = EthRpcServer::into_rpc(rpc.clone()).into();
-    let net_methods: Methods = NetRpcServer::into_rpc(rpc).into();
+    let net_methods: Methods = NetRpcServer::into_rpc(rpc.clone()).into();
 
     methods.merge(eth_methods)?;
     methods.merge(net_methods)?;
 
+    if rpc.node.config.helios_rpc {
+        let helios_methods: Methods = HeliosRpcServer::into_rpc(rpc).into();
+        methods.merge(helios_methods)?;
+    }
+
     let handle = server.start(methods)?;
 
     Ok((handle, addr))
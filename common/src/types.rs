@@ -27,6 +27,14 @@ pub struct Block {
     pub transactions: Transactions,
     pub transactions_root: H256,
     pub uncles: Vec<H256>,
+    /// The root of the parent beacon block, stored by the EIP-4788 beacon roots contract.
+    /// Only set for Deneb blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+    /// The root of the trie of the withdrawals, rebuilt from the verified payload. Only set for
+    /// Capella blocks and later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
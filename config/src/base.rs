@@ -95,6 +95,41 @@ impl BaseConfig {
             checkpoint: None,
             fallback: None,
             database_type: None,
+            force_update_timeout: None,
+            sync_concurrency: None,
+            rpc_retry: Default::default(),
+            rpc_rate_limits: Default::default(),
+            rpc_credentials: Default::default(),
+            rpc_tls: Default::default(),
+            rpc_proxy: None,
+            rpc_proxies: Default::default(),
+            rpc_record_dir: None,
+            gossip_peers: Vec::new(),
+            fork_overrides: Default::default(),
+            rpc_unknown_forks: false,
+            slot_clock: Default::default(),
+            advance: Default::default(),
+            retention: Default::default(),
+            backup: None,
+            sync_health: Default::default(),
+            helios_rpc: false,
+            grpc_port: None,
+            light_client_server_port: None,
+            broadcast_rpcs: Vec::new(),
+            sync_from_genesis: false,
+            update_archive: None,
+            checkpoint_services: Vec::new(),
+            checkpoint_quorum: None,
+            stale_checkpoint_age: None,
+            encrypt_database: false,
+            database_key: None,
+            database_url: None,
+            database_snapshot: None,
+            min_optimistic_participation: None,
+            strict_finality: false,
+            verify_transactions_root: false,
+            quorum_rpcs: Vec::new(),
+            rpc_quorum: None,
         }
     }
 }
@@ -210,6 +210,75 @@ impl CheckpointFallback {
         most_common.ok_or_else(|| eyre::eyre!("No checkpoint found"))
     }
 
+    /// Fetch the latest checkpoint that the checkpoint sync services at `endpoints` agree on.
+    ///
+    /// Unlike [`Self::fetch_latest_checkpoint_from_services`], which returns the most common
+    /// checkpoint, a checkpoint is only returned when at least `quorum` of the services report it,
+    /// and none reports another one for the same epoch. The quorum defaults to all the services.
+    pub async fn fetch_agreed_checkpoint(
+        endpoints: &[String],
+        quorum: Option<usize>,
+    ) -> Result<H256> {
+        let quorum = quorum.unwrap_or(endpoints.len());
+        let tasks = endpoints
+            .iter()
+            .map(|endpoint| Self::query_service(endpoint));
+        let responses = futures::future::join_all(tasks)
+            .await
+            .into_iter()
+            .map(|raw| raw.map(|raw| raw.data.slots).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        Self::agreed_checkpoint(&responses, quorum)
+    }
+
+    /// Returns the block root of the latest epoch for which at least `quorum` of the `responses`,
+    /// the slots returned by each service, report the same checkpoint.
+    ///
+    /// Fails if the services report different checkpoints for that epoch, since one of them is
+    /// then wrong or malicious.
+    pub fn agreed_checkpoint(responses: &[Vec<Slot>], quorum: usize) -> Result<H256> {
+        if quorum == 0 {
+            return Err(eyre::eyre!("the checkpoint quorum must be at least 1"));
+        }
+
+        // The checkpoints reported by each service for every epoch, counting a service once.
+        let mut epochs: HashMap<u64, HashMap<H256, usize>> = HashMap::new();
+        for slots in responses {
+            let mut reported = HashMap::new();
+            for slot in slots {
+                if let Some(block_root) = slot.block_root {
+                    reported.entry(slot.epoch).or_insert(block_root);
+                }
+            }
+
+            for (epoch, block_root) in reported {
+                *epochs
+                    .entry(epoch)
+                    .or_default()
+                    .entry(block_root)
+                    .or_default() += 1;
+            }
+        }
+
+        let mut candidates = epochs
+            .into_iter()
+            .filter(|(_, roots)| roots.values().sum::<usize>() >= quorum)
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(epoch, _)| *epoch);
+
+        let (epoch, roots) = candidates.pop().ok_or_else(|| {
+            eyre::eyre!("fewer than {quorum} checkpoint services reported a checkpoint")
+        })?;
+
+        match roots.into_iter().collect::<Vec<_>>()[..] {
+            [(block_root, _)] => Ok(block_root),
+            _ => Err(eyre::eyre!(
+                "checkpoint services disagree on the checkpoint of epoch {epoch}"
+            )),
+        }
+    }
+
     /// Associated function to fetch the latest checkpoint from a specific checkpoint sync fallback
     /// service api url.
     pub async fn fetch_checkpoint_from_api(url: &str) -> Result<H256> {
@@ -15,6 +15,23 @@ pub struct CliConfig {
     pub fallback: Option<String>,
     pub load_external_fallback: Option<bool>,
     pub strict_checkpoint_age: Option<bool>,
+    pub helios_rpc: Option<bool>,
+    pub grpc_port: Option<u16>,
+    pub light_client_server_port: Option<u16>,
+    pub broadcast_rpcs: Option<Vec<String>>,
+    pub sync_from_genesis: Option<bool>,
+    pub update_archive: Option<PathBuf>,
+    pub checkpoint_services: Option<Vec<String>>,
+    pub checkpoint_quorum: Option<usize>,
+    pub stale_checkpoint_age: Option<u64>,
+    pub encrypt_database: Option<bool>,
+    pub database_type: Option<String>,
+    pub database_url: Option<String>,
+    pub min_optimistic_participation: Option<f64>,
+    pub strict_finality: Option<bool>,
+    pub verify_transactions_root: Option<bool>,
+    pub quorum_rpcs: Option<Vec<String>>,
+    pub rpc_quorum: Option<usize>,
 }
 
 impl CliConfig {
@@ -57,6 +74,74 @@ impl CliConfig {
             user_dict.insert("strict_checkpoint_age", Value::from(s));
         }
 
+        if let Some(h) = self.helios_rpc {
+            user_dict.insert("helios_rpc", Value::from(h));
+        }
+
+        if let Some(port) = self.grpc_port {
+            user_dict.insert("grpc_port", Value::from(port));
+        }
+
+        if let Some(port) = self.light_client_server_port {
+            user_dict.insert("light_client_server_port", Value::from(port));
+        }
+
+        if let Some(rpcs) = &self.broadcast_rpcs {
+            user_dict.insert("broadcast_rpcs", Value::from(rpcs.clone()));
+        }
+
+        if let Some(genesis) = self.sync_from_genesis {
+            user_dict.insert("sync_from_genesis", Value::from(genesis));
+        }
+
+        if let Some(archive) = self.update_archive.as_ref() {
+            user_dict.insert("update_archive", Value::from(archive.to_str().unwrap()));
+        }
+
+        if let Some(services) = &self.checkpoint_services {
+            user_dict.insert("checkpoint_services", Value::from(services.clone()));
+        }
+
+        if let Some(quorum) = self.checkpoint_quorum {
+            user_dict.insert("checkpoint_quorum", Value::from(quorum));
+        }
+
+        if let Some(age) = self.stale_checkpoint_age {
+            user_dict.insert("stale_checkpoint_age", Value::from(age));
+        }
+
+        if let Some(encrypt) = self.encrypt_database {
+            user_dict.insert("encrypt_database", Value::from(encrypt));
+        }
+
+        if let Some(database_type) = &self.database_type {
+            user_dict.insert("database_type", Value::from(database_type.clone()));
+        }
+
+        if let Some(url) = &self.database_url {
+            user_dict.insert("database_url", Value::from(url.clone()));
+        }
+
+        if let Some(participation) = self.min_optimistic_participation {
+            user_dict.insert("min_optimistic_participation", Value::from(participation));
+        }
+
+        if let Some(strict) = self.strict_finality {
+            user_dict.insert("strict_finality", Value::from(strict));
+        }
+
+        if let Some(verify) = self.verify_transactions_root {
+            user_dict.insert("verify_transactions_root", Value::from(verify));
+        }
+
+        if let Some(rpcs) = &self.quorum_rpcs {
+            user_dict.insert("quorum_rpcs", Value::from(rpcs.clone()));
+        }
+
+        if let Some(quorum) = self.rpc_quorum {
+            user_dict.insert("rpc_quorum", Value::from(quorum));
+        }
+
         Serialized::from(user_dict, network)
     }
 }
@@ -1,24 +1,38 @@
 use std::{
+    collections::HashMap,
     net::{IpAddr, Ipv4Addr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::exit,
     str::FromStr,
 };
 
 use figment::{
-    providers::{Format, Serialized, Toml},
-    Figment,
+    providers::{Env, Format, Serialized, Toml},
+    Figment, Provider,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
     base::BaseConfig,
     cli::CliConfig,
-    types::{ChainConfig, Forks},
+    fork_schedule::ForkSchedule,
+    types::{
+        AdvanceConfig, BackupConfig, ChainConfig, DatabaseKey, ForkOverride, Forks, RateLimit,
+        RetentionConfig, RetryPolicy, RpcCredentials, SlotClockConfig, SyncHealthConfig, TlsConfig,
+    },
     utils::{bytes_deserialize, bytes_opt_deserialize, bytes_serialize},
     Network,
 };
 
+/// The prefix of the environment variables that override the config file, such as
+/// `HELIOS_CONSENSUS_RPC` or `HELIOS_CHECKPOINT`. The fields of the tables are separated by `__`,
+/// as in `HELIOS_SLOT_CLOCK__SECONDS_PER_SLOT`.
+pub const ENV_PREFIX: &str = "HELIOS_";
+
+/// The environment variable of the hex key of the encryption of the database, when the config has
+/// no [`Config::database_key`].
+pub const DATABASE_KEY_ENV: &str = "HELIOS_DATABASE_KEY";
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Config {
     pub consensus_rpc: String,
@@ -41,31 +55,164 @@ pub struct Config {
     pub fallback: Option<String>,
     pub load_external_fallback: bool,
     pub strict_checkpoint_age: bool,
+    /// The database of the checkpoints: `file`, `sled` or `postgres` for the CLI, the files of
+    /// the data dir by default, or `config` or `localstorage` in the browser.
     pub database_type: Option<String>,
+    /// The number of slots without a finality update after which the best valid non-finalized
+    /// update is force applied. Force updates are disabled when this is not set.
+    #[serde(default)]
+    pub force_update_timeout: Option<u64>,
+    /// The number of update requests issued concurrently while syncing from an old checkpoint.
+    /// Updates are fetched with a single request when this is not set.
+    #[serde(default)]
+    pub sync_concurrency: Option<usize>,
+    /// The retry policy of the requests to the consensus RPC.
+    #[serde(default)]
+    pub rpc_retry: RetryPolicy,
+    /// The rate limits of the consensus RPC endpoints, by URL. Requests to endpoints without a
+    /// rate limit are not limited.
+    #[serde(default)]
+    pub rpc_rate_limits: HashMap<String, RateLimit>,
+    /// The credentials of the consensus RPC endpoints, by URL.
+    #[serde(default)]
+    pub rpc_credentials: HashMap<String, RpcCredentials>,
+    /// The TLS configuration of the consensus RPC endpoints, by URL.
+    #[serde(default)]
+    pub rpc_tls: HashMap<String, TlsConfig>,
+    /// The proxy of the requests to the consensus RPC, such as `socks5h://127.0.0.1:9050` or
+    /// `http://proxy:3128`.
+    #[serde(default)]
+    pub rpc_proxy: Option<String>,
+    /// The proxies of specific consensus RPC endpoints, by URL, which override `rpc_proxy`. An
+    /// empty proxy connects to the endpoint directly.
+    #[serde(default)]
+    pub rpc_proxies: HashMap<String, String>,
+    /// The directory to which the responses of the consensus RPC are recorded, when it is a
+    /// recording RPC.
+    #[serde(default)]
+    pub rpc_record_dir: Option<PathBuf>,
+    /// The multiaddrs of the peers from the gossip of which the finality and optimistic updates
+    /// are also received, when the consensus RPC is a gossip RPC.
+    #[serde(default)]
+    pub gossip_peers: Vec<String>,
+    /// The overrides of the epochs and versions of the forks of the network, by fork name, such as
+    /// Deneb at epoch 0 on a local devnet. They are applied when the config is loaded.
+    #[serde(default)]
+    pub fork_overrides: HashMap<String, ForkOverride>,
+    /// Whether the responses of the consensus RPC for forks that are not supported yet are
+    /// parsed as the latest supported fork, instead of failing the requests.
+    #[serde(default)]
+    pub rpc_unknown_forks: bool,
+    /// The length of a slot and the tolerated drift of the local clock.
+    #[serde(default)]
+    pub slot_clock: SlotClockConfig,
+    /// The schedule of the advancement loop.
+    #[serde(default)]
+    pub advance: AdvanceConfig,
+    /// How long the verified data is kept, see [`RetentionConfig`].
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Backs the verified state up to an object store, sealed with the key of
+    /// [`Self::encrypt_database`], see [`BackupConfig`].
+    #[serde(default)]
+    pub backup: Option<BackupConfig>,
+    /// The thresholds of the warnings of a degraded sync, see [`SyncHealthConfig`].
+    #[serde(default)]
+    pub sync_health: SyncHealthConfig,
+    /// Whether the RPC server also serves the verified data of the `helios` namespace, such as
+    /// the finalized state root and the light client updates.
+    #[serde(default)]
+    pub helios_rpc: bool,
+    /// The port of the gRPC server, which is only started when this is set and the client is
+    /// built with the `grpc` feature. It binds to `rpc_bind_ip`.
+    #[serde(default)]
+    pub grpc_port: Option<u16>,
+    /// The port on which the verified light client data is served with the light client
+    /// endpoints of the beacon API, for other light clients to sync from. It binds to
+    /// `rpc_bind_ip`.
+    #[serde(default)]
+    pub light_client_server_port: Option<u16>,
+    /// The additional execution RPCs that transactions are broadcast to, along with
+    /// `execution_rpc`.
+    #[serde(default)]
+    pub broadcast_rpcs: Vec<String>,
+    /// Whether to bootstrap from the genesis block instead of a checkpoint when no checkpoint is
+    /// given or saved, for the devnets that were launched too recently to have one. The genesis
+    /// block must have the [`ChainConfig::genesis_block_root`] of the chain.
+    #[serde(default)]
+    pub sync_from_genesis: bool,
+    /// The archive of light client updates, a directory or an era file, that is imported after
+    /// bootstrapping and before fetching the remaining updates from the consensus RPC.
+    #[serde(default)]
+    pub update_archive: Option<PathBuf>,
+    /// The allowlist of checkpoint sync services, such as checkpointz instances, that the
+    /// checkpoint is fetched from when none is given or saved, or when syncing fails. A
+    /// checkpoint is only used when the services agree on it.
+    #[serde(default)]
+    pub checkpoint_services: Vec<String>,
+    /// The number of `checkpoint_services` that must report the same checkpoint, all of them by
+    /// default.
+    #[serde(default)]
+    pub checkpoint_quorum: Option<usize>,
+    /// The age, in seconds, beyond which a checkpoint is too old to sync forward from. The
+    /// client then bootstraps from a fresh checkpoint of its fallbacks instead of catching up
+    /// period by period. Disabled when unset.
+    #[serde(default)]
+    pub stale_checkpoint_age: Option<u64>,
+    /// Encrypts the checkpoint and the other contents of the database with AES-256-GCM, with the
+    /// key of `database_key`, or else the hex key of the [`DATABASE_KEY_ENV`] environment
+    /// variable.
+    #[serde(default)]
+    pub encrypt_database: bool,
+    /// Returns the key of `encrypt_database`, e.g. from a KMS. It cannot be set in the config
+    /// file.
+    #[serde(skip)]
+    pub database_key: Option<DatabaseKey>,
+    /// The connection string of a PostgreSQL database, e.g. `postgres://helios@localhost/helios`,
+    /// in which the checkpoints, the verified headers and the conflict evidence are kept instead
+    /// of the data dir. Only used by the builds with the `postgres` feature.
+    #[serde(default)]
+    pub database_url: Option<String>,
+    /// The contents exported by the `MemoryDB` of a previous run, which the `MemoryDB` of this
+    /// run starts from. It cannot be set in the config file.
+    #[serde(skip)]
+    pub database_snapshot: Option<Vec<u8>>,
+    /// The minimum sync committee participation, between 0 and 1, of the optimistic updates that
+    /// are accepted, on top of the one of the specification. The finality updates below it are
+    /// still applied, but do not advance the optimistic header. Disabled when unset.
+    #[serde(default)]
+    pub min_optimistic_participation: Option<f64>,
+    /// Only advances on the updates of which the finality is proven: the optimistic and safe
+    /// headers are the finalized header, so that no optimistic data is served or proven. It
+    /// cannot be combined with `force_update_timeout`.
+    #[serde(default)]
+    pub strict_finality: bool,
+    /// Rebuilds the transactions trie of the execution payloads from their transactions, and
+    /// checks the transactions root of the blocks returned by the execution RPC against it,
+    /// instead of trusting it through the header hash.
+    #[serde(default)]
+    pub verify_transactions_root: bool,
+    /// The additional execution RPCs that the data no proof covers, such as the pending state and
+    /// gas estimates, is queried from, along with `execution_rpc`. That data is only returned when
+    /// `rpc_quorum` of the RPCs agree on it, and is still not verified.
+    #[serde(default)]
+    pub quorum_rpcs: Vec<String>,
+    /// The number of execution RPCs, among `execution_rpc` and the `quorum_rpcs`, that must return
+    /// the same unproven data, all of them by default.
+    #[serde(default)]
+    pub rpc_quorum: Option<usize>,
 }
 
 impl Config {
+    /// Loads the config of `network` from the defaults of the network, overridden by the config
+    /// file, the environment variables prefixed with [`ENV_PREFIX`] and then the command line
+    /// arguments. Exits with a diagnostic if the config cannot be loaded.
     pub fn from_file(config_path: &PathBuf, network: &str, cli_config: &CliConfig) -> Self {
-        let base_config = Network::from_str(network)
-            .map(|n| n.to_base_config())
-            .unwrap_or(BaseConfig::default());
-
-        let base_provider = Serialized::from(base_config, network);
-        let toml_provider = Toml::file(config_path).nested();
-        let cli_provider = cli_config.as_provider(network);
-
-        let config_res = Figment::new()
-            .merge(base_provider)
-            .merge(toml_provider)
-            .merge(cli_provider)
-            .select(network)
-            .extract();
-
-        match config_res {
+        match Self::try_from_file(config_path, network, cli_config) {
             Ok(config) => config,
             Err(err) => {
-                match err.kind {
-                    figment::error::Kind::MissingField(field) => {
+                match err.downcast_ref::<figment::Error>().map(|err| &err.kind) {
+                    Some(figment::error::Kind::MissingField(field)) => {
                         let field = field.replace('_', "-");
 
                         println!("\x1b[91merror\x1b[0m: missing configuration field: {field}");
@@ -75,27 +222,138 @@ impl Config {
                         println!("\talternatively, you can add the field to your helios.toml file or as an environment variable");
                         println!("\nfor more information, check the GitHub README");
                     }
-                    _ => println!("cannot parse configuration: {err}"),
+                    Some(_) => println!("cannot parse configuration: {err}"),
+                    None => println!("\x1b[91merror\x1b[0m: {err}"),
                 }
                 exit(1);
             }
         }
     }
 
-    pub fn fork_version(&self, slot: u64) -> Vec<u8> {
-        let epoch = slot / 32;
-
-        if epoch >= self.forks.deneb.epoch {
-            self.forks.deneb.fork_version.clone()
-        } else if epoch >= self.forks.capella.epoch {
-            self.forks.capella.fork_version.clone()
-        } else if epoch >= self.forks.bellatrix.epoch {
-            self.forks.bellatrix.fork_version.clone()
-        } else if epoch >= self.forks.altair.epoch {
-            self.forks.altair.fork_version.clone()
-        } else {
-            self.forks.genesis.fork_version.clone()
+    /// Same as [`Self::from_file`], but returns the error instead of exiting, e.g. to keep running
+    /// with the current config when the reloaded config file is invalid.
+    pub fn try_from_file(
+        config_path: &Path,
+        network: &str,
+        cli_config: &CliConfig,
+    ) -> eyre::Result<Self> {
+        let mut config = Self::figment(network, Some(config_path), Self::env(network))
+            .merge(cli_config.as_provider(network))
+            .select(network)
+            .extract::<Config>()?;
+        config.apply_fork_overrides()?;
+
+        Ok(config)
+    }
+
+    /// Loads the config of `network` from the defaults of the network, overridden by the config
+    /// file at `config_path`, if any, and then the environment variables prefixed with
+    /// [`ENV_PREFIX`], for the deployments that are configured by their environment. The settings
+    /// of the `ClientBuilder` override the loaded config.
+    pub fn load(network: &str, config_path: Option<&Path>) -> eyre::Result<Self> {
+        let mut config = Self::figment(network, config_path, Self::env(network))
+            .select(network)
+            .extract::<Config>()?;
+        config.apply_fork_overrides()?;
+
+        Ok(config)
+    }
+
+    /// The layers of the config below the command line arguments: the defaults of the network,
+    /// the config file and the environment variables of `env`.
+    fn figment(network: &str, config_path: Option<&Path>, env: impl Provider) -> Figment {
+        let base_config = Network::from_str(network)
+            .map(|n| n.to_base_config())
+            .unwrap_or(BaseConfig::default());
+
+        let mut figment = Figment::new().merge(Serialized::from(base_config, network));
+        if let Some(config_path) = config_path {
+            figment = figment.merge(Toml::file(config_path).nested());
+        }
+
+        figment.merge(env)
+    }
+
+    /// The environment variables prefixed with [`ENV_PREFIX`]. The variables are in the profile of
+    /// the network, which overrides the default profile.
+    fn env(network: &str) -> Env {
+        Env::prefixed(ENV_PREFIX).split("__").profile(network)
+    }
+
+    fn apply_fork_overrides(&mut self) -> eyre::Result<()> {
+        let Config {
+            forks,
+            fork_overrides,
+            ..
+        } = self;
+        forks.apply_overrides(fork_overrides)
+    }
+
+    /// Replaces the settings that are applied without a restart with the ones of `config`: the
+    /// consensus RPC and the settings of its requests, and the checkpoint fallbacks. The other
+    /// settings, such as the network, the data directory and the ports, are kept.
+    pub fn reload(&mut self, config: &Config) {
+        self.consensus_rpc = config.consensus_rpc.clone();
+        self.rpc_retry = config.rpc_retry.clone();
+        self.rpc_rate_limits = config.rpc_rate_limits.clone();
+        self.rpc_credentials = config.rpc_credentials.clone();
+        self.rpc_tls = config.rpc_tls.clone();
+        self.rpc_proxy = config.rpc_proxy.clone();
+        self.rpc_proxies = config.rpc_proxies.clone();
+        self.fallback = config.fallback.clone();
+        self.load_external_fallback = config.load_external_fallback;
+        self.checkpoint_services = config.checkpoint_services.clone();
+        self.checkpoint_quorum = config.checkpoint_quorum;
+    }
+
+    /// Returns the key of the encryption of the database, from [`Self::database_key`] or else the
+    /// [`DATABASE_KEY_ENV`] environment variable, or `None` when the database is not encrypted.
+    pub fn encryption_key(&self) -> eyre::Result<Option<[u8; 32]>> {
+        if !self.encrypt_database {
+            return Ok(None);
         }
+
+        match &self.database_key {
+            Some(key) => key.get().map(Some),
+            None => env_database_key().map(Some),
+        }
+    }
+
+    /// The fork schedule of the network, from its forks and genesis validators root.
+    pub fn fork_schedule(&self) -> ForkSchedule {
+        ForkSchedule::new(&self.forks, &self.chain.genesis_root)
+    }
+
+    pub fn fork_version(&self, slot: u64) -> Vec<u8> {
+        self.fork_schedule().fork_version_at_slot(slot).to_vec()
+    }
+
+    /// The name of the fork at `slot`, as in the `version` of the beacon API responses.
+    pub fn fork_name(&self, slot: u64) -> &'static str {
+        self.fork_schedule().fork_at_slot(slot).name
+    }
+
+    /// The rate limit of the consensus RPC endpoint `rpc`, if any.
+    pub fn rpc_rate_limit(&self, rpc: &str) -> Option<&RateLimit> {
+        endpoint_entry(&self.rpc_rate_limits, rpc)
+    }
+
+    /// The credentials of the consensus RPC endpoint `rpc`, if any.
+    pub fn rpc_credentials(&self, rpc: &str) -> Option<&RpcCredentials> {
+        endpoint_entry(&self.rpc_credentials, rpc)
+    }
+
+    /// The TLS configuration of the consensus RPC endpoint `rpc`, if any.
+    pub fn rpc_tls(&self, rpc: &str) -> Option<&TlsConfig> {
+        endpoint_entry(&self.rpc_tls, rpc)
+    }
+
+    /// The proxy of the consensus RPC endpoint `rpc`, if any.
+    pub fn rpc_proxy(&self, rpc: &str) -> Option<&str> {
+        endpoint_entry(&self.rpc_proxies, rpc)
+            .or(self.rpc_proxy.as_ref())
+            .map(String::as_str)
+            .filter(|proxy| !proxy.is_empty())
     }
 
     pub fn to_base_config(&self) -> BaseConfig {
@@ -113,3 +371,63 @@ impl Config {
         }
     }
 }
+
+/// Returns the entry of the endpoint `rpc`, which may be configured with or without a trailing
+/// slash.
+fn endpoint_entry<'a, T>(entries: &'a HashMap<String, T>, rpc: &str) -> Option<&'a T> {
+    entries
+        .get(rpc)
+        .or_else(|| entries.get(rpc.trim_end_matches('/')))
+}
+
+/// Reads the hex key of [`DATABASE_KEY_ENV`].
+pub(crate) fn env_database_key() -> eyre::Result<[u8; 32]> {
+    let key = std::env::var(DATABASE_KEY_ENV)
+        .map_err(|_| eyre::eyre!("{DATABASE_KEY_ENV} is not set"))?;
+    let key = hex::decode(key.trim().trim_start_matches("0x"))?;
+
+    key.try_into()
+        .map_err(|key: Vec<u8>| eyre::eyre!("the database key must be 32 bytes, got {}", key.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_load_layers() {
+        let dir = tempdir::TempDir::new("helios-config").unwrap();
+        let config_path = dir.path().join("helios.toml");
+        fs::write(
+            &config_path,
+            "[mainnet]\nconsensus_rpc = \"http://file\"\nexecution_rpc = \"http://file\"\n",
+        )
+        .unwrap();
+
+        // The variables `HELIOS_CONSENSUS_RPC` and `HELIOS_SLOT_CLOCK__SECONDS_PER_SLOT`, without
+        // setting them in the environment of the other tests.
+        let env = Serialized::from(
+            serde_json::json!({
+                "consensus_rpc": "http://env",
+                "slot_clock": { "seconds_per_slot": 6 },
+            }),
+            "mainnet",
+        );
+        let config = Config::figment("mainnet", Some(&config_path), env)
+            .select("mainnet")
+            .extract::<Config>()
+            .unwrap();
+
+        assert_eq!("http://env", config.consensus_rpc);
+        assert_eq!("http://file", config.execution_rpc);
+        assert_eq!(6, config.slot_clock.seconds_per_slot);
+        // The defaults of the network are kept.
+        assert_eq!(1, config.chain.chain_id);
+        assert_eq!(
+            Network::MAINNET.to_base_config().default_checkpoint,
+            config.default_checkpoint
+        );
+    }
+}
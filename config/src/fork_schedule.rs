@@ -0,0 +1,134 @@
+use consensus_core::domain::compute_fork_data_root;
+use eyre::{eyre, Result};
+
+use crate::types::{Fork, Forks};
+
+/// The number of slots of an epoch.
+const SLOTS_PER_EPOCH: u64 = 32;
+
+/// A fork of the schedule of a network, with the name used in the `version` of the beacon API
+/// responses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledFork {
+    pub name: &'static str,
+    pub epoch: u64,
+    pub fork_version: Vec<u8>,
+}
+
+/// The forks of a network, in activation order, and its genesis validators root, from which the
+/// fork versions and digests of any slot are derived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkSchedule {
+    forks: Vec<ScheduledFork>,
+    genesis_root: Vec<u8>,
+}
+
+impl ForkSchedule {
+    pub fn new(forks: &Forks, genesis_root: &[u8]) -> Self {
+        let scheduled = |name: &'static str, fork: &Fork| ScheduledFork {
+            name,
+            epoch: fork.epoch,
+            fork_version: fork.fork_version.clone(),
+        };
+
+        Self {
+            forks: vec![
+                scheduled("phase0", &forks.genesis),
+                scheduled("altair", &forks.altair),
+                scheduled("bellatrix", &forks.bellatrix),
+                scheduled("capella", &forks.capella),
+                scheduled("deneb", &forks.deneb),
+            ],
+            genesis_root: genesis_root.to_vec(),
+        }
+    }
+
+    /// The known forks, in activation order, including the ones that are not scheduled yet.
+    pub fn forks(&self) -> &[ScheduledFork] {
+        &self.forks
+    }
+
+    /// The fork active at `epoch`, which is the last one activated at or before it.
+    pub fn fork_at_epoch(&self, epoch: u64) -> &ScheduledFork {
+        self.forks
+            .iter()
+            .rev()
+            .find(|fork| fork.epoch <= epoch)
+            .unwrap_or(&self.forks[0])
+    }
+
+    pub fn fork_at_slot(&self, slot: u64) -> &ScheduledFork {
+        self.fork_at_epoch(slot / SLOTS_PER_EPOCH)
+    }
+
+    pub fn fork_version_at_slot(&self, slot: u64) -> &[u8] {
+        &self.fork_at_slot(slot).fork_version
+    }
+
+    /// The fork digest at `slot`, as in the ENRs and gossip topics of the peer to peer network.
+    pub fn fork_digest_at_slot(&self, slot: u64) -> Result<[u8; 4]> {
+        let fork_version = self
+            .fork_version_at_slot(slot)
+            .try_into()
+            .map_err(|_| eyre!("invalid fork version"))?;
+        let genesis_root = self
+            .genesis_root
+            .as_slice()
+            .try_into()
+            .map_err(|_| eyre!("invalid genesis root"))?;
+
+        let root = compute_fork_data_root(fork_version, genesis_root)?;
+        Ok(root.as_ref()[..4].try_into()?)
+    }
+
+    /// The activation epoch of the fork `name`, if it is known.
+    pub fn activation_epoch(&self, name: &str) -> Option<u64> {
+        self.forks
+            .iter()
+            .find(|fork| fork.name == name)
+            .map(|fork| fork.epoch)
+    }
+
+    /// The first fork activated after `epoch`, if any is scheduled.
+    pub fn next_fork(&self, epoch: u64) -> Option<&ScheduledFork> {
+        self.forks
+            .iter()
+            .find(|fork| fork.epoch > epoch && fork.epoch != u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks;
+
+    #[test]
+    fn test_fork_schedule() {
+        let config = networks::mainnet();
+        let schedule = ForkSchedule::new(&config.forks, &config.chain.genesis_root);
+
+        assert_eq!(5, schedule.forks().len());
+        assert_eq!(Some(269568), schedule.activation_epoch("deneb"));
+        assert_eq!(None, schedule.activation_epoch("electra"));
+
+        assert_eq!("phase0", schedule.fork_at_slot(0).name);
+        assert_eq!("capella", schedule.fork_at_slot(269567 * 32).name);
+        assert_eq!("deneb", schedule.fork_at_slot(269568 * 32).name);
+        assert_eq!(
+            config.forks.deneb.fork_version.as_slice(),
+            schedule.fork_version_at_slot(u64::MAX)
+        );
+
+        assert_eq!(
+            Some("deneb"),
+            schedule.next_fork(269567).map(|fork| fork.name)
+        );
+        assert_eq!(None, schedule.next_fork(269568));
+
+        // The digest of Deneb on mainnet, as in its gossip topics.
+        assert_eq!(
+            [0x6a, 0x95, 0xa1, 0xa9],
+            schedule.fork_digest_at_slot(269568 * 32).unwrap()
+        );
+    }
+}
@@ -1,8 +1,11 @@
 pub use base::*;
 pub use checkpoints::*;
 pub use cli::*;
+pub use fork_schedule::*;
+pub use network_config::*;
 pub use networks::*;
 pub use types::*;
+pub use validation::*;
 
 pub use crate::config::*;
 
@@ -14,11 +17,17 @@ pub mod checkpoints;
 pub mod cli;
 /// Core Config
 pub mod config;
+/// Fork Schedule
+pub mod fork_schedule;
+/// Custom Network Configuration
+pub mod network_config;
 /// Network Configuration
 pub mod networks;
 /// Generic Config Types
 pub mod types;
 /// Generic Utilities
 pub mod utils;
+/// Config Validation
+pub mod validation;
 
 const CHECKPOINT_AGE_14_DAYS: u64 = 1_209_600;
@@ -0,0 +1,163 @@
+use std::{fs, path::Path};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    base::BaseConfig,
+    types::{ChainConfig, Forks},
+    utils::{bytes_opt_deserialize, default_max_checkpoint_age},
+};
+
+/// The chain parameters of a network that is not one of the built-in [`crate::Network`]s,
+/// e.g. a private devnet with its own genesis validators root and fork schedule.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkConfig {
+    pub chain: ChainConfig,
+    pub forks: Forks,
+    #[serde(default)]
+    pub consensus_rpc: Option<String>,
+    #[serde(default, deserialize_with = "bytes_opt_deserialize")]
+    pub default_checkpoint: Option<Vec<u8>>,
+    #[serde(default = "default_max_checkpoint_age")]
+    pub max_checkpoint_age: u64,
+    #[serde(default)]
+    pub strict_checkpoint_age: bool,
+}
+
+impl NetworkConfig {
+    /// Load a network configuration from a file.
+    /// The format is picked by the file extension: `.toml`, `.json`, `.yaml` or `.yml`.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match extension.as_str() {
+            "toml" => Self::from_toml(&content),
+            "json" => Self::from_json(&content),
+            "yaml" | "yml" => Ok(serde_yaml::from_str(&content)?),
+            _ => Err(eyre::eyre!(
+                "unsupported network config format: {}",
+                path.display()
+            )),
+        }
+    }
+
+    /// Parse a network configuration from a TOML string.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Parse a network configuration from a JSON string.
+    pub fn from_json(content: &str) -> Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn to_base_config(&self) -> BaseConfig {
+        BaseConfig {
+            consensus_rpc: self.consensus_rpc.clone(),
+            default_checkpoint: self.default_checkpoint.clone().unwrap_or_default(),
+            chain: self.chain.clone(),
+            forks: self.forks.clone(),
+            max_checkpoint_age: self.max_checkpoint_age,
+            strict_checkpoint_age: self.strict_checkpoint_age,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use common::utils::hex_str_to_bytes;
+    use tempdir::TempDir;
+
+    use super::*;
+    use crate::CHECKPOINT_AGE_14_DAYS;
+
+    const TOML_CONFIG: &str = r#"
+consensus_rpc = "http://localhost:3500"
+
+[chain]
+chain_id = 32382
+genesis_time = 1720442031
+genesis_root = "0x83431ec7fcf92cfc44947fc0418e831c25e1d0806590231c439830db7ad54fda"
+
+[forks.genesis]
+epoch = 0
+fork_version = "0x20000089"
+
+[forks.altair]
+epoch = 0
+fork_version = "0x20000090"
+
+[forks.bellatrix]
+epoch = 0
+fork_version = "0x20000091"
+
+[forks.capella]
+epoch = 0
+fork_version = "0x20000092"
+
+[forks.deneb]
+epoch = 132608
+fork_version = "0x20000093"
+"#;
+
+    #[test]
+    fn test_network_config_from_toml_file() {
+        let temp_dir = TempDir::new("network_config").unwrap();
+        let path = temp_dir.path().join("devnet.toml");
+        fs::write(&path, TOML_CONFIG).unwrap();
+
+        let config = NetworkConfig::from_file(&path).unwrap();
+
+        assert_eq!(
+            Some("http://localhost:3500".to_string()),
+            config.consensus_rpc
+        );
+        assert_eq!(32382, config.chain.chain_id);
+        assert_eq!(1720442031, config.chain.genesis_time);
+        assert_eq!(132608, config.forks.deneb.epoch);
+        assert_eq!(
+            hex_str_to_bytes("0x20000093").unwrap(),
+            config.forks.deneb.fork_version
+        );
+        assert_eq!(None, config.default_checkpoint);
+        assert_eq!(CHECKPOINT_AGE_14_DAYS, config.max_checkpoint_age);
+    }
+
+    #[test]
+    fn test_network_config_from_json_matches_toml() {
+        let toml_config = NetworkConfig::from_toml(TOML_CONFIG).unwrap();
+        let json = serde_json::to_string(&toml_config).unwrap();
+
+        let temp_dir = TempDir::new("network_config").unwrap();
+        let path = temp_dir.path().join("devnet.json");
+        fs::write(&path, json).unwrap();
+
+        let json_config = NetworkConfig::from_file(&path).unwrap();
+        assert_eq!(toml_config.chain, json_config.chain);
+        assert_eq!(
+            toml_config.forks.capella.fork_version,
+            json_config.forks.capella.fork_version
+        );
+    }
+
+    #[test]
+    fn test_network_config_unsupported_extension() {
+        let temp_dir = TempDir::new("network_config").unwrap();
+        let path = temp_dir.path().join("devnet.ini");
+        fs::write(&path, TOML_CONFIG).unwrap();
+
+        let err = NetworkConfig::from_file(&path).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unsupported network config format"));
+    }
+}
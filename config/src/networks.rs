@@ -84,6 +84,7 @@ pub fn mainnet() -> BaseConfig {
                 "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95",
             )
             .unwrap(),
+            genesis_block_root: None,
         },
         forks: Forks {
             genesis: Fork {
@@ -129,6 +130,7 @@ pub fn goerli() -> BaseConfig {
                 "0x043db0d9a83813551ee2f33450d23797757d430911a9320530ad8a0eabc43efb",
             )
             .unwrap(),
+            genesis_block_root: None,
         },
         forks: Forks {
             genesis: Fork {
@@ -174,6 +176,7 @@ pub fn sepolia() -> BaseConfig {
                 "0xd8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078",
             )
             .unwrap(),
+            genesis_block_root: None,
         },
         forks: Forks {
             genesis: Fork {
@@ -219,6 +222,7 @@ pub fn holesky() -> BaseConfig {
                 "0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1",
             )
             .unwrap(),
+            genesis_block_root: None,
         },
         forks: Forks {
             genesis: Fork {
@@ -1,6 +1,17 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use eyre::{eyre, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::utils::{bytes_deserialize, bytes_serialize};
+use crate::utils::{
+    bytes_deserialize, bytes_opt_deserialize, bytes_opt_serialize, bytes_serialize,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct ChainConfig {
@@ -11,6 +22,14 @@ pub struct ChainConfig {
         serialize_with = "bytes_serialize"
     )]
     pub genesis_root: Vec<u8>,
+    /// The root of the genesis block, which is trusted like a checkpoint when syncing from
+    /// genesis, see `Config::sync_from_genesis`. Devnets publish it along with their genesis.
+    #[serde(
+        default,
+        deserialize_with = "bytes_opt_deserialize",
+        serialize_with = "bytes_opt_serialize"
+    )]
+    pub genesis_block_root: Option<Vec<u8>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -22,6 +41,35 @@ pub struct Forks {
     pub deneb: Fork,
 }
 
+impl Forks {
+    /// Applies the overrides of `overrides`, by fork name such as `deneb`, to the forks. Fails on
+    /// an unknown fork name or a fork version that is not 4 bytes long.
+    pub fn apply_overrides(&mut self, overrides: &HashMap<String, ForkOverride>) -> Result<()> {
+        for (name, fork_override) in overrides {
+            let fork = match name.to_lowercase().as_str() {
+                "genesis" | "phase0" => &mut self.genesis,
+                "altair" => &mut self.altair,
+                "bellatrix" => &mut self.bellatrix,
+                "capella" => &mut self.capella,
+                "deneb" => &mut self.deneb,
+                _ => return Err(eyre!("cannot override unknown fork {name}")),
+            };
+
+            if let Some(epoch) = fork_override.epoch {
+                fork.epoch = epoch;
+            }
+            if let Some(fork_version) = &fork_override.fork_version {
+                if fork_version.len() != 4 {
+                    return Err(eyre!("invalid fork version override of {name}"));
+                }
+                fork.fork_version = fork_version.clone();
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Fork {
     pub epoch: u64,
@@ -31,3 +79,342 @@ pub struct Fork {
     )]
     pub fork_version: Vec<u8>,
 }
+
+/// The override of the activation epoch and fork version of a fork, for the devnets of which the
+/// fork schedule differs from the one of their network. Unset fields are not overridden.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ForkOverride {
+    #[serde(default)]
+    pub epoch: Option<u64>,
+    #[serde(default, deserialize_with = "bytes_opt_deserialize")]
+    pub fork_version: Option<Vec<u8>>,
+}
+
+/// The retry policy of the requests to the consensus RPC. Failed requests are retried with an
+/// exponential backoff, of which a random fraction of up to `jitter` is cut, so that clients
+/// that were rate limited together do not retry together.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// The number of attempts of a request, including the first one.
+    pub max_attempts: u32,
+    /// The backoff before the first retry, in milliseconds. It doubles on every retry.
+    pub initial_backoff_ms: u64,
+    /// The maximum backoff between two attempts, in milliseconds.
+    pub max_backoff_ms: u64,
+    /// The fraction of the backoff that is randomized, between 0 and 1.
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    /// The backoff before the retry that follows the given attempt, starting at 1, without
+    /// jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(63);
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ms);
+
+        Duration::from_millis(backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            jitter: 0.5,
+        }
+    }
+}
+
+/// The clock that maps the time since genesis to slots. `max_clock_drift_ms` is how far ahead of
+/// the local clock a slot may start and still be accepted, which defaults to the
+/// `MAXIMUM_GOSSIP_CLOCK_DISPARITY` of the specification.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct SlotClockConfig {
+    pub seconds_per_slot: u64,
+    pub max_clock_drift_ms: u64,
+}
+
+impl Default for SlotClockConfig {
+    fn default() -> Self {
+        Self {
+            seconds_per_slot: 12,
+            max_clock_drift_ms: 500,
+        }
+    }
+}
+
+/// The schedule of the advancement loop of the consensus client. Without an interval, updates are
+/// fetched a third into each slot, once the block of the slot was likely propagated. After a
+/// failed advance, the next one is attempted after a backoff that doubles on every consecutive
+/// failure.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AdvanceConfig {
+    /// The interval between two advances, in milliseconds.
+    pub interval_ms: Option<u64>,
+    /// The backoff after the first failed advance, in milliseconds.
+    pub initial_backoff_ms: u64,
+    /// The maximum backoff between two failed advances, in milliseconds.
+    pub max_backoff_ms: u64,
+}
+
+impl AdvanceConfig {
+    /// The backoff after the given number of consecutive failures, starting at 1.
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(63);
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ms);
+
+        Duration::from_millis(backoff)
+    }
+}
+
+impl Default for AdvanceConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: None,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+/// The rate limit of the requests to a consensus RPC endpoint, enforced with a token bucket that
+/// holds up to `burst` requests and is refilled at `requests_per_second`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_second: f64,
+    /// The number of requests that can be sent at once after being idle. Defaults to 1.
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+/// The credentials of a consensus RPC endpoint, such as an `Authorization` header or an API key
+/// in the query string, which are sent with every request. Their values are redacted from the
+/// `Debug` output, so that they do not end up in logs.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct RpcCredentials {
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub query: HashMap<String, String>,
+}
+
+impl fmt::Debug for RpcCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |params: &HashMap<String, String>| {
+            params
+                .keys()
+                .map(|key| (key.clone(), "<redacted>"))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        f.debug_struct("RpcCredentials")
+            .field("headers", &redacted(&self.headers))
+            .field("query", &redacted(&self.query))
+            .finish()
+    }
+}
+
+/// The TLS configuration of a consensus RPC endpoint, for beacon nodes behind self-signed or
+/// pinned certificates.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM files of the certificates that are trusted as roots, on top of the system ones.
+    #[serde(default)]
+    pub root_certificates: Vec<PathBuf>,
+    /// The SHA-256 fingerprints of the DER encoding of the certificates that the endpoint may
+    /// present, in hex, optionally separated by colons. Any certificate is accepted when empty.
+    #[serde(default)]
+    pub pinned_certificates: Vec<String>,
+}
+
+/// How long a long-running client keeps the data it verified, so that its memory and disk usage
+/// do not grow without bound. Everything is kept by default.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// The number of sync committee periods of light client updates kept to be served, up to the
+    /// one of the finalized header.
+    pub update_periods: Option<u64>,
+    /// The number of days of verified headers, and of the conflict evidence of the database,
+    /// that are kept.
+    pub header_days: Option<u64>,
+    /// The interval between two compactions of the database, in seconds.
+    pub compaction_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            update_periods: None,
+            header_days: None,
+            compaction_interval_secs: 60 * 60,
+        }
+    }
+}
+
+/// Backs the verified state up to an S3-compatible object store, such as S3, GCS with HMAC keys
+/// or MinIO, so that a container restarted without its disk resumes from it. The credentials of
+/// the store are read from the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and optional
+/// `AWS_SESSION_TOKEN` environment variables.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// The URL of the bucket, in the path style, e.g. `https://s3.eu-west-1.amazonaws.com/helios`.
+    pub bucket_url: String,
+    /// The region the requests are signed for.
+    pub region: String,
+    /// The prefix of the key of the backup, which is followed by the chain id.
+    pub prefix: String,
+    /// The minimum interval between two uploads, in seconds. A backup is only uploaded once a new
+    /// header is finalized.
+    pub interval_secs: u64,
+    /// Whether the client starts from the backup, if there is one, instead of bootstrapping from
+    /// a checkpoint.
+    pub restore: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            bucket_url: String::new(),
+            region: "us-east-1".to_string(),
+            prefix: "helios".to_string(),
+            interval_secs: 10 * 60,
+            restore: true,
+        }
+    }
+}
+
+/// The thresholds of the warnings of a degraded sync, which the client raises when the sync
+/// committee participation of the recent updates is low, e.g. because the consensus RPC serves
+/// poor updates or the network is struggling, and when the finalized header lags far behind the
+/// wall clock.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SyncHealthConfig {
+    /// The number of recent finality and optimistic updates of the rolling participation.
+    pub participation_window: usize,
+    /// The mean participation of the window, between 0 and 1, below which a warning is raised.
+    pub min_participation: f64,
+    /// The number of slots between the wall clock and the finalized header above which a warning
+    /// is raised. The finalized header is normally two to three epochs behind.
+    pub max_finality_lag: u64,
+}
+
+impl Default for SyncHealthConfig {
+    fn default() -> Self {
+        Self {
+            participation_window: 32,
+            min_participation: 0.8,
+            max_finality_lag: 6 * 32,
+        }
+    }
+}
+
+/// Returns the 32 byte key of the encryption of the database, e.g. by asking a KMS, see
+/// [`crate::Config::database_key`]. It is called when the database is opened.
+#[derive(Clone)]
+pub struct DatabaseKey(Arc<dyn Fn() -> Result<[u8; 32]> + Send + Sync>);
+
+impl DatabaseKey {
+    pub fn new(key: impl Fn() -> Result<[u8; 32]> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(key))
+    }
+
+    pub fn get(&self) -> Result<[u8; 32]> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for DatabaseKey {
+    /// The key is not printed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DatabaseKey(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_are_redacted() {
+        let credentials = RpcCredentials {
+            headers: HashMap::from([("Authorization".to_string(), "Bearer secret".to_string())]),
+            query: HashMap::from([("apikey".to_string(), "secret".to_string())]),
+        };
+
+        let debug = format!("{credentials:?}");
+        assert!(debug.contains("Authorization"));
+        assert!(debug.contains("apikey"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn test_fork_overrides() {
+        let mut forks = crate::networks::mainnet().forks;
+        let overrides = HashMap::from([
+            (
+                "deneb".to_string(),
+                ForkOverride {
+                    epoch: Some(0),
+                    ..Default::default()
+                },
+            ),
+            (
+                "capella".to_string(),
+                ForkOverride {
+                    fork_version: Some(vec![3, 0, 0, 1]),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        forks.apply_overrides(&overrides).unwrap();
+        assert_eq!(0, forks.deneb.epoch);
+        assert_eq!(vec![4, 0, 0, 0], forks.deneb.fork_version);
+        assert_eq!(194048, forks.capella.epoch);
+        assert_eq!(vec![3, 0, 0, 1], forks.capella.fork_version);
+
+        let unknown = HashMap::from([("fulu".to_string(), ForkOverride::default())]);
+        assert!(forks.apply_overrides(&unknown).is_err());
+
+        let invalid = HashMap::from([(
+            "altair".to_string(),
+            ForkOverride {
+                fork_version: Some(vec![1]),
+                ..Default::default()
+            },
+        )]);
+        assert!(forks.apply_overrides(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_advance_backoff() {
+        let advance = AdvanceConfig {
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 5_000,
+            ..Default::default()
+        };
+
+        assert_eq!(Duration::from_secs(1), advance.backoff(1));
+        assert_eq!(Duration::from_secs(4), advance.backoff(3));
+        assert_eq!(Duration::from_secs(5), advance.backoff(4));
+        assert_eq!(Duration::from_secs(5), advance.backoff(100));
+    }
+}
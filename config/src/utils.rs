@@ -30,6 +30,16 @@ where
     }
 }
 
+pub fn bytes_opt_serialize<S>(bytes: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match bytes {
+        Some(bytes) => serializer.serialize_some(&hex::encode(bytes)),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// The maximum age of a checkpoint in seconds. If the checkpoint is older than this, Helios will
 /// attempt to fetch a new checkpoint.
 pub(crate) fn default_max_checkpoint_age() -> u64 {
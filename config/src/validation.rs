@@ -0,0 +1,370 @@
+use std::{fmt, path::Path};
+
+use crate::{config::env_database_key, types::Fork, Config};
+
+/// A problem of a [`Config`], found by [`Config::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// The field of the config, as in the config file.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ConfigProblem {}
+
+const HTTP_SCHEMES: &[&str] = &["http", "https"];
+const EXECUTION_SCHEMES: &[&str] = &["http", "https", "ws", "wss"];
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+const DATABASE_SCHEMES: &[&str] = &["postgres", "postgresql"];
+
+impl Config {
+    /// Checks the checkpoints, the URLs of the RPCs, the fork schedule and the paths of the
+    /// config, and returns every problem found, so that they are all fixed at once instead of
+    /// failing on the first request that uses them.
+    pub fn validate(&self) -> Result<(), Vec<ConfigProblem>> {
+        let mut problems = Problems::default();
+
+        if let Some(checkpoint) = &self.checkpoint {
+            problems.check_checkpoint("checkpoint", checkpoint);
+        }
+        if !self.default_checkpoint.is_empty() {
+            problems.check_checkpoint("default_checkpoint", &self.default_checkpoint);
+        }
+
+        // The consensus RPC of a file RPC is a directory.
+        if !Path::new(&self.consensus_rpc).is_dir() {
+            problems.check_url("consensus_rpc", &self.consensus_rpc, HTTP_SCHEMES);
+        }
+        // The execution RPC is left empty by the users of the consensus only.
+        if !self.execution_rpc.is_empty() {
+            problems.check_url("execution_rpc", &self.execution_rpc, EXECUTION_SCHEMES);
+        }
+        for rpc in &self.broadcast_rpcs {
+            problems.check_url("broadcast_rpcs", rpc, EXECUTION_SCHEMES);
+        }
+        for rpc in &self.quorum_rpcs {
+            problems.check_url("quorum_rpcs", rpc, EXECUTION_SCHEMES);
+        }
+        if let Some(fallback) = &self.fallback {
+            problems.check_url("fallback", fallback, HTTP_SCHEMES);
+        }
+        for service in &self.checkpoint_services {
+            problems.check_url("checkpoint_services", service, HTTP_SCHEMES);
+        }
+        if let Some(proxy) = &self.rpc_proxy {
+            problems.check_url("rpc_proxy", proxy, PROXY_SCHEMES);
+        }
+        for proxy in self.rpc_proxies.values().filter(|proxy| !proxy.is_empty()) {
+            problems.check_url("rpc_proxies", proxy, PROXY_SCHEMES);
+        }
+        for (endpoint, limit) in &self.rpc_rate_limits {
+            let rate = limit.requests_per_second;
+            if !rate.is_finite() || rate <= 0.0 {
+                let message = format!("the requests per second of {endpoint} must be positive");
+                problems.push("rpc_rate_limits", message);
+            }
+        }
+
+        if let Some(url) = &self.database_url {
+            problems.check_url("database_url", url, DATABASE_SCHEMES);
+        }
+
+        if let Some(backup) = &self.backup {
+            problems.check_url("backup.bucket_url", &backup.bucket_url, HTTP_SCHEMES);
+            if backup.region.is_empty() {
+                problems.push("backup.region", "is empty");
+            }
+            if backup.interval_secs == 0 {
+                problems.push("backup.interval_secs", "must be positive");
+            }
+            if !self.encrypt_database {
+                problems.push(
+                    "backup",
+                    "requires encrypt_database, the backups are sealed with its key",
+                );
+            }
+            for name in ["AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"] {
+                if std::env::var_os(name).is_none() {
+                    problems.push("backup", format!("{name} is not set"));
+                }
+            }
+        }
+
+        if let Some(participation) = self.min_optimistic_participation {
+            if !(0.0..=1.0).contains(&participation) {
+                problems.push("min_optimistic_participation", "must be between 0 and 1");
+            }
+        }
+
+        if self.strict_finality && self.force_update_timeout.is_some() {
+            problems.push(
+                "force_update_timeout",
+                "cannot be set with strict_finality, forced updates are not finality-proven",
+            );
+        }
+
+        let sync_health = &self.sync_health;
+        if sync_health.participation_window == 0 {
+            problems.push("sync_health.participation_window", "must be positive");
+        }
+        if !(0.0..=1.0).contains(&sync_health.min_participation) {
+            problems.push("sync_health.min_participation", "must be between 0 and 1");
+        }
+
+        if let Some(quorum) = self.rpc_quorum {
+            let rpcs = self.quorum_rpcs.len() + 1;
+            if quorum == 0 || quorum > rpcs {
+                problems.push(
+                    "rpc_quorum",
+                    format!("must be between 1 and the {rpcs} execution rpcs"),
+                );
+            }
+        }
+
+        if let Some(quorum) = self.checkpoint_quorum {
+            if quorum == 0 || quorum > self.checkpoint_services.len() {
+                problems.push(
+                    "checkpoint_quorum",
+                    format!(
+                        "must be between 1 and the {} checkpoint services",
+                        self.checkpoint_services.len()
+                    ),
+                );
+            }
+        }
+
+        problems.check_forks(self);
+
+        if self.chain.genesis_root.len() != 32 {
+            problems.push("chain.genesis_root", "must be 32 bytes");
+        }
+        if self.slot_clock.seconds_per_slot == 0 {
+            problems.push("slot_clock.seconds_per_slot", "must be positive");
+        }
+
+        if let Some(data_dir) = &self.data_dir {
+            problems.check_dir("data_dir", data_dir);
+        }
+        if let Some(dir) = &self.rpc_record_dir {
+            problems.check_dir("rpc_record_dir", dir);
+        }
+        if let Some(archive) = &self.update_archive {
+            if !archive.exists() {
+                problems.push(
+                    "update_archive",
+                    format!("{} does not exist", archive.display()),
+                );
+            }
+        }
+
+        if self.encrypt_database && self.database_key.is_none() {
+            if let Err(err) = env_database_key() {
+                problems.push("encrypt_database", err.to_string());
+            }
+        }
+
+        problems.into_result()
+    }
+}
+
+#[derive(Default)]
+struct Problems(Vec<ConfigProblem>);
+
+impl Problems {
+    fn push(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(ConfigProblem {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn into_result(self) -> Result<(), Vec<ConfigProblem>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+
+    fn check_checkpoint(&mut self, field: &str, checkpoint: &[u8]) {
+        if checkpoint.len() != 32 {
+            self.push(
+                field,
+                format!(
+                    "must be a 32 byte block root, got {} bytes",
+                    checkpoint.len()
+                ),
+            );
+        }
+    }
+
+    fn check_url(&mut self, field: &str, url: &str, schemes: &[&str]) {
+        if url.is_empty() {
+            self.push(field, "is empty");
+            return;
+        }
+
+        match url.split_once("://") {
+            Some((scheme, host)) if schemes.contains(&scheme) => {
+                if host.is_empty() {
+                    self.push(field, format!("{url} has no host"));
+                }
+            }
+            _ => self.push(
+                field,
+                format!(
+                    "{url} must start with {}",
+                    schemes
+                        .iter()
+                        .map(|scheme| format!("{scheme}://"))
+                        .collect::<Vec<_>>()
+                        .join(" or ")
+                ),
+            ),
+        }
+    }
+
+    /// The forks must be scheduled in order, with distinct 4 byte versions. An unscheduled fork
+    /// has the epoch `u64::MAX`.
+    fn check_forks(&mut self, config: &Config) {
+        let forks = &config.forks;
+        let forks: [(&str, &Fork); 5] = [
+            ("genesis", &forks.genesis),
+            ("altair", &forks.altair),
+            ("bellatrix", &forks.bellatrix),
+            ("capella", &forks.capella),
+            ("deneb", &forks.deneb),
+        ];
+
+        for (name, fork) in &forks {
+            if fork.fork_version.len() != 4 {
+                self.push(
+                    &format!("forks.{name}.fork_version"),
+                    format!("must be 4 bytes, got {} bytes", fork.fork_version.len()),
+                );
+            }
+        }
+
+        for pair in forks.windows(2) {
+            let ((previous_name, previous), (name, fork)) = (pair[0], pair[1]);
+            if fork.epoch < previous.epoch {
+                self.push(
+                    &format!("forks.{name}.epoch"),
+                    format!(
+                        "epoch {} is before the epoch {} of {previous_name}",
+                        fork.epoch, previous.epoch
+                    ),
+                );
+            }
+        }
+
+        for (i, (name, fork)) in forks.iter().enumerate() {
+            let duplicate = forks[..i]
+                .iter()
+                .find(|(_, previous)| previous.fork_version == fork.fork_version);
+            if let Some((previous_name, _)) = duplicate {
+                self.push(
+                    &format!("forks.{name}.fork_version"),
+                    format!("same fork version as {previous_name}"),
+                );
+            }
+        }
+    }
+
+    /// A directory that does not exist yet is created when it is first written to.
+    fn check_dir(&mut self, field: &str, dir: &Path) {
+        if dir.exists() && !dir.is_dir() {
+            self.push(field, format!("{} is not a directory", dir.display()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{networks, types::RateLimit};
+
+    fn config() -> Config {
+        let mut config = networks::mainnet().as_config();
+        config.consensus_rpc = "https://www.lightclientdata.org".to_string();
+        config.execution_rpc = "https://eth-mainnet.g.alchemy.com/v2/key".to_string();
+        config
+    }
+
+    fn fields(config: &Config) -> Vec<String> {
+        config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|problem| problem.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        config().validate().unwrap();
+
+        let mut config = config();
+        config.checkpoint = Some(vec![1; 20]);
+        config.consensus_rpc = "www.lightclientdata.org".to_string();
+        config.execution_rpc = "ftp://node".to_string();
+        config.broadcast_rpcs = vec!["wss://node".to_string(), "https://".to_string()];
+        config.rpc_rate_limits.insert(
+            "https://node".to_string(),
+            RateLimit {
+                requests_per_second: 0.0,
+                burst: 1,
+            },
+        );
+        config.sync_health.min_participation = 1.5;
+        config.rpc_quorum = Some(2);
+        config.forks.deneb.epoch = 0;
+        config.forks.capella.fork_version = config.forks.bellatrix.fork_version.clone();
+        config.data_dir = Some(PathBuf::from("Cargo.toml"));
+
+        assert_eq!(
+            vec![
+                "checkpoint",
+                "consensus_rpc",
+                "execution_rpc",
+                "broadcast_rpcs",
+                "rpc_rate_limits",
+                "sync_health.min_participation",
+                "rpc_quorum",
+                "forks.deneb.epoch",
+                "forks.capella.fork_version",
+                "data_dir",
+            ],
+            fields(&config)
+        );
+    }
+
+    #[test]
+    fn test_problem_messages() {
+        let mut config = config();
+        config.consensus_rpc = "www.lightclientdata.org".to_string();
+        config.checkpoint = Some(vec![1; 20]);
+
+        let problems = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "checkpoint: must be a 32 byte block root, got 20 bytes",
+                "consensus_rpc: www.lightclientdata.org must start with http:// or https://",
+            ],
+            problems
+        );
+    }
+}
@@ -91,3 +91,39 @@ async fn test_get_healthy_fallback_endpoints() {
     let urls = cf.get_healthy_fallback_endpoints(&networks::Network::HOLESKY);
     assert!(!urls.is_empty());
 }
+
+#[test]
+fn test_agreed_checkpoint() {
+    use config::checkpoints::{CheckpointFallback, Slot};
+
+    let slot = |epoch: u64, root: u8| Slot {
+        slot: epoch * 32,
+        block_root: Some(H256::repeat_byte(root)),
+        epoch,
+        ..Default::default()
+    };
+
+    // The latest epoch reported by every service is picked.
+    let responses = vec![
+        vec![slot(11, 2), slot(10, 1)],
+        vec![slot(10, 1), slot(9, 0)],
+        vec![slot(11, 2), slot(10, 1)],
+    ];
+    assert_eq!(
+        H256::repeat_byte(1),
+        CheckpointFallback::agreed_checkpoint(&responses, 3).unwrap()
+    );
+    assert_eq!(
+        H256::repeat_byte(2),
+        CheckpointFallback::agreed_checkpoint(&responses, 2).unwrap()
+    );
+
+    // A service that does not respond does not count towards the quorum.
+    let responses = vec![vec![slot(10, 1)], vec![]];
+    assert!(CheckpointFallback::agreed_checkpoint(&responses, 2).is_err());
+
+    // Conflicting checkpoints are never selected.
+    let responses = vec![vec![slot(10, 1)], vec![slot(10, 1)], vec![slot(10, 3)]];
+    assert!(CheckpointFallback::agreed_checkpoint(&responses, 2).is_err());
+    assert!(CheckpointFallback::agreed_checkpoint(&responses, 0).is_err());
+}
@@ -0,0 +1,231 @@
+//! Generalized indices of the fields of a beacon state and of the fields of its validators, to
+//! verify state proofs against a finalized `state_root`, see the
+//! [phase0 beacon state](https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconstate).
+
+use crate::merkle::is_valid_gindex_branch;
+
+/// The depth of a beacon state. Every state from Bellatrix to Deneb has between 17 and 32 fields.
+pub const BEACON_STATE_DEPTH: u32 = 5;
+/// The index of `validators` in a beacon state.
+pub const VALIDATORS_INDEX: u64 = 11;
+/// The index of `balances` in a beacon state.
+pub const BALANCES_INDEX: u64 = 12;
+/// `log2(VALIDATOR_REGISTRY_LIMIT)`
+pub const VALIDATOR_REGISTRY_DEPTH: u32 = 40;
+/// The depth of a `Validator`, which has 8 fields.
+pub const VALIDATOR_DEPTH: u32 = 3;
+/// The epoch of a validator that did not initiate its exit.
+pub const FAR_FUTURE_EPOCH: u64 = u64::MAX;
+
+/// The depth of a `Fork`, which has 3 fields.
+pub const FORK_DEPTH: u32 = 2;
+/// The depth of the execution payload header of a Bellatrix or Capella state.
+pub const EXECUTION_PAYLOAD_HEADER_DEPTH_CAPELLA: u32 = 4;
+/// The depth of the execution payload header of a Deneb state, which has 17 fields.
+pub const EXECUTION_PAYLOAD_HEADER_DEPTH_DENEB: u32 = 5;
+
+/// The fields of a beacon state, in order. Fields added by later forks are appended, so the
+/// indices of a Deneb state hold for the earlier states that have the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconStateField {
+    GenesisTime,
+    GenesisValidatorsRoot,
+    Slot,
+    Fork,
+    LatestBlockHeader,
+    BlockRoots,
+    StateRoots,
+    HistoricalRoots,
+    Eth1Data,
+    Eth1DataVotes,
+    Eth1DepositIndex,
+    Validators,
+    Balances,
+    RandaoMixes,
+    Slashings,
+    PreviousEpochParticipation,
+    CurrentEpochParticipation,
+    JustificationBits,
+    PreviousJustifiedCheckpoint,
+    CurrentJustifiedCheckpoint,
+    FinalizedCheckpoint,
+    InactivityScores,
+    CurrentSyncCommittee,
+    NextSyncCommittee,
+    LatestExecutionPayloadHeader,
+    NextWithdrawalIndex,
+    NextWithdrawalValidatorIndex,
+    HistoricalSummaries,
+}
+
+/// The fields of a `Fork`, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkField {
+    PreviousVersion,
+    CurrentVersion,
+    Epoch,
+}
+
+/// The fields of an `ExecutionPayloadHeader`, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionPayloadHeaderField {
+    ParentHash,
+    FeeRecipient,
+    StateRoot,
+    ReceiptsRoot,
+    LogsBloom,
+    PrevRandao,
+    BlockNumber,
+    GasLimit,
+    GasUsed,
+    Timestamp,
+    ExtraData,
+    BaseFeePerGas,
+    BlockHash,
+    TransactionsRoot,
+    WithdrawalsRoot,
+    BlobGasUsed,
+    ExcessBlobGas,
+}
+
+/// The fields of a `Validator`, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorField {
+    Pubkey,
+    WithdrawalCredentials,
+    EffectiveBalance,
+    Slashed,
+    ActivationEligibilityEpoch,
+    ActivationEpoch,
+    ExitEpoch,
+    WithdrawableEpoch,
+}
+
+/// The generalized index of `field` in a beacon state.
+pub fn state_field_gindex(field: BeaconStateField) -> u64 {
+    (1 << BEACON_STATE_DEPTH) | field as u64
+}
+
+/// The generalized index of `field` of the `fork` of a beacon state.
+pub fn fork_field_gindex(field: ForkField) -> u64 {
+    (state_field_gindex(BeaconStateField::Fork) << FORK_DEPTH) | field as u64
+}
+
+/// The generalized index of `field` of the latest execution payload header of a beacon state,
+/// whose depth depends on the fork of the state, see [`EXECUTION_PAYLOAD_HEADER_DEPTH_DENEB`].
+pub fn execution_payload_header_field_gindex(
+    field: ExecutionPayloadHeaderField,
+    header_depth: u32,
+) -> u64 {
+    let header = state_field_gindex(BeaconStateField::LatestExecutionPayloadHeader);
+    (header << header_depth) | field as u64
+}
+
+/// Checks that `leaf` is at `gindex` of the beacon state with `state_root`. The branch starts
+/// with the sibling of the leaf.
+pub fn verify_beacon_state_field(
+    state_root: &[u8; 32],
+    gindex: u64,
+    leaf: &[u8; 32],
+    branch: &[[u8; 32]],
+) -> bool {
+    is_valid_gindex_branch(leaf, branch, gindex, state_root)
+}
+
+/// The generalized index of `field` of the validator at `validator_index`.
+pub fn validator_field_gindex(validator_index: u64, field: ValidatorField) -> u64 {
+    // The validators are a list, their root is hashed with their length, which is on the right.
+    let validators = ((1 << BEACON_STATE_DEPTH) | VALIDATORS_INDEX) << 1;
+    let validator = (validators << VALIDATOR_REGISTRY_DEPTH) | validator_index;
+    (validator << VALIDATOR_DEPTH) | field as u64
+}
+
+/// The generalized index of the chunk with the balance of the validator at `validator_index`.
+/// Balances are packed by four in each chunk.
+pub fn balance_gindex(validator_index: u64) -> u64 {
+    let balances = ((1 << BEACON_STATE_DEPTH) | BALANCES_INDEX) << 1;
+    (balances << (VALIDATOR_REGISTRY_DEPTH - 2)) | (validator_index / 4)
+}
+
+/// Returns the balance of the validator at `validator_index` from its balances chunk.
+pub fn balance_from_chunk(chunk: &[u8; 32], validator_index: u64) -> u64 {
+    let offset = (validator_index % 4) as usize * 8;
+    u64::from_le_bytes(chunk[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_gindices() {
+        // The validators and balances of a state are at the generalized indices 43 and 44.
+        assert_eq!(
+            (43 * 2) << 43,
+            validator_field_gindex(0, ValidatorField::Pubkey)
+        );
+        assert_eq!(
+            ((43 * 2) << 43) + 5 * 8 + 6,
+            validator_field_gindex(5, ValidatorField::ExitEpoch)
+        );
+        assert_eq!(((44 * 2) << 38) + 2, balance_gindex(9));
+    }
+
+    #[test]
+    fn test_state_field_gindices() {
+        assert_eq!(32, state_field_gindex(BeaconStateField::GenesisTime));
+        assert_eq!(
+            54,
+            state_field_gindex(BeaconStateField::CurrentSyncCommittee)
+        );
+        assert_eq!(
+            state_field_gindex(BeaconStateField::Validators),
+            (1 << BEACON_STATE_DEPTH) | VALIDATORS_INDEX
+        );
+        assert_eq!(35 * 4 + 1, fork_field_gindex(ForkField::CurrentVersion));
+        assert_eq!(
+            (56 << 5) | 12,
+            execution_payload_header_field_gindex(
+                ExecutionPayloadHeaderField::BlockHash,
+                EXECUTION_PAYLOAD_HEADER_DEPTH_DENEB
+            )
+        );
+    }
+
+    #[test]
+    fn test_verify_beacon_state_field() {
+        use sha2::{Digest, Sha256};
+
+        let leaf = [1u8; 32];
+        let branch = [[2u8; 32]; BEACON_STATE_DEPTH as usize];
+        let gindex = state_field_gindex(BeaconStateField::Slot);
+
+        let mut root = leaf;
+        for (depth, sibling) in branch.iter().enumerate() {
+            let hasher = if (gindex >> depth) & 1 == 1 {
+                Sha256::new().chain_update(sibling).chain_update(root)
+            } else {
+                Sha256::new().chain_update(root).chain_update(sibling)
+            };
+            root = hasher.finalize().into();
+        }
+
+        assert!(verify_beacon_state_field(&root, gindex, &leaf, &branch));
+        assert!(!verify_beacon_state_field(
+            &root,
+            state_field_gindex(BeaconStateField::GenesisTime),
+            &leaf,
+            &branch
+        ));
+        assert!(!verify_beacon_state_field(&root, gindex, &[0; 32], &branch));
+    }
+
+    #[test]
+    fn test_balance_from_chunk() {
+        let mut chunk = [0u8; 32];
+        chunk[8..16].copy_from_slice(&32_000_000_000u64.to_le_bytes());
+
+        assert_eq!(32_000_000_000, balance_from_chunk(&chunk, 5));
+        assert_eq!(0, balance_from_chunk(&chunk, 4));
+    }
+}
@@ -0,0 +1,30 @@
+use alloc::vec::Vec;
+
+use milagro_bls::AggregateSignature;
+pub use milagro_bls::PublicKey;
+
+use crate::CoreError;
+
+/// Verifies the aggregate signature `signature` of `msg` by every key of `pks`.
+pub fn is_aggregate_valid(signature: &[u8], msg: &[u8], pks: &[&PublicKey]) -> bool {
+    match AggregateSignature::from_bytes(signature) {
+        Ok(signature) => signature.fast_aggregate_verify(msg, pks),
+        Err(_) => false,
+    }
+}
+
+/// Returns the keys of the committee members whose participation bit is set.
+pub fn get_participating_keys<'a>(
+    pubkeys: impl IntoIterator<Item = &'a [u8]>,
+    bits: impl IntoIterator<Item = bool>,
+) -> Result<Vec<PublicKey>, CoreError> {
+    pubkeys
+        .into_iter()
+        .zip(bits)
+        .enumerate()
+        .filter(|(_, (_, bit))| *bit)
+        .map(|(i, (pubkey, _))| {
+            PublicKey::from_bytes_unchecked(pubkey).map_err(|_| CoreError::InvalidPublicKey(i))
+        })
+        .collect()
+}
@@ -0,0 +1,54 @@
+use ssz_rs::prelude::*;
+
+use crate::CoreError;
+
+/// The domain type of sync committee signatures.
+pub const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+#[derive(SimpleSerialize, Default, Debug)]
+struct SigningData {
+    object_root: Vector<u8, 32>,
+    domain: Vector<u8, 32>,
+}
+
+#[derive(SimpleSerialize, Default, Debug)]
+struct ForkData {
+    current_version: Vector<u8, 4>,
+    genesis_validator_root: Vector<u8, 32>,
+}
+
+fn to_vector<const N: usize>(bytes: &[u8; N]) -> Vector<u8, N> {
+    Vector::try_from(bytes.to_vec()).expect("the vector has exactly N bytes")
+}
+
+pub fn compute_fork_data_root(
+    fork_version: &[u8; 4],
+    genesis_validator_root: &[u8; 32],
+) -> Result<Node, CoreError> {
+    let mut fork_data = ForkData {
+        current_version: to_vector(fork_version),
+        genesis_validator_root: to_vector(genesis_validator_root),
+    };
+    Ok(fork_data.hash_tree_root()?)
+}
+
+pub fn compute_domain(
+    domain_type: &[u8; 4],
+    fork_version: &[u8; 4],
+    genesis_validator_root: &[u8; 32],
+) -> Result<[u8; 32], CoreError> {
+    let fork_data_root = compute_fork_data_root(fork_version, genesis_validator_root)?;
+
+    let mut domain = [0u8; 32];
+    domain[..4].copy_from_slice(domain_type);
+    domain[4..].copy_from_slice(&fork_data_root.as_ref()[..28]);
+    Ok(domain)
+}
+
+pub fn compute_signing_root(object_root: &[u8; 32], domain: &[u8; 32]) -> Result<Node, CoreError> {
+    let mut data = SigningData {
+        object_root: to_vector(object_root),
+        domain: to_vector(domain),
+    };
+    Ok(data.hash_tree_root()?)
+}
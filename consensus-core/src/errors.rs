@@ -0,0 +1,27 @@
+use core::fmt;
+
+use ssz_rs::MerkleizationError;
+
+#[derive(Debug)]
+pub enum CoreError {
+    Merkleization(MerkleizationError),
+    InvalidPublicKey(usize),
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Merkleization(err) => write!(f, "merkleization failed: {err:?}"),
+            Self::InvalidPublicKey(index) => write!(f, "invalid public key at index {index}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CoreError {}
+
+impl From<MerkleizationError> for CoreError {
+    fn from(err: MerkleizationError) -> Self {
+        Self::Merkleization(err)
+    }
+}
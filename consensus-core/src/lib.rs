@@ -0,0 +1,22 @@
+//! The pure verification logic of the light client: merkle branch checks, signing domains, BLS
+//! verification, and the ranking and application of updates from the altair light client
+//! specification.
+//!
+//! The crate has no networking, and builds without `std` (disable the default `std` feature), so
+//! that updates can be verified in constrained and on-chain environments. It works on plain byte
+//! arrays and [`ssz_rs::Node`]s; the `consensus` crate converts its types before calling it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod beacon_state;
+pub mod bls;
+pub mod domain;
+pub mod errors;
+pub mod merkle;
+pub mod sync;
+pub mod update;
+
+pub use errors::CoreError;
+pub use ssz_rs::Node;
@@ -0,0 +1,192 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use sha2::{Digest, Sha256};
+use ssz_rs::prelude::*;
+
+/// The depth and index of the finalized header in a beacon state.
+pub const FINALIZED_ROOT_DEPTH: usize = 6;
+pub const FINALIZED_ROOT_INDEX: usize = 41;
+/// The depth and index of the current sync committee in a beacon state.
+pub const CURRENT_SYNC_COMMITTEE_DEPTH: usize = 5;
+pub const CURRENT_SYNC_COMMITTEE_INDEX: usize = 22;
+/// The depth and index of the next sync committee in a beacon state.
+pub const NEXT_SYNC_COMMITTEE_DEPTH: usize = 5;
+pub const NEXT_SYNC_COMMITTEE_INDEX: usize = 23;
+
+/// Checks that `leaf` is at `index` of the tree of `root`, with the given `branch`.
+pub fn is_valid_branch(
+    leaf: &Node,
+    branch: &[Node],
+    depth: usize,
+    index: usize,
+    root: &Node,
+) -> bool {
+    is_valid_merkle_branch(leaf, branch.iter(), depth, index, root)
+}
+
+/// Checks that `leaf` is at the generalized index `gindex` of the tree of `root`, with the given
+/// `branch`, which starts with the sibling of the leaf. Unlike [`is_valid_branch`], the index is
+/// a `u64`, so that the deep trees of a beacon state can be checked on 32-bit targets.
+pub fn is_valid_gindex_branch(
+    leaf: &[u8; 32],
+    branch: &[[u8; 32]],
+    gindex: u64,
+    root: &[u8; 32],
+) -> bool {
+    if gindex == 0 || branch.len() != gindex.ilog2() as usize {
+        return false;
+    }
+
+    let mut node = *leaf;
+    for (depth, sibling) in branch.iter().enumerate() {
+        let hasher = if (gindex >> depth) & 1 == 1 {
+            Sha256::new().chain_update(sibling).chain_update(node)
+        } else {
+            Sha256::new().chain_update(node).chain_update(sibling)
+        };
+        node = hasher.finalize().into();
+    }
+
+    &node == root
+}
+
+/// Returns the generalized indices of the nodes of a multiproof of the leaves at `gindices`, in
+/// the order of the proof, as in the
+/// [SSZ multiproof specification](https://github.com/ethereum/consensus-specs/blob/dev/ssz/merkle-proofs.md#merkle-multiproofs):
+/// the siblings of the paths of the leaves that are not on a path, in decreasing order.
+pub fn multiproof_helper_indices(gindices: &[u64]) -> Vec<u64> {
+    let mut paths = Vec::new();
+    let mut siblings = Vec::new();
+    for gindex in gindices {
+        let mut node = *gindex;
+        while node > 1 {
+            paths.push(node);
+            siblings.push(node ^ 1);
+            node /= 2;
+        }
+    }
+
+    let mut helpers = siblings
+        .into_iter()
+        .filter(|sibling| !paths.contains(sibling))
+        .collect::<Vec<_>>();
+    helpers.sort_unstable_by(|a, b| b.cmp(a));
+    helpers.dedup();
+    helpers
+}
+
+/// Checks that the `leaves` are at the generalized indices `gindices` of the tree of `root`,
+/// with the nodes of `proof` at the indices of [`multiproof_helper_indices`].
+pub fn is_valid_multiproof(
+    leaves: &[[u8; 32]],
+    gindices: &[u64],
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+) -> bool {
+    let helpers = multiproof_helper_indices(gindices);
+    if leaves.is_empty()
+        || leaves.len() != gindices.len()
+        || proof.len() != helpers.len()
+        || gindices.contains(&0)
+    {
+        return false;
+    }
+
+    let mut nodes = BTreeMap::new();
+    for (gindex, node) in gindices.iter().zip(leaves).chain(helpers.iter().zip(proof)) {
+        // A leaf given twice must be the same node.
+        if nodes
+            .insert(*gindex, *node)
+            .is_some_and(|known| known != *node)
+        {
+            return false;
+        }
+    }
+
+    // Hash the siblings up, deepest first, until the root is reached.
+    let mut gindex = match nodes.keys().next_back() {
+        Some(gindex) => *gindex,
+        None => return false,
+    };
+    while gindex > 1 {
+        if let (Some(left), Some(right)) = (nodes.get(&(gindex & !1)), nodes.get(&(gindex | 1))) {
+            let parent: [u8; 32] = Sha256::new()
+                .chain_update(left)
+                .chain_update(right)
+                .finalize()
+                .into();
+            // A node that is given must be the one its children hash to.
+            if *nodes.entry(gindex / 2).or_insert(parent) != parent {
+                return false;
+            }
+        }
+
+        gindex = match nodes.range(..gindex).next_back() {
+            Some((next, _)) => *next,
+            None => break,
+        };
+    }
+
+    nodes.get(&1) == Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        Sha256::new()
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .into()
+    }
+
+    #[test]
+    fn test_is_valid_gindex_branch() {
+        let leaves = [[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]];
+        let left = hash(&leaves[0], &leaves[1]);
+        let right = hash(&leaves[2], &leaves[3]);
+        let root = hash(&left, &right);
+
+        // The third leaf of a tree of depth 2 is at the generalized index 0b110.
+        let branch = [leaves[3], left];
+        assert!(is_valid_gindex_branch(&leaves[2], &branch, 6, &root));
+        assert!(!is_valid_gindex_branch(&leaves[2], &branch, 7, &root));
+        assert!(!is_valid_gindex_branch(&leaves[2], &branch, 14, &root));
+        assert!(!is_valid_gindex_branch(&leaves[1], &branch, 6, &root));
+    }
+
+    #[test]
+    fn test_is_valid_multiproof() {
+        let leaves = [[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]];
+        let left = hash(&leaves[0], &leaves[1]);
+        let right = hash(&leaves[2], &leaves[3]);
+        let root = hash(&left, &right);
+
+        // The first and the third leaves share the root, and need their siblings.
+        let gindices = [4, 6];
+        assert_eq!(vec![7, 5], multiproof_helper_indices(&gindices));
+
+        let proof = [leaves[3], leaves[1]];
+        let proven = [leaves[0], leaves[2]];
+        assert!(is_valid_multiproof(&proven, &gindices, &proof, &root));
+        assert!(!is_valid_multiproof(&proven, &[4, 7], &proof, &root));
+        assert!(!is_valid_multiproof(
+            &[leaves[0], leaves[3]],
+            &gindices,
+            &proof,
+            &root
+        ));
+        assert!(!is_valid_multiproof(&proven, &gindices, &proof[..1], &root));
+
+        // Two siblings need the node of their parent's sibling only.
+        assert_eq!(vec![3], multiproof_helper_indices(&[4, 5]));
+        assert!(is_valid_multiproof(
+            &[leaves[0], leaves[1]],
+            &[4, 5],
+            &[right],
+            &root
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+use core::cmp;
+
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+pub const SYNC_COMMITTEE_SIZE: u64 = 512;
+
+pub fn calc_sync_period(slot: u64) -> u64 {
+    let epoch = slot / SLOTS_PER_EPOCH;
+    epoch / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+/// Whether more than two thirds of the sync committee participated.
+pub fn has_supermajority(participants: u64) -> bool {
+    participants * 3 >= SYNC_COMMITTEE_SIZE * 2
+}
+
+/// The participation an optimistic update needs to be applied, given the maximum participation
+/// of the previous and of the current period.
+pub fn safety_threshold(
+    previous_max_active_participants: u64,
+    current_max_active_participants: u64,
+) -> u64 {
+    cmp::max(
+        previous_max_active_participants,
+        current_max_active_participants,
+    ) / 2
+}
+
+/// The properties of an update that `is_better_update` ranks it by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateSummary {
+    pub participants: u64,
+    pub attested_slot: u64,
+    pub signature_slot: u64,
+    /// The slot of the finalized header, if the update has a finality proof.
+    pub finalized_slot: Option<u64>,
+    pub has_next_sync_committee: bool,
+}
+
+impl UpdateSummary {
+    fn has_relevant_sync_committee(&self) -> bool {
+        self.has_next_sync_committee
+            && calc_sync_period(self.attested_slot) == calc_sync_period(self.signature_slot)
+    }
+
+    fn has_sync_committee_finality(&self) -> bool {
+        calc_sync_period(self.finalized_slot.unwrap_or_default())
+            == calc_sync_period(self.attested_slot)
+    }
+}
+
+/// Implements `is_better_update` from the specification.
+///
+/// Returns `true` if `new_update` should be preferred over `old_update`. Updates are ranked by
+/// supermajority participation, presence of a relevant next sync committee, presence of finality,
+/// sync committee finality, participation and finally by preferring older data.
+pub fn is_better_update(new_update: &UpdateSummary, old_update: &UpdateSummary) -> bool {
+    // Compare supermajority (> 2/3) sync committee participation.
+    let new_has_supermajority = has_supermajority(new_update.participants);
+    let old_has_supermajority = has_supermajority(old_update.participants);
+    if new_has_supermajority != old_has_supermajority {
+        return new_has_supermajority;
+    }
+    if !new_has_supermajority && new_update.participants != old_update.participants {
+        return new_update.participants > old_update.participants;
+    }
+
+    // Compare presence of relevant sync committee.
+    let new_has_relevant_sync_committee = new_update.has_relevant_sync_committee();
+    if new_has_relevant_sync_committee != old_update.has_relevant_sync_committee() {
+        return new_has_relevant_sync_committee;
+    }
+
+    // Compare indication of any finality.
+    let new_has_finality = new_update.finalized_slot.is_some();
+    if new_has_finality != old_update.finalized_slot.is_some() {
+        return new_has_finality;
+    }
+
+    // Compare sync committee finality.
+    if new_has_finality {
+        let new_has_sync_committee_finality = new_update.has_sync_committee_finality();
+        if new_has_sync_committee_finality != old_update.has_sync_committee_finality() {
+            return new_has_sync_committee_finality;
+        }
+    }
+
+    // Tiebreaker 1: Sync committee participation beyond supermajority.
+    if new_update.participants != old_update.participants {
+        return new_update.participants > old_update.participants;
+    }
+
+    // Tiebreaker 2: Prefer older data (fewer changes to best).
+    if new_update.attested_slot != old_update.attested_slot {
+        return new_update.attested_slot < old_update.attested_slot;
+    }
+    new_update.signature_slot < old_update.signature_slot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(participants: u64, attested_slot: u64, finalized: bool) -> UpdateSummary {
+        UpdateSummary {
+            participants,
+            attested_slot,
+            signature_slot: attested_slot + 1,
+            finalized_slot: finalized.then_some(attested_slot - 64),
+            has_next_sync_committee: true,
+        }
+    }
+
+    #[test]
+    fn test_is_better_update() {
+        // Supermajority wins over participation below it.
+        assert!(is_better_update(
+            &summary(400, 8192, false),
+            &summary(300, 8192, true)
+        ));
+        // Finality wins at equal participation.
+        assert!(is_better_update(
+            &summary(400, 8192, true),
+            &summary(400, 8192, false)
+        ));
+        // Older data wins when everything else is equal.
+        assert!(is_better_update(
+            &summary(400, 8192, true),
+            &summary(400, 8224, true)
+        ));
+        assert!(!is_better_update(
+            &summary(400, 8192, true),
+            &summary(400, 8192, true)
+        ));
+    }
+
+    #[test]
+    fn test_calc_sync_period() {
+        assert_eq!(0, calc_sync_period(8191));
+        assert_eq!(1, calc_sync_period(8192));
+    }
+}
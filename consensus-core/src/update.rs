@@ -0,0 +1,285 @@
+//! The store transitions of `process_light_client_update`, `apply_light_client_update` and
+//! `process_light_client_store_force_update` from the specification.
+//!
+//! The functions only decide which parts of the store an update changes, from the
+//! [`UpdateSummary`] of the update and the [`StoreSummary`] of the store. The caller owns the
+//! headers and sync committees, and carries out the changes.
+
+use crate::sync::{calc_sync_period, has_supermajority, safety_threshold, UpdateSummary};
+
+/// The properties of a light client store that processing an update depends on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreSummary {
+    pub finalized_slot: u64,
+    pub optimistic_slot: u64,
+    pub safe_slot: u64,
+    pub has_next_sync_committee: bool,
+    pub previous_max_active_participants: u64,
+    pub current_max_active_participants: u64,
+}
+
+/// Restrictions on top of the specification on how the optimistic and safe headers advance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpdatePolicy {
+    /// The optimistic and safe headers only follow the finalized header.
+    pub strict_finality: bool,
+    /// The participation an update needs to advance the optimistic header.
+    pub min_optimistic_participants: u64,
+}
+
+/// The store changes of `process_light_client_update`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessedUpdate {
+    /// The new maximum participation of the current period.
+    pub current_max_active_participants: u64,
+    /// The attested header becomes the optimistic header.
+    pub update_optimistic_header: bool,
+    /// The attested header becomes the safe header.
+    pub update_safe_header: bool,
+    /// The update is applied with [`apply_update`].
+    pub apply: bool,
+}
+
+/// How applying an update changes the sync committees of the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitteeChange {
+    None,
+    /// The next sync committee of the update is stored, as the store had none.
+    SetNext,
+    /// The next sync committee becomes the current one, and the next sync committee of the update
+    /// is stored. The maximum participation of the current period moves to the previous one.
+    Rotate,
+}
+
+/// The store changes of `apply_light_client_update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedUpdate {
+    pub committee: CommitteeChange,
+    /// The finalized header of the update becomes the finalized header.
+    pub update_finalized_header: bool,
+    /// The finalized header of the update becomes the optimistic header.
+    pub update_optimistic_header: bool,
+    /// The finalized header of the update becomes the safe header.
+    pub update_safe_header: bool,
+}
+
+/// Whether `update` finalizes a header newer than the finalized header of `store`.
+pub fn finalizes_newer_header(store: &StoreSummary, update: &UpdateSummary) -> bool {
+    update.finalized_slot.unwrap_or_default() > store.finalized_slot
+}
+
+/// Implements the store changes of `process_light_client_update`, for an update whose
+/// signature was verified.
+pub fn process_update(
+    store: &StoreSummary,
+    update: &UpdateSummary,
+    policy: &UpdatePolicy,
+) -> ProcessedUpdate {
+    let current_max_active_participants =
+        u64::max(store.current_max_active_participants, update.participants);
+
+    let update_optimistic_header = !policy.strict_finality
+        && update.participants
+            > safety_threshold(
+                store.previous_max_active_participants,
+                store.current_max_active_participants,
+            )
+        && update.participants >= policy.min_optimistic_participants
+        && update.attested_slot > store.optimistic_slot;
+
+    let update_safe_header = !policy.strict_finality
+        && has_supermajority(update.participants)
+        && update.attested_slot > store.safe_slot;
+
+    let update_has_finalized_next_committee = !store.has_next_sync_committee
+        && update.has_next_sync_committee
+        && update
+            .finalized_slot
+            .is_some_and(|slot| calc_sync_period(slot) == calc_sync_period(update.attested_slot));
+
+    let apply = has_supermajority(update.participants)
+        && (finalizes_newer_header(store, update) || update_has_finalized_next_committee);
+
+    ProcessedUpdate {
+        current_max_active_participants,
+        update_optimistic_header,
+        update_safe_header,
+        apply,
+    }
+}
+
+/// Implements the store changes of `apply_light_client_update`.
+pub fn apply_update(store: &StoreSummary, update: &UpdateSummary) -> AppliedUpdate {
+    let store_period = calc_sync_period(store.finalized_slot);
+    let update_finalized_slot = update.finalized_slot.unwrap_or_default();
+
+    let committee = if !store.has_next_sync_committee {
+        CommitteeChange::SetNext
+    } else if calc_sync_period(update_finalized_slot) == store_period + 1 {
+        CommitteeChange::Rotate
+    } else {
+        CommitteeChange::None
+    };
+
+    let update_finalized_header = finalizes_newer_header(store, update);
+
+    AppliedUpdate {
+        committee,
+        update_finalized_header,
+        update_optimistic_header: update_finalized_header
+            && update_finalized_slot > store.optimistic_slot,
+        update_safe_header: update_finalized_header && update_finalized_slot > store.safe_slot,
+    }
+}
+
+/// Whether `process_light_client_store_force_update` applies the best valid update at
+/// `current_slot`, as no header was finalized for more than `timeout` slots.
+pub fn is_force_update_due(store: &StoreSummary, current_slot: u64, timeout: u64) -> bool {
+    current_slot > store.finalized_slot + timeout
+}
+
+/// The update applied by `process_light_client_store_force_update` in place of `update`. If
+/// `update` does not finalize a newer header, its attested header is applied as finalized.
+pub fn forced_update(store: &StoreSummary, update: &UpdateSummary) -> UpdateSummary {
+    let mut forced = *update;
+    if !finalizes_newer_header(store, update) {
+        forced.finalized_slot = Some(update.attested_slot);
+    }
+
+    forced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> StoreSummary {
+        StoreSummary {
+            finalized_slot: 8000,
+            optimistic_slot: 8064,
+            safe_slot: 8064,
+            has_next_sync_committee: true,
+            previous_max_active_participants: 400,
+            current_max_active_participants: 300,
+        }
+    }
+
+    fn summary(participants: u64, attested_slot: u64, finalized: bool) -> UpdateSummary {
+        UpdateSummary {
+            participants,
+            attested_slot,
+            signature_slot: attested_slot + 1,
+            finalized_slot: finalized.then_some(attested_slot - 64),
+            has_next_sync_committee: false,
+        }
+    }
+
+    #[test]
+    fn test_process_update() {
+        let processed = process_update(
+            &store(),
+            &summary(450, 8128, true),
+            &UpdatePolicy::default(),
+        );
+        assert_eq!(
+            processed,
+            ProcessedUpdate {
+                current_max_active_participants: 450,
+                update_optimistic_header: true,
+                update_safe_header: true,
+                apply: true,
+            }
+        );
+
+        // Below the supermajority, only the optimistic header advances.
+        let processed = process_update(
+            &store(),
+            &summary(300, 8128, true),
+            &UpdatePolicy::default(),
+        );
+        assert!(processed.update_optimistic_header);
+        assert!(!processed.update_safe_header);
+        assert!(!processed.apply);
+
+        // An update that finalizes an older header is not applied.
+        let processed = process_update(
+            &store(),
+            &summary(450, 8032, true),
+            &UpdatePolicy::default(),
+        );
+        assert!(!processed.update_optimistic_header);
+        assert!(!processed.apply);
+    }
+
+    #[test]
+    fn test_process_update_policy() {
+        let strict = UpdatePolicy {
+            strict_finality: true,
+            ..Default::default()
+        };
+        let processed = process_update(&store(), &summary(450, 8128, true), &strict);
+        assert!(!processed.update_optimistic_header);
+        assert!(!processed.update_safe_header);
+        assert!(processed.apply);
+
+        let min_participants = UpdatePolicy {
+            min_optimistic_participants: 400,
+            ..Default::default()
+        };
+        let processed = process_update(&store(), &summary(300, 8128, false), &min_participants);
+        assert!(!processed.update_optimistic_header);
+    }
+
+    #[test]
+    fn test_process_update_finalized_next_committee() {
+        let store = StoreSummary {
+            has_next_sync_committee: false,
+            ..store()
+        };
+        // The update finalizes an older header, but of the same period as its next committee.
+        let update = UpdateSummary {
+            has_next_sync_committee: true,
+            ..summary(450, 7900, true)
+        };
+
+        assert!(process_update(&store, &update, &UpdatePolicy::default()).apply);
+    }
+
+    #[test]
+    fn test_apply_update() {
+        let applied = apply_update(&store(), &summary(450, 8128, true));
+        assert_eq!(
+            applied,
+            AppliedUpdate {
+                committee: CommitteeChange::None,
+                update_finalized_header: true,
+                update_optimistic_header: false,
+                update_safe_header: false,
+            }
+        );
+
+        let applied = apply_update(&store(), &summary(450, 8256, true));
+        assert_eq!(applied.committee, CommitteeChange::Rotate);
+        assert!(applied.update_optimistic_header);
+
+        let store = StoreSummary {
+            has_next_sync_committee: false,
+            ..store()
+        };
+        let applied = apply_update(&store, &summary(450, 7900, true));
+        assert_eq!(applied.committee, CommitteeChange::SetNext);
+        assert!(!applied.update_finalized_header);
+    }
+
+    #[test]
+    fn test_forced_update() {
+        assert!(!is_force_update_due(&store(), 8100, 100));
+        assert!(is_force_update_due(&store(), 8101, 100));
+
+        let update = summary(300, 8128, false);
+        assert_eq!(forced_update(&store(), &update).finalized_slot, Some(8128));
+
+        let update = summary(300, 8128, true);
+        assert_eq!(forced_update(&store(), &update), update);
+    }
+}
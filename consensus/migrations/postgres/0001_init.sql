@@ -0,0 +1,47 @@
+-- The checkpoints saved by the clients, of which the latest one of a chain is loaded.
+CREATE TABLE checkpoints (
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    checkpoint BYTEA NOT NULL,
+    saved_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX checkpoints_chain_id ON checkpoints (chain_id, id);
+
+-- The finalized headers verified by the clients.
+CREATE TABLE verified_headers (
+    chain_id BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_root BYTEA NOT NULL,
+    header JSONB NOT NULL,
+    execution_block_hash BYTEA,
+    execution_state_root BYTEA,
+    verified_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (chain_id, slot)
+);
+
+CREATE INDEX verified_headers_block_root ON verified_headers (block_root);
+
+-- The sync committees verified by the clients, by period.
+CREATE TABLE sync_committees (
+    chain_id BIGINT NOT NULL,
+    period BIGINT NOT NULL,
+    aggregate_pubkey BYTEA NOT NULL,
+    pubkeys BYTEA[] NOT NULL,
+    verified_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (chain_id, period)
+);
+
+-- The evidence of conflicting finalized headers, as an audit record of the misbehaviour of the
+-- sync committees.
+CREATE TABLE finality_conflicts (
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    existing_root BYTEA NOT NULL,
+    conflicting_root BYTEA NOT NULL,
+    evidence JSONB NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX finality_conflicts_chain_id ON finality_conflicts (chain_id, slot);
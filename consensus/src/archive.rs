@@ -0,0 +1,170 @@
+//! Bulk import of historical light client updates from a local archive, so that months of
+//! history can be synced without fetching every period from a beacon API.
+//!
+//! An archive is either:
+//!
+//! * a directory laid out like the one of [`FileRpc`], with `updates/<period>.json` and/or
+//!   `updates.json` files;
+//! * an era file, or a directory of `*.era` files, in the [e2store] format. Their
+//!   [`LIGHT_CLIENT_UPDATE`] entries hold the JSON of one update each, as an item of the response
+//!   of `/eth/v1/beacon/light_client/updates`. The entries of other types, such as the blocks and
+//!   states of the era files of the consensus clients, are skipped.
+//!
+//! The updates are not trusted, they are verified in sequence like the ones of a beacon node.
+//!
+//! [`FileRpc`]: crate::rpc::file_rpc::FileRpc
+//! [e2store]: https://github.com/status-im/nimbus-eth2/blob/stable/docs/e2store.md
+
+use std::path::{Path, PathBuf};
+
+use eyre::{eyre, Result};
+use serde_json::Value;
+
+use crate::{
+    errors::ConsensusError,
+    rpc::{file_rpc::FileRpc, provider, responses::UpdateResponse, ConsensusRpc},
+    types::Update,
+    utils::calc_sync_period,
+};
+
+/// The type of the version entry that starts every e2store file.
+pub const VERSION: [u8; 2] = [0x65, 0x32];
+/// The type of the entries of light client updates, which is specific to Helios.
+pub const LIGHT_CLIENT_UPDATE: [u8; 2] = [0x4c, 0x55];
+
+/// The length of the header of an e2store entry: its type, the length of its data and two
+/// reserved bytes.
+const HEADER_LEN: usize = 8;
+
+/// An entry of an e2store file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub entry_type: [u8; 2],
+    pub data: Vec<u8>,
+}
+
+/// Parses the entries of an e2store file, which must start with a version entry.
+pub fn read_entries(mut bytes: &[u8]) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < HEADER_LEN {
+            return Err(
+                ConsensusError::InvalidArchive("truncated entry header".to_string()).into(),
+            );
+        }
+
+        let entry_type = [bytes[0], bytes[1]];
+        let len = u32::from_le_bytes(bytes[2..6].try_into()?) as usize;
+        if bytes[6..8] != [0, 0] {
+            return Err(ConsensusError::InvalidArchive("invalid entry header".to_string()).into());
+        }
+        if bytes.len() < HEADER_LEN + len {
+            return Err(ConsensusError::InvalidArchive("truncated entry".to_string()).into());
+        }
+
+        entries.push(Entry {
+            entry_type,
+            data: bytes[HEADER_LEN..HEADER_LEN + len].to_vec(),
+        });
+        bytes = &bytes[HEADER_LEN + len..];
+    }
+
+    match entries.first() {
+        Some(entry) if entry.entry_type == VERSION && entry.data.is_empty() => Ok(entries),
+        _ => Err(ConsensusError::InvalidArchive("missing version entry".to_string()).into()),
+    }
+}
+
+/// Appends the entry of `entry_type` with `data` to the e2store file `buf`.
+pub fn write_entry(buf: &mut Vec<u8>, entry_type: [u8; 2], data: &[u8]) {
+    buf.extend_from_slice(&entry_type);
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&[0, 0]);
+    buf.extend_from_slice(data);
+}
+
+/// Returns the updates of the [`LIGHT_CLIENT_UPDATE`] entries of an era file, in file order.
+pub fn read_era_updates(bytes: &[u8]) -> Result<Vec<Update>> {
+    let items = read_entries(bytes)?
+        .into_iter()
+        .filter(|entry| entry.entry_type == LIGHT_CLIENT_UPDATE)
+        .map(|entry| {
+            let mut value = serde_json::from_slice::<Value>(&entry.data)?;
+            provider::normalize(&mut value);
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let value = provider::normalize_updates(Value::Array(items));
+    let res: UpdateResponse = serde_json::from_value(value)?;
+    Ok(res.into_iter().map(|update| update.data).collect())
+}
+
+/// Returns the updates of the archive at `path` from `start_period` on. They are neither sorted
+/// nor deduplicated.
+pub async fn read_archive_updates(path: &Path, start_period: u64) -> Result<Vec<Update>> {
+    let mut updates = Vec::new();
+    for era_file in era_files(path)? {
+        let bytes = std::fs::read(&era_file)
+            .map_err(|err| eyre!("cannot read {}: {}", era_file.display(), err))?;
+        updates.extend(read_era_updates(&bytes)?);
+    }
+
+    if path.is_dir() {
+        let rpc = FileRpc::new(&path.to_string_lossy());
+        updates.extend(
+            rpc.get_updates(start_period, u64::MAX - start_period)
+                .await?,
+        );
+    }
+
+    updates.retain(|update| calc_sync_period(update.attested_header.slot.into()) >= start_period);
+    Ok(updates)
+}
+
+/// Returns `path` if it is a file, or the `*.era` files of the directory `path`, by name.
+fn era_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let file = entry?.path();
+        if file.is_file() && file.extension().is_some_and(|ext| ext == "era") {
+            files.push(file);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_era_updates() {
+        let updates = std::fs::read_to_string("testdata/updates.json").unwrap();
+        let items = serde_json::from_str::<Vec<Value>>(&updates).unwrap();
+
+        let mut era = Vec::new();
+        write_entry(&mut era, VERSION, &[]);
+        for item in &items {
+            write_entry(&mut era, LIGHT_CLIENT_UPDATE, item.to_string().as_bytes());
+            // The blocks and states of the era files of the consensus clients are skipped.
+            write_entry(&mut era, [0x01, 0x00], &[1, 2, 3]);
+        }
+
+        let updates = read_era_updates(&era).unwrap();
+        assert_eq!(items.len(), updates.len());
+        assert_eq!(
+            items[0]["data"]["attested_header"]["beacon"]["slot"],
+            updates[0].attested_header.slot.as_u64().to_string()
+        );
+
+        assert!(read_entries(&era[HEADER_LEN..]).is_err());
+        assert!(read_entries(&era[..era.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Verification of Deneb blob sidecars against the `blob_kzg_commitments` of a verified block
+//! body, see the
+//! [Deneb spec](https://github.com/ethereum/consensus-specs/blob/dev/specs/deneb/p2p-interface.md#blob-subnets).
+//!
+//! The KZG proofs are checked with the trusted setup of the ceremony, which is loaded by the
+//! caller, e.g. with [`KzgSettings::load_trusted_setup_file`].
+
+pub use c_kzg::KzgSettings;
+use c_kzg::{Bytes48, KzgProof};
+use eyre::{eyre, Result};
+
+use crate::{
+    errors::ConsensusError,
+    types::{BeaconBlock, BlobSidecar},
+};
+
+/// Verifies that every sidecar is committed to by the body of `block`, and that its blob matches
+/// the commitment. `block` must have been verified.
+pub fn verify_blob_sidecars(
+    block: &BeaconBlock,
+    sidecars: &[BlobSidecar],
+    kzg_settings: &KzgSettings,
+) -> Result<()> {
+    verify_blob_commitments(block, sidecars)?;
+
+    for sidecar in sidecars {
+        let index = sidecar.index.as_u64();
+        let blob = c_kzg::Blob::from_bytes(sidecar.blob.as_slice())
+            .map_err(|err| eyre!("invalid blob {index}: {err:?}"))?;
+        let commitment = Bytes48::from_bytes(sidecar.kzg_commitment.as_slice())
+            .map_err(|err| eyre!("invalid commitment {index}: {err:?}"))?;
+        let proof = Bytes48::from_bytes(sidecar.kzg_proof.as_slice())
+            .map_err(|err| eyre!("invalid proof {index}: {err:?}"))?;
+
+        let is_valid = KzgProof::verify_blob_kzg_proof(&blob, &commitment, &proof, kzg_settings)
+            .map_err(|err| eyre!("kzg verification of blob {index} failed: {err:?}"))?;
+
+        if !is_valid {
+            return Err(ConsensusError::InvalidBlobProof(index).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the commitment of every sidecar is the one at its index in the block body.
+fn verify_blob_commitments(block: &BeaconBlock, sidecars: &[BlobSidecar]) -> Result<()> {
+    let commitments = block
+        .body
+        .blob_kzg_commitments()
+        .map_err(|_| ConsensusError::NoBlobs(block.slot.as_u64()))?;
+
+    for sidecar in sidecars {
+        let index = sidecar.index.as_u64();
+        let commitment = commitments
+            .get(index as usize)
+            .ok_or(ConsensusError::UnknownBlobIndex(index))?;
+
+        if commitment != &sidecar.kzg_commitment {
+            return Err(ConsensusError::BlobCommitmentMismatch(index).into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BeaconBlockBody, BeaconBlockBodyDeneb, KzgCommitment};
+
+    fn sidecar(index: u64, commitment: u8) -> BlobSidecar {
+        BlobSidecar {
+            index: index.into(),
+            kzg_commitment: KzgCommitment::try_from(vec![commitment; 48]).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_verify_blob_commitments() {
+        let mut body = BeaconBlockBodyDeneb::default();
+        body.blob_kzg_commitments
+            .push(KzgCommitment::try_from(vec![1; 48]).unwrap());
+        let block = BeaconBlock {
+            body: BeaconBlockBody::Deneb(body),
+            ..Default::default()
+        };
+
+        verify_blob_commitments(&block, &[sidecar(0, 1)]).unwrap();
+
+        let err = verify_blob_commitments(&block, &[sidecar(0, 2)]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::BlobCommitmentMismatch(0))
+        ));
+
+        let err = verify_blob_commitments(&block, &[sidecar(1, 1)]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::UnknownBlobIndex(1))
+        ));
+
+        // Blocks before Deneb have no blobs.
+        let block = BeaconBlock::default();
+        assert!(verify_blob_commitments(&block, &[sidecar(0, 1)]).is_err());
+    }
+}
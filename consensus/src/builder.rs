@@ -0,0 +1,266 @@
+//! A builder of [`ConsensusStateManager`]s, for the users that do not load a full [`Config`] up
+//! front. The options are validated at [`ConsensusStateManagerBuilder::build`].
+
+use std::sync::Arc;
+
+use config::{Config, Network, NetworkConfig, SlotClockConfig};
+use eyre::Result;
+
+use crate::{database::Database, errors::ConsensusError, rpc::ConsensusRpc, ConsensusStateManager};
+
+/// A builder of a [`ConsensusStateManager`], see [`ConsensusStateManager::builder`].
+pub struct ConsensusStateManagerBuilder<R: ConsensusRpc> {
+    rpc: Option<String>,
+    rpc_client: Option<R>,
+    network: Option<Network>,
+    network_config: Option<NetworkConfig>,
+    checkpoint: Option<Vec<u8>>,
+    saved_checkpoint: Option<Vec<u8>>,
+    strict_checkpoint_age: bool,
+    max_checkpoint_age: Option<u64>,
+    slot_clock: Option<SlotClockConfig>,
+}
+
+impl<R: ConsensusRpc> Default for ConsensusStateManagerBuilder<R> {
+    fn default() -> Self {
+        Self {
+            rpc: None,
+            rpc_client: None,
+            network: None,
+            network_config: None,
+            checkpoint: None,
+            saved_checkpoint: None,
+            strict_checkpoint_age: false,
+            max_checkpoint_age: None,
+            slot_clock: None,
+        }
+    }
+}
+
+impl<R: ConsensusRpc> ConsensusStateManagerBuilder<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The URL of the consensus RPC, to which the RPC settings of the config are applied.
+    pub fn rpc(mut self, rpc: &str) -> Self {
+        self.rpc = Some(rpc.to_string());
+        self
+    }
+
+    /// An already built RPC client, e.g. a `MockRpc` of the `testing` feature, instead of
+    /// [`Self::rpc`].
+    pub fn rpc_client(mut self, rpc: R) -> Self {
+        self.rpc_client = Some(rpc);
+        self
+    }
+
+    /// One of the built-in networks.
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// A network that is not built in, such as a private devnet, instead of [`Self::network`].
+    pub fn network_config(mut self, network_config: NetworkConfig) -> Self {
+        self.network_config = Some(network_config);
+        self
+    }
+
+    /// The trusted block root to bootstrap from. It takes precedence over the checkpoint saved to
+    /// the [`Self::database`] and the default checkpoint of the network.
+    pub fn checkpoint(mut self, checkpoint: Vec<u8>) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Loads the checkpoint saved to `db`, from which the manager resumes when no checkpoint is
+    /// given. A database that has none is ignored.
+    pub fn database<DB: Database>(mut self, db: &DB) -> Self {
+        self.saved_checkpoint = db.load_checkpoint().ok();
+        self
+    }
+
+    /// Whether to fail the bootstrap from a checkpoint older than the maximum checkpoint age,
+    /// instead of warning.
+    pub fn strict_checkpoint_age(mut self, strict: bool) -> Self {
+        self.strict_checkpoint_age = strict;
+        self
+    }
+
+    /// The maximum age of the checkpoint, in seconds, instead of the one of the network.
+    pub fn max_checkpoint_age(mut self, age: u64) -> Self {
+        self.max_checkpoint_age = Some(age);
+        self
+    }
+
+    /// The length of a slot and the tolerated drift of the local clock.
+    pub fn slot_clock(mut self, slot_clock: SlotClockConfig) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    /// Builds the manager, which still has to be synced, e.g. with
+    /// [`ConsensusStateManager::sync`]. Fails if an option is missing, conflicts with another one
+    /// or is invalid.
+    pub fn build(self) -> Result<ConsensusStateManager<R>> {
+        let base_config = match (self.network, &self.network_config) {
+            (Some(network), None) => network.to_base_config(),
+            (None, Some(network_config)) => network_config.to_base_config(),
+            (Some(_), Some(_)) => {
+                return Err(ConsensusError::ConflictingOptions("network", "network_config").into())
+            }
+            (None, None) => return Err(ConsensusError::MissingOption("network").into()),
+        };
+
+        let rpc = match (self.rpc, &self.rpc_client) {
+            (Some(rpc), None) => rpc,
+            (None, Some(_)) => String::new(),
+            (Some(_), Some(_)) => {
+                return Err(ConsensusError::ConflictingOptions("rpc", "rpc_client").into())
+            }
+            (None, None) => return Err(ConsensusError::MissingOption("rpc").into()),
+        };
+
+        if let Some(checkpoint) = &self.checkpoint {
+            if checkpoint.len() != 32 {
+                return Err(ConsensusError::InvalidOption(format!(
+                    "checkpoint must be 32 bytes, got {}",
+                    checkpoint.len()
+                ))
+                .into());
+            }
+        }
+
+        if let Some(slot_clock) = &self.slot_clock {
+            if slot_clock.seconds_per_slot == 0 {
+                return Err(ConsensusError::InvalidOption(
+                    "seconds per slot must be positive".to_string(),
+                )
+                .into());
+            }
+        }
+
+        let mut config: Config = base_config.as_config();
+        config.consensus_rpc = rpc.clone();
+        config.strict_checkpoint_age = self.strict_checkpoint_age;
+        if let Some(age) = self.max_checkpoint_age {
+            config.max_checkpoint_age = age;
+        }
+        if let Some(slot_clock) = self.slot_clock {
+            config.slot_clock = slot_clock;
+        }
+
+        // The databases return the default checkpoint when none was saved.
+        let saved_checkpoint = self.saved_checkpoint.filter(|checkpoint| {
+            checkpoint.len() == 32 && *checkpoint != config.default_checkpoint
+        });
+        let checkpoint = self
+            .checkpoint
+            .or(saved_checkpoint)
+            .unwrap_or_else(|| config.default_checkpoint.clone());
+        config.checkpoint = Some(checkpoint.clone());
+
+        let mut manager =
+            ConsensusStateManager::new(&rpc, None, None, None, Arc::new(config), Some(checkpoint));
+        if let Some(rpc_client) = self.rpc_client {
+            manager.set_rpc_client(rpc_client);
+        }
+
+        Ok(manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::ConfigDB, rpc::mock_rpc::MockRpc};
+
+    const CHECKPOINT: &str = "5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275";
+
+    fn error(builder: ConsensusStateManagerBuilder<MockRpc>) -> String {
+        builder.build().err().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_build() {
+        let mut manager = ConsensusStateManager::builder()
+            .network(Network::MAINNET)
+            .rpc_client(MockRpc::new("testdata/"))
+            .checkpoint(hex::decode(CHECKPOINT).unwrap())
+            .build()
+            .unwrap();
+
+        let checkpoint = manager.last_checkpoint.clone().unwrap();
+        assert_eq!(CHECKPOINT, hex::encode(&checkpoint));
+        manager.sync(&checkpoint).await.unwrap();
+        assert!(manager.get_finalized_header().slot.as_u64() > 0);
+    }
+
+    #[test]
+    fn test_build_checkpoint_precedence() {
+        let mainnet = Network::MAINNET.to_base_config().as_config();
+        let db = ConfigDB::new(&Config {
+            checkpoint: Some(vec![1; 32]),
+            ..Default::default()
+        })
+        .unwrap();
+        let builder = || {
+            ConsensusStateManager::<MockRpc>::builder()
+                .network(Network::MAINNET)
+                .rpc("testdata/")
+        };
+
+        let manager = builder().build().unwrap();
+        assert_eq!(Some(mainnet.default_checkpoint), manager.last_checkpoint);
+        let manager = builder().database(&db).build().unwrap();
+        assert_eq!(Some(vec![1; 32]), manager.last_checkpoint);
+        let manager = builder()
+            .database(&db)
+            .checkpoint(vec![2; 32])
+            .build()
+            .unwrap();
+        assert_eq!(Some(vec![2; 32]), manager.last_checkpoint);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let builder = ConsensusStateManager::<MockRpc>::builder;
+
+        assert_eq!("missing option: network", error(builder().rpc("testdata/")));
+        assert_eq!(
+            "missing option: rpc",
+            error(builder().network(Network::MAINNET))
+        );
+        assert_eq!(
+            "conflicting options: rpc and rpc_client",
+            error(
+                builder()
+                    .network(Network::MAINNET)
+                    .rpc("testdata/")
+                    .rpc_client(MockRpc::new("testdata/"))
+            )
+        );
+        assert_eq!(
+            "invalid option: checkpoint must be 32 bytes, got 3",
+            error(
+                builder()
+                    .network(Network::MAINNET)
+                    .rpc("testdata/")
+                    .checkpoint(vec![1, 2, 3])
+            )
+        );
+        assert_eq!(
+            "invalid option: seconds per slot must be positive",
+            error(
+                builder()
+                    .network(Network::MAINNET)
+                    .rpc("testdata/")
+                    .slot_clock(SlotClockConfig {
+                        seconds_per_slot: 0,
+                        max_clock_drift_ms: 0,
+                    })
+            )
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use config::Config;
+use consensus_core::sync::{calc_sync_period, SLOTS_PER_EPOCH};
+use zduny_wasm_timer::{SystemTime, UNIX_EPOCH};
+
+/// Maps the time since genesis to slots, and tells which slots have started given a tolerated
+/// drift of the local clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotClock {
+    /// The unix timestamp of the genesis, in seconds.
+    pub genesis_time: u64,
+    pub seconds_per_slot: u64,
+    /// How far ahead of the local clock a slot may start and still be accepted.
+    pub max_clock_drift: Duration,
+}
+
+impl SlotClock {
+    pub fn new(genesis_time: u64, seconds_per_slot: u64, max_clock_drift: Duration) -> Self {
+        Self {
+            genesis_time,
+            seconds_per_slot: seconds_per_slot.max(1),
+            max_clock_drift,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.chain.genesis_time,
+            config.slot_clock.seconds_per_slot,
+            Duration::from_millis(config.slot_clock.max_clock_drift_ms),
+        )
+    }
+
+    /// The time since the unix epoch.
+    pub fn now() -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+    }
+
+    /// The unix timestamp of the start of `slot`, in seconds.
+    pub fn slot_timestamp(&self, slot: u64) -> u64 {
+        slot * self.seconds_per_slot + self.genesis_time
+    }
+
+    /// The slot at the time `since_epoch`, which is the genesis slot before genesis.
+    pub fn slot_at(&self, since_epoch: Duration) -> u64 {
+        let since_genesis = since_epoch.saturating_sub(Duration::from_secs(self.genesis_time));
+        since_genesis.as_secs() / self.seconds_per_slot
+    }
+
+    /// The slot of the local clock.
+    pub fn current_slot(&self) -> u64 {
+        self.slot_at(Self::now())
+    }
+
+    /// The latest slot that may have started, given the drift of the local clock.
+    pub fn latest_started_slot(&self) -> u64 {
+        self.slot_at(Self::now() + self.max_clock_drift)
+    }
+
+    /// Whether `slot` starts after the local clock plus the tolerated drift, e.g. the signature
+    /// slot of an update from a misbehaving RPC.
+    pub fn is_future_slot(&self, slot: u64) -> bool {
+        slot > self.latest_started_slot()
+    }
+
+    /// The time left until the start of the slot after the current one.
+    pub fn duration_until_next_slot(&self) -> Duration {
+        let now = Self::now();
+        let next_slot = Duration::from_secs(self.slot_timestamp(self.slot_at(now) + 1));
+        next_slot.saturating_sub(now)
+    }
+}
+
+/// The unix timestamp of the start of `slot` on the network of `config`, in seconds.
+pub fn slot_to_timestamp(config: &Config, slot: u64) -> u64 {
+    SlotClock::from_config(config).slot_timestamp(slot)
+}
+
+/// The slot at the unix timestamp `timestamp`, in seconds, on the network of `config`. Returns
+/// `None` before genesis.
+pub fn timestamp_to_slot(config: &Config, timestamp: u64) -> Option<u64> {
+    if timestamp < config.chain.genesis_time {
+        return None;
+    }
+
+    Some(SlotClock::from_config(config).slot_at(Duration::from_secs(timestamp)))
+}
+
+/// The epoch of `slot`. The length of an epoch is the one of the mainnet preset, which every
+/// supported network uses.
+pub fn slot_to_epoch(_config: &Config, slot: u64) -> u64 {
+    slot / SLOTS_PER_EPOCH
+}
+
+/// The sync committee period of `slot`, which is the one the light client verifies the
+/// signatures of its updates with.
+pub fn slot_to_sync_committee_period(_config: &Config, slot: u64) -> u64 {
+    calc_sync_period(slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_clock() {
+        let clock = SlotClock::new(1000, 12, Duration::from_millis(500));
+
+        assert_eq!(0, clock.slot_at(Duration::from_secs(10)));
+        assert_eq!(0, clock.slot_at(Duration::from_secs(1011)));
+        assert_eq!(1, clock.slot_at(Duration::from_secs(1012)));
+        assert_eq!(1024, clock.slot_timestamp(2));
+
+        let current_slot = clock.current_slot();
+        assert!(!clock.is_future_slot(current_slot));
+        assert!(clock.is_future_slot(current_slot + 2));
+        assert!(clock.duration_until_next_slot() <= Duration::from_secs(12));
+    }
+
+    #[test]
+    fn test_slot_clock_drift() {
+        // A slot that starts within the drift is accepted.
+        let now = SlotClock::now();
+        let genesis_time = now.as_secs() + 1;
+        let clock = SlotClock::new(genesis_time, 12, Duration::from_secs(2));
+        assert!(!clock.is_future_slot(0));
+        assert!(clock.is_future_slot(1));
+
+        let strict = SlotClock::new(genesis_time + 10, 12, Duration::ZERO);
+        assert_eq!(0, strict.latest_started_slot());
+    }
+
+    #[test]
+    fn test_slot_conversions() {
+        let config = config::networks::mainnet().as_config();
+        let genesis_time = config.chain.genesis_time;
+
+        assert_eq!(genesis_time + 12 * 100, slot_to_timestamp(&config, 100));
+        assert_eq!(
+            Some(100),
+            timestamp_to_slot(&config, genesis_time + 12 * 100 + 11)
+        );
+        assert_eq!(None, timestamp_to_slot(&config, genesis_time - 1));
+        assert_eq!(3, slot_to_epoch(&config, 127));
+        assert_eq!(862, slot_to_sync_committee_period(&config, 7061719));
+    }
+}
@@ -1,33 +1,94 @@
-use std::{cmp, marker::PhantomData, process, sync::Arc};
+use std::{cmp, collections::BTreeMap, path::Path, process, sync::Arc};
 
 use chrono::Duration;
 use common::types::Block;
-use config::{CheckpointFallback, Config, Network};
+use config::{CheckpointFallback, Config, Network, NetworkConfig};
+use consensus_core::{
+    sync::{has_supermajority, is_better_update, UpdateSummary},
+    update::{
+        apply_update, finalizes_newer_header, forced_update, is_force_update_due, process_update,
+        CommitteeChange, StoreSummary, UpdatePolicy,
+    },
+};
+use ethers::types::H256;
 use eyre::{eyre, Result};
-use futures::future::join_all;
+use futures::{
+    future::{join_all, try_join_all},
+    stream, StreamExt, TryStreamExt,
+};
 use milagro_bls::PublicKey;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use ssz_rs::prelude::*;
-use tokio::sync::{
-    mpsc::{channel, Receiver, Sender},
-    watch,
+use tokio::{
+    select,
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{channel, Receiver, Sender},
+        watch, RwLock,
+    },
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
-use zduny_wasm_timer::{SystemTime, UNIX_EPOCH};
 
 use super::{rpc::ConsensusRpc, types::*, utils::*};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::backup::ObjectStoreBackup;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blobs::{verify_blob_sidecars, KzgSettings};
 use crate::{
-    constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES, database::Database, errors::ConsensusError,
-    types::primitives::U64,
+    archive,
+    builder::ConsensusStateManagerBuilder,
+    clock::{timestamp_to_slot, SlotClock},
+    constants::{
+        BACKFILL_CONCURRENCY, MAX_BACKFILL_SLOTS, MAX_SYNCED_HEAD_AGE_SECS,
+        PARALLEL_SYNC_PERIODS_PER_REQUEST, SYNC_COMMITTEE_SIZE,
+    },
+    database::Database,
+    errors::ConsensusError,
+    header_cache::{HeaderCache, VerifiedHeader},
+    light_client_data::LightClientData,
+    snapshot::SignedStateSnapshot,
+    sync_health::SyncHealthMonitor,
+    types::{
+        conflict::FinalityConflict,
+        diff::{ExecutionRange, StateDiff},
+        encoding::{decode_versioned, encode_versioned, sync_committee_root, DeltaUpdatesResponse},
+        execution_header::{execution_block_hash, transactions_root},
+        genesis,
+        multiproof::StateMultiproof,
+        payload_proof::ExecutionPayloadProof,
+        primitives::U64,
+        validator::{StateProof, ValidatorProof},
+        withdrawals::WithdrawalProof,
+    },
+    update_cache::{update_key, VerifiedUpdateCache},
 };
 
 pub struct ConsensusClient<R: ConsensusRpc, DB: Database> {
     pub block_recv: Option<Receiver<Block>>,
     pub finalized_block_recv: Option<watch::Receiver<Option<Block>>>,
     pub checkpoint_recv: watch::Receiver<Option<Vec<u8>>>,
-    genesis_time: u64,
+    /// Alerts of conflicting finalized headers, whose evidence is also saved to the database.
+    pub conflict_recv: Option<Receiver<FinalityConflict>>,
+    /// Warnings of stale checkpoints, from which the client did not sync forward, see
+    /// [`Config::stale_checkpoint_age`].
+    pub stale_checkpoint_recv: Option<Receiver<StaleCheckpoint>>,
+    /// Warnings of a degraded sync, see [`Config::sync_health`].
+    pub sync_health_recv: Option<Receiver<SyncHealthWarning>>,
+    finalized_header_send: broadcast::Sender<FinalizedHeader>,
+    light_client_data: LightClientData,
+    /// Stops the tasks of the client, see [`Self::shutdown`].
+    cancel: CancellationToken,
+    /// Closed once the task of the state manager stopped, see [`Self::shutdown`].
+    stopped_recv: watch::Receiver<()>,
+    slot_clock: SlotClock,
+    /// Serves the requests for light client data, which do not go through the state manager.
+    /// It is replaced by [`Self::reload`].
+    rpc: RwLock<R>,
+    /// Sends the reloaded configs to the state manager, and holds the current one.
+    reload_send: watch::Sender<Config>,
     db: DB,
-    phantom: PhantomData<R>,
 }
 
 /// This struct original name was `Inner`, but it was renamed to `ConsensusStateManager`
@@ -44,32 +105,130 @@ pub struct ConsensusStateManager<R: ConsensusRpc> {
     finalized_block_send: Option<watch::Sender<Option<Block>>>,
     #[serde(skip)]
     checkpoint_send: Option<watch::Sender<Option<Vec<u8>>>>,
+    /// Receives the evidence of conflicting finalized headers, see [`FinalityConflict`].
+    #[serde(skip)]
+    conflict_send: Option<Sender<FinalityConflict>>,
+    /// Receives the warnings of stale checkpoints, see [`StaleCheckpoint`].
+    #[serde(skip)]
+    stale_checkpoint_send: Option<Sender<StaleCheckpoint>>,
+    /// Receives the warnings of a degraded sync, see [`SyncHealthWarning`].
+    #[serde(skip)]
+    sync_health_send: Option<Sender<SyncHealthWarning>>,
+    /// Receives the configs to reload while running, see [`Self::reload`].
+    #[serde(skip)]
+    reload_recv: Option<watch::Receiver<Config>>,
+    /// Receives the exported state of every new finalized header, see [`Self::set_state_sender`].
+    #[serde(skip)]
+    state_send: Option<watch::Sender<Option<Vec<u8>>>>,
     pub config: Config,
+    /// Recently verified finalized headers, so that repeated requests for the same block are not
+    /// verified again.
+    #[serde(skip)]
+    header_cache: HeaderCache,
+    /// Recently verified sync aggregate signatures, so that an update that is received twice is
+    /// not verified again.
+    #[serde(skip)]
+    update_cache: VerifiedUpdateCache,
+    /// The verified light client data, to serve it to other light clients.
+    #[serde(skip)]
+    light_client_data: LightClientData,
+    /// Sends every newly verified finalized header, see [`Self::subscribe_finalized`].
+    #[serde(skip, default = "finalized_channel")]
+    finalized_header_send: broadcast::Sender<FinalizedHeader>,
+    /// The slot of the last finalized header that was broadcast.
+    #[serde(skip)]
+    broadcast_finalized_slot: u64,
+    /// The unix timestamp of the last sync or advance that succeeded, see [`Self::health`].
+    #[serde(skip)]
+    last_success: Option<u64>,
+    /// The error of the last sync or advance, if it failed.
+    #[serde(skip)]
+    last_error: Option<String>,
+    /// The participation of the recent updates, see [`Self::health`].
+    #[serde(skip)]
+    sync_health: SyncHealthMonitor,
+}
+
+/// The number of finalized headers a lagging subscriber can miss before it starts losing them.
+const FINALIZED_CHANNEL_CAPACITY: usize = 16;
+
+fn finalized_channel() -> broadcast::Sender<FinalizedHeader> {
+    broadcast::channel(FINALIZED_CHANNEL_CAPACITY).0
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 struct LightClientStore {
     finalized_header: Header,
-    current_sync_committee: SyncCommittee,
-    next_sync_committee: Option<SyncCommittee>,
+    current_sync_committee: Arc<SyncCommittee>,
+    next_sync_committee: Option<Arc<SyncCommittee>>,
     optimistic_header: Header,
+    /// The latest attested header signed by a supermajority of the sync committee.
+    #[serde(default)]
+    safe_header: Header,
     previous_max_active_participants: u64,
     current_max_active_participants: u64,
+    /// The best valid update that was not applied since it lacked finality or a supermajority.
+    /// Used by [`ConsensusStateManager::process_force_update`].
+    #[serde(default)]
+    best_valid_update: Option<GenericUpdate>,
+}
+
+/// The part of the store that is hashed by [`ConsensusStateManager::state_digest`].
+#[derive(Serialize)]
+struct StateDigestInput<'a> {
+    finalized_header: &'a Header,
+    current_sync_committee: &'a SyncCommittee,
+    next_sync_committee: Option<&'a SyncCommittee>,
+}
+
+/// The state exported by [`ConsensusStateManager::export_state`].
+#[derive(Serialize, Deserialize)]
+struct StateSnapshot {
+    chain_id: u64,
+    genesis_root: Vec<u8>,
+    last_checkpoint: Option<Vec<u8>>,
+    store: LightClientStore,
 }
 
 impl<R: ConsensusRpc, DB: Database> ConsensusClient<R, DB> {
     pub fn new(rpc: &str, config: Arc<Config>) -> Result<ConsensusClient<R, DB>> {
+        Self::new_with_cancellation(rpc, config, CancellationToken::new())
+    }
+
+    /// Same as [`Self::new`], but the tasks of the client also stop once `cancel` is cancelled,
+    /// e.g. with the token of the service the client is embedded in.
+    pub fn new_with_cancellation(
+        rpc: &str,
+        config: Arc<Config>,
+        cancel: CancellationToken,
+    ) -> Result<ConsensusClient<R, DB>> {
         let (block_send, block_recv) = channel(256);
         let (finalized_block_send, finalized_block_recv) = watch::channel(None);
         let (checkpoint_send, checkpoint_recv) = watch::channel(None);
+        let (conflict_send, mut conflict_events) = channel(16);
+        let (conflict_alert, conflict_recv) = channel(16);
+        let (stale_checkpoint_send, stale_checkpoint_recv) = channel(16);
+        let (sync_health_send, sync_health_recv) = channel(16);
+        let (reload_send, reload_recv) = watch::channel((*config).clone());
 
         let rpc = rpc.to_string();
-        let genesis_time = config.chain.genesis_time;
+        let client_rpc = build_rpc::<R>(&rpc, &config);
+        let slot_clock = SlotClock::from_config(&config);
         let db = DB::new(&config)?;
-        let initial_checkpoint = config.checkpoint.clone().unwrap_or_else(|| {
+        // The databases fall back to the default checkpoint when none was saved.
+        let saved_checkpoint = config.checkpoint.clone().or_else(|| {
             db.load_checkpoint()
-                .unwrap_or(config.default_checkpoint.clone())
+                .ok()
+                .filter(|checkpoint| *checkpoint != config.default_checkpoint)
         });
+        // A devnet synced from genesis resumes from its saved checkpoint once it has one.
+        let sync_from_genesis = config.sync_from_genesis && saved_checkpoint.is_none();
+        // The checkpoint services replace the default checkpoint, which may be stale.
+        let sync_from_services = !sync_from_genesis
+            && saved_checkpoint.is_none()
+            && !config.checkpoint_services.is_empty();
+        let initial_checkpoint =
+            saved_checkpoint.unwrap_or_else(|| config.default_checkpoint.clone());
 
         #[cfg(not(target_arch = "wasm32"))]
         let run = tokio::spawn;
@@ -77,19 +236,186 @@ impl<R: ConsensusRpc, DB: Database> ConsensusClient<R, DB> {
         #[cfg(target_arch = "wasm32")]
         let run = wasm_bindgen_futures::spawn_local;
 
+        let conflict_db = db.clone();
         run(async move {
-            let mut consensus_state_manager = ConsensusStateManager::<R>::new(
-                &rpc,
-                Some(block_send),
-                Some(finalized_block_send),
-                Some(checkpoint_send),
-                config.clone(),
-                None,
-            );
+            while let Some(conflict) = conflict_events.recv().await {
+                if let Err(err) = conflict_db.save_finality_conflict(&conflict) {
+                    error!(target: "helios::consensus", err = %err, "failed to save finality conflict");
+                }
+                _ = conflict_alert.send(conflict).await;
+            }
+        });
+
+        let retention = config.retention.clone();
+        if retention.update_periods.is_some() || retention.header_days.is_some() {
+            let compact_db = db.clone();
+            let compact_cancel = cancel.clone();
+            let interval =
+                std::time::Duration::from_secs(retention.compaction_interval_secs.max(1));
+            run(async move {
+                loop {
+                    select! {
+                        res = zduny_wasm_timer::Delay::new(interval) => res.unwrap(),
+                        _ = compact_cancel.cancelled() => return,
+                    }
+
+                    match compact_db.compact(&retention) {
+                        Ok(0) => {}
+                        Ok(removed) => {
+                            debug!(target: "helios::consensus", removed, "compacted database")
+                        }
+                        Err(err) => {
+                            warn!(target: "helios::consensus", err = %err, "failed to compact database")
+                        }
+                    }
+                }
+            });
+        }
+
+        let mut consensus_state_manager = ConsensusStateManager::<R>::new(
+            &rpc,
+            Some(block_send),
+            Some(finalized_block_send),
+            Some(checkpoint_send),
+            config.clone(),
+            None,
+        );
+        consensus_state_manager.set_conflict_sender(conflict_send);
+        consensus_state_manager.set_stale_checkpoint_sender(stale_checkpoint_send);
+        consensus_state_manager.set_sync_health_sender(sync_health_send);
+        consensus_state_manager.set_reload_receiver(reload_recv);
 
-            let res = consensus_state_manager.sync(&initial_checkpoint).await;
+        #[cfg(not(target_arch = "wasm32"))]
+        let restore = match &config.backup {
+            Some(backup_config) => {
+                let key = config
+                    .encryption_key()?
+                    .ok_or_else(|| eyre!("backups are sealed with the key of encrypt_database"))?;
+                let backup = ObjectStoreBackup::new(backup_config, config.chain.chain_id, key)?;
+                let (state_send, mut state_recv) = watch::channel(None);
+                consensus_state_manager.set_state_sender(state_send);
+
+                let backup_cancel = cancel.clone();
+                let upload_backup = backup.clone();
+                let interval = std::time::Duration::from_secs(backup.interval_secs().max(1));
+                run(async move {
+                    loop {
+                        select! {
+                            res = state_recv.changed() => if res.is_err() { return },
+                            _ = backup_cancel.cancelled() => return,
+                        }
+
+                        let state = state_recv.borrow_and_update().clone();
+                        if let Some(state) = state {
+                            match upload_backup.upload(&state).await {
+                                Ok(()) => debug!(target: "helios::consensus", "uploaded backup"),
+                                Err(err) => {
+                                    warn!(target: "helios::consensus", err = %err, "failed to upload backup")
+                                }
+                            }
+                        }
+
+                        // The states finalized in between are replaced by the latest one.
+                        select! {
+                            res = zduny_wasm_timer::Delay::new(interval) => res.unwrap(),
+                            _ = backup_cancel.cancelled() => return,
+                        }
+                    }
+                });
+
+                backup.restores().then_some(backup)
+            }
+            None => None,
+        };
+
+        let finalized_header_send = consensus_state_manager.finalized_header_send.clone();
+        let light_client_data = consensus_state_manager.light_client_data();
+        if db.keeps_history() {
+            let history_db = db.clone();
+            let history_data = light_client_data.clone();
+            let mut headers = finalized_header_send.subscribe();
+            run(async move {
+                let mut saved_period = None;
+                loop {
+                    match headers.recv().await {
+                        Ok(header) => {
+                            if let Err(err) = history_db.save_finalized_header(&header) {
+                                warn!(target: "helios::consensus", err = %err, slot = header.slot, "failed to save finalized header");
+                            }
+
+                            // The update of a period carries the committee of the next one.
+                            let period = calc_sync_period(header.slot);
+                            if saved_period.map_or(true, |saved| period >= saved) {
+                                if let Some(update) = history_data.updates(period, 1).first() {
+                                    let res = history_db.save_sync_committee(
+                                        period + 1,
+                                        &update.next_sync_committee,
+                                    );
+                                    match res {
+                                        Ok(()) => saved_period = Some(period + 1),
+                                        Err(err) => {
+                                            warn!(target: "helios::consensus", err = %err, period = period + 1, "failed to save sync committee")
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!(target: "helios::consensus", missed, "finalized headers not saved");
+                        }
+                        Err(RecvError::Closed) => return,
+                    }
+                }
+            });
+        }
+
+        let task_cancel = cancel.clone();
+        let (stopped_send, stopped_recv) = watch::channel(());
+        run(async move {
+            let cancel = task_cancel;
+            // Dropped along with the task, whichever way it stops.
+            let _stopped_send = stopped_send;
+
+            // A restored state only has to catch up with the chain.
+            #[cfg(not(target_arch = "wasm32"))]
+            let restored = match &restore {
+                Some(backup) => restore_backup(&mut consensus_state_manager, backup).await,
+                None => false,
+            };
+            #[cfg(target_arch = "wasm32")]
+            let restored = false;
+
+            let sync = async {
+                if restored {
+                    consensus_state_manager.catch_up_periods().await?;
+                    consensus_state_manager.advance().await
+                } else if sync_from_genesis {
+                    consensus_state_manager.sync_from_genesis().await
+                } else if sync_from_services {
+                    sync_agreed_checkpoint(&mut consensus_state_manager, &config).await
+                } else {
+                    consensus_state_manager.sync(&initial_checkpoint).await
+                }
+            };
+            let res = select! {
+                res = sync => res,
+                _ = cancel.cancelled() => return,
+            };
             if let Err(err) = res {
-                if config.load_external_fallback {
+                // The fallbacks of a config reloaded during the sync are used.
+                consensus_state_manager.reload_pending();
+                let config = consensus_state_manager.config.clone();
+                let stale = matches!(
+                    err.downcast_ref::<ConsensusError>(),
+                    Some(ConsensusError::StaleCheckpoint(..))
+                );
+                if !sync_from_services && !config.checkpoint_services.is_empty() {
+                    let res = sync_agreed_checkpoint(&mut consensus_state_manager, &config).await;
+                    if let Err(err) = res {
+                        error!(target: "helios::consensus", err = %err, "sync failed");
+                        process::exit(1);
+                    }
+                } else if config.load_external_fallback {
                     let res =
                         sync_all_fallbacks(&mut consensus_state_manager, config.chain.chain_id)
                             .await;
@@ -103,6 +429,17 @@ impl<R: ConsensusRpc, DB: Database> ConsensusClient<R, DB> {
                         error!(target: "helios::consensus", err = %err, "sync failed");
                         process::exit(1);
                     }
+                } else if stale {
+                    // Catching up from a stale checkpoint is hopeless, so a fresh one is fetched
+                    // even though no fallback is configured.
+                    warn!(target: "helios::consensus", "fetching a fresh checkpoint from the community checkpoint services");
+                    let res =
+                        sync_all_fallbacks(&mut consensus_state_manager, config.chain.chain_id)
+                            .await;
+                    if let Err(err) = res {
+                        error!(target: "helios::consensus", err = %err, "sync failed");
+                        process::exit(1);
+                    }
                 } else {
                     error!(target: "helios::consensus", err = %err, "sync failed");
                     process::exit(1);
@@ -111,42 +448,60 @@ impl<R: ConsensusRpc, DB: Database> ConsensusClient<R, DB> {
 
             _ = consensus_state_manager.send_blocks().await;
 
-            loop {
-                zduny_wasm_timer::Delay::new(
-                    consensus_state_manager
-                        .duration_until_next_update()
-                        .to_std()
-                        .unwrap(),
-                )
-                .await
-                .unwrap();
-
-                let res = consensus_state_manager.advance().await;
-                if let Err(err) = res {
-                    warn!(target: "helios::consensus", "advance error: {}", err);
-                    continue;
-                }
-
-                let res = consensus_state_manager.send_blocks().await;
-                if let Err(err) = res {
-                    warn!(target: "helios::consensus", "send error: {}", err);
-                    continue;
-                }
-            }
+            consensus_state_manager.run(&cancel).await;
+            info!(target: "helios::consensus", "consensus client stopped");
         });
 
         Ok(ConsensusClient {
             block_recv: Some(block_recv),
             finalized_block_recv: Some(finalized_block_recv),
             checkpoint_recv,
-            genesis_time,
+            conflict_recv: Some(conflict_recv),
+            stale_checkpoint_recv: Some(stale_checkpoint_recv),
+            sync_health_recv: Some(sync_health_recv),
+            finalized_header_send,
+            light_client_data,
+            cancel,
+            stopped_recv,
+            slot_clock,
+            rpc: RwLock::new(client_rpc),
+            reload_send,
             db,
-            phantom: PhantomData,
         })
     }
 
-    pub fn shutdown(&self) -> Result<()> {
-        let checkpoint = self.checkpoint_recv.borrow();
+    /// Returns the light client data verified by the client, see [`LightClientData`].
+    pub fn light_client_data(&self) -> LightClientData {
+        self.light_client_data.clone()
+    }
+
+    /// Returns a receiver of every finalized header verified from now on, see
+    /// [`ConsensusStateManager::subscribe_finalized`].
+    pub fn subscribe_finalized(&self) -> broadcast::Receiver<FinalizedHeader> {
+        self.finalized_header_send.subscribe()
+    }
+
+    /// Returns the database of the client, e.g. to export a `MemoryDB` once the client was shut
+    /// down, see [`memory`](crate::database::memory).
+    pub fn database(&self) -> &DB {
+        &self.db
+    }
+
+    /// Returns the token that stops the tasks of the client, to stop the tasks that depend on it
+    /// along with it.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Stops the tasks of the client, and saves the last checkpoint to the database. An advance
+    /// in progress is completed first, so the state is never partially applied.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.cancel.cancel();
+
+        let mut stopped_recv = self.stopped_recv.clone();
+        while stopped_recv.changed().await.is_ok() {}
+
+        let checkpoint = self.checkpoint_recv.borrow().clone();
         if let Some(checkpoint) = checkpoint.as_ref() {
             self.db.save_checkpoint(checkpoint)?;
         }
@@ -154,11 +509,58 @@ impl<R: ConsensusRpc, DB: Database> ConsensusClient<R, DB> {
         Ok(())
     }
 
+    /// Applies the consensus RPC and checkpoint fallback settings of `config` without restarting
+    /// or bootstrapping again, e.g. after the config file changed. The verified state is kept,
+    /// see [`ConsensusStateManager::reload`]. Fails if `config` is of another chain.
+    pub async fn reload(&self, config: &Config) -> Result<()> {
+        let mut reloaded = self.reload_send.borrow().clone();
+        if config.chain.chain_id != reloaded.chain.chain_id
+            || config.chain.genesis_root != reloaded.chain.genesis_root
+        {
+            return Err(ConsensusError::ReloadChainMismatch(
+                config.chain.chain_id,
+                reloaded.chain.chain_id,
+            )
+            .into());
+        }
+
+        reloaded.reload(config);
+        if !reloaded.consensus_rpc.is_empty() {
+            *self.rpc.write().await = build_rpc(&reloaded.consensus_rpc, &reloaded);
+        }
+        self.reload_send.send_replace(reloaded);
+
+        Ok(())
+    }
+
     pub fn expected_current_slot(&self) -> u64 {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let since_genesis = now - std::time::Duration::from_secs(self.genesis_time);
+        self.slot_clock.current_slot()
+    }
 
-        since_genesis.as_secs() / 12
+    /// Returns the updates of the `count` periods from `start_period`, with the latest finality
+    /// and optimistic updates. They are served as received from the consensus RPC: each carries
+    /// the sync committee signature over it, which the light client that applies them verifies.
+    pub async fn get_updates(&self, start_period: u64, count: u64) -> Result<AggregateUpdates> {
+        let rpc = self.rpc.read().await;
+        let (updates, finality_update, optimistic_update) = futures::try_join!(
+            rpc.get_updates(start_period, count),
+            rpc.get_finality_update(),
+            rpc.get_optimistic_update(),
+        )?;
+
+        Ok(AggregateUpdates {
+            updates,
+            finality_update,
+            optimistic_update,
+        })
+    }
+}
+
+impl<R: ConsensusRpc, DB: Database> Drop for ConsensusClient<R, DB> {
+    /// Stops the tasks of the client. Unlike [`ConsensusClient::shutdown`], the checkpoint is not
+    /// saved.
+    fn drop(&mut self) {
+        self.cancel.cancel();
     }
 }
 
@@ -170,6 +572,24 @@ async fn sync_fallback<R: ConsensusRpc>(
     consensus_state_manager.sync(checkpoint.as_bytes()).await
 }
 
+async fn sync_agreed_checkpoint<R: ConsensusRpc>(
+    consensus_state_manager: &mut ConsensusStateManager<R>,
+    config: &Config,
+) -> Result<()> {
+    let checkpoint = CheckpointFallback::fetch_agreed_checkpoint(
+        &config.checkpoint_services,
+        config.checkpoint_quorum,
+    )
+    .await?;
+    info!(
+        target: "helios::consensus",
+        checkpoint = ?checkpoint,
+        "checkpoint services agreed on checkpoint"
+    );
+
+    consensus_state_manager.sync(checkpoint.as_bytes()).await
+}
+
 async fn sync_all_fallbacks<R: ConsensusRpc>(
     consensus_state_manager: &mut ConsensusStateManager<R>,
     chain_id: u64,
@@ -184,7 +604,37 @@ async fn sync_all_fallbacks<R: ConsensusRpc>(
     consensus_state_manager.sync(checkpoint.as_bytes()).await
 }
 
+/// Imports the state of the latest backup, and returns whether there was one that could be
+/// imported.
+#[cfg(not(target_arch = "wasm32"))]
+async fn restore_backup<R: ConsensusRpc>(
+    consensus_state_manager: &mut ConsensusStateManager<R>,
+    backup: &ObjectStoreBackup,
+) -> bool {
+    let state = match backup.download().await {
+        Ok(Some(state)) => state,
+        Ok(None) => return false,
+        Err(err) => {
+            warn!(target: "helios::consensus", err = %err, "failed to download backup");
+            return false;
+        }
+    };
+
+    match consensus_state_manager.import_state(&state) {
+        Ok(()) => true,
+        Err(err) => {
+            warn!(target: "helios::consensus", err = %err, "failed to restore backup");
+            false
+        }
+    }
+}
+
 impl<R: ConsensusRpc> ConsensusStateManager<R> {
+    /// Returns a builder of a manager, for the users that do not load a full [`Config`] up front.
+    pub fn builder() -> ConsensusStateManagerBuilder<R> {
+        ConsensusStateManagerBuilder::new()
+    }
+
     pub fn new(
         rpc: &str,
         block_send: Option<Sender<Block>>,
@@ -193,7 +643,7 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         config: Arc<Config>,
         checkpoint: Option<Vec<u8>>,
     ) -> ConsensusStateManager<R> {
-        let rpc = R::new(rpc);
+        let rpc = build_rpc(rpc, &config);
 
         ConsensusStateManager {
             rpc,
@@ -202,7 +652,20 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
             block_send,
             finalized_block_send,
             checkpoint_send,
+            conflict_send: None,
+            stale_checkpoint_send: None,
+            sync_health_send: None,
+            reload_recv: None,
+            state_send: None,
             config: (*config).clone(),
+            header_cache: HeaderCache::default(),
+            update_cache: VerifiedUpdateCache::default(),
+            light_client_data: LightClientData::default(),
+            finalized_header_send: finalized_channel(),
+            broadcast_finalized_slot: 0,
+            last_success: None,
+            last_error: None,
+            sync_health: SyncHealthMonitor::default(),
         }
     }
 
@@ -225,17 +688,22 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         network: Network,
         rpc: String,
     ) -> Result<ConsensusStateManager<R>> {
-        let rpc = R::new(&rpc);
         let config = network.to_base_config().as_config();
-        let mut state = ConsensusStateManager {
-            rpc,
-            config,
-            last_checkpoint: Some(checkpoint.clone()),
-            store: LightClientStore::default(),
-            block_send: None,
-            finalized_block_send: None,
-            checkpoint_send: None,
-        };
+        let mut state = Self::new_with_config(&rpc, config, checkpoint.clone());
+        state.bootstrap(&checkpoint).await?;
+        Ok(state)
+    }
+
+    /// Same as [`Self::new_from_checkpoint`], but for a network that is described by a
+    /// [`NetworkConfig`] (usually loaded with [`NetworkConfig::from_file`]) instead of one of the
+    /// built-in networks. This allows verifying private devnets without patching the crate.
+    pub async fn new_from_checkpoint_and_network_config(
+        checkpoint: Vec<u8>,
+        network_config: &NetworkConfig,
+        rpc: String,
+    ) -> Result<ConsensusStateManager<R>> {
+        let config = network_config.to_base_config().as_config();
+        let mut state = Self::new_with_config(&rpc, config, checkpoint.clone());
         state.bootstrap(&checkpoint).await?;
         Ok(state)
     }
@@ -246,19 +714,50 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         rpc: String,
         bootstrap: &mut Bootstrap,
     ) -> Result<ConsensusStateManager<R>> {
-        let rpc = R::new(&rpc);
         let config = network.to_base_config().as_config();
-        let mut state = ConsensusStateManager {
-            rpc,
+        let mut state = Self::new_with_config(&rpc, config, checkpoint.clone());
+        state.bootstrap_offline(&checkpoint, bootstrap)?;
+        Ok(state)
+    }
+
+    /// Same as [`Self::new_from_checkpoint_and_bootstrap`], but for a network that is described
+    /// by a [`NetworkConfig`].
+    pub fn new_from_checkpoint_bootstrap_and_network_config(
+        checkpoint: Vec<u8>,
+        network_config: &NetworkConfig,
+        rpc: String,
+        bootstrap: &mut Bootstrap,
+    ) -> Result<ConsensusStateManager<R>> {
+        let config = network_config.to_base_config().as_config();
+        let mut state = Self::new_with_config(&rpc, config, checkpoint.clone());
+        state.bootstrap_offline(&checkpoint, bootstrap)?;
+        Ok(state)
+    }
+
+    /// Creates a [`ConsensusStateManager`] with an empty store and without any channels.
+    fn new_with_config(rpc: &str, config: Config, checkpoint: Vec<u8>) -> Self {
+        ConsensusStateManager {
+            rpc: build_rpc(rpc, &config),
             config,
-            last_checkpoint: Some(checkpoint.clone()),
+            last_checkpoint: Some(checkpoint),
             store: LightClientStore::default(),
             block_send: None,
             finalized_block_send: None,
             checkpoint_send: None,
-        };
-        state.bootstrap_offline(&checkpoint, bootstrap)?;
-        Ok(state)
+            conflict_send: None,
+            stale_checkpoint_send: None,
+            sync_health_send: None,
+            reload_recv: None,
+            state_send: None,
+            header_cache: HeaderCache::default(),
+            update_cache: VerifiedUpdateCache::default(),
+            light_client_data: LightClientData::default(),
+            finalized_header_send: finalized_channel(),
+            broadcast_finalized_slot: 0,
+            last_success: None,
+            last_error: None,
+            sync_health: SyncHealthMonitor::default(),
+        }
     }
 
     pub async fn check_rpc(&self) -> Result<()> {
@@ -271,6 +770,12 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         }
     }
 
+    /// Returns the best valid update that was not applied yet, since its finality could not be
+    /// proven or it lacked a supermajority, as ranked by `is_better_update` of the specification.
+    pub fn get_best_valid_update(&self) -> Option<&GenericUpdate> {
+        self.store.best_valid_update.as_ref()
+    }
+
     /// Get the latest finalized slot from the store.
     pub fn get_latest_slot(&self) -> U64 {
         self.store.finalized_header.slot
@@ -278,10 +783,99 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
 
     /// Set the RPC URL for the consensus client.
     pub fn set_rpc(&mut self, rpc: &str) -> &mut Self {
-        self.rpc = R::new(rpc);
+        self.rpc = build_rpc(rpc, &self.config);
+        self
+    }
+
+    /// Set an already built RPC client, e.g. a `MockRpc` of the `testing` feature. Unlike
+    /// [`Self::set_rpc`], the retry, rate limit and transport settings of the config are not
+    /// applied to it.
+    pub fn set_rpc_client(&mut self, rpc: R) -> &mut Self {
+        self.rpc = rpc;
+        self
+    }
+
+    /// Set the channel that receives the evidence of conflicting finalized headers. Updates that
+    /// conflict with a verified header are rejected whether or not a channel is set.
+    pub fn set_conflict_sender(&mut self, conflict_send: Sender<FinalityConflict>) -> &mut Self {
+        self.conflict_send = Some(conflict_send);
+        self
+    }
+
+    /// Set the channel that receives the warnings of stale checkpoints. A stale checkpoint fails
+    /// the sync whether or not a channel is set.
+    pub fn set_stale_checkpoint_sender(
+        &mut self,
+        stale_checkpoint_send: Sender<StaleCheckpoint>,
+    ) -> &mut Self {
+        self.stale_checkpoint_send = Some(stale_checkpoint_send);
+        self
+    }
+
+    /// Set the channel that receives the warnings of a degraded sync, which are also logged.
+    pub fn set_sync_health_sender(
+        &mut self,
+        sync_health_send: Sender<SyncHealthWarning>,
+    ) -> &mut Self {
+        self.sync_health_send = Some(sync_health_send);
+        self
+    }
+
+    /// Set the channel of the configs to reload while running, which are applied with
+    /// [`Self::reload`] between the advances of [`Self::run`].
+    pub fn set_reload_receiver(&mut self, reload_recv: watch::Receiver<Config>) -> &mut Self {
+        self.reload_recv = Some(reload_recv);
         self
     }
 
+    /// Set the channel that receives the state exported by [`Self::export_state`] whenever a new
+    /// header is finalized, e.g. to back it up.
+    pub fn set_state_sender(&mut self, state_send: watch::Sender<Option<Vec<u8>>>) -> &mut Self {
+        self.state_send = Some(state_send);
+        self
+    }
+
+    /// Applies the consensus RPC and checkpoint fallback settings of `config`, see
+    /// [`Config::reload`], without bootstrapping again: the verified state is kept, and the new
+    /// RPC serves the next requests. An empty `consensus_rpc` keeps the current RPC. Fails if
+    /// `config` is of another chain.
+    pub fn reload(&mut self, config: &Config) -> Result<()> {
+        if config.chain.chain_id != self.config.chain.chain_id
+            || config.chain.genesis_root != self.config.chain.genesis_root
+        {
+            return Err(ConsensusError::ReloadChainMismatch(
+                config.chain.chain_id,
+                self.config.chain.chain_id,
+            )
+            .into());
+        }
+
+        self.config.reload(config);
+        if !self.config.consensus_rpc.is_empty() {
+            self.rpc = build_rpc(&self.config.consensus_rpc, &self.config);
+        }
+        info!(target: "helios::consensus", rpc = %self.config.consensus_rpc, "reloaded config");
+
+        Ok(())
+    }
+
+    /// Applies the last config sent to the channel of [`Self::set_reload_receiver`], if it was
+    /// not applied yet.
+    pub(crate) fn reload_pending(&mut self) {
+        let config = match &mut self.reload_recv {
+            Some(recv) if recv.has_changed().unwrap_or(false) => recv.borrow_and_update().clone(),
+            _ => return,
+        };
+
+        self.reload_or_warn(&config);
+    }
+
+    fn reload_or_warn(&mut self, config: &Config) {
+        if let Err(err) = self.reload(config) {
+            warn!(target: "helios::consensus", "reload error: {}", err);
+        }
+    }
+
     /// Set the network configuration for the consensus client.
     pub fn set_configuration(&mut self, config: Config) -> &mut Self {
         self.config = config;
@@ -292,16 +886,371 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         Network::from_chain_id(self.config.chain.chain_id)
     }
 
-    pub fn get_finalized_header(self) -> Header {
+    /// Returns the latest header attested by an [`OptimisticUpdate`] signed by more than the
+    /// safety threshold of the sync committee. It is the most recent verified header, but it
+    /// can be reorged.
+    pub fn get_optimistic_header(&self) -> Header {
+        self.store.optimistic_header.clone()
+    }
+
+    /// Returns the latest attested header signed by a supermajority of the sync committee, or
+    /// the finalized header if it is more recent. It is less likely to be reorged than the
+    /// optimistic header, but unlike the finalized header it is not proven final.
+    pub fn get_safe_header(&self) -> Header {
+        if self.store.safe_header.slot > self.store.finalized_header.slot {
+            self.store.safe_header.clone()
+        } else {
+            self.store.finalized_header.clone()
+        }
+    }
+
+    /// Returns the latest header proven final by the finality branch of a verified update.
+    pub fn get_finalized_header(&self) -> Header {
         self.store.finalized_header.clone()
     }
 
+    /// Returns the sync status of the manager, and the outcome of its last sync or advance.
+    pub fn health(&self) -> Health {
+        let clock = self.slot_clock();
+        let current_slot = clock.current_slot();
+        let finalized_slot = self.store.finalized_header.slot.as_u64();
+        let optimistic_slot = self.store.optimistic_header.slot.as_u64();
+
+        let head_age = SlotClock::now()
+            .as_secs()
+            .saturating_sub(clock.slot_timestamp(optimistic_slot));
+        let is_bootstrapped = self.last_checkpoint.is_some() && finalized_slot > 0;
+
+        Health {
+            synced: is_bootstrapped && head_age <= MAX_SYNCED_HEAD_AGE_SECS,
+            finalized_slot,
+            optimistic_slot,
+            finalized_lag: current_slot.saturating_sub(finalized_slot),
+            optimistic_lag: current_slot.saturating_sub(optimistic_slot),
+            last_success: self.last_success,
+            last_error: self.last_error.clone(),
+            participation: self.sync_health.participation(),
+        }
+    }
+
+    fn record_result(&mut self, res: &Result<()>) {
+        match res {
+            Ok(()) => {
+                self.last_success = Some(SlotClock::now().as_secs());
+                self.last_error = None;
+            }
+            Err(err) => self.last_error = Some(err.to_string()),
+        }
+
+        self.check_sync_health();
+    }
+
+    /// Checks the participation of the recent updates and the lag of the finalized header against
+    /// the thresholds of [`Config::sync_health`], and raises the warnings of the ones that were
+    /// just crossed. The lag is only checked once the state was bootstrapped.
+    fn check_sync_health(&mut self) {
+        let finalized_slot = self.store.finalized_header.slot.as_u64();
+        let finality_lag = if finalized_slot > 0 {
+            self.slot_clock()
+                .current_slot()
+                .saturating_sub(finalized_slot)
+        } else {
+            0
+        };
+
+        for warning in self
+            .sync_health
+            .check(&self.config.sync_health, finality_lag)
+        {
+            match &warning {
+                SyncHealthWarning::LowParticipation {
+                    participation,
+                    threshold,
+                } => warn!(
+                    target: "helios::consensus",
+                    participation,
+                    threshold,
+                    "low sync committee participation"
+                ),
+                SyncHealthWarning::FinalityLag { lag, threshold } => warn!(
+                    target: "helios::consensus",
+                    lag,
+                    threshold,
+                    "finalized header is lagging"
+                ),
+            }
+
+            if let Some(sync_health_send) = &self.sync_health_send {
+                if let Err(err) = sync_health_send.try_send(warning) {
+                    warn!(target: "helios::consensus", "failed to send sync health warning: {err}");
+                }
+            }
+        }
+    }
+
+    /// Records the participation of a finality or optimistic update, see [`Self::health`].
+    fn record_participation(&mut self, update: &GenericUpdate) {
+        let participants = get_bits(&update.sync_aggregate.sync_committee_bits);
+        self.sync_health
+            .record_participation(participants, self.config.sync_health.participation_window);
+    }
+
+    /// Returns the light client data verified by the manager. It is shared with the manager, so it
+    /// keeps up with the manager as it advances.
+    pub fn light_client_data(&self) -> LightClientData {
+        self.light_client_data.clone()
+    }
+
+    /// Returns a receiver of every finalized header verified from now on. Headers are sent when
+    /// the manager syncs or advances; a receiver that lags by more than a few headers misses the
+    /// oldest ones.
+    pub fn subscribe_finalized(&self) -> broadcast::Receiver<FinalizedHeader> {
+        self.finalized_header_send.subscribe()
+    }
+
+    /// Sends the finalized header to the subscribers if it was not sent yet, with its execution
+    /// block when it can be fetched.
+    async fn broadcast_finalized(&mut self) {
+        let header = self.store.finalized_header.clone();
+        let slot = header.slot.as_u64();
+        if slot <= self.broadcast_finalized_slot {
+            return;
+        }
+        self.broadcast_finalized_slot = slot;
+
+        if let Some(state_send) = &self.state_send {
+            match self.export_state() {
+                Ok(state) => {
+                    state_send.send_replace(Some(state));
+                }
+                Err(err) => warn!(target: "helios::consensus", "failed to export state: {err}"),
+            }
+        }
+
+        if self.finalized_header_send.receiver_count() == 0 {
+            return;
+        }
+
+        let block_root = match header.clone().hash_tree_root() {
+            Ok(root) => root,
+            Err(err) => {
+                warn!(target: "helios::consensus", "failed to hash finalized header: {err}");
+                return;
+            }
+        };
+
+        let payload = match self.get_execution_payload(&Some(slot)).await {
+            Ok(payload) => Some(payload),
+            Err(err) => {
+                warn!(target: "helios::consensus", "failed to fetch finalized payload: {err}");
+                None
+            }
+        };
+
+        let finalized_header = FinalizedHeader {
+            slot,
+            block_root: Bytes32::try_from(block_root.as_ref()).expect("a root is 32 bytes"),
+            header,
+            execution_block_hash: payload.as_ref().map(|p| p.block_hash().clone()),
+            execution_state_root: payload.as_ref().map(|p| p.state_root().clone()),
+        };
+
+        // The receivers may have been dropped since they were counted.
+        _ = self.finalized_header_send.send(finalized_header);
+    }
+
+    /// Returns the SHA-256 hash of the BCS encoding of the finalized header and the sync
+    /// committees. Light clients that verified the same state have the same digest, regardless of
+    /// their optimistic header or the updates they saw along the way.
+    pub fn state_digest(&self) -> Result<[u8; 32]> {
+        let input = StateDigestInput {
+            finalized_header: &self.store.finalized_header,
+            current_sync_committee: &self.store.current_sync_committee,
+            next_sync_committee: self.store.next_sync_committee.as_deref(),
+        };
+
+        Ok(Sha256::digest(bcs::to_bytes(&input)?).into())
+    }
+
+    /// Returns the versioned BCS encoding of the verified state, with the chain it belongs to, to
+    /// hand it off to another manager with [`Self::import_signed_state`] or
+    /// [`Self::import_state`]. It holds neither the config nor the caches.
+    pub fn export_state(&self) -> Result<Vec<u8>> {
+        encode_versioned(&StateSnapshot {
+            chain_id: self.config.chain.chain_id,
+            genesis_root: self.config.chain.genesis_root.clone(),
+            last_checkpoint: self.last_checkpoint.clone(),
+            store: self.store.clone(),
+        })
+    }
+
+    /// Exports the state, see [`Self::export_state`], signed with the BLS secret key
+    /// `secret_key` of the operator, see [`snapshot`](crate::snapshot).
+    pub fn export_signed_state(&self, secret_key: &[u8]) -> Result<SignedStateSnapshot> {
+        SignedStateSnapshot::sign(self.export_state()?, secret_key)
+    }
+
+    /// Replaces the state with the one of `snapshot`, once its signature by the BLS public key
+    /// `public_key` of the operator is checked. The state itself is not verified again. Fails if
+    /// the snapshot is of another chain, or older than the finalized header of the manager.
+    pub fn import_signed_state(
+        &mut self,
+        snapshot: &SignedStateSnapshot,
+        public_key: &[u8],
+    ) -> Result<()> {
+        snapshot.verify(public_key)?;
+        self.import_state(&snapshot.state)
+    }
+
+    /// Replaces the state with `state`, exported by [`Self::export_state`], which is trusted as it
+    /// is, e.g. a backup of the same operator. Fails if the state is of another chain, or older
+    /// than the finalized header of the manager.
+    pub fn import_state(&mut self, state: &[u8]) -> Result<()> {
+        let StateSnapshot {
+            chain_id,
+            genesis_root,
+            last_checkpoint,
+            store,
+        } = decode_versioned(state)?;
+        if chain_id != self.config.chain.chain_id || genesis_root != self.config.chain.genesis_root
+        {
+            return Err(ConsensusError::SnapshotChainMismatch(
+                chain_id,
+                self.config.chain.chain_id,
+            )
+            .into());
+        }
+
+        let finalized_slot = self.store.finalized_header.slot.as_u64();
+        let snapshot_slot = store.finalized_header.slot.as_u64();
+        if snapshot_slot < finalized_slot {
+            return Err(ConsensusError::StateRegression(finalized_slot, snapshot_slot).into());
+        }
+
+        self.header_cache.insert(&store.finalized_header)?;
+        self.store = store;
+        self.last_checkpoint = last_checkpoint;
+        info!(
+            target: "helios::consensus",
+            finalized_slot = snapshot_slot,
+            "imported state snapshot"
+        );
+
+        Ok(())
+    }
+
+    /// Returns the verified finalized header at `slot`, if it is still cached.
+    pub fn get_verified_header(&self, slot: u64) -> Option<VerifiedHeader> {
+        self.header_cache.get_by_slot(slot)
+    }
+
+    /// Returns the verified finalized header with the given beacon block root, if it is still
+    /// cached.
+    pub fn get_verified_header_by_root(&self, block_root: &[u8]) -> Option<VerifiedHeader> {
+        self.header_cache.get_by_block_root(block_root)
+    }
+
+    /// Returns the verified finalized header of the execution block `block_number`, if it is still
+    /// cached and its payload was verified.
+    pub fn get_verified_header_by_block_number(&self, block_number: u64) -> Option<VerifiedHeader> {
+        self.header_cache.get_by_block_number(block_number)
+    }
+
+    /// Returns the execution block `block_number`, verified against its cached finalized header,
+    /// so that proofs can be checked against blocks older than the finalized one.
+    pub async fn get_verified_block_by_number(&self, block_number: u64) -> Result<Block> {
+        let header = self
+            .header_cache
+            .get_by_block_number(block_number)
+            .ok_or(ConsensusError::BlockNumberNotCached(block_number))?;
+        self.get_execution_block(header.header.slot.as_u64()).await
+    }
+
     pub async fn get_beacon_block(&self, slot: u64) -> Result<BeaconBlock> {
         self.rpc.get_block(slot).await
     }
 
     pub async fn get_execution_payload(&self, slot: &Option<u64>) -> Result<ExecutionPayload> {
         let slot = slot.unwrap_or(self.store.optimistic_header.slot.into());
+        let block = self.get_verified_beacon_block(slot).await?;
+
+        let payload = block.body.execution_payload().clone();
+        self.header_cache.set_execution_payload(slot, &payload)?;
+        Ok(payload)
+    }
+
+    /// Returns the execution block of the beacon block at `slot`, verified like
+    /// [`Self::get_execution_payload`]. Deneb blocks also carry the root of their parent beacon
+    /// block.
+    pub async fn get_execution_block(&self, slot: u64) -> Result<Block> {
+        let beacon_block = self.get_verified_beacon_block(slot).await?;
+        self.header_cache
+            .set_execution_payload(slot, beacon_block.body.execution_payload())?;
+
+        Ok(execution_block(&beacon_block, &self.config))
+    }
+
+    /// Returns the finalized execution block covering the unix timestamp `timestamp`, in seconds,
+    /// i.e. the latest block of the finalized chain that does not start after it.
+    ///
+    /// The cached verified headers are binary searched for the slot of `timestamp`. When that slot
+    /// is not cached, the blocks are fetched back from the closest verified header after it,
+    /// following their parent roots, until the first block at or before the slot.
+    pub async fn find_finalized_block_at(&self, timestamp: u64) -> Result<Block> {
+        let finalized_slot = self.store.finalized_header.slot.as_u64();
+        let target = timestamp_to_slot(&self.config, timestamp)
+            .filter(|slot| *slot <= finalized_slot)
+            .ok_or(ConsensusError::NoFinalizedBlockAt(timestamp))?;
+        if target == finalized_slot {
+            return self.get_execution_block(target).await;
+        }
+
+        let slots = self.header_cache.slots();
+        let index = slots.partition_point(|slot| *slot <= target);
+        let lower = index.checked_sub(1).map(|index| slots[index]);
+        if lower == Some(target) {
+            return self.get_execution_block(target).await;
+        }
+
+        let anchor = slots
+            .get(index)
+            .filter(|slot| **slot < finalized_slot)
+            .and_then(|slot| self.header_cache.get_by_slot(*slot))
+            .map(|verified| verified.header)
+            .unwrap_or_else(|| self.store.finalized_header.clone());
+
+        let mut parent_root = anchor.parent_root;
+        for slot in (lower.unwrap_or_default()..anchor.slot.as_u64()).rev() {
+            if Some(slot) == lower {
+                let verified = self.header_cache.get_by_slot(slot);
+                if verified.map(|verified| verified.block_root) == Some(parent_root.clone()) {
+                    return self.get_execution_block(slot).await;
+                }
+                break;
+            }
+
+            // The RPC fails for empty slots, which the parent roots skip over.
+            let Ok(mut block) = self.rpc.get_block(slot).await else {
+                continue;
+            };
+            if Bytes32::try_from(block.hash_tree_root()?.as_ref())? != parent_root {
+                continue;
+            }
+            if slot <= target {
+                verify_execution_block_hash(&block)?;
+                return Ok(execution_block(&block, &self.config));
+            }
+            parent_root = block.parent_root;
+        }
+
+        Err(ConsensusError::NoFinalizedBlockAt(timestamp).into())
+    }
+
+    /// Fetches the beacon block at `slot` and verifies it against the optimistic header, the
+    /// finalized header or a cached verified header. The `block_hash` of its execution payload is
+    /// checked against the hash of the header rebuilt from the payload, see
+    /// [`execution_block_hash`].
+    pub async fn get_verified_beacon_block(&self, slot: u64) -> Result<BeaconBlock> {
         let mut block = self.rpc.get_block(slot).await?;
         let block_hash = block.hash_tree_root()?;
 
@@ -312,19 +1261,201 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
             self.store.optimistic_header.clone().hash_tree_root()?
         } else if slot == finalized_slot.as_u64() {
             self.store.finalized_header.clone().hash_tree_root()?
+        } else if let Some(verified_header) = self.header_cache.get_by_slot(slot) {
+            bytes32_to_node(&verified_header.block_root)?
         } else {
             return Err(ConsensusError::PayloadNotFound(slot).into());
         };
 
-        if verified_block_hash != block_hash {
-            Err(ConsensusError::InvalidHeaderHash(
-                block_hash.to_string(),
-                verified_block_hash.to_string(),
-            )
-            .into())
-        } else {
-            Ok(block.body.execution_payload().clone())
+        if verified_block_hash != block_hash {
+            return Err(ConsensusError::InvalidHeaderHash(
+                block_hash.to_string(),
+                verified_block_hash.to_string(),
+            )
+            .into());
+        }
+
+        verify_execution_block_hash(&block)?;
+
+        Ok(block)
+    }
+
+    /// Fetches the proofs of the balance, withdrawal credentials and exit status of the validator
+    /// at `validator_index` in the finalized beacon state, and verifies them against the
+    /// `state_root` of the finalized header.
+    pub async fn get_validator_proof(&self, validator_index: u64) -> Result<ValidatorProof> {
+        let header = &self.store.finalized_header;
+        let proofs_fut = ValidatorProof::gindices(validator_index)
+            .into_iter()
+            .map(|gindex| {
+                self.rpc
+                    .get_state_proof(header.state_root.as_slice(), gindex)
+            });
+        let proofs = try_join_all(proofs_fut).await?;
+
+        ValidatorProof::new(
+            header.slot.as_u64(),
+            header.state_root.clone(),
+            validator_index,
+            proofs,
+        )
+    }
+
+    /// Fetches the proof of the leaf at `gindex` of the finalized beacon state, such as one of the
+    /// indices of [`consensus_core::beacon_state`], and verifies it against the `state_root` of
+    /// the finalized header.
+    pub async fn get_state_field_proof(&self, gindex: u64) -> Result<StateProof> {
+        let state_root = &self.store.finalized_header.state_root;
+        let proof = self
+            .rpc
+            .get_state_proof(state_root.as_slice(), gindex)
+            .await?;
+
+        proof.verify(state_root, gindex)?;
+        Ok(proof)
+    }
+
+    /// Fetches the multiproof of the leaves at `gindices` of the finalized beacon state, and
+    /// verifies it against the `state_root` of the finalized header.
+    pub async fn get_state_multiproof(&self, gindices: &[u64]) -> Result<StateMultiproof> {
+        let state_root = &self.store.finalized_header.state_root;
+        let multiproof = self
+            .rpc
+            .get_state_multiproof(state_root.as_slice(), gindices)
+            .await?;
+
+        multiproof.verify(state_root)?;
+        Ok(multiproof)
+    }
+
+    /// Returns the proofs of the withdrawals to `address` in the execution payload of the block at
+    /// `slot`, against the root of the verified block.
+    pub async fn get_withdrawal_proofs(
+        &self,
+        slot: u64,
+        address: &Address,
+    ) -> Result<Vec<WithdrawalProof>> {
+        let block = self.get_verified_beacon_block(slot).await?;
+        let withdrawals = block
+            .body
+            .execution_payload()
+            .withdrawals()
+            .map_err(|_| ConsensusError::NoWithdrawals(slot))?;
+
+        withdrawals
+            .iter()
+            .enumerate()
+            .filter(|(_, withdrawal)| &withdrawal.address == address)
+            .map(|(index, _)| WithdrawalProof::new(&block, index))
+            .collect()
+    }
+
+    /// Returns the deposits, withdrawals and consolidations requested by the execution layer in
+    /// the verified block at `slot`, which are empty before Electra.
+    pub async fn get_execution_requests(&self, slot: u64) -> Result<ExecutionRequests> {
+        let block = self.get_verified_beacon_block(slot).await?;
+        Ok(block.body.execution_requests_or_default())
+    }
+
+    /// Returns the proof linking the execution `state_root` and `block_hash` of the finalized
+    /// block to the `body_root` of the finalized header.
+    pub async fn get_finalized_payload_proof(&self) -> Result<ExecutionPayloadProof> {
+        let slot = self.store.finalized_header.slot.as_u64();
+        let block = self.get_verified_beacon_block(slot).await?;
+        ExecutionPayloadProof::new(&block)
+    }
+
+    /// Fetches the blob sidecars of the block at `slot`, and verifies them against the
+    /// commitments of the verified block body.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn get_verified_blob_sidecars(
+        &self,
+        slot: u64,
+        kzg_settings: &KzgSettings,
+    ) -> Result<Vec<BlobSidecar>> {
+        let (block, sidecars) = futures::try_join!(
+            self.get_verified_beacon_block(slot),
+            self.rpc.get_blob_sidecars(slot)
+        )?;
+
+        verify_blob_sidecars(&block, &sidecars, kzg_settings)?;
+        Ok(sidecars)
+    }
+
+    /// Returns the execution state root of the finalized block. The execution payload is verified
+    /// against the finalized header.
+    pub async fn get_finalized_state_root(&self) -> Result<Bytes32> {
+        let slot = self.store.finalized_header.slot.as_u64();
+        let payload = self.get_execution_payload(&Some(slot)).await?;
+        Ok(payload.state_root().clone())
+    }
+
+    /// Fetches and verifies the beacon headers between the finalized headers `start` and `end`,
+    /// exclusive, by chasing the parent roots from `end` back to `start`. Both headers must be
+    /// verified by the caller, e.g. with [`Self::get_verified_header`]. The headers are returned in
+    /// slot order, with the execution block hashes of their payloads. Empty slots are skipped.
+    /// The blocks are fetched [`BACKFILL_CONCURRENCY`] at a time, and at most
+    /// [`MAX_BACKFILL_SLOTS`] slots apart.
+    pub async fn backfill_headers(
+        &self,
+        start: &Header,
+        end: &Header,
+    ) -> Result<Vec<VerifiedHeader>> {
+        let start_slot = start.slot.as_u64();
+        let end_slot = end.slot.as_u64();
+        if start_slot >= end_slot {
+            return Err(ConsensusError::InvalidBackfillRange(start_slot, end_slot).into());
+        }
+        if end_slot - start_slot > MAX_BACKFILL_SLOTS {
+            let slots = end_slot - start_slot;
+            return Err(ConsensusError::BackfillRangeTooLong(slots, MAX_BACKFILL_SLOTS).into());
+        }
+
+        // The RPC fails for empty slots, which the parent roots skip over.
+        let blocks: Vec<Option<BeaconBlock>> = stream::iter((start_slot + 1..end_slot).rev())
+            .map(|slot| async move { self.rpc.get_block(slot).await.ok() })
+            .buffered(BACKFILL_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut parent_root = end.parent_root.clone();
+        let mut headers = Vec::new();
+        for mut block in blocks.into_iter().flatten() {
+            let block_root = Bytes32::try_from(block.hash_tree_root()?.as_ref())?;
+            if block_root != parent_root {
+                // Not an ancestor of `end`, the gap is caught once the chain reaches `start`.
+                debug!(target: "helios::consensus", slot = block.slot.as_u64(), "skipping a block that is not an ancestor");
+                continue;
+            }
+
+            let mut payload = block.body.execution_payload().clone();
+            let header = Header {
+                slot: block.slot,
+                proposer_index: block.proposer_index,
+                parent_root: block.parent_root.clone(),
+                state_root: block.state_root.clone(),
+                body_root: Bytes32::try_from(block.body.hash_tree_root()?.as_ref())?,
+            };
+            headers.push(VerifiedHeader {
+                header,
+                block_root,
+                execution_payload_root: Some(Bytes32::try_from(
+                    payload.hash_tree_root()?.as_ref(),
+                )?),
+                execution_block_hash: Some(payload.block_hash().clone()),
+                execution_block_number: Some(payload.block_number().as_u64()),
+                execution_state_root: Some(payload.state_root().clone()),
+            });
+            parent_root = block.parent_root;
+        }
+
+        let start_root = Bytes32::try_from(start.clone().hash_tree_root()?.as_ref())?;
+        if parent_root != start_root {
+            return Err(ConsensusError::BackfillGap(start_slot).into());
         }
+
+        headers.reverse();
+        Ok(headers)
     }
 
     pub async fn get_payloads(
@@ -369,29 +1500,150 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
     }
 
     pub async fn sync(&mut self, checkpoint: &[u8]) -> Result<()> {
+        let res = self.sync_inner(checkpoint).await;
+        self.record_result(&res);
+        res
+    }
+
+    async fn sync_inner(&mut self, checkpoint: &[u8]) -> Result<()> {
+        self.reset();
+        self.bootstrap(checkpoint).await?;
+        self.check_stale_checkpoint(checkpoint)?;
+        self.sync_forward(checkpoint).await
+    }
+
+    /// Fails if the bootstrapped header of `checkpoint` is older than
+    /// [`Config::stale_checkpoint_age`], since it would take too many periods of updates to catch
+    /// up, and sends a [`StaleCheckpoint`] warning.
+    fn check_stale_checkpoint(&self, checkpoint: &[u8]) -> Result<()> {
+        let Some(max_age) = self.config.stale_checkpoint_age else {
+            return Ok(());
+        };
+
+        let slot = self.store.finalized_header.slot.as_u64();
+        let age = self.checkpoint_age(slot);
+        if age <= max_age {
+            return Ok(());
+        }
+
+        warn!(
+            target: "helios::consensus",
+            slot,
+            age,
+            "checkpoint is stale, bootstrapping from a fresh checkpoint"
+        );
+
+        if let Some(stale_checkpoint_send) = &self.stale_checkpoint_send {
+            let stale = StaleCheckpoint {
+                checkpoint: checkpoint.to_vec(),
+                slot,
+                age,
+            };
+            if let Err(err) = stale_checkpoint_send.try_send(stale) {
+                warn!(target: "helios::consensus", "failed to send stale checkpoint: {err}");
+            }
+        }
+
+        Err(ConsensusError::StaleCheckpoint(slot, age).into())
+    }
+
+    /// Same as [`Self::sync`], but bootstraps from the genesis block of the network instead of a
+    /// checkpoint, for the freshly launched devnets that have no recent checkpoint. The genesis
+    /// header is trusted like a checkpoint, so it must have the genesis block root of the config,
+    /// see [`genesis::genesis_bootstrap`].
+    pub async fn sync_from_genesis(&mut self) -> Result<()> {
+        let res = self.sync_from_genesis_inner().await;
+        self.record_result(&res);
+        res
+    }
+
+    async fn sync_from_genesis_inner(&mut self) -> Result<()> {
+        self.reset();
+
+        let genesis_block_root = self
+            .config
+            .chain
+            .genesis_block_root
+            .clone()
+            .ok_or_else(|| {
+                ConsensusError::InvalidGenesis("no genesis block root is configured".to_string())
+            })?;
+
+        let header = self.rpc.get_genesis_header().await?;
+        let state_root = header.state_root.as_slice();
+        let (sync_committee, root_proof, committee_proof) = futures::try_join!(
+            self.rpc.get_genesis_sync_committee(),
+            self.rpc
+                .get_state_proof(state_root, genesis::genesis_validators_root_gindex()),
+            self.rpc
+                .get_state_proof(state_root, genesis::current_sync_committee_gindex()),
+        )?;
+
+        let mut bootstrap = genesis::genesis_bootstrap(
+            header,
+            sync_committee,
+            &root_proof,
+            &committee_proof,
+            &self.config.chain.genesis_root,
+            &genesis_block_root,
+        )?;
+        self.bootstrap_offline(&genesis_block_root, &mut bootstrap)?;
+
+        self.sync_forward(&genesis_block_root).await
+    }
+
+    /// Verifies and applies, in period order, the updates of the archive at `path` from the period
+    /// of the finalized header on, see [`archive`]. Returns the number of applied updates. Fails at
+    /// the first update that does not verify, after applying the ones before it.
+    pub async fn import_archive(&mut self, path: &Path) -> Result<usize> {
+        let period = calc_sync_period(self.store.finalized_header.slot.into());
+        let updates = archive::read_archive_updates(path, period).await?;
+
+        let updates = select_best_updates(&updates);
+        for update in &updates {
+            self.verify_generic_update(update)?;
+            self.apply_generic_update(update);
+        }
+
+        Ok(updates.len())
+    }
+
+    /// Clears the store and the caches before a new bootstrap.
+    fn reset(&mut self) {
         self.store = LightClientStore::default();
         self.last_checkpoint = None;
+        self.header_cache.clear();
+        self.update_cache.clear();
+        self.light_client_data.clear();
+        self.broadcast_finalized_slot = 0;
+    }
 
-        self.bootstrap(checkpoint).await?;
+    /// Applies the updates from the period of the bootstrapped `checkpoint` up to the latest
+    /// finality and optimistic updates.
+    async fn sync_forward(&mut self, checkpoint: &[u8]) -> Result<()> {
+        if let Some(path) = self.config.update_archive.clone() {
+            let imported = self.import_archive(&path).await?;
+            info!(target: "helios::consensus", imported, path = %path.display(), "imported archived updates");
+        }
 
         let current_period = calc_sync_period(self.store.finalized_header.slot.into());
-        let updates = self
-            .rpc
-            .get_updates(current_period, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
-            .await?;
+        let updates = self.fetch_updates(current_period).await?;
 
-        for update in updates {
-            self.verify_update(&update)?;
-            self.apply_update(&update);
+        for update in select_best_updates(&updates) {
+            self.verify_generic_update(&update)?;
+            self.apply_generic_update(&update);
         }
 
         let finality_update = self.rpc.get_finality_update().await?;
         self.verify_finality_update(&finality_update)?;
         self.apply_finality_update(&finality_update);
+        self.broadcast_finalized().await;
 
-        let optimistic_update = self.rpc.get_optimistic_update().await?;
-        self.verify_optimistic_update(&optimistic_update)?;
-        self.apply_optimistic_update(&optimistic_update);
+        if !self.config.strict_finality {
+            let optimistic_update = self.rpc.get_optimistic_update().await?;
+            self.verify_optimistic_update(&optimistic_update)?;
+            self.apply_optimistic_update(&optimistic_update);
+        }
 
         info!(
             target: "helios::consensus",
@@ -402,14 +1654,72 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         Ok(())
     }
 
+    /// Fetches the updates of every period from `period` up to the current one, either with a
+    /// single call to the RPC, which pages the range itself, or, when
+    /// [`Config::sync_concurrency`] is set, with [`Self::get_updates_parallel`].
+    async fn fetch_updates(&self, period: u64) -> Result<Vec<Update>> {
+        match self.config.sync_concurrency {
+            Some(concurrency) => self.get_updates_parallel(period, concurrency).await,
+            None => {
+                let count = self.sync_end_period(period) - period + 1;
+                self.rpc.get_updates(period, count).await
+            }
+        }
+    }
+
+    /// The last period to sync from `start_period`, which is the current one.
+    fn sync_end_period(&self, start_period: u64) -> u64 {
+        cmp::max(start_period, calc_sync_period(self.expected_current_slot()))
+    }
+
+    /// Fetches the updates of every period from `start_period` up to the current one.
+    /// The range is split into requests of [`PARALLEL_SYNC_PERIODS_PER_REQUEST`] periods, of which
+    /// up to `concurrency` are in flight at once. The updates are returned in period order, so
+    /// they can still be verified sequentially.
+    pub async fn get_updates_parallel(
+        &self,
+        start_period: u64,
+        concurrency: usize,
+    ) -> Result<Vec<Update>> {
+        let end_period = self.sync_end_period(start_period);
+        let periods_per_request = PARALLEL_SYNC_PERIODS_PER_REQUEST;
+
+        let requests = (start_period..=end_period)
+            .step_by(periods_per_request as usize)
+            .map(|period| {
+                let count = cmp::min(periods_per_request, end_period - period + 1);
+                self.rpc.get_updates(period, count)
+            });
+
+        let updates: Vec<Vec<Update>> = stream::iter(requests)
+            .buffered(cmp::max(concurrency, 1))
+            .try_collect()
+            .await?;
+
+        Ok(updates.into_iter().flatten().collect())
+    }
+
     pub async fn advance(&mut self) -> Result<()> {
+        let res = self.advance_inner().await;
+        self.record_result(&res);
+        res
+    }
+
+    async fn advance_inner(&mut self) -> Result<()> {
         let finality_update = self.rpc.get_finality_update().await?;
         self.verify_finality_update(&finality_update)?;
         self.apply_finality_update(&finality_update);
+        self.broadcast_finalized().await;
 
-        let optimistic_update = self.rpc.get_optimistic_update().await?;
-        self.verify_optimistic_update(&optimistic_update)?;
-        self.apply_optimistic_update(&optimistic_update);
+        if !self.config.strict_finality {
+            let optimistic_update = self.rpc.get_optimistic_update().await?;
+            self.verify_optimistic_update(&optimistic_update)?;
+            self.apply_optimistic_update(&optimistic_update);
+        }
+
+        if self.process_force_update() {
+            self.broadcast_finalized().await;
+        }
 
         if self.store.next_sync_committee.is_none() {
             debug!(target: "helios::consensus", "Checking for sync committee update");
@@ -430,25 +1740,134 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         Ok(())
     }
 
+    /// Advances the synced state on the schedule of [`Config::advance`] until `cancel` is
+    /// cancelled, and sends the new blocks after every advance. The sync committees of the periods
+    /// that passed while the advances failed are fetched before the next advance.
+    ///
+    /// The cancellation is only checked between advances, so that an update is never partially
+    /// applied.
+    pub async fn run(&mut self, cancel: &CancellationToken) {
+        let mut failures = 0;
+
+        loop {
+            let delay = if failures == 0 {
+                self.duration_until_next_advance()
+            } else {
+                self.config.advance.backoff(failures)
+            };
+
+            select! {
+                res = zduny_wasm_timer::Delay::new(delay) => res.unwrap(),
+                config = next_reload(&mut self.reload_recv) => {
+                    self.reload_or_warn(&config);
+                    continue;
+                }
+                _ = cancel.cancelled() => return,
+            }
+
+            let res = match self.catch_up_periods().await {
+                Ok(()) => self.advance().await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = res {
+                failures += 1;
+                warn!(target: "helios::consensus", failures, "advance error: {}", err);
+                continue;
+            }
+            failures = 0;
+            self.prune();
+
+            let res = self.send_blocks().await;
+            if let Err(err) = res {
+                warn!(target: "helios::consensus", "send error: {}", err);
+            }
+        }
+    }
+
+    /// Drops the light client updates and the verified headers that are older than the retention
+    /// of the config, see [`config::RetentionConfig`]. The updates of the period of the finalized
+    /// header are always kept. Called after every advance of [`Self::run`].
+    pub fn prune(&self) {
+        let retention = &self.config.retention;
+
+        let mut pruned_updates = 0;
+        if let Some(periods) = retention.update_periods {
+            let period = calc_sync_period(self.store.finalized_header.slot.into());
+            let first_kept = period.saturating_sub(periods.saturating_sub(1));
+            pruned_updates = self.light_client_data.prune_updates(first_kept);
+        }
+
+        let mut pruned_headers = 0;
+        if let Some(days) = retention.header_days {
+            let cutoff = SlotClock::now()
+                .as_secs()
+                .saturating_sub(days.saturating_mul(24 * 60 * 60));
+            if let Some(slot) = timestamp_to_slot(&self.config, cutoff) {
+                pruned_headers = self.header_cache.prune_before(slot);
+            }
+        }
+
+        if pruned_updates > 0 || pruned_headers > 0 {
+            debug!(
+                target: "helios::consensus",
+                pruned_updates,
+                pruned_headers,
+                "pruned verified data"
+            );
+        }
+    }
+
+    /// The delay until the next advance of [`Self::run`].
+    pub fn duration_until_next_advance(&self) -> std::time::Duration {
+        match self.config.advance.interval_ms {
+            Some(interval) => std::time::Duration::from_millis(interval),
+            None => self.duration_until_next_update().to_std().unwrap(),
+        }
+    }
+
+    /// Applies the updates of the periods between the store and the current slot, when the
+    /// current period is past the one of the next sync committee. The updates of such periods
+    /// cannot be verified with the committees of the store, e.g. after the client was suspended
+    /// for more than a period.
+    pub(crate) async fn catch_up_periods(&mut self) -> Result<()> {
+        let store_period = calc_sync_period(self.store.finalized_header.slot.into());
+        let known_period = match self.store.next_sync_committee {
+            Some(_) => store_period + 1,
+            None => store_period,
+        };
+
+        if calc_sync_period(self.expected_current_slot()) <= known_period {
+            return Ok(());
+        }
+
+        info!(target: "helios::consensus", "catching up from sync committee period {store_period}");
+        let updates = self.fetch_updates(store_period).await?;
+        for update in select_best_updates(&updates) {
+            // The updates of the store period were usually applied already.
+            if self.verify_generic_update(&update).is_ok() {
+                self.apply_generic_update(&update);
+            }
+        }
+
+        self.broadcast_finalized().await;
+        Ok(())
+    }
+
     pub async fn send_blocks(&self) -> Result<()> {
         if self.block_send.is_some()
             && self.finalized_block_send.is_some()
             && self.checkpoint_send.is_some()
         {
             let slot = self.store.optimistic_header.slot.as_u64();
-            let payload = self.get_execution_payload(&Some(slot)).await?;
+            let block = self.get_execution_block(slot).await?;
             let finalized_slot = self.store.finalized_header.slot.as_u64();
-            let finalized_payload = self.get_execution_payload(&Some(finalized_slot)).await?;
+            let finalized_block = self.get_execution_block(finalized_slot).await?;
 
-            self.block_send
-                .as_ref()
-                .unwrap()
-                .send(payload.into())
-                .await?;
+            self.block_send.as_ref().unwrap().send(block).await?;
             self.finalized_block_send
                 .as_ref()
                 .unwrap()
-                .send(Some(finalized_payload.into()))?;
+                .send(Some(finalized_block))?;
             self.checkpoint_send
                 .as_ref()
                 .unwrap()
@@ -459,21 +1878,13 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
     }
 
     /// Gets the duration until the next update
-    /// Updates are scheduled for 4 seconds into each slot.
+    /// Updates are scheduled for a third into each slot, i.e. 4 seconds on mainnet.
     pub fn duration_until_next_update(&self) -> Duration {
-        let current_slot = self.expected_current_slot();
-        let next_slot = current_slot + 1;
-        let next_slot_timestamp = self.slot_timestamp(next_slot);
+        let clock = self.slot_clock();
+        let next_update = clock.duration_until_next_slot()
+            + std::time::Duration::from_secs(clock.seconds_per_slot / 3);
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        let time_to_next_slot = next_slot_timestamp - now;
-        let next_update = time_to_next_slot + 4;
-
-        Duration::try_seconds(next_update as i64).unwrap()
+        Duration::from_std(next_update).unwrap()
     }
 
     async fn bootstrap(&mut self, checkpoint: &[u8]) -> Result<()> {
@@ -491,13 +1902,18 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         self.validate_bootstrap(checkpoint, bootstrap)?;
 
         self.last_checkpoint = Some(checkpoint.to_vec());
+        self.header_cache.insert(&bootstrap.header)?;
+        self.light_client_data
+            .record_bootstrap(checkpoint, bootstrap);
         self.store = LightClientStore {
             finalized_header: bootstrap.header.clone(),
             current_sync_committee: bootstrap.current_sync_committee.clone(),
             next_sync_committee: None,
             optimistic_header: bootstrap.header.clone(),
+            safe_header: bootstrap.header.clone(),
             previous_max_active_participants: 0,
             current_max_active_participants: 0,
+            best_valid_update: None,
         };
 
         Ok(())
@@ -516,7 +1932,7 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
 
         let committee_valid = is_current_committee_proof_valid(
             &bootstrap.header,
-            &mut bootstrap.current_sync_committee,
+            Arc::make_mut(&mut bootstrap.current_sync_committee),
             &bootstrap.current_sync_committee_branch,
         );
 
@@ -537,14 +1953,14 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
 
     // implements checks from validate_light_client_update and process_light_client_update in the
     // specification.
-    fn verify_generic_update(&self, update: &GenericUpdate) -> Result<()> {
+    fn verify_generic_update(&self, update: &GenericUpdate) -> Result<VerificationReport> {
         let bits = get_bits(&update.sync_aggregate.sync_committee_bits);
         if bits == 0 {
             return Err(ConsensusError::InsufficientParticipation.into());
         }
 
         let update_finalized_slot = update.finalized_header.clone().unwrap_or_default().slot;
-        let valid_time = self.expected_current_slot() >= update.signature_slot
+        let valid_time = !self.slot_clock().is_future_slot(update.signature_slot)
             && update.signature_slot > update.attested_header.slot.as_u64()
             && update.attested_header.slot >= update_finalized_slot;
 
@@ -575,7 +1991,9 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
             return Err(ConsensusError::NotRelevant.into());
         }
 
-        if update.finalized_header.is_some() && update.finality_branch.is_some() {
+        let has_finality_proof =
+            update.finalized_header.is_some() && update.finality_branch.is_some();
+        if has_finality_proof {
             let is_valid = is_finality_proof_valid(
                 &update.attested_header,
                 &mut update.finalized_header.clone().unwrap(),
@@ -587,10 +2005,12 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
             }
         }
 
-        if update.next_sync_committee.is_some() && update.next_sync_committee_branch.is_some() {
+        let has_next_sync_committee_proof =
+            update.next_sync_committee.is_some() && update.next_sync_committee_branch.is_some();
+        if has_next_sync_committee_proof {
             let is_valid = is_next_committee_proof_valid(
                 &update.attested_header,
-                &mut update.next_sync_committee.clone().unwrap(),
+                &mut update.next_sync_committee.as_deref().cloned().unwrap(),
                 &update.next_sync_committee_branch.clone().unwrap(),
             );
 
@@ -605,87 +2025,185 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
             self.store.next_sync_committee.as_ref().unwrap()
         };
 
-        let pks =
-            get_participating_keys(sync_committee, &update.sync_aggregate.sync_committee_bits)?;
+        let header_root =
+            Bytes32::try_from(update.attested_header.clone().hash_tree_root()?.as_ref())?;
+        let signing_root = self.compute_committee_sign_root(header_root, update.signature_slot)?;
+        let key = update_key(&signing_root, &update.sync_aggregate, sync_committee)?;
 
-        let is_valid_sig = self.verify_sync_committee_signature(
-            &pks,
-            &update.attested_header,
-            &update.sync_aggregate.sync_committee_signature,
-            update.signature_slot,
-        );
+        if !self.update_cache.contains(&key) {
+            let pks =
+                get_participating_keys(sync_committee, &update.sync_aggregate.sync_committee_bits)?;
+
+            let is_valid_sig = self.verify_sync_committee_signature(
+                &pks,
+                &signing_root,
+                &update.sync_aggregate.sync_committee_signature,
+            );
+
+            if !is_valid_sig {
+                return Err(ConsensusError::InvalidSignature.into());
+            }
 
-        if !is_valid_sig {
-            return Err(ConsensusError::InvalidSignature.into());
+            self.update_cache.insert(key);
         }
 
-        Ok(())
+        if let Some(conflict) = self.detect_conflict(update)? {
+            let slot = conflict.slot;
+            self.report_conflict(conflict);
+            return Err(ConsensusError::ConflictingFinality(slot).into());
+        }
+
+        Ok(VerificationReport {
+            participation: bits,
+            attested_slot: update.attested_header.slot.as_u64(),
+            signature_slot: update.signature_slot,
+            finalized_slot: update.finalized_header.as_ref().map(|h| h.slot.as_u64()),
+            has_finality_proof,
+            has_next_sync_committee_proof,
+        })
+    }
+
+    /// Returns the evidence if the finalized header of a verified `update` is not the header that
+    /// was already verified at its slot.
+    fn detect_conflict(&self, update: &GenericUpdate) -> Result<Option<FinalityConflict>> {
+        let Some(finalized_header) = &update.finalized_header else {
+            return Ok(None);
+        };
+
+        let slot = finalized_header.slot.as_u64();
+        let existing = match self.header_cache.get_by_slot(slot) {
+            Some(verified) => verified.header,
+            None if self.store.finalized_header.slot.as_u64() == slot => {
+                self.store.finalized_header.clone()
+            }
+            None => return Ok(None),
+        };
+
+        FinalityConflict::detect(&existing, update)
+    }
+
+    fn report_conflict(&self, conflict: FinalityConflict) {
+        error!(
+            target: "helios::consensus",
+            slot = conflict.slot,
+            existing = %hex::encode(conflict.existing_root.as_slice()),
+            conflicting = %hex::encode(conflict.conflicting_root.as_slice()),
+            "verified updates finalize conflicting headers"
+        );
+
+        if let Some(conflict_send) = &self.conflict_send {
+            if let Err(err) = conflict_send.try_send(conflict) {
+                warn!(target: "helios::consensus", "failed to send finality conflict: {err}");
+            }
+        }
     }
 
-    fn verify_update(&self, update: &Update) -> Result<()> {
+    /// Verifies an update against the current store without applying it.
+    pub fn verify_update(&self, update: &Update) -> Result<VerificationReport> {
         let update = GenericUpdate::from(update);
         self.verify_generic_update(&update)
     }
 
-    fn verify_finality_update(&self, update: &FinalityUpdate) -> Result<()> {
+    /// Verifies a finality update against the current store without applying it.
+    pub fn verify_finality_update(&self, update: &FinalityUpdate) -> Result<VerificationReport> {
         let update = GenericUpdate::from(update);
         self.verify_generic_update(&update)
     }
 
-    fn verify_optimistic_update(&self, update: &OptimisticUpdate) -> Result<()> {
+    /// Verifies an optimistic update against the current store without applying it. Fails with
+    /// [`ConsensusError::ParticipationBelowMinimum`] if less than
+    /// [`Config::min_optimistic_participation`] of the sync committee signed it.
+    pub fn verify_optimistic_update(
+        &self,
+        update: &OptimisticUpdate,
+    ) -> Result<VerificationReport> {
         let update = GenericUpdate::from(update);
+        let bits = get_bits(&update.sync_aggregate.sync_committee_bits);
+        if let Some(min_participants) = self.min_optimistic_participants() {
+            if bits < min_participants {
+                return Err(
+                    ConsensusError::ParticipationBelowMinimum(bits, min_participants).into(),
+                );
+            }
+        }
+
         self.verify_generic_update(&update)
     }
 
+    /// The number of participants of [`Config::min_optimistic_participation`].
+    fn min_optimistic_participants(&self) -> Option<u64> {
+        self.config
+            .min_optimistic_participation
+            .map(|participation| (participation * SYNC_COMMITTEE_SIZE as f64).ceil() as u64)
+    }
+
+    /// The [`StoreSummary`] of the store, for the update processing of [`consensus_core::update`].
+    fn store_summary(&self) -> StoreSummary {
+        StoreSummary {
+            finalized_slot: self.store.finalized_header.slot.as_u64(),
+            optimistic_slot: self.store.optimistic_header.slot.as_u64(),
+            safe_slot: self.store.safe_header.slot.as_u64(),
+            has_next_sync_committee: self.store.next_sync_committee.is_some(),
+            previous_max_active_participants: self.store.previous_max_active_participants,
+            current_max_active_participants: self.store.current_max_active_participants,
+        }
+    }
+
     // implements state changes from apply_light_client_update and process_light_client_update in
-    // the specification.
+    // the specification, as decided by consensus_core::update::process_update.
     fn apply_generic_update(&mut self, update: &GenericUpdate) {
-        let committee_bits = get_bits(&update.sync_aggregate.sync_committee_bits);
-
-        self.store.current_max_active_participants =
-            u64::max(self.store.current_max_active_participants, committee_bits);
+        self.light_client_data
+            .record_update(update, !self.config.strict_finality);
 
-        let should_update_optimistic = committee_bits > self.safety_threshold()
-            && update.attested_header.slot > self.store.optimistic_header.slot;
+        let summary = update_summary(update);
+        let is_best_valid_update = match &self.store.best_valid_update {
+            Some(best_valid_update) => {
+                is_better_update(&summary, &update_summary(best_valid_update))
+            }
+            None => true,
+        };
 
-        if should_update_optimistic {
-            self.store.optimistic_header = update.attested_header.clone();
-            self.log_optimistic_update(update);
+        if is_best_valid_update {
+            self.store.best_valid_update = Some(update.clone());
         }
 
-        let update_attested_period = calc_sync_period(update.attested_header.slot.into());
-
-        let update_finalized_slot = update
-            .finalized_header
-            .as_ref()
-            .map(|h| h.slot.as_u64())
-            .unwrap_or(0);
+        let policy = UpdatePolicy {
+            strict_finality: self.config.strict_finality,
+            min_optimistic_participants: self.min_optimistic_participants().unwrap_or_default(),
+        };
+        let processed = process_update(&self.store_summary(), &summary, &policy);
 
-        let update_finalized_period = calc_sync_period(update_finalized_slot);
+        self.store.current_max_active_participants = processed.current_max_active_participants;
 
-        let update_has_finalized_next_committee = self.store.next_sync_committee.is_none()
-            && self.has_sync_update(update)
-            && self.has_finality_update(update)
-            && update_finalized_period == update_attested_period;
+        if processed.update_optimistic_header {
+            self.store.optimistic_header = update.attested_header.clone();
+            self.log_optimistic_update(update);
+        }
 
-        let should_apply_update = {
-            let has_majority = committee_bits * 3 >= 512 * 2;
-            if !has_majority {
-                warn!("skipping block with low vote count");
-            }
+        if processed.update_safe_header {
+            self.store.safe_header = update.attested_header.clone();
+        }
 
-            let update_is_newer = update_finalized_slot > self.store.finalized_header.slot.as_u64();
-            let good_update = update_is_newer || update_has_finalized_next_committee;
+        if !has_supermajority(summary.participants) {
+            warn!("skipping block with low vote count");
+        }
 
-            has_majority && good_update
-        };
+        if processed.apply {
+            self.apply_light_client_update(update, &summary);
+            self.store.best_valid_update = None;
+        }
+    }
 
-        if should_apply_update {
-            let store_period = calc_sync_period(self.store.finalized_header.slot.into());
+    // implements apply_light_client_update from the specification, as decided by
+    // consensus_core::update::apply_update.
+    fn apply_light_client_update(&mut self, update: &GenericUpdate, summary: &UpdateSummary) {
+        let applied = apply_update(&self.store_summary(), summary);
 
-            if self.store.next_sync_committee.is_none() {
+        match applied.committee {
+            CommitteeChange::SetNext => {
                 self.store.next_sync_committee = update.next_sync_committee.clone();
-            } else if update_finalized_period == store_period + 1 {
+            }
+            CommitteeChange::Rotate => {
                 info!(target: "helios::consensus", "sync committee updated");
                 self.store.current_sync_committee = self.store.next_sync_committee.clone().unwrap();
                 self.store.next_sync_committee = update.next_sync_committee.clone();
@@ -693,23 +2211,71 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
                     self.store.current_max_active_participants;
                 self.store.current_max_active_participants = 0;
             }
+            CommitteeChange::None => {}
+        }
 
-            if update_finalized_slot > self.store.finalized_header.slot.as_u64() {
-                self.store.finalized_header = update.finalized_header.clone().unwrap();
-                self.log_finality_update(update);
+        if applied.update_finalized_header {
+            self.store.finalized_header = update.finalized_header.clone().unwrap();
+            self.log_finality_update(update);
 
-                if self.store.finalized_header.slot.as_u64() % 32 == 0 {
-                    let checkpoint_res = self.store.finalized_header.hash_tree_root();
-                    if let Ok(checkpoint) = checkpoint_res {
-                        self.last_checkpoint = Some(checkpoint.as_ref().to_vec());
-                    }
-                }
+            if let Err(err) = self.header_cache.insert(&self.store.finalized_header) {
+                warn!(target: "helios::consensus", "failed to cache finalized header: {err}");
+            }
 
-                if self.store.finalized_header.slot > self.store.optimistic_header.slot {
-                    self.store.optimistic_header = self.store.finalized_header.clone();
+            if self.store.finalized_header.slot.as_u64() % 32 == 0 {
+                let checkpoint_res = self.store.finalized_header.hash_tree_root();
+                if let Ok(checkpoint) = checkpoint_res {
+                    self.last_checkpoint = Some(checkpoint.as_ref().to_vec());
                 }
             }
+
+            if applied.update_optimistic_header {
+                self.store.optimistic_header = self.store.finalized_header.clone();
+            }
+
+            if applied.update_safe_header {
+                self.store.safe_header = self.store.finalized_header.clone();
+            }
+        }
+    }
+
+    /// Implements `process_light_client_store_force_update` from the specification.
+    /// If no finality update was applied for [`Config::force_update_timeout`] slots, the best
+    /// valid update seen since the last finalized header is applied, even though its finality
+    /// could not be proven. This keeps the state advancing on chains that stopped finalizing. It
+    /// is disabled by [`Config::strict_finality`].
+    /// # Returns
+    /// * `true` if a forced update was applied.
+    pub fn process_force_update(&mut self) -> bool {
+        let timeout = match self.config.force_update_timeout {
+            Some(timeout) if !self.config.strict_finality => timeout,
+            _ => return false,
+        };
+
+        let store = self.store_summary();
+        if !is_force_update_due(&store, self.expected_current_slot(), timeout) {
+            return false;
+        }
+
+        let mut update = match self.store.best_valid_update.take() {
+            Some(update) => update,
+            None => return false,
+        };
+
+        let summary = update_summary(&update);
+        if !finalizes_newer_header(&store, &summary) {
+            update.finalized_header = Some(update.attested_header.clone());
         }
+
+        warn!(
+            target: "helios::consensus",
+            "no finality for more than {} slots, force updating to slot {}",
+            timeout,
+            update.attested_header.slot.as_u64()
+        );
+        self.apply_light_client_update(&update, &forced_update(&store, &summary));
+
+        true
     }
 
     fn apply_update(&mut self, update: &Update) {
@@ -719,6 +2285,7 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
 
     fn apply_finality_update(&mut self, update: &FinalityUpdate) {
         let update = GenericUpdate::from(update);
+        self.record_participation(&update);
         self.apply_generic_update(&update);
     }
 
@@ -742,6 +2309,7 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
 
     fn apply_optimistic_update(&mut self, update: &OptimisticUpdate) {
         let update = GenericUpdate::from(update);
+        self.record_participation(&update);
         self.apply_generic_update(&update);
     }
 
@@ -763,80 +2331,64 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         );
     }
 
-    fn has_finality_update(&self, update: &GenericUpdate) -> bool {
-        update.finalized_header.is_some() && update.finality_branch.is_some()
-    }
-
-    fn has_sync_update(&self, update: &GenericUpdate) -> bool {
-        update.next_sync_committee.is_some() && update.next_sync_committee_branch.is_some()
-    }
-
-    fn safety_threshold(&self) -> u64 {
-        cmp::max(
-            self.store.current_max_active_participants,
-            self.store.previous_max_active_participants,
-        ) / 2
-    }
-
     fn verify_sync_committee_signature(
         &self,
         pks: &[PublicKey],
-        attested_header: &Header,
+        signing_root: &Node,
         signature: &SignatureBytes,
-        signature_slot: u64,
     ) -> bool {
-        let res: Result<bool> = (move || {
-            let pks: Vec<&PublicKey> = pks.iter().collect();
-            let header_root =
-                Bytes32::try_from(attested_header.clone().hash_tree_root()?.as_ref())?;
-            let signing_root = self.compute_committee_sign_root(header_root, signature_slot)?;
-
-            Ok(is_aggregate_valid(signature, signing_root.as_ref(), &pks))
-        })();
-
-        res.unwrap_or_default()
+        let pks: Vec<&PublicKey> = pks.iter().collect();
+        is_aggregate_valid(signature, signing_root.as_ref(), &pks)
     }
 
     fn compute_committee_sign_root(&self, header: Bytes32, slot: u64) -> Result<Node> {
         let genesis_root = self.config.chain.genesis_root.to_vec().try_into().unwrap();
 
         let domain_type = &hex::decode("07000000")?[..];
-        let fork_version =
-            Vector::try_from(self.config.fork_version(slot)).map_err(|(_, err)| err)?;
+        let fork_version = self
+            .config
+            .fork_schedule()
+            .fork_version_at_slot(slot)
+            .to_vec();
+        let fork_version = Vector::try_from(fork_version).map_err(|(_, err)| err)?;
         let domain = compute_domain(domain_type, fork_version, genesis_root)?;
         compute_signing_root(header, domain)
     }
 
     fn age(&self, slot: u64) -> Duration {
         let expected_time = self.slot_timestamp(slot);
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let now = SlotClock::now();
         let delay = now - std::time::Duration::from_secs(expected_time);
         Duration::from_std(delay).unwrap()
     }
 
-    fn expected_current_slot(&self) -> u64 {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let genesis_time = self.config.chain.genesis_time;
-        let since_genesis = now - std::time::Duration::from_secs(genesis_time);
+    /// The slot clock of the configured network.
+    pub fn slot_clock(&self) -> SlotClock {
+        SlotClock::from_config(&self.config)
+    }
 
-        since_genesis.as_secs() / 12
+    fn expected_current_slot(&self) -> u64 {
+        self.slot_clock().current_slot()
     }
 
     fn slot_timestamp(&self, slot: u64) -> u64 {
-        slot * 12 + self.config.chain.genesis_time
+        self.slot_clock().slot_timestamp(slot)
     }
 
     // Determines `blockhash_slot` age and returns true if it is less than 14 days old.
     fn is_valid_checkpoint(&self, blockhash_slot: u64) -> bool {
+        self.checkpoint_age(blockhash_slot) < self.config.max_checkpoint_age
+    }
+
+    /// The number of seconds between `blockhash_slot` and the current slot.
+    fn checkpoint_age(&self, blockhash_slot: u64) -> u64 {
         let current_slot = self.expected_current_slot();
         let current_slot_timestamp = self.slot_timestamp(current_slot);
         let blockhash_slot_timestamp = self.slot_timestamp(blockhash_slot);
 
-        let slot_age = current_slot_timestamp
+        current_slot_timestamp
             .checked_sub(blockhash_slot_timestamp)
-            .unwrap_or_default();
-
-        slot_age < self.config.max_checkpoint_age
+            .unwrap_or_default()
     }
 
     /// Synchronizes the local state with the blockchain state since the last verified checkpoint.
@@ -870,10 +2422,7 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         }
 
         let current_period = calc_sync_period(self.store.finalized_header.slot.into());
-        let updates = self
-            .rpc
-            .get_updates(current_period, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
-            .await?;
+        let updates = self.fetch_updates(current_period).await?;
 
         let finality_update = self.rpc.get_finality_update().await?;
 
@@ -886,6 +2435,60 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         })
     }
 
+    /// The roots of the sync committees of the state, which a sender of a
+    /// [`DeltaUpdatesResponse`] to this state can leave out.
+    pub fn known_sync_committee_roots(&self) -> Result<Vec<Bytes32>> {
+        self.known_sync_committees()
+            .iter()
+            .map(|committee| sync_committee_root(committee))
+            .collect()
+    }
+
+    /// Resolves the committee references of `delta` against the sync committees of the state. The
+    /// resolved updates still have to be verified, e.g. with
+    /// [`Self::verify_and_apply_initial_updates`].
+    pub fn resolve_delta_updates(&self, delta: DeltaUpdatesResponse) -> Result<AggregateUpdates> {
+        Ok(delta.resolve(&self.known_sync_committees())?.into())
+    }
+
+    /// Returns what the transition from the state of `old` to the state of `new` accepted. Both
+    /// states must be of the same chain.
+    pub fn diff(old: &Self, new: &Self) -> Result<StateDiff> {
+        StateDiff::new(
+            &old.store.finalized_header,
+            &old.store.current_sync_committee,
+            &new.store.finalized_header,
+            &new.store.current_sync_committee,
+        )
+    }
+
+    /// Like [`Self::diff`], and also fetches the execution payloads of both finalized headers,
+    /// each verified by its own state, to find the execution blocks finalized by the transition.
+    pub async fn diff_with_execution(old: &Self, new: &Self) -> Result<StateDiff> {
+        let mut diff = Self::diff(old, new)?;
+
+        let old_slot = old.store.finalized_header.slot.as_u64();
+        let new_slot = new.store.finalized_header.slot.as_u64();
+        let (old_payload, new_payload) = futures::try_join!(
+            old.get_execution_payload(&Some(old_slot)),
+            new.get_execution_payload(&Some(new_slot)),
+        )?;
+
+        diff.execution = Some(ExecutionRange {
+            from_block: old_payload.block_number().as_u64(),
+            from_block_hash: old_payload.block_hash().clone(),
+            to_block: new_payload.block_number().as_u64(),
+            to_block_hash: new_payload.block_hash().clone(),
+        });
+        Ok(diff)
+    }
+
+    fn known_sync_committees(&self) -> Vec<Arc<SyncCommittee>> {
+        let mut committees = vec![self.store.current_sync_committee.clone()];
+        committees.extend(self.store.next_sync_committee.clone());
+        committees
+    }
+
     /// Determines the sync period for the current finalized header.
     pub fn get_sync_period(&mut self) -> u64 {
         calc_sync_period(self.store.finalized_header.slot.into())
@@ -911,16 +2514,18 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         &mut self,
         updates: &AggregateUpdates,
     ) -> Result<(), eyre::Error> {
-        for update in &updates.updates {
-            self.verify_update(update)?;
-            self.apply_update(update);
+        for update in select_best_updates(&updates.updates) {
+            self.verify_generic_update(&update)?;
+            self.apply_generic_update(&update);
         }
 
         self.verify_finality_update(&updates.finality_update)?;
         self.apply_finality_update(&updates.finality_update);
 
-        self.verify_optimistic_update(&updates.optimistic_update)?;
-        self.apply_optimistic_update(&updates.optimistic_update);
+        if !self.config.strict_finality {
+            self.verify_optimistic_update(&updates.optimistic_update)?;
+            self.apply_optimistic_update(&updates.optimistic_update);
+        }
 
         Ok(())
     }
@@ -941,8 +2546,12 @@ impl<R: ConsensusRpc> ConsensusStateManager<R> {
         self.verify_finality_update(&updates.finality_update)?;
         self.apply_finality_update(&updates.finality_update);
 
-        self.verify_optimistic_update(&updates.optimistic_update)?;
-        self.apply_optimistic_update(&updates.optimistic_update);
+        if !self.config.strict_finality {
+            self.verify_optimistic_update(&updates.optimistic_update)?;
+            self.apply_optimistic_update(&updates.optimistic_update);
+        }
+
+        self.process_force_update();
 
         if self.store.next_sync_committee.is_none() {
             let updates = &updates.updates;
@@ -978,28 +2587,144 @@ fn get_participating_keys(
     committee: &SyncCommittee,
     bitfield: &Bitvector<512>,
 ) -> Result<Vec<PublicKey>> {
-    let mut pks: Vec<PublicKey> = Vec::new();
-    bitfield.iter().enumerate().for_each(|(i, bit)| {
+    Ok(consensus_core::bls::get_participating_keys(
+        committee.pubkeys.iter().map(|pk| pk.as_slice()),
+        bitfield.iter().map(|bit| *bit),
+    )?)
+}
+
+/// Counts the number of bits set to `true` in a given [`Bitvector<512>`].
+fn get_bits(bitfield: &Bitvector<512>) -> u64 {
+    let mut count = 0;
+    bitfield.iter().for_each(|bit| {
         if bit == true {
-            let pk = &committee.pubkeys[i];
-            let pk = PublicKey::from_bytes_unchecked(pk).unwrap();
-            pks.push(pk);
+            count += 1;
         }
     });
 
-    Ok(pks)
+    count
+}
+
+/// The [`UpdateSummary`] that [`is_better_update`] and [`consensus_core::update`] rank and apply
+/// `update` by.
+pub(crate) fn update_summary(update: &GenericUpdate) -> UpdateSummary {
+    let has_finality = update.finalized_header.is_some() && update.finality_branch.is_some();
+
+    UpdateSummary {
+        participants: get_bits(&update.sync_aggregate.sync_committee_bits),
+        attested_slot: update.attested_header.slot.as_u64(),
+        signature_slot: update.signature_slot,
+        finalized_slot: update
+            .finalized_header
+            .as_ref()
+            .filter(|_| has_finality)
+            .map(|header| header.slot.as_u64()),
+        has_next_sync_committee: update.next_sync_committee.is_some()
+            && update.next_sync_committee_branch.is_some(),
+    }
+}
+
+/// Creates the RPC client of the endpoint `rpc`, with the client options of `config`.
+fn build_rpc<R: ConsensusRpc>(rpc: &str, config: &Config) -> R {
+    let mut client = R::new(rpc).with_retry_policy(config.rpc_retry.clone());
+    if let Some(limit) = config.rpc_rate_limit(rpc) {
+        client = client.with_rate_limit(limit.clone());
+    }
+    if let Some(credentials) = config.rpc_credentials(rpc) {
+        client = client.with_credentials(credentials.clone());
+    }
+    if let Some(tls) = config.rpc_tls(rpc) {
+        client = client.with_tls(tls.clone());
+    }
+    if let Some(proxy) = config.rpc_proxy(rpc) {
+        client = client.with_proxy(proxy);
+    }
+    if config.rpc_unknown_forks {
+        client = client.with_unknown_forks(true);
+    }
+    if let Some(dir) = &config.rpc_record_dir {
+        client = client.with_record_dir(dir);
+    }
+    if !config.gossip_peers.is_empty() {
+        client = client.with_gossip(&config.gossip_peers, &config.fork_schedule());
+    }
+
+    client
+}
+
+/// Returns the next config sent to `reload_recv`, see [`ConsensusStateManager::reload`]. Waits
+/// forever if there is no channel, or once its sender is dropped.
+async fn next_reload(reload_recv: &mut Option<watch::Receiver<Config>>) -> Config {
+    match reload_recv {
+        Some(recv) if recv.changed().await.is_ok() => recv.borrow_and_update().clone(),
+        _ => std::future::pending().await,
+    }
 }
 
-/// Counts the number of bits set to `true` in a given [`Bitvector<512>`].
-fn get_bits(bitfield: &Bitvector<512>) -> u64 {
-    let mut count = 0;
-    bitfield.iter().for_each(|bit| {
-        if bit == true {
-            count += 1;
+/// Fails unless the `block_hash` of the execution payload of `block` is the hash of the header
+/// rebuilt from the payload, see [`execution_block_hash`]. The payload of a block from before the
+/// merge is empty.
+fn verify_execution_block_hash(block: &BeaconBlock) -> Result<()> {
+    let payload_hash = H256::from_slice(block.body.execution_payload().block_hash());
+    if payload_hash.is_zero() {
+        return Ok(());
+    }
+
+    let execution_hash = execution_block_hash(block)?;
+    if execution_hash != payload_hash {
+        return Err(ConsensusError::InvalidExecutionBlockHash(
+            format!("{execution_hash:?}"),
+            format!("{payload_hash:?}"),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Returns the execution block of the verified beacon block `beacon_block`. Deneb blocks also
+/// carry the root of their parent beacon block, and the transactions root is rebuilt when
+/// [`Config::verify_transactions_root`] is set, otherwise it is left to the execution RPC.
+fn execution_block(beacon_block: &BeaconBlock, config: &Config) -> Block {
+    let payload = beacon_block.body.execution_payload().clone();
+    let is_deneb = matches!(payload, ExecutionPayload::Deneb(_));
+    let transactions_root = config
+        .verify_transactions_root
+        .then(|| transactions_root(&payload));
+    let mut block = Block::from(payload);
+    if is_deneb {
+        block.parent_beacon_block_root =
+            Some(H256::from_slice(beacon_block.parent_root.as_slice()));
+    }
+    if let Some(transactions_root) = transactions_root {
+        block.transactions_root = transactions_root;
+    }
+
+    block
+}
+
+/// Keeps only the best update of every sync committee period, as ranked by [`is_better_update`].
+/// The returned updates are ordered by period, so they can be applied sequentially.
+fn select_best_updates(updates: &[Update]) -> Vec<GenericUpdate> {
+    let mut best_updates: BTreeMap<u64, GenericUpdate> = BTreeMap::new();
+
+    for update in updates {
+        let period = calc_sync_period(update.attested_header.slot.into());
+        let update = GenericUpdate::from(update);
+
+        let is_best = match best_updates.get(&period) {
+            Some(best_update) => {
+                is_better_update(&update_summary(&update), &update_summary(best_update))
+            }
+            None => true,
+        };
+
+        if is_best {
+            best_updates.insert(period, update);
         }
-    });
+    }
 
-    count
+    best_updates.into_values().collect()
 }
 
 fn is_finality_proof_valid(
@@ -1069,15 +2794,25 @@ mod tests {
     #![allow(clippy::needless_return)]
     use std::sync::Arc;
 
-    use config::{networks, Config};
+    use config::{networks, Config, Network};
+    use ssz_rs::prelude::*;
     use tokio::sync::{mpsc::channel, watch};
+    use tokio_util::sync::CancellationToken;
 
     use crate::{
-        consensus::calc_sync_period,
-        constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES,
+        consensus::{calc_sync_period, select_best_updates},
+        constants::{MAX_BACKFILL_SLOTS, MAX_REQUEST_LIGHT_CLIENT_UPDATES, UPDATE_TIMEOUT},
         errors::ConsensusError,
         rpc::{mock_rpc::MockRpc, ConsensusRpc},
-        types::{BLSPubKey, Header, SignatureBytes},
+        snapshot::{self, SignedStateSnapshot},
+        types::{
+            encoding::{
+                Compression, DeltaUpdatesResponse, SyncCommitteeRef, UpdatesResponse,
+                COMPRESSED_FLAG, ENCODING_VERSION,
+            },
+            AggregateUpdates, BLSPubKey, BeaconBlock, Bootstrap, Bytes32, ExecutionPayload,
+            ExecutionPayloadBellatrix, Header, SignatureBytes,
+        },
         ConsensusStateManager,
     };
 
@@ -1118,6 +2853,480 @@ mod tests {
         client
     }
 
+    #[tokio::test]
+    async fn test_sync_parallel() {
+        let mut client = get_client(false, false).await;
+        client.config.sync_concurrency = Some(4);
+
+        let period = client.get_sync_period();
+        let updates = client.get_updates_parallel(period, 4).await.unwrap();
+        let expected = client
+            .rpc
+            .get_updates(period, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+            .await
+            .unwrap();
+        // The mock returns the same updates for every request, which the selection deduplicates.
+        assert!(updates.len() >= expected.len());
+        assert_eq!(select_best_updates(&updates).len(), expected.len());
+
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        client.sync(&checkpoint).await.unwrap();
+        assert!(client.get_finalized_header().slot.as_u64() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_headers() {
+        let mut client = get_client(false, false).await;
+
+        let root = |block: &BeaconBlock| {
+            Bytes32::try_from(block.clone().hash_tree_root().unwrap().as_ref()).unwrap()
+        };
+        let start = Header {
+            slot: 10u64.into(),
+            ..Default::default()
+        };
+        let first = BeaconBlock {
+            slot: 11u64.into(),
+            parent_root: Bytes32::try_from(start.clone().hash_tree_root().unwrap().as_ref())
+                .unwrap(),
+            ..Default::default()
+        };
+        // Slot 12 is empty.
+        let second = BeaconBlock {
+            slot: 13u64.into(),
+            parent_root: root(&first),
+            ..Default::default()
+        };
+        let end = Header {
+            slot: 14u64.into(),
+            parent_root: root(&second),
+            ..Default::default()
+        };
+
+        client.set_rpc_client(MockRpc::new().with_block(first.clone()).with_block(second));
+        let headers = client.backfill_headers(&start, &end).await.unwrap();
+        assert_eq!(
+            vec![11, 13],
+            headers
+                .iter()
+                .map(|header| header.header.slot.as_u64())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(root(&first), headers[0].block_root);
+
+        // Without the block of slot 13, the chain cannot be linked to the start.
+        client.set_rpc_client(MockRpc::new().with_block(first));
+        assert!(client.backfill_headers(&start, &end).await.is_err());
+        assert!(client.backfill_headers(&end, &start).await.is_err());
+
+        let far_end = Header {
+            slot: (10 + MAX_BACKFILL_SLOTS + 1).into(),
+            ..Default::default()
+        };
+        let err = client.backfill_headers(&start, &far_end).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::BackfillRangeTooLong(MAX_BACKFILL_SLOTS + 1, MAX_BACKFILL_SLOTS)
+                .to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_finalized_block_at() {
+        let mut client = get_client(false, false).await;
+        client.header_cache.clear();
+
+        let block = |slot: u64, parent_root: Bytes32| {
+            let mut block = BeaconBlock {
+                slot: slot.into(),
+                parent_root,
+                ..Default::default()
+            };
+            *block.body.execution_payload_mut() =
+                ExecutionPayload::Bellatrix(ExecutionPayloadBellatrix {
+                    block_number: slot.into(),
+                    ..Default::default()
+                });
+            block
+        };
+        let root = |block: &BeaconBlock| {
+            Bytes32::try_from(block.clone().hash_tree_root().unwrap().as_ref()).unwrap()
+        };
+
+        // Slot 12 is empty, so its time is covered by the block of slot 11.
+        let first = block(11, Bytes32::default());
+        let second = block(13, root(&first));
+        client.store.finalized_header = Header {
+            slot: 14u64.into(),
+            parent_root: root(&second),
+            ..Default::default()
+        };
+        client.set_rpc_client(MockRpc::new().with_block(first.clone()).with_block(second));
+
+        let clock = client.slot_clock();
+        let found = client
+            .find_finalized_block_at(clock.slot_timestamp(12) + 5)
+            .await
+            .unwrap();
+        assert_eq!(11, found.number.as_u64());
+        let found = client
+            .find_finalized_block_at(clock.slot_timestamp(13))
+            .await
+            .unwrap();
+        assert_eq!(13, found.number.as_u64());
+
+        // Blocks after the finalized header are not finalized yet.
+        assert!(client
+            .find_finalized_block_at(clock.slot_timestamp(15))
+            .await
+            .is_err());
+
+        // The execution block hash of a block found from the parent roots is checked too.
+        let mut tampered = block(13, root(&first));
+        *tampered.body.execution_payload_mut() =
+            ExecutionPayload::Bellatrix(ExecutionPayloadBellatrix {
+                block_number: 13u64.into(),
+                block_hash: Bytes32::try_from([1u8; 32].as_slice()).unwrap(),
+                ..Default::default()
+            });
+        client.store.finalized_header.parent_root = root(&tampered);
+        client.set_rpc_client(MockRpc::new().with_block(first).with_block(tampered));
+        assert!(client
+            .find_finalized_block_at(clock.slot_timestamp(13))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_archive() {
+        let mut client = get_client(false, false).await;
+        let period = client.get_sync_period();
+        let expected = client
+            .rpc
+            .get_updates(period, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+            .await
+            .unwrap();
+
+        // The test data directory is laid out like an archive directory.
+        let imported = client.import_archive(Path::new("testdata/")).await.unwrap();
+        assert_eq!(select_best_updates(&expected).len(), imported);
+
+        assert!(client
+            .import_archive(Path::new("testdata/missing.era"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_finalized() {
+        let mut client = get_client(false, false).await;
+        let mut finalized_recv = client.subscribe_finalized();
+
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        client.sync(&checkpoint).await.unwrap();
+
+        let finalized = finalized_recv.try_recv().unwrap();
+        let header = client.get_finalized_header();
+        assert_eq!(finalized.slot, header.slot.as_u64());
+        assert_eq!(
+            finalized.block_root.as_slice(),
+            header.clone().hash_tree_root().unwrap().as_ref()
+        );
+
+        // The same header is only sent once.
+        client.broadcast_finalized().await;
+        assert!(finalized_recv.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_cancelled() {
+        let mut client = get_client(false, true).await;
+        client.config.advance.interval_ms = Some(60_000);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        // A cancelled loop stops without waiting for the next advance.
+        client.run(&cancel).await;
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let mut client = get_client(false, true).await;
+
+        let health = client.health();
+        assert_eq!(
+            health.finalized_slot,
+            client.store.finalized_header.slot.as_u64()
+        );
+        assert!(health.finalized_lag >= health.optimistic_lag);
+        assert!(health.last_success.is_some());
+        assert!(health.last_error.is_none());
+        // The headers of the test data are long past.
+        assert!(!health.synced);
+
+        client.set_rpc_client(MockRpc::new());
+        assert!(client.advance().await.is_err());
+
+        let health = client.health();
+        assert!(health.last_success.is_some());
+        assert!(health.last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_light_client_data_recorded() {
+        let client = get_client(false, true).await;
+        let data = client.light_client_data();
+
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        let bootstrap = data.bootstrap(&checkpoint).unwrap();
+        assert_eq!(
+            bootstrap.current_sync_committee.aggregate_pubkey,
+            client.store.current_sync_committee.aggregate_pubkey
+        );
+        assert!(data.bootstrap(&[0u8; 32]).is_none());
+
+        let finality_update = data.finality_update().unwrap();
+        client.verify_finality_update(&finality_update).unwrap();
+        let optimistic_update = data.optimistic_update().unwrap();
+        client.verify_optimistic_update(&optimistic_update).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_head_accessors() {
+        let mut client = get_client(false, true).await;
+
+        let update = client.rpc.get_finality_update().await.unwrap();
+        client.verify_finality_update(&update).unwrap();
+        client.apply_finality_update(&update);
+
+        let finalized = client.get_finalized_header();
+        let safe = client.get_safe_header();
+        let optimistic = client.get_optimistic_header();
+
+        assert_eq!(finalized.slot, update.finalized_header.slot);
+        assert!(finalized.slot <= safe.slot);
+        assert!(safe.slot <= optimistic.slot);
+        assert_eq!(optimistic.slot, update.attested_header.slot);
+    }
+
+    #[tokio::test]
+    async fn test_verified_header_cache() {
+        let client = get_client(false, true).await;
+
+        let finalized_header = client.store.finalized_header.clone();
+        let verified_header = client
+            .get_verified_header(finalized_header.slot.as_u64())
+            .unwrap();
+        assert_eq!(
+            verified_header.block_root.as_slice(),
+            finalized_header.clone().hash_tree_root().unwrap().as_ref()
+        );
+
+        let by_root = client
+            .get_verified_header_by_root(verified_header.block_root.as_slice())
+            .unwrap();
+        assert_eq!(by_root.header.slot, finalized_header.slot);
+
+        // The block number is only known once the payload of the header is verified.
+        assert!(client.get_verified_header_by_block_number(0).is_none());
+        assert!(client.get_verified_block_by_number(0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_bcs_roundtrip() {
+        let checkpoint =
+            hex::decode("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275")
+                .unwrap();
+        let rpc = MockRpc::new().with_testdata("testdata/");
+        let bootstrap = rpc.get_bootstrap(&checkpoint).await.unwrap();
+
+        let bytes = bootstrap.serialize_to_bytes().unwrap();
+        let mut decoded = Bootstrap::deserialize_from_bytes(&bytes).unwrap();
+
+        let client = ConsensusStateManager::<MockRpc>::new_from_checkpoint_and_bootstrap(
+            checkpoint,
+            Network::MAINNET,
+            "testdata/".to_string(),
+            &mut decoded,
+        )
+        .unwrap();
+        assert_eq!(client.store.finalized_header.slot, bootstrap.header.slot);
+    }
+
+    #[tokio::test]
+    async fn test_updates_response_roundtrip() {
+        let mut client = get_client(false, false).await;
+        let updates = client.get_updates_since_finalized().await.unwrap();
+
+        let bytes = UpdatesResponse::from(&updates)
+            .serialize_to_bytes()
+            .unwrap();
+        assert_eq!(bytes[0], ENCODING_VERSION);
+
+        let decoded: AggregateUpdates = UpdatesResponse::deserialize_from_bytes(&bytes)
+            .unwrap()
+            .into();
+        assert_eq!(decoded.updates.len(), updates.updates.len());
+        client.verify_and_apply_initial_updates(&decoded).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_updates_response_compressed() {
+        let mut client = get_client(false, false).await;
+        let updates = client.get_updates_since_finalized().await.unwrap();
+        let response = UpdatesResponse::from(&updates);
+        let uncompressed = response.serialize_to_bytes().unwrap();
+
+        let bytes = response
+            .serialize_to_bytes_compressed(Compression::Snappy)
+            .unwrap();
+        assert_eq!(bytes[0], ENCODING_VERSION | COMPRESSED_FLAG);
+        assert_eq!(bytes[1], Compression::Snappy.id());
+        assert!(bytes.len() < uncompressed.len());
+
+        let decoded: AggregateUpdates = UpdatesResponse::deserialize_from_bytes(&bytes)
+            .unwrap()
+            .into();
+        client.verify_and_apply_initial_updates(&decoded).unwrap();
+
+        let mut bytes = bytes;
+        bytes[1] = 7;
+        let err = UpdatesResponse::deserialize_from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::UnsupportedCompression(7).to_string()
+        );
+
+        let err = UpdatesResponse::deserialize_from_bytes(&[ENCODING_VERSION | COMPRESSED_FLAG])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::MissingCompression.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_state_diff() {
+        let mut client = get_client(false, false).await;
+        let old = client.clone();
+        let updates = client.get_updates_since_finalized().await.unwrap();
+        client.verify_and_apply_initial_updates(&updates).unwrap();
+
+        let diff = ConsensusStateManager::diff(&old, &client).unwrap();
+        assert_eq!(
+            diff.new_finalized_header.slot,
+            client.store.finalized_header.slot
+        );
+        assert_eq!(
+            diff.advanced_periods.len() as u64,
+            calc_sync_period(client.store.finalized_header.slot.as_u64())
+                - calc_sync_period(old.store.finalized_header.slot.as_u64())
+        );
+        assert!(diff.execution.is_none());
+
+        if diff.slots_advanced() > 0 {
+            assert!(ConsensusStateManager::diff(&client, &old).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_updates_response_delta() {
+        let mut client = get_client(false, false).await;
+        let updates = client.get_updates_since_finalized().await.unwrap();
+        let response = UpdatesResponse::from(&updates);
+        let fresh = client.clone();
+
+        client.verify_and_apply_initial_updates(&updates).unwrap();
+        let known = client.known_sync_committee_roots().unwrap();
+
+        // The receiver knows the next committee once it applied the updates.
+        let delta = response.to_delta(&known).unwrap();
+        assert!(matches!(
+            delta.updates[0].next_sync_committee,
+            SyncCommitteeRef::Root(_)
+        ));
+        let bytes = delta.serialize_to_bytes().unwrap();
+        assert!(bytes.len() < response.serialize_to_bytes().unwrap().len());
+
+        let delta = DeltaUpdatesResponse::deserialize_from_bytes(&bytes).unwrap();
+        let resolved = client.resolve_delta_updates(delta.clone()).unwrap();
+        assert_eq!(
+            resolved.updates[0].next_sync_committee.aggregate_pubkey,
+            updates.updates[0].next_sync_committee.aggregate_pubkey
+        );
+
+        // A receiver that was only bootstrapped does not know it.
+        let err = fresh.resolve_delta_updates(delta).unwrap_err();
+        assert!(err.to_string().starts_with("unknown sync committee"));
+
+        // Without known committees, the first occurrence of a committee is sent in full.
+        let delta = response.to_delta(&[]).unwrap();
+        assert!(matches!(
+            delta.updates[0].next_sync_committee,
+            SyncCommitteeRef::Full(_)
+        ));
+        let resolved: AggregateUpdates = delta.resolve(&[]).unwrap().into();
+        fresh
+            .clone()
+            .verify_and_apply_initial_updates(&resolved)
+            .unwrap();
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_updates_response_zstd() {
+        let mut client = get_client(false, false).await;
+        let updates = client.get_updates_since_finalized().await.unwrap();
+
+        let bytes = UpdatesResponse::from(&updates)
+            .serialize_to_bytes_compressed(Compression::Zstd)
+            .unwrap();
+        let decoded: AggregateUpdates = UpdatesResponse::deserialize_from_bytes(&bytes)
+            .unwrap()
+            .into();
+        client.verify_and_apply_initial_updates(&decoded).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_updates_response_unknown_version() {
+        let mut client = get_client(false, false).await;
+        let updates = client.get_updates_since_finalized().await.unwrap();
+
+        let mut bytes = UpdatesResponse::from(&updates)
+            .serialize_to_bytes()
+            .unwrap();
+        bytes[0] = ENCODING_VERSION + 1;
+
+        let err = UpdatesResponse::deserialize_from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::UnsupportedEncodingVersion(ENCODING_VERSION + 1).to_string()
+        );
+
+        let err = UpdatesResponse::deserialize_from_bytes(&[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::MissingEncodingVersion.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_state_digest() {
+        let client = get_client(false, true).await;
+        let other = get_client(false, true).await;
+        assert_eq!(
+            client.state_digest().unwrap(),
+            other.state_digest().unwrap()
+        );
+
+        let bootstrapped = get_client(false, false).await;
+        assert_ne!(
+            client.state_digest().unwrap(),
+            bootstrapped.state_digest().unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_verify_update() {
         let client = get_client(false, false).await;
@@ -1143,7 +3352,7 @@ mod tests {
             .unwrap();
 
         let mut update = updates[0].clone();
-        update.next_sync_committee.pubkeys[0] = BLSPubKey::default();
+        Arc::make_mut(&mut update.next_sync_committee).pubkeys[0] = BLSPubKey::default();
 
         let err = client.verify_update(&update).err().unwrap();
         assert_eq!(
@@ -1198,7 +3407,15 @@ mod tests {
 
         let update = client.rpc.get_finality_update().await.unwrap();
 
-        client.verify_finality_update(&update).unwrap();
+        let report = client.verify_finality_update(&update).unwrap();
+        assert!(report.has_finality_proof);
+        assert!(!report.has_next_sync_committee_proof);
+        assert_eq!(report.attested_slot, update.attested_header.slot.as_u64());
+        assert_eq!(
+            report.finalized_slot,
+            Some(update.finalized_header.slot.as_u64())
+        );
+        assert!(report.participation_ratio() > 0.0 && report.participation_ratio() <= 1.0);
     }
 
     #[tokio::test]
@@ -1229,6 +3446,230 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_verify_finality_memoized() {
+        let client = get_client(false, true).await;
+
+        let mut update = client.rpc.get_finality_update().await.unwrap();
+        client.verify_finality_update(&update).unwrap();
+        client.verify_finality_update(&update).unwrap();
+        assert_eq!(client.update_cache.len(), 1);
+
+        // An invalid signature of the same header is not covered by the cached one.
+        update.sync_aggregate.sync_committee_signature = SignatureBytes::default();
+        assert!(client.verify_finality_update(&update).is_err());
+        assert_eq!(client.update_cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_finality_conflict() {
+        let mut client = get_client(false, true).await;
+        let (conflict_send, mut conflict_recv) = channel(1);
+        client.set_conflict_sender(conflict_send);
+
+        let update = client.rpc.get_finality_update().await.unwrap();
+        let mut existing = update.finalized_header.clone();
+        existing.proposer_index = (existing.proposer_index.as_u64() + 1).into();
+        client.header_cache.insert(&existing).unwrap();
+
+        let err = client.verify_finality_update(&update).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::ConflictingFinality(existing.slot.as_u64()).to_string()
+        );
+
+        let conflict = conflict_recv.try_recv().unwrap();
+        assert_eq!(conflict.slot, existing.slot.as_u64());
+        assert_eq!(
+            conflict.existing_root.as_slice(),
+            existing.hash_tree_root().unwrap().as_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_stale_checkpoint() {
+        let mut client = get_client(false, false).await;
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        let (stale_checkpoint_send, mut stale_checkpoint_recv) = channel(1);
+        client.set_stale_checkpoint_sender(stale_checkpoint_send);
+
+        // The checkpoint of the test data is far older than a day.
+        client.config.stale_checkpoint_age = Some(24 * 60 * 60);
+        let err = client.sync(&checkpoint).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::StaleCheckpoint(..))
+        ));
+
+        let stale = stale_checkpoint_recv.try_recv().unwrap();
+        assert_eq!(stale.checkpoint, checkpoint);
+        assert_eq!(stale.slot, client.store.finalized_header.slot.as_u64());
+        assert!(stale.age > 24 * 60 * 60);
+
+        client.config.stale_checkpoint_age = Some(u64::MAX);
+        client.check_stale_checkpoint(&checkpoint).unwrap();
+        client.config.stale_checkpoint_age = None;
+        client.check_stale_checkpoint(&checkpoint).unwrap();
+        assert!(stale_checkpoint_recv.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_health_warnings() {
+        let mut client = get_client(false, false).await;
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        let (sync_health_send, mut sync_health_recv) = channel(4);
+        client.set_sync_health_sender(sync_health_send);
+        client.config.sync_health.min_participation = 1.0;
+        client.sync(&checkpoint).await.unwrap();
+
+        // The finalized header of the test data is far behind the wall clock.
+        let participation = client.health().participation.unwrap();
+        assert!(participation.updates >= 2);
+        assert!(participation.mean < 1.0);
+        assert!(matches!(
+            sync_health_recv.try_recv().unwrap(),
+            SyncHealthWarning::LowParticipation { threshold, .. } if threshold == 1.0
+        ));
+        assert!(matches!(
+            sync_health_recv.try_recv().unwrap(),
+            SyncHealthWarning::FinalityLag { lag, .. } if lag == client.health().finalized_lag
+        ));
+
+        // The warnings are not raised again while the thresholds are crossed.
+        client.check_sync_health();
+        assert!(sync_health_recv.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let mut client = get_client(false, true).await;
+        let finalized = client.get_finalized_header();
+
+        let mut config = client.config.clone();
+        config.consensus_rpc = "testdata/".to_string();
+        config.fallback = Some("https://sync-mainnet.beaconcha.in".to_string());
+        config.sync_concurrency = Some(1);
+        client.reload(&config).unwrap();
+
+        assert_eq!("testdata/", client.config.consensus_rpc);
+        assert_eq!(config.fallback, client.config.fallback);
+        // Only the RPC and fallback settings are reloaded.
+        assert_eq!(None, client.config.sync_concurrency);
+        // The verified state is kept, and the new RPC is used.
+        assert_eq!(finalized.slot, client.get_finalized_header().slot);
+        client.rpc.get_finality_update().await.unwrap();
+
+        let (reload_send, reload_recv) = watch::channel(client.config.clone());
+        client.set_reload_receiver(reload_recv);
+        client.reload_pending();
+        config.fallback = None;
+        reload_send.send_replace(config.clone());
+        client.reload_pending();
+        assert_eq!(None, client.config.fallback);
+
+        config.chain.chain_id = 5;
+        let err = client.reload(&config).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::ReloadChainMismatch(5, 1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_signed_state_snapshot() {
+        let client = get_client(false, true).await;
+        let secret_key = [1; 32];
+        let public_key = snapshot::public_key(&secret_key).unwrap();
+        let snapshot = client.export_signed_state(&secret_key).unwrap();
+        let snapshot =
+            SignedStateSnapshot::deserialize_from_bytes(&snapshot.serialize_to_bytes().unwrap())
+                .unwrap();
+
+        let mut other = get_client(false, false).await;
+        other.import_signed_state(&snapshot, &public_key).unwrap();
+        assert_eq!(
+            client.state_digest().unwrap(),
+            other.state_digest().unwrap()
+        );
+        assert_eq!(client.last_checkpoint, other.last_checkpoint);
+
+        // The snapshot must be signed by the key of the operator.
+        let other_key = snapshot::public_key(&[2; 32]).unwrap();
+        let err = other
+            .import_signed_state(&snapshot, &other_key)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::InvalidSnapshotSignature)
+        ));
+        let mut tampered = snapshot.clone();
+        *tampered.state.last_mut().unwrap() ^= 1;
+        assert!(other.import_signed_state(&tampered, &public_key).is_err());
+
+        // An older state is not imported over a newer one.
+        let older = get_client(false, false)
+            .await
+            .export_signed_state(&secret_key)
+            .unwrap();
+        let err = other.import_signed_state(&older, &public_key).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::StateRegression(..))
+        ));
+
+        let mut testnet = get_client(false, false).await;
+        testnet.config.chain.chain_id = 5;
+        let err = testnet
+            .import_signed_state(&snapshot, &public_key)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::SnapshotChainMismatch(1, 5))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_state_sender() {
+        let mut client = get_client(false, false).await;
+        let (state_send, mut state_recv) = watch::channel(None);
+        client.set_state_sender(state_send);
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        client.sync(&checkpoint).await.unwrap();
+
+        assert!(state_recv.has_changed().unwrap());
+        let state = state_recv.borrow_and_update().clone().unwrap();
+        let mut restored = get_client(false, false).await;
+        restored.import_state(&state).unwrap();
+        assert_eq!(
+            client.state_digest().unwrap(),
+            restored.state_digest().unwrap()
+        );
+
+        // The state is only sent again once a new header is finalized.
+        client.broadcast_finalized().await;
+        assert!(!state_recv.has_changed().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prune() {
+        let mut client = get_client(false, true).await;
+        let slot = client.get_finalized_header().slot.as_u64();
+        let period = calc_sync_period(slot);
+        assert!(client.get_verified_header(slot).is_some());
+
+        client.prune();
+        assert!(client.get_verified_header(slot).is_some());
+
+        client.config.retention.update_periods = Some(1);
+        client.prune();
+        assert!(client.light_client_data.updates(0, period).is_empty());
+
+        // The headers of the test data are far older than a day.
+        client.config.retention.header_days = Some(1);
+        client.prune();
+        assert!(client.get_verified_header(slot).is_none());
+    }
+
     #[tokio::test]
     async fn test_verify_optimistic() {
         let client = get_client(false, true).await;
@@ -1251,6 +3692,62 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_verify_optimistic_min_participation() {
+        let mut client = get_client(false, true).await;
+        let update = client.rpc.get_optimistic_update().await.unwrap();
+
+        // 510 of the 512 members of the committee signed the update of the test data.
+        client.config.min_optimistic_participation = Some(1.0);
+        let err = client.verify_optimistic_update(&update).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::ParticipationBelowMinimum(510, 512))
+        ));
+
+        client.config.min_optimistic_participation = Some(0.99);
+        client.verify_optimistic_update(&update).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_strict_finality() {
+        let mut client = get_client(false, false).await;
+        let checkpoint = client.last_checkpoint.clone().unwrap();
+        client.config.strict_finality = true;
+        client.sync(&checkpoint).await.unwrap();
+
+        let finalized_slot = client.get_finalized_header().slot;
+        assert_eq!(finalized_slot, client.get_optimistic_header().slot);
+        assert_eq!(finalized_slot, client.get_safe_header().slot);
+        assert!(client.store.best_valid_update.is_some());
+        // No optimistic update is served ahead of the finalized header either.
+        assert!(client.light_client_data().optimistic_update().is_none());
+
+        // Forced updates are not finality-proven either.
+        client.config.force_update_timeout = Some(0);
+        assert!(!client.process_force_update());
+        assert_eq!(finalized_slot, client.get_finalized_header().slot);
+    }
+
+    #[tokio::test]
+    async fn test_force_update() {
+        let mut client = get_client(false, true).await;
+        assert!(!client.process_force_update());
+
+        let best_valid_slot = client
+            .store
+            .best_valid_update
+            .as_ref()
+            .unwrap()
+            .attested_header
+            .slot;
+
+        client.config.force_update_timeout = Some(UPDATE_TIMEOUT);
+        assert!(client.process_force_update());
+        assert_eq!(client.store.finalized_header.slot, best_valid_slot);
+        assert!(client.store.best_valid_update.is_none());
+    }
+
     #[tokio::test]
     #[should_panic]
     async fn test_verify_checkpoint_age_invalid() {
@@ -1,4 +1,30 @@
 // Consensus constants
 
 // https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/light-client/p2p-interface.md#configuration
-pub const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u8 = 128;
+pub const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u64 = 128;
+
+// Age of the optimistic header, in seconds, above which the client is reported as not synced.
+// Matches the head age above which the client rejects requests for the latest block.
+pub const MAX_SYNCED_HEAD_AGE_SECS: u64 = 60;
+
+// Number of periods requested at once by each of the concurrent requests of a parallel sync.
+pub const PARALLEL_SYNC_PERIODS_PER_REQUEST: u64 = 8;
+
+// Number of blocks requested at once while backfilling the headers between two finalized headers.
+pub const BACKFILL_CONCURRENCY: usize = 16;
+
+// Maximum number of slots between the two finalized headers of a backfill, one sync committee
+// period, so that a single call cannot flood the consensus RPC.
+pub const MAX_BACKFILL_SLOTS: u64 = 8192;
+
+// https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/beacon-chain.md#sync-committee
+pub use consensus_core::sync::SYNC_COMMITTEE_SIZE;
+
+// https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/light-client/sync-protocol.md#constants
+// `SLOTS_PER_EPOCH * EPOCHS_PER_SYNC_COMMITTEE_PERIOD`, the recommended value for
+// `Config::force_update_timeout`.
+pub const UPDATE_TIMEOUT: u64 = 8192;
+
+// https://github.com/ethereum/consensus-specs/blob/dev/specs/deneb/polynomial-commitments.md#blob
+// `FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT`
+pub const BYTES_PER_BLOB: usize = 131072;
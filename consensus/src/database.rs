@@ -3,10 +3,30 @@ use std::{
     fs,
     io::{Read, Write},
     path::PathBuf,
+    time::Duration,
 };
 
-use config::Config;
+use config::{Config, RetentionConfig};
 use eyre::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use openssl::{
+    rand::rand_bytes,
+    symm::{decrypt_aead, encrypt_aead, Cipher},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use sha2::{Digest, Sha256};
+
+use crate::types::{conflict::FinalityConflict, FinalizedHeader, SyncCommittee};
+
+/// A database in memory, see [`memory::MemoryDB`].
+#[cfg(not(target_arch = "wasm32"))]
+pub mod memory;
+/// A database in PostgreSQL, see [`postgres::PostgresDB`].
+#[cfg(all(feature = "postgres", not(target_arch = "wasm32")))]
+pub mod postgres;
+/// An embedded database, see [`sled::SledDB`].
+#[cfg(all(feature = "sled", not(target_arch = "wasm32")))]
+pub mod sled;
 
 pub trait Database: Clone + Sync + Send + 'static {
     fn new(config: &Config) -> Result<Self>
@@ -15,13 +35,111 @@ pub trait Database: Clone + Sync + Send + 'static {
 
     fn save_checkpoint(&self, checkpoint: &[u8]) -> Result<()>;
     fn load_checkpoint(&self) -> Result<Vec<u8>>;
+
+    /// Persists the evidence of conflicting finalized headers. Databases that cannot keep it
+    /// only rely on the alert of the consensus client.
+    fn save_finality_conflict(&self, _conflict: &FinalityConflict) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the database keeps the verified headers and sync committees, so that the
+    /// consensus client only sends them to the databases that do.
+    fn keeps_history(&self) -> bool {
+        false
+    }
+
+    /// Persists a newly verified finalized header, see [`Self::keeps_history`].
+    fn save_finalized_header(&self, _header: &FinalizedHeader) -> Result<()> {
+        Ok(())
+    }
+
+    /// Persists the verified sync committee of `period`, see [`Self::keeps_history`].
+    fn save_sync_committee(&self, _period: u64, _committee: &SyncCommittee) -> Result<()> {
+        Ok(())
+    }
+
+    /// Drops the contents that are older than `retention`, and returns how many entries were
+    /// dropped. The consensus client calls it on the compaction interval of `retention`.
+    fn compact(&self, _retention: &RetentionConfig) -> Result<usize> {
+        Ok(0)
+    }
 }
 
+/// The length of the nonces of AES-256-GCM.
+#[cfg(not(target_arch = "wasm32"))]
+const NONCE_LEN: usize = 12;
+/// The length of the authentication tags of AES-256-GCM.
+#[cfg(not(target_arch = "wasm32"))]
+const TAG_LEN: usize = 16;
+
+/// A database of files in the data dir. With [`Config::encrypt_database`], every file is encrypted
+/// with AES-256-GCM as its nonce, ciphertext and tag, and authenticated along with its name, so
+/// that its contents cannot be read, altered or swapped with another file. Since the names of
+/// the conflict files would tell their slots, they are hashed too. A file that was saved before
+/// the encryption was enabled is not loaded.
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Clone)]
 pub struct FileDB {
     data_dir: PathBuf,
     default_checkpoint: Vec<u8>,
+    key: Option<[u8; 32]>,
+}
+
+/// Encrypts `contents` with AES-256-GCM under `key`, authenticated along with `name`. The
+/// contents are returned as they are without a key.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn seal(key: Option<&[u8; 32]>, name: &str, contents: &[u8]) -> Result<Vec<u8>> {
+    let Some(key) = key else {
+        return Ok(contents.to_vec());
+    };
+
+    let mut nonce = [0; NONCE_LEN];
+    rand_bytes(&mut nonce)?;
+    let mut tag = [0; TAG_LEN];
+    let ciphertext = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(&nonce),
+        name.as_bytes(),
+        contents,
+        &mut tag,
+    )?;
+
+    Ok([&nonce[..], &ciphertext, &tag].concat())
+}
+
+/// Decrypts the contents sealed by [`seal`] with the same key and name.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn unseal(key: Option<&[u8; 32]>, name: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    let Some(key) = key else {
+        return Ok(sealed.to_vec());
+    };
+
+    if sealed.len() < NONCE_LEN + TAG_LEN {
+        eyre::bail!("encrypted {name} is truncated");
+    }
+    let (nonce, rest) = sealed.split_at(NONCE_LEN);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+    decrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(nonce),
+        name.as_bytes(),
+        ciphertext,
+        tag,
+    )
+    .map_err(|_| eyre::eyre!("cannot decrypt {name}, the database key may be wrong"))
+}
+
+/// The name under which `name` is stored, which is hashed when the database is encrypted so that
+/// it does not tell a slot.
+#[cfg(not(target_arch = "wasm32"))]
+fn hidden_name(key: Option<&[u8; 32]>, name: &str) -> String {
+    match key {
+        Some(_) => hex::encode(Sha256::digest(name.as_bytes())),
+        None => name.to_string(),
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -31,6 +149,7 @@ impl Database for FileDB {
             return Ok(FileDB {
                 data_dir: data_dir.to_path_buf(),
                 default_checkpoint: config.default_checkpoint.clone(),
+                key: config.encryption_key()?,
             });
         }
 
@@ -46,7 +165,7 @@ impl Database for FileDB {
             .truncate(true)
             .open(self.data_dir.join("checkpoint"))?;
 
-        f.write_all(checkpoint)?;
+        f.write_all(&seal(self.key.as_ref(), "checkpoint", checkpoint)?)?;
 
         Ok(())
     }
@@ -58,6 +177,9 @@ impl Database for FileDB {
             .read(true)
             .open(self.data_dir.join("checkpoint"))
             .map(|mut f| f.read_to_end(&mut buf));
+        if res.is_ok() {
+            buf = unseal(self.key.as_ref(), "checkpoint", &buf)?;
+        }
 
         if buf.len() == 32 && res.is_ok() {
             Ok(buf)
@@ -65,6 +187,54 @@ impl Database for FileDB {
             Ok(self.default_checkpoint.clone())
         }
     }
+
+    /// Writes the evidence to `conflicts/<slot>_<conflicting root>.json` in the data dir.
+    fn save_finality_conflict(&self, conflict: &FinalityConflict) -> Result<()> {
+        let dir = self.data_dir.join("conflicts");
+        fs::create_dir_all(&dir)?;
+
+        let name = format!(
+            "{}_0x{}.json",
+            conflict.slot,
+            hex::encode(conflict.conflicting_root.as_slice())
+        );
+        let contents = seal(
+            self.key.as_ref(),
+            &name,
+            &serde_json::to_vec_pretty(conflict)?,
+        )?;
+        fs::write(dir.join(hidden_name(self.key.as_ref(), &name)), contents)?;
+
+        Ok(())
+    }
+
+    /// Removes the conflict evidence that was written more than the `header_days` of
+    /// `retention` ago.
+    fn compact(&self, retention: &RetentionConfig) -> Result<usize> {
+        let Some(days) = retention.header_days else {
+            return Ok(0);
+        };
+        let dir = self.data_dir.join("conflicts");
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+
+        let max_age = Duration::from_secs(days.saturating_mul(24 * 60 * 60));
+        let mut removed = 0;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let age = fs::metadata(&path)?
+                .modified()?
+                .elapsed()
+                .unwrap_or_default();
+            if path.is_file() && age >= max_age {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
 }
 
 #[derive(Clone)]
@@ -90,3 +260,61 @@ impl Database for ConfigDB {
         Ok(())
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::env;
+
+    use config::DatabaseKey;
+
+    use super::*;
+
+    fn config(data_dir: &str, key: Option<[u8; 32]>) -> Config {
+        Config {
+            data_dir: Some(env::temp_dir().join(data_dir)),
+            default_checkpoint: vec![0; 32],
+            encrypt_database: key.is_some(),
+            database_key: key.map(|key| DatabaseKey::new(move || Ok(key))),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_encrypted_file_db() {
+        let data_dir = format!("helios-encrypted-db-{}", std::process::id());
+        let db = FileDB::new(&config(&data_dir, Some([1; 32]))).unwrap();
+        db.save_checkpoint(&[2; 32]).unwrap();
+        assert_eq!(vec![2; 32], db.load_checkpoint().unwrap());
+
+        let contents = fs::read(db.data_dir.join("checkpoint")).unwrap();
+        assert_eq!(NONCE_LEN + 32 + TAG_LEN, contents.len());
+        assert!(!contents.windows(32).any(|window| window == [2; 32]));
+
+        let wrong_key = FileDB::new(&config(&data_dir, Some([3; 32]))).unwrap();
+        assert!(wrong_key.load_checkpoint().is_err());
+        let plain = FileDB::new(&config(&data_dir, None)).unwrap();
+        assert_eq!(vec![0; 32], plain.load_checkpoint().unwrap());
+
+        fs::remove_dir_all(&db.data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_compact_file_db() {
+        let data_dir = format!("helios-compacted-db-{}", std::process::id());
+        let db = FileDB::new(&config(&data_dir, None)).unwrap();
+        let conflicts = db.data_dir.join("conflicts");
+        fs::create_dir_all(&conflicts).unwrap();
+        fs::write(conflicts.join("1_0x00.json"), "{}").unwrap();
+
+        let retention = |header_days| RetentionConfig {
+            header_days,
+            ..Default::default()
+        };
+        assert_eq!(0, db.compact(&retention(None)).unwrap());
+        assert_eq!(0, db.compact(&retention(Some(1))).unwrap());
+        assert_eq!(1, db.compact(&retention(Some(0))).unwrap());
+        assert_eq!(0, fs::read_dir(&conflicts).unwrap().count());
+
+        fs::remove_dir_all(&db.data_dir).unwrap();
+    }
+}
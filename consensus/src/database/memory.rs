@@ -0,0 +1,213 @@
+//! A [`Database`] in memory, for the ephemeral environments such as CI jobs and serverless
+//! functions, which have no disk that outlives them but still resume from their previous run.
+//!
+//! The contents are exported to a single blob with [`MemoryDB::export`], e.g. once the client was
+//! shut down, which saves its last checkpoint, and are given to the next run with
+//! [`Config::database_snapshot`]. The blob is the BCS encoding of the contents, with the version
+//! byte of the other encodings, see [`encode_versioned`].
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use config::{Config, RetentionConfig};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+use crate::{
+    errors::ConsensusError,
+    types::{
+        conflict::FinalityConflict,
+        encoding::{decode_versioned, encode_versioned},
+        FinalizedHeader, SyncCommittee,
+    },
+};
+
+/// A database in memory. Clones share the same contents, so that a clone kept by the caller
+/// exports the checkpoints saved by the consensus client.
+#[derive(Clone)]
+pub struct MemoryDB {
+    contents: Arc<RwLock<MemoryContents>>,
+    default_checkpoint: Vec<u8>,
+}
+
+/// The contents of a [`MemoryDB`], as exported. Every entry of the history has the time it was
+/// saved at, in seconds since the epoch, for the compaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MemoryContents {
+    chain_id: u64,
+    checkpoint: Option<Vec<u8>>,
+    finalized_headers: BTreeMap<u64, (u64, FinalizedHeader)>,
+    sync_committees: BTreeMap<u64, (u64, SyncCommittee)>,
+    finality_conflicts: Vec<(u64, FinalityConflict)>,
+}
+
+impl MemoryDB {
+    /// Exports the contents, to give them to the next run with [`Config::database_snapshot`].
+    pub fn export(&self) -> Result<Vec<u8>> {
+        encode_versioned(&*self.contents.read().unwrap())
+    }
+
+    /// Creates a database with the contents exported by [`Self::export`]. Fails if they are of
+    /// another chain than the one of `config`.
+    pub fn import(config: &Config, snapshot: &[u8]) -> Result<Self> {
+        let contents: MemoryContents = decode_versioned(snapshot)?;
+        if contents.chain_id != config.chain.chain_id {
+            return Err(ConsensusError::SnapshotChainMismatch(
+                contents.chain_id,
+                config.chain.chain_id,
+            )
+            .into());
+        }
+
+        Ok(Self {
+            contents: Arc::new(RwLock::new(contents)),
+            default_checkpoint: config.default_checkpoint.clone(),
+        })
+    }
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+impl Database for MemoryDB {
+    /// Starts from the [`Config::database_snapshot`], or else empty.
+    fn new(config: &Config) -> Result<Self> {
+        if let Some(snapshot) = &config.database_snapshot {
+            return Self::import(config, snapshot);
+        }
+
+        Ok(Self {
+            contents: Arc::new(RwLock::new(MemoryContents {
+                chain_id: config.chain.chain_id,
+                ..Default::default()
+            })),
+            default_checkpoint: config.default_checkpoint.clone(),
+        })
+    }
+
+    fn save_checkpoint(&self, checkpoint: &[u8]) -> Result<()> {
+        self.contents.write().unwrap().checkpoint = Some(checkpoint.to_vec());
+        Ok(())
+    }
+
+    fn load_checkpoint(&self) -> Result<Vec<u8>> {
+        match &self.contents.read().unwrap().checkpoint {
+            Some(checkpoint) if checkpoint.len() == 32 => Ok(checkpoint.clone()),
+            _ => Ok(self.default_checkpoint.clone()),
+        }
+    }
+
+    fn save_finality_conflict(&self, conflict: &FinalityConflict) -> Result<()> {
+        let saved_at = now()?;
+        self.contents
+            .write()
+            .unwrap()
+            .finality_conflicts
+            .push((saved_at, conflict.clone()));
+        Ok(())
+    }
+
+    fn keeps_history(&self) -> bool {
+        true
+    }
+
+    fn save_finalized_header(&self, header: &FinalizedHeader) -> Result<()> {
+        let saved_at = now()?;
+        self.contents
+            .write()
+            .unwrap()
+            .finalized_headers
+            .entry(header.slot)
+            .or_insert_with(|| (saved_at, header.clone()));
+        Ok(())
+    }
+
+    fn save_sync_committee(&self, period: u64, committee: &SyncCommittee) -> Result<()> {
+        let saved_at = now()?;
+        self.contents
+            .write()
+            .unwrap()
+            .sync_committees
+            .entry(period)
+            .or_insert_with(|| (saved_at, committee.clone()));
+        Ok(())
+    }
+
+    /// Drops the history that was saved more than the `header_days` of `retention` ago.
+    fn compact(&self, retention: &RetentionConfig) -> Result<usize> {
+        let Some(days) = retention.header_days else {
+            return Ok(0);
+        };
+        let cutoff = now()?.saturating_sub(days.saturating_mul(24 * 60 * 60));
+
+        let mut contents = self.contents.write().unwrap();
+        let before = contents.finalized_headers.len()
+            + contents.sync_committees.len()
+            + contents.finality_conflicts.len();
+        contents
+            .finalized_headers
+            .retain(|_, (saved_at, _)| *saved_at > cutoff);
+        contents
+            .sync_committees
+            .retain(|_, (saved_at, _)| *saved_at > cutoff);
+        contents
+            .finality_conflicts
+            .retain(|(saved_at, _)| *saved_at > cutoff);
+        let after = contents.finalized_headers.len()
+            + contents.sync_committees.len()
+            + contents.finality_conflicts.len();
+
+        Ok(before - after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::networks;
+
+    use super::*;
+
+    fn header(slot: u64) -> FinalizedHeader {
+        FinalizedHeader {
+            slot,
+            block_root: Default::default(),
+            header: Default::default(),
+            execution_block_hash: None,
+            execution_state_root: None,
+        }
+    }
+
+    #[test]
+    fn test_memory_db_snapshot() {
+        let mut config = networks::mainnet().as_config();
+        let db = MemoryDB::new(&config).unwrap();
+        assert_eq!(config.default_checkpoint, db.load_checkpoint().unwrap());
+
+        // The clones share the contents.
+        db.clone().save_checkpoint(&[1; 32]).unwrap();
+        db.save_finalized_header(&header(1)).unwrap();
+        db.save_sync_committee(1, &SyncCommittee::default())
+            .unwrap();
+
+        config.database_snapshot = Some(db.export().unwrap());
+        let imported = MemoryDB::new(&config).unwrap();
+        assert_eq!(vec![1; 32], imported.load_checkpoint().unwrap());
+        assert_eq!(db.export().unwrap(), imported.export().unwrap());
+
+        let retention = |header_days| RetentionConfig {
+            header_days,
+            ..Default::default()
+        };
+        assert_eq!(0, imported.compact(&retention(Some(1))).unwrap());
+        assert_eq!(2, imported.compact(&retention(Some(0))).unwrap());
+        assert_eq!(vec![1; 32], imported.load_checkpoint().unwrap());
+
+        config.chain.chain_id = 5;
+        assert!(MemoryDB::new(&config).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! A [`Database`] in PostgreSQL, for the deployments that keep their state in a database server
+//! rather than in the data dir of every machine, so that it is backed up, replicated and queried
+//! with the tools of the server.
+//!
+//! Besides the checkpoints, the database keeps the history of the client: the finalized headers
+//! and the sync committees it verified, and the evidence of the finality conflicts it detected,
+//! as audit records. Every row has the chain id of its client, so that the clients of several
+//! chains share one database.
+//!
+//! The schema is created and upgraded by the [`MIGRATIONS`], which are applied in order when the
+//! database is opened.
+
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use ::postgres::{types::Json, Client, NoTls};
+use config::{Config, RetentionConfig};
+use eyre::{eyre, Result};
+
+use super::Database;
+use crate::types::{conflict::FinalityConflict, FinalizedHeader, SyncCommittee};
+
+/// The migrations of the schema, by version. A released migration is never edited, the schema is
+/// changed by a new one instead.
+pub const MIGRATIONS: &[(i32, &str)] =
+    &[(1, include_str!("../../migrations/postgres/0001_init.sql"))];
+
+type Job = Box<dyn FnOnce(&mut Client) + Send>;
+
+/// A database in the PostgreSQL server of [`Config::database_url`], to which it connects without
+/// TLS, so the server should be local or on a private network.
+///
+/// The blocking client of `postgres` runs a runtime of its own, which cannot be started or dropped
+/// within the runtime of the consensus client. The connection is thus owned by a thread of its
+/// own, which runs the statements of the clones one at a time.
+#[derive(Clone)]
+pub struct PostgresDB {
+    jobs: mpsc::Sender<Job>,
+    chain_id: i64,
+    default_checkpoint: Vec<u8>,
+}
+
+impl PostgresDB {
+    /// Runs `f` on the connection, and waits for its result.
+    fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Client) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let (result_send, result_recv) = mpsc::sync_channel(1);
+        self.jobs
+            .send(Box::new(move |client| {
+                _ = result_send.send(f(client));
+            }))
+            .map_err(|_| eyre!("postgres connection closed"))?;
+
+        result_recv
+            .recv()
+            .map_err(|_| eyre!("postgres connection closed"))?
+    }
+}
+
+impl Database for PostgresDB {
+    /// Connects to the server and applies the migrations that were not applied yet.
+    fn new(config: &Config) -> Result<Self> {
+        let Some(url) = config.database_url.clone() else {
+            eyre::bail!("database url not in config")
+        };
+        // The database is inspected with the tools of the server, so it is encrypted by the
+        // server too.
+        if config.encrypt_database {
+            eyre::bail!(
+                "the postgres database is not encrypted by helios, encrypt the server instead"
+            )
+        }
+
+        let (jobs, job_recv) = mpsc::channel::<Job>();
+        let (ready_send, ready_recv) = mpsc::sync_channel(1);
+        thread::Builder::new()
+            .name("helios-postgres".to_string())
+            .spawn(move || {
+                let res = Client::connect(&url, NoTls)
+                    .map_err(eyre::Report::from)
+                    .and_then(|mut client| migrate(&mut client).map(|()| client));
+                let mut client = match res {
+                    Ok(client) => {
+                        _ = ready_send.send(Ok(()));
+                        client
+                    }
+                    Err(err) => {
+                        _ = ready_send.send(Err(err));
+                        return;
+                    }
+                };
+
+                // Stops once every clone of the database was dropped.
+                for job in job_recv {
+                    job(&mut client);
+                }
+            })?;
+        ready_recv
+            .recv()
+            .map_err(|_| eyre!("postgres connection closed"))??;
+
+        Ok(Self {
+            jobs,
+            chain_id: i64::try_from(config.chain.chain_id)?,
+            default_checkpoint: config.default_checkpoint.clone(),
+        })
+    }
+
+    fn save_checkpoint(&self, checkpoint: &[u8]) -> Result<()> {
+        let chain_id = self.chain_id;
+        let checkpoint = checkpoint.to_vec();
+        self.run(move |client| {
+            client.execute(
+                "INSERT INTO checkpoints (chain_id, checkpoint) VALUES ($1, $2)",
+                &[&chain_id, &checkpoint],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Loads the latest checkpoint saved for the chain.
+    fn load_checkpoint(&self) -> Result<Vec<u8>> {
+        let chain_id = self.chain_id;
+        let checkpoint = self.run(move |client| {
+            let row = client.query_opt(
+                "SELECT checkpoint FROM checkpoints WHERE chain_id = $1 ORDER BY id DESC LIMIT 1",
+                &[&chain_id],
+            )?;
+            Ok(row.map(|row| row.get::<_, Vec<u8>>(0)))
+        })?;
+
+        match checkpoint {
+            Some(checkpoint) if checkpoint.len() == 32 => Ok(checkpoint),
+            _ => Ok(self.default_checkpoint.clone()),
+        }
+    }
+
+    fn save_finality_conflict(&self, conflict: &FinalityConflict) -> Result<()> {
+        let chain_id = self.chain_id;
+        let slot = i64::try_from(conflict.slot)?;
+        let existing_root = conflict.existing_root.to_vec();
+        let conflicting_root = conflict.conflicting_root.to_vec();
+        let evidence = serde_json::to_value(conflict)?;
+        self.run(move |client| {
+            client.execute(
+                "INSERT INTO finality_conflicts \
+                 (chain_id, slot, existing_root, conflicting_root, evidence) \
+                 VALUES ($1, $2, $3, $4, $5)",
+                &[
+                    &chain_id,
+                    &slot,
+                    &existing_root,
+                    &conflicting_root,
+                    &Json(evidence),
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn keeps_history(&self) -> bool {
+        true
+    }
+
+    /// Saves the header, unless one was already saved at its slot.
+    fn save_finalized_header(&self, header: &FinalizedHeader) -> Result<()> {
+        let chain_id = self.chain_id;
+        let slot = i64::try_from(header.slot)?;
+        let block_root = header.block_root.to_vec();
+        let json = serde_json::to_value(&header.header)?;
+        let execution_block_hash = header
+            .execution_block_hash
+            .as_ref()
+            .map(|hash| hash.to_vec());
+        let execution_state_root = header
+            .execution_state_root
+            .as_ref()
+            .map(|root| root.to_vec());
+        self.run(move |client| {
+            client.execute(
+                "INSERT INTO verified_headers \
+                 (chain_id, slot, block_root, header, execution_block_hash, execution_state_root) \
+                 VALUES ($1, $2, $3, $4, $5, $6) \
+                 ON CONFLICT (chain_id, slot) DO NOTHING",
+                &[
+                    &chain_id,
+                    &slot,
+                    &block_root,
+                    &Json(json),
+                    &execution_block_hash,
+                    &execution_state_root,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn save_sync_committee(&self, period: u64, committee: &SyncCommittee) -> Result<()> {
+        let chain_id = self.chain_id;
+        let period = i64::try_from(period)?;
+        let aggregate_pubkey = committee.aggregate_pubkey.to_vec();
+        let pubkeys = committee
+            .pubkeys
+            .iter()
+            .map(|pubkey| pubkey.to_vec())
+            .collect::<Vec<_>>();
+        self.run(move |client| {
+            client.execute(
+                "INSERT INTO sync_committees (chain_id, period, aggregate_pubkey, pubkeys) \
+                 VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (chain_id, period) DO NOTHING",
+                &[&chain_id, &period, &aggregate_pubkey, &pubkeys],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Deletes the headers, sync committees and conflict evidence of the chain that were saved
+    /// more than the `header_days` of `retention` ago, and the checkpoints but the latest one.
+    fn compact(&self, retention: &RetentionConfig) -> Result<usize> {
+        let Some(days) = retention.header_days else {
+            return Ok(0);
+        };
+        let chain_id = self.chain_id;
+        let cutoff = SystemTime::now()
+            .checked_sub(Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        self.run(move |client| {
+            let mut transaction = client.transaction()?;
+            let mut removed = 0;
+            for statement in [
+                "DELETE FROM verified_headers WHERE chain_id = $1 AND verified_at <= $2",
+                "DELETE FROM sync_committees WHERE chain_id = $1 AND verified_at <= $2",
+                "DELETE FROM finality_conflicts WHERE chain_id = $1 AND recorded_at <= $2",
+                "DELETE FROM checkpoints WHERE chain_id = $1 AND saved_at <= $2 \
+                 AND id < (SELECT max(id) FROM checkpoints WHERE chain_id = $1)",
+            ] {
+                removed += transaction.execute(statement, &[&chain_id, &cutoff])?;
+            }
+            transaction.commit()?;
+
+            Ok(usize::try_from(removed)?)
+        })
+    }
+}
+
+/// Applies the [`MIGRATIONS`] that are not in the `schema_migrations` table yet, each in a
+/// transaction of its own.
+fn migrate(client: &mut Client) -> Result<()> {
+    client.batch_execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )",
+    )?;
+
+    for (version, migration) in MIGRATIONS {
+        let mut transaction = client.transaction()?;
+        // The clients that start together do not apply the same migration twice.
+        transaction.batch_execute("LOCK TABLE schema_migrations IN EXCLUSIVE MODE")?;
+        let applied = transaction
+            .query_opt(
+                "SELECT 1 FROM schema_migrations WHERE version = $1",
+                &[version],
+            )?
+            .is_some();
+        if !applied {
+            transaction.batch_execute(migration)?;
+            transaction.execute(
+                "INSERT INTO schema_migrations (version) VALUES ($1)",
+                &[version],
+            )?;
+        }
+        transaction.commit()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use config::networks;
+
+    use super::*;
+
+    /// The URL of a server to run the ignored tests against, see the `postgres` job of the CI.
+    const TEST_DATABASE_URL_ENV: &str = "HELIOS_TEST_DATABASE_URL";
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions = MIGRATIONS
+            .iter()
+            .map(|(version, _)| *version)
+            .collect::<Vec<_>>();
+        assert_eq!((1..=MIGRATIONS.len() as i32).collect::<Vec<_>>(), versions);
+    }
+
+    #[test]
+    #[ignore = "needs a PostgreSQL server at HELIOS_TEST_DATABASE_URL"]
+    fn test_postgres_db() {
+        let url = std::env::var(TEST_DATABASE_URL_ENV)
+            .unwrap_or_else(|_| panic!("{TEST_DATABASE_URL_ENV} is not set"));
+        let mut config = networks::mainnet().as_config();
+        // A chain of its own, so that the test does not see the rows of another run.
+        config.chain.chain_id = u32::MAX as u64 + u64::from(std::process::id());
+        config.database_url = Some(url);
+
+        let db = PostgresDB::new(&config).unwrap();
+        // Opening the database again does not apply the migrations again.
+        PostgresDB::new(&config).unwrap();
+        assert_eq!(config.default_checkpoint, db.load_checkpoint().unwrap());
+
+        db.save_checkpoint(&[1; 32]).unwrap();
+        db.save_checkpoint(&[2; 32]).unwrap();
+        assert_eq!(vec![2; 32], db.load_checkpoint().unwrap());
+
+        let header = FinalizedHeader {
+            slot: 1,
+            block_root: Default::default(),
+            header: Default::default(),
+            execution_block_hash: None,
+            execution_state_root: None,
+        };
+        db.save_finalized_header(&header).unwrap();
+        db.save_finalized_header(&header).unwrap();
+        db.save_sync_committee(1, &SyncCommittee::default())
+            .unwrap();
+
+        let retention = RetentionConfig {
+            header_days: Some(0),
+            ..Default::default()
+        };
+        // The header, the committee and the older checkpoint.
+        assert_eq!(3, db.compact(&retention).unwrap());
+        assert_eq!(vec![2; 32], db.load_checkpoint().unwrap());
+    }
+}
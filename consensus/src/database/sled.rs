@@ -0,0 +1,238 @@
+//! A [`Database`] in an embedded sled database, for the single binary deployments that keep the
+//! history of the client without a database server or a C++ toolchain, since sled is pure Rust.
+//!
+//! Like the PostgreSQL database, it keeps the finalized headers and the sync committees verified
+//! by the client, and the evidence of the finality conflicts, each in a tree of its own. Every
+//! entry starts with the time it was saved at, so that the entries older than the retention are
+//! dropped on compaction.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ::sled::{Db, Tree};
+use config::{Config, RetentionConfig};
+use eyre::Result;
+
+use super::{hidden_name, seal, unseal, Database};
+use crate::types::{conflict::FinalityConflict, FinalizedHeader, SyncCommittee};
+
+/// The trees of the history, which compaction goes through.
+const HISTORY_TREES: [&str; 3] = ["verified_headers", "sync_committees", "finality_conflicts"];
+
+/// A sled database in the `sled` directory of the data dir. With [`Config::encrypt_database`],
+/// the entries are encrypted like the files of a [`FileDB`], and their keys are hashed.
+///
+/// [`FileDB`]: super::FileDB
+#[derive(Clone)]
+pub struct SledDB {
+    db: Db,
+    default_checkpoint: Vec<u8>,
+    key: Option<[u8; 32]>,
+}
+
+impl SledDB {
+    /// Saves `value` as the entry `name` of `tree`, unless the entry exists.
+    fn insert_new(&self, tree: &str, name: &str, value: &[u8]) -> Result<()> {
+        let tree = self.db.open_tree(tree)?;
+        let saved_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let entry = [
+            &saved_at.to_be_bytes()[..],
+            &seal(self.key.as_ref(), name, value)?,
+        ]
+        .concat();
+
+        // An entry that already exists was verified first, and is kept.
+        _ = tree.compare_and_swap(
+            hidden_name(self.key.as_ref(), name),
+            None as Option<&[u8]>,
+            Some(entry),
+        )?;
+
+        Ok(())
+    }
+
+    /// Removes the entries of `tree` that were saved at or before `cutoff`, in seconds since the
+    /// epoch.
+    fn remove_before(tree: &Tree, cutoff: u64) -> Result<usize> {
+        let mut removed = 0;
+        for entry in tree.iter() {
+            let (name, entry) = entry?;
+            let saved_at = entry
+                .get(..8)
+                .and_then(|saved_at| saved_at.try_into().ok())
+                .map(u64::from_be_bytes)
+                .unwrap_or_default();
+            if saved_at <= cutoff {
+                tree.remove(name)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+impl Database for SledDB {
+    fn new(config: &Config) -> Result<Self> {
+        let Some(data_dir) = &config.data_dir else {
+            eyre::bail!("data dir not in config")
+        };
+
+        Ok(Self {
+            db: ::sled::open(data_dir.join("sled"))?,
+            default_checkpoint: config.default_checkpoint.clone(),
+            key: config.encryption_key()?,
+        })
+    }
+
+    /// Saves the checkpoint, and flushes the database so that it is kept on a crash.
+    fn save_checkpoint(&self, checkpoint: &[u8]) -> Result<()> {
+        self.db.insert(
+            "checkpoint",
+            seal(self.key.as_ref(), "checkpoint", checkpoint)?,
+        )?;
+        self.db.flush()?;
+
+        Ok(())
+    }
+
+    fn load_checkpoint(&self) -> Result<Vec<u8>> {
+        let checkpoint = match self.db.get("checkpoint")? {
+            Some(sealed) => unseal(self.key.as_ref(), "checkpoint", &sealed)?,
+            None => Vec::new(),
+        };
+
+        if checkpoint.len() == 32 {
+            Ok(checkpoint)
+        } else {
+            Ok(self.default_checkpoint.clone())
+        }
+    }
+
+    fn save_finality_conflict(&self, conflict: &FinalityConflict) -> Result<()> {
+        let name = format!(
+            "{}_0x{}",
+            conflict.slot,
+            hex::encode(conflict.conflicting_root.as_slice())
+        );
+        self.insert_new("finality_conflicts", &name, &serde_json::to_vec(conflict)?)
+    }
+
+    fn keeps_history(&self) -> bool {
+        true
+    }
+
+    fn save_finalized_header(&self, header: &FinalizedHeader) -> Result<()> {
+        self.insert_new(
+            "verified_headers",
+            &header.slot.to_string(),
+            &serde_json::to_vec(header)?,
+        )
+    }
+
+    fn save_sync_committee(&self, period: u64, committee: &SyncCommittee) -> Result<()> {
+        self.insert_new(
+            "sync_committees",
+            &period.to_string(),
+            &serde_json::to_vec(committee)?,
+        )
+    }
+
+    /// Removes the history that was saved more than the `header_days` of `retention` ago.
+    fn compact(&self, retention: &RetentionConfig) -> Result<usize> {
+        let Some(days) = retention.header_days else {
+            return Ok(0);
+        };
+        let cutoff = SystemTime::now()
+            .checked_sub(Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+            .unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH)?
+            .as_secs();
+
+        let mut removed = 0;
+        for tree in HISTORY_TREES {
+            removed += Self::remove_before(&self.db.open_tree(tree)?, cutoff)?;
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use config::DatabaseKey;
+
+    use super::*;
+
+    fn config(data_dir: &str, key: Option<[u8; 32]>) -> Config {
+        Config {
+            data_dir: Some(env::temp_dir().join(data_dir)),
+            default_checkpoint: vec![0; 32],
+            encrypt_database: key.is_some(),
+            database_key: key.map(|key| DatabaseKey::new(move || Ok(key))),
+            ..Default::default()
+        }
+    }
+
+    fn header(slot: u64) -> FinalizedHeader {
+        FinalizedHeader {
+            slot,
+            block_root: Default::default(),
+            header: Default::default(),
+            execution_block_hash: None,
+            execution_state_root: None,
+        }
+    }
+
+    #[test]
+    fn test_sled_db() {
+        let data_dir = format!("helios-sled-db-{}", std::process::id());
+        let config = config(&data_dir, None);
+        let db = SledDB::new(&config).unwrap();
+        assert_eq!(vec![0; 32], db.load_checkpoint().unwrap());
+        db.save_checkpoint(&[1; 32]).unwrap();
+        assert_eq!(vec![1; 32], db.load_checkpoint().unwrap());
+
+        db.save_finalized_header(&header(1)).unwrap();
+        db.save_finalized_header(&header(1)).unwrap();
+        db.save_finalized_header(&header(2)).unwrap();
+        db.save_sync_committee(1, &SyncCommittee::default())
+            .unwrap();
+
+        let retention = |header_days| RetentionConfig {
+            header_days,
+            ..Default::default()
+        };
+        assert_eq!(0, db.compact(&retention(Some(1))).unwrap());
+        assert_eq!(3, db.compact(&retention(Some(0))).unwrap());
+        assert_eq!(vec![1; 32], db.load_checkpoint().unwrap());
+
+        drop(db);
+        fs::remove_dir_all(config.data_dir.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_sled_db() {
+        let data_dir = format!("helios-encrypted-sled-db-{}", std::process::id());
+        let config = config(&data_dir, Some([1; 32]));
+        let db = SledDB::new(&config).unwrap();
+        db.save_checkpoint(&[2; 32]).unwrap();
+        db.save_finalized_header(&header(3)).unwrap();
+        assert_eq!(vec![2; 32], db.load_checkpoint().unwrap());
+
+        let headers = db.db.open_tree("verified_headers").unwrap();
+        let (name, _) = headers.first().unwrap().unwrap();
+        assert_ne!(b"3", &*name);
+        assert!(!db
+            .db
+            .get("checkpoint")
+            .unwrap()
+            .unwrap()
+            .windows(32)
+            .any(|window| window == [2; 32]));
+
+        drop(db);
+        fs::remove_dir_all(config.data_dir.unwrap()).unwrap();
+    }
+}
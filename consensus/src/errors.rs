@@ -4,6 +4,8 @@ use thiserror::Error;
 pub enum ConsensusError {
     #[error("insufficient participation")]
     InsufficientParticipation,
+    #[error("participation of {0} is below the minimum of {1}")]
+    ParticipationBelowMinimum(u64, u64),
     #[error("invalid timestamp")]
     InvalidTimestamp,
     #[error("invalid sync committee period")]
@@ -20,10 +22,87 @@ pub enum ConsensusError {
     InvalidSignature,
     #[error("invalid header hash found: {0}, expected: {1}")]
     InvalidHeaderHash(String, String),
+    #[error("invalid execution block hash found: {0}, expected: {1}")]
+    InvalidExecutionBlockHash(String, String),
     #[error("payload not found for slot: {0}")]
     PayloadNotFound(u64),
     #[error("checkpoint is too old")]
     CheckpointTooOld,
     #[error("consensus rpc is for the incorrect network")]
     IncorrectRpcNetwork,
+    #[error("block at slot {0} has no blobs")]
+    NoBlobs(u64),
+    #[error("blob sidecar index {0} has no commitment in the block")]
+    UnknownBlobIndex(u64),
+    #[error("blob sidecar {0} commitment does not match the block")]
+    BlobCommitmentMismatch(u64),
+    #[error("invalid kzg proof for blob sidecar {0}")]
+    InvalidBlobProof(u64),
+    #[error("block at slot {0} has no withdrawals")]
+    NoWithdrawals(u64),
+    #[error("block at slot {0} has no withdrawal at index {1}")]
+    WithdrawalNotFound(u64, u64),
+    #[error("invalid withdrawal proof")]
+    InvalidWithdrawalProof,
+    #[error("invalid state proof for validator {0}")]
+    InvalidStateProof(u64),
+    #[error("invalid state proof for generalized index {0}")]
+    InvalidStateFieldProof(u64),
+    #[error("invalid state multiproof")]
+    InvalidStateMultiproof,
+    #[error("conflicting finalized headers at slot {0}")]
+    ConflictingFinality(u64),
+    /// The response of a fork that is not modeled yet, with the raw response.
+    #[error("unsupported fork: {0}")]
+    UnsupportedFork(String, Vec<u8>),
+    #[error("missing encoding version")]
+    MissingEncodingVersion,
+    #[error("unsupported encoding version: {0}")]
+    UnsupportedEncodingVersion(u8),
+    #[error("missing compression of a compressed encoding")]
+    MissingCompression,
+    #[error("unsupported compression: {0}")]
+    UnsupportedCompression(u8),
+    #[error("decompressed payload larger than {0} bytes")]
+    DecompressedTooLarge(usize),
+    #[error("unknown sync committee: {0}")]
+    UnknownSyncCommittee(String),
+    #[error("state regressed from finalized slot {0} to {1}")]
+    StateRegression(u64, u64),
+    #[error("invalid execution payload proof")]
+    InvalidPayloadProof,
+    #[error("invalid genesis: {0}")]
+    InvalidGenesis(String),
+    #[error("invalid update archive: {0}")]
+    InvalidArchive(String),
+    #[error("invalid backfill range from slot {0} to {1}")]
+    InvalidBackfillRange(u64, u64),
+    #[error("no verified header is cached for execution block {0}")]
+    BlockNumberNotCached(u64),
+    #[error("no finalized block covers timestamp {0}")]
+    NoFinalizedBlockAt(u64),
+    #[error("invalid light client ssz: {0}")]
+    InvalidLightClientSsz(String),
+    #[error("cannot link the backfilled headers to the header at slot {0}")]
+    BackfillGap(u64),
+    #[error("cannot backfill {0} slots, at most {1} are backfilled at once")]
+    BackfillRangeTooLong(u64, u64),
+    #[error("checkpoint at slot {0} is stale, it is {1} seconds old")]
+    StaleCheckpoint(u64, u64),
+    #[error("chain {0} already has a state manager")]
+    DuplicateChain(u64),
+    #[error("missing option: {0}")]
+    MissingOption(&'static str),
+    #[error("conflicting options: {0} and {1}")]
+    ConflictingOptions(&'static str, &'static str),
+    #[error("invalid option: {0}")]
+    InvalidOption(String),
+    #[error("cannot reload a config of chain {0} into the state of chain {1}")]
+    ReloadChainMismatch(u64, u64),
+    #[error("invalid snapshot key")]
+    InvalidSnapshotKey,
+    #[error("invalid snapshot signature")]
+    InvalidSnapshotSignature,
+    #[error("cannot import a snapshot of chain {0} into the state of chain {1}")]
+    SnapshotChainMismatch(u64, u64),
 }
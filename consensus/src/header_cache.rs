@@ -0,0 +1,305 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use ssz_rs::prelude::*;
+
+use crate::types::{Bytes32, ExecutionPayload, Header};
+
+/// Number of verified finalized headers kept by a [`HeaderCache`] created with `Default`.
+pub const DEFAULT_HEADER_CACHE_CAPACITY: usize = 256;
+
+/// A finalized header that passed light client verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiedHeader {
+    pub header: Header,
+    /// The hash tree root of `header`, i.e. the beacon block root.
+    pub block_root: Bytes32,
+    /// The hash tree root of the execution payload of the block, once it was fetched and
+    /// verified against `header`.
+    pub execution_payload_root: Option<Bytes32>,
+    /// The execution block hash of the block, once its payload was fetched and verified.
+    pub execution_block_hash: Option<Bytes32>,
+    /// The execution block number of the block, once its payload was fetched and verified.
+    #[serde(default)]
+    pub execution_block_number: Option<u64>,
+    /// The execution state root of the block, once its payload was fetched and verified.
+    #[serde(default)]
+    pub execution_state_root: Option<Bytes32>,
+}
+
+impl VerifiedHeader {
+    fn set_execution_payload(&mut self, payload: &ExecutionPayload) -> Result<()> {
+        let payload_root = payload.clone().hash_tree_root()?;
+        self.execution_payload_root = Some(Bytes32::try_from(payload_root.as_ref())?);
+        self.execution_block_hash = Some(payload.block_hash().clone());
+        self.execution_block_number = Some(payload.block_number().as_u64());
+        self.execution_state_root = Some(payload.state_root().clone());
+        Ok(())
+    }
+}
+
+/// A bounded cache of recently verified finalized headers, keyed by slot, by block root and, once
+/// their payload is verified, by execution block number. When full, the least recently used
+/// header is evicted.
+#[derive(Debug)]
+pub struct HeaderCache {
+    capacity: usize,
+    inner: Mutex<HeaderCacheInner>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct HeaderCacheInner {
+    headers: HashMap<u64, VerifiedHeader>,
+    slots_by_root: HashMap<Vec<u8>, u64>,
+    slots_by_block_number: HashMap<u64, u64>,
+    /// Slots ordered from the least to the most recently used.
+    recency: VecDeque<u64>,
+}
+
+impl HeaderCacheInner {
+    fn touch(&mut self, slot: u64) {
+        if let Some(position) = self.recency.iter().position(|s| *s == slot) {
+            self.recency.remove(position);
+        }
+        self.recency.push_back(slot);
+    }
+
+    fn get(&mut self, slot: u64) -> Option<VerifiedHeader> {
+        let header = self.headers.get(&slot).cloned()?;
+        self.touch(slot);
+        Some(header)
+    }
+
+    fn remove(&mut self, slot: u64) {
+        if let Some(header) = self.headers.remove(&slot) {
+            self.slots_by_root.remove(header.block_root.as_slice());
+            if let Some(number) = header.execution_block_number {
+                self.slots_by_block_number.remove(&number);
+            }
+        }
+    }
+}
+
+impl HeaderCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(HeaderCacheInner::default()),
+        }
+    }
+
+    /// Inserts a verified header, evicting the least recently used header if the cache is full.
+    /// Callers must only insert headers that passed light client verification.
+    pub fn insert(&self, header: &Header) -> Result<()> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        let block_root = header.clone().hash_tree_root()?;
+        let slot = header.slot.as_u64();
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.remove(slot);
+
+        inner
+            .slots_by_root
+            .insert(block_root.as_ref().to_vec(), slot);
+        inner.headers.insert(
+            slot,
+            VerifiedHeader {
+                header: header.clone(),
+                block_root: Bytes32::try_from(block_root.as_ref())?,
+                execution_payload_root: None,
+                execution_block_hash: None,
+                execution_block_number: None,
+                execution_state_root: None,
+            },
+        );
+        inner.touch(slot);
+
+        while inner.headers.len() > self.capacity {
+            let Some(evicted) = inner.recency.pop_front() else {
+                break;
+            };
+            inner.remove(evicted);
+        }
+
+        Ok(())
+    }
+
+    /// Records the execution payload of a cached header. The payload must already be verified to
+    /// belong to the block of the header at `slot`.
+    pub fn set_execution_payload(&self, slot: u64, payload: &ExecutionPayload) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(header) = inner.headers.get_mut(&slot) {
+            header.set_execution_payload(payload)?;
+            inner
+                .slots_by_block_number
+                .insert(payload.block_number().as_u64(), slot);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_by_slot(&self, slot: u64) -> Option<VerifiedHeader> {
+        self.inner.lock().unwrap().get(slot)
+    }
+
+    pub fn get_by_block_root(&self, block_root: &[u8]) -> Option<VerifiedHeader> {
+        let mut inner = self.inner.lock().unwrap();
+        let slot = *inner.slots_by_root.get(block_root)?;
+        inner.get(slot)
+    }
+
+    /// Returns the header of the execution block `block_number`, if its payload was verified.
+    pub fn get_by_block_number(&self, block_number: u64) -> Option<VerifiedHeader> {
+        let mut inner = self.inner.lock().unwrap();
+        let slot = *inner.slots_by_block_number.get(&block_number)?;
+        inner.get(slot)
+    }
+
+    /// Drops the headers before `slot`, and returns how many were dropped.
+    pub fn prune_before(&self, slot: u64) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let pruned: Vec<u64> = inner
+            .headers
+            .keys()
+            .copied()
+            .filter(|s| *s < slot)
+            .collect();
+        for slot in &pruned {
+            inner.remove(*slot);
+        }
+        inner.recency.retain(|s| !pruned.contains(s));
+
+        pruned.len()
+    }
+
+    /// Returns the slots of the cached headers, in ascending order.
+    pub fn slots(&self) -> Vec<u64> {
+        let mut slots: Vec<u64> = self.inner.lock().unwrap().headers.keys().copied().collect();
+        slots.sort_unstable();
+        slots
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().headers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap() = HeaderCacheInner::default();
+    }
+}
+
+impl Default for HeaderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEADER_CACHE_CAPACITY)
+    }
+}
+
+impl Clone for HeaderCache {
+    fn clone(&self) -> Self {
+        Self {
+            capacity: self.capacity,
+            inner: Mutex::new(self.inner.lock().unwrap().clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz_rs::prelude::*;
+
+    use super::HeaderCache;
+    use crate::types::{ExecutionPayload, ExecutionPayloadDeneb, Header};
+
+    fn header(slot: u64) -> Header {
+        Header {
+            slot: slot.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_header_cache_lookup() {
+        let cache = HeaderCache::new(2);
+        cache.insert(&header(32)).unwrap();
+
+        let root = header(32).hash_tree_root().unwrap();
+        let by_slot = cache.get_by_slot(32).unwrap();
+        let by_root = cache.get_by_block_root(root.as_ref()).unwrap();
+
+        assert_eq!(by_slot.block_root, by_root.block_root);
+        assert_eq!(by_slot.block_root.as_slice(), root.as_ref());
+        assert!(cache.get_by_slot(64).is_none());
+    }
+
+    #[test]
+    fn test_header_cache_lookup_by_block_number() {
+        let cache = HeaderCache::new(1);
+        cache.insert(&header(32)).unwrap();
+        assert!(cache.get_by_block_number(100).is_none());
+
+        let payload = ExecutionPayload::Deneb(ExecutionPayloadDeneb {
+            block_number: 100u64.into(),
+            ..Default::default()
+        });
+        cache.set_execution_payload(32, &payload).unwrap();
+
+        let verified = cache.get_by_block_number(100).unwrap();
+        assert_eq!(verified.header.slot.as_u64(), 32);
+        assert_eq!(verified.execution_block_number, Some(100));
+        assert_eq!(
+            verified.execution_state_root,
+            Some(payload.state_root().clone())
+        );
+
+        // The block number index follows the evictions.
+        cache.insert(&header(64)).unwrap();
+        assert!(cache.get_by_block_number(100).is_none());
+        assert_eq!(cache.slots(), vec![64]);
+    }
+
+    #[test]
+    fn test_header_cache_evicts_least_recently_used() {
+        let cache = HeaderCache::new(2);
+        cache.insert(&header(32)).unwrap();
+        cache.insert(&header(64)).unwrap();
+
+        // Using the oldest header makes the other one the eviction candidate.
+        cache.get_by_slot(32).unwrap();
+        cache.insert(&header(96)).unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_by_slot(32).is_some());
+        assert!(cache.get_by_slot(64).is_none());
+        assert!(cache.get_by_slot(96).is_some());
+    }
+
+    #[test]
+    fn test_header_cache_prune() {
+        let cache = HeaderCache::new(4);
+        for slot in [32, 64, 96] {
+            cache.insert(&header(slot)).unwrap();
+        }
+
+        assert_eq!(2, cache.prune_before(96));
+        assert_eq!(cache.slots(), vec![96]);
+        let root = header(32).hash_tree_root().unwrap();
+        assert!(cache.get_by_block_root(root.as_ref()).is_none());
+
+        // The pruned slots are not eviction candidates anymore.
+        for slot in [128, 160, 192] {
+            cache.insert(&header(slot)).unwrap();
+        }
+        assert_eq!(cache.slots(), vec![96, 128, 160, 192]);
+    }
+}
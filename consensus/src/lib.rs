@@ -1,7 +1,21 @@
+pub mod archive;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod backup;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod blobs;
+pub mod builder;
+pub mod clock;
 pub mod database;
 pub mod errors;
+pub mod header_cache;
+pub mod light_client_data;
+pub mod multi_chain;
 pub mod rpc;
+pub mod shared;
+pub mod snapshot;
+pub mod sync_health;
 pub mod types;
+pub mod update_cache;
 
 mod consensus;
 pub use rpc::*;
@@ -9,5 +23,5 @@ pub use types::Bytes32;
 
 pub use crate::consensus::{ConsensusStateManager, *};
 
-mod constants;
+pub mod constants;
 mod utils;
@@ -0,0 +1,287 @@
+//! The light client data verified by a [`ConsensusStateManager`], kept so that it can be served
+//! again to other light clients.
+//!
+//! [`ConsensusStateManager`]: crate::ConsensusStateManager
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use consensus_core::sync::{calc_sync_period, is_better_update};
+use tokio::sync::broadcast;
+
+use crate::{
+    consensus::update_summary,
+    types::{Bootstrap, FinalityUpdate, GenericUpdate, OptimisticUpdate, Update},
+};
+
+/// The number of finality updates a lagging subscriber can miss before it starts losing them.
+const FINALITY_UPDATE_CHANNEL_CAPACITY: usize = 16;
+
+/// The verified bootstrap, the best verified update of every period, and the latest verified
+/// finality and optimistic updates. Clones share the same data.
+#[derive(Debug, Clone)]
+pub struct LightClientData {
+    inner: Arc<RwLock<LightClientDataInner>>,
+    finality_update_send: broadcast::Sender<FinalityUpdate>,
+}
+
+impl Default for LightClientData {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+            finality_update_send: broadcast::channel(FINALITY_UPDATE_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LightClientDataInner {
+    /// The bootstrap of the checkpoint, with the block root of the checkpoint.
+    bootstrap: Option<(Vec<u8>, Bootstrap)>,
+    /// The sync committee updates, by the period of their attested header.
+    updates: BTreeMap<u64, GenericUpdate>,
+    finality_update: Option<GenericUpdate>,
+    optimistic_update: Option<GenericUpdate>,
+}
+
+impl LightClientData {
+    /// Records the bootstrap of `block_root`. Callers must only record verified bootstraps.
+    pub fn record_bootstrap(&self, block_root: &[u8], bootstrap: &Bootstrap) {
+        self.inner.write().unwrap().bootstrap = Some((block_root.to_vec(), bootstrap.clone()));
+    }
+
+    /// Records an applied update. Callers must only record updates whose signature was verified.
+    /// With `optimistic`, the attested header of the update is also served as the optimistic
+    /// update, which a client under strict finality leaves out, as it only follows finalized
+    /// headers.
+    pub fn record_update(&self, update: &GenericUpdate, optimistic: bool) {
+        let mut inner = self.inner.write().unwrap();
+
+        if update.next_sync_committee.is_some() && update.next_sync_committee_branch.is_some() {
+            let period = calc_sync_period(update.attested_header.slot.as_u64());
+            let is_best = match inner.updates.get(&period) {
+                Some(best) => is_better_update(&update_summary(update), &update_summary(best)),
+                None => true,
+            };
+            if is_best {
+                inner.updates.insert(period, update.clone());
+            }
+        }
+
+        if update.finalized_header.is_some() && update.finality_branch.is_some() {
+            let is_newer = match &inner.finality_update {
+                Some(latest) => update.attested_header.slot > latest.attested_header.slot,
+                None => true,
+            };
+            if is_newer {
+                inner.finality_update = Some(update.clone());
+                _ = self.finality_update_send.send(to_finality_update(update));
+            }
+        }
+
+        if !optimistic {
+            return;
+        }
+
+        // Every update attests a header, so any of them can be served as an optimistic update.
+        let is_newer = match &inner.optimistic_update {
+            Some(latest) => update.attested_header.slot > latest.attested_header.slot,
+            None => true,
+        };
+        if is_newer {
+            inner.optimistic_update = Some(update.clone());
+        }
+    }
+
+    /// Returns the bootstrap of `block_root`, if it is the one of the checkpoint.
+    pub fn bootstrap(&self, block_root: &[u8]) -> Option<Bootstrap> {
+        let inner = self.inner.read().unwrap();
+        let (root, bootstrap) = inner.bootstrap.as_ref()?;
+        (root.as_slice() == block_root).then(|| bootstrap.clone())
+    }
+
+    /// Returns the updates of the `count` periods from `start_period`, up to the first period
+    /// without a verified update.
+    pub fn updates(&self, start_period: u64, count: u64) -> Vec<Update> {
+        let inner = self.inner.read().unwrap();
+
+        (start_period..start_period.saturating_add(count))
+            .map_while(|period| inner.updates.get(&period))
+            .map(to_update)
+            .collect()
+    }
+
+    pub fn finality_update(&self) -> Option<FinalityUpdate> {
+        let inner = self.inner.read().unwrap();
+        inner.finality_update.as_ref().map(to_finality_update)
+    }
+
+    /// Returns a receiver of every finality update recorded from now on.
+    pub fn subscribe_finality_updates(&self) -> broadcast::Receiver<FinalityUpdate> {
+        self.finality_update_send.subscribe()
+    }
+
+    pub fn optimistic_update(&self) -> Option<OptimisticUpdate> {
+        let inner = self.inner.read().unwrap();
+        inner.optimistic_update.as_ref().map(to_optimistic_update)
+    }
+
+    /// Drops the sync committee updates of the periods before `period`, and returns how many were
+    /// dropped.
+    pub fn prune_updates(&self, period: u64) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        let kept = inner.updates.split_off(&period);
+        let pruned = inner.updates.len();
+        inner.updates = kept;
+
+        pruned
+    }
+
+    pub fn clear(&self) {
+        *self.inner.write().unwrap() = LightClientDataInner::default();
+    }
+}
+
+fn to_update(update: &GenericUpdate) -> Update {
+    Update {
+        attested_header: update.attested_header.clone(),
+        next_sync_committee: update.next_sync_committee.clone().unwrap_or_default(),
+        next_sync_committee_branch: update
+            .next_sync_committee_branch
+            .clone()
+            .unwrap_or_default(),
+        finalized_header: update.finalized_header.clone().unwrap_or_default(),
+        finality_branch: update.finality_branch.clone().unwrap_or_default(),
+        sync_aggregate: update.sync_aggregate.clone(),
+        signature_slot: update.signature_slot.into(),
+    }
+}
+
+fn to_finality_update(update: &GenericUpdate) -> FinalityUpdate {
+    FinalityUpdate {
+        attested_header: update.attested_header.clone(),
+        finalized_header: update.finalized_header.clone().unwrap_or_default(),
+        finality_branch: update.finality_branch.clone().unwrap_or_default(),
+        sync_aggregate: update.sync_aggregate.clone(),
+        signature_slot: update.signature_slot.into(),
+    }
+}
+
+fn to_optimistic_update(update: &GenericUpdate) -> OptimisticUpdate {
+    OptimisticUpdate {
+        attested_header: update.attested_header.clone(),
+        sync_aggregate: update.sync_aggregate.clone(),
+        signature_slot: update.signature_slot.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Header;
+
+    fn update(slot: u64) -> Update {
+        Update {
+            attested_header: Header {
+                slot: slot.into(),
+                ..Default::default()
+            },
+            signature_slot: (slot + 1).into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_light_client_data() {
+        let data = LightClientData::default();
+        data.record_update(&GenericUpdate::from(&update(8192)), true);
+        data.record_update(&GenericUpdate::from(&update(8192 * 2)), true);
+        data.record_update(&GenericUpdate::from(&update(8192 * 4)), true);
+
+        let updates = data.updates(1, 4);
+        assert_eq!(2, updates.len());
+        assert_eq!(8192 * 2, updates[1].attested_header.slot.as_u64());
+        assert!(data.updates(3, 1).is_empty());
+
+        let optimistic_update = data.optimistic_update().unwrap();
+        assert_eq!(8192 * 4, optimistic_update.attested_header.slot.as_u64());
+        assert_eq!(
+            8192 * 4,
+            data.finality_update()
+                .unwrap()
+                .attested_header
+                .slot
+                .as_u64()
+        );
+
+        // Updates without a sync committee are only served as finality and optimistic updates.
+        let finality_update = FinalityUpdate {
+            attested_header: Header {
+                slot: (8192 * 5).into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        data.record_update(&GenericUpdate::from(&finality_update), true);
+        assert!(data.updates(5, 1).is_empty());
+        assert_eq!(
+            8192 * 5,
+            data.optimistic_update()
+                .unwrap()
+                .attested_header
+                .slot
+                .as_u64()
+        );
+
+        // Under strict finality, the attested headers are not served as optimistic updates.
+        data.record_update(&GenericUpdate::from(&update(8192 * 6)), false);
+        assert_eq!(
+            8192 * 6,
+            data.finality_update()
+                .unwrap()
+                .attested_header
+                .slot
+                .as_u64()
+        );
+        assert_eq!(
+            8192 * 5,
+            data.optimistic_update()
+                .unwrap()
+                .attested_header
+                .slot
+                .as_u64()
+        );
+
+        data.clear();
+        assert!(data.finality_update().is_none());
+    }
+
+    #[test]
+    fn test_subscribe_finality_updates() {
+        let data = LightClientData::default();
+        let mut finality_updates = data.subscribe_finality_updates();
+
+        data.record_update(&GenericUpdate::from(&update(8192 * 2)), true);
+        // Older finality updates are not recorded, so they are not sent either.
+        data.record_update(&GenericUpdate::from(&update(8192)), true);
+
+        let finality_update = finality_updates.try_recv().unwrap();
+        assert_eq!(8192 * 2, finality_update.attested_header.slot.as_u64());
+        assert!(finality_updates.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_prune_updates() {
+        let data = LightClientData::default();
+        for period in 1..=4 {
+            data.record_update(&GenericUpdate::from(&update(8192 * period)), true);
+        }
+
+        assert_eq!(2, data.prune_updates(3));
+        assert!(data.updates(1, 2).is_empty());
+        assert_eq!(2, data.updates(3, 2).len());
+        assert_eq!(0, data.prune_updates(3));
+    }
+}
@@ -0,0 +1,229 @@
+//! Several independent [`ConsensusStateManager`]s, one per network, so that the proofs of more
+//! than one chain are verified by the same process. The managers share nothing: each has its own
+//! RPC, store and caches, and a chain that fails to sync does not hold back the others.
+//!
+//! Everything that outlives the managers is namespaced by chain id: the serialized state is a map
+//! from the chain id to the state of its manager, the data directories are suffixed with the
+//! chain id, see [`MultiChainManager::chain_config`], and the health is reported per chain.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use config::Config;
+use eyre::Result;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    errors::ConsensusError,
+    rpc::ConsensusRpc,
+    types::{FinalizedHeader, Health},
+    ConsensusStateManager,
+};
+
+/// The state managers of several networks, keyed by chain id.
+///
+/// Once deserialized, the RPC of every manager must be set again with
+/// [`ConsensusStateManager::set_rpc`], since it is not part of the state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "",
+    deserialize = "ConsensusStateManager<R>: Deserialize<'de>"
+))]
+pub struct MultiChainManager<R: ConsensusRpc> {
+    managers: BTreeMap<u64, ConsensusStateManager<R>>,
+}
+
+impl<R: ConsensusRpc> Default for MultiChainManager<R> {
+    fn default() -> Self {
+        Self {
+            managers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<R: ConsensusRpc> MultiChainManager<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a manager for every config, which connects to its `consensus_rpc` and uses the
+    /// data directory of [`Self::chain_config`]. The managers still have to be synced, see
+    /// [`Self::sync_all`].
+    pub fn from_configs(configs: impl IntoIterator<Item = Config>) -> Result<Self> {
+        let mut multi_chain = Self::new();
+        for config in configs {
+            let config = Self::chain_config(&config);
+            let manager = ConsensusStateManager::new(
+                &config.consensus_rpc,
+                None,
+                None,
+                None,
+                Arc::new(config.clone()),
+                config.checkpoint.clone(),
+            );
+            multi_chain.insert(manager)?;
+        }
+
+        Ok(multi_chain)
+    }
+
+    /// Returns `config` with its data directory suffixed with its chain id, so that the databases
+    /// of the chains do not overwrite the checkpoints of each other.
+    pub fn chain_config(config: &Config) -> Config {
+        let chain_id = config.chain.chain_id;
+        let mut config = config.clone();
+        config.data_dir = config
+            .data_dir
+            .take()
+            .map(|data_dir| data_dir.join(chain_id.to_string()));
+        config
+    }
+
+    /// Adds the manager of the chain of its config. Fails if the chain already has one.
+    pub fn insert(&mut self, manager: ConsensusStateManager<R>) -> Result<()> {
+        let chain_id = manager.config.chain.chain_id;
+        if self.managers.contains_key(&chain_id) {
+            return Err(ConsensusError::DuplicateChain(chain_id).into());
+        }
+
+        self.managers.insert(chain_id, manager);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, chain_id: u64) -> Option<ConsensusStateManager<R>> {
+        self.managers.remove(&chain_id)
+    }
+
+    pub fn get(&self, chain_id: u64) -> Option<&ConsensusStateManager<R>> {
+        self.managers.get(&chain_id)
+    }
+
+    pub fn get_mut(&mut self, chain_id: u64) -> Option<&mut ConsensusStateManager<R>> {
+        self.managers.get_mut(&chain_id)
+    }
+
+    /// Returns the chain ids of the managers, in ascending order.
+    pub fn chain_ids(&self) -> Vec<u64> {
+        self.managers.keys().copied().collect()
+    }
+
+    /// Syncs every manager concurrently from its last checkpoint, or else the checkpoint of its
+    /// config, and returns the outcome of each chain.
+    pub async fn sync_all(&mut self) -> BTreeMap<u64, Result<()>> {
+        let tasks = self
+            .managers
+            .iter_mut()
+            .map(|(chain_id, manager)| async move {
+                let checkpoint = manager
+                    .last_checkpoint
+                    .clone()
+                    .or_else(|| manager.config.checkpoint.clone())
+                    .unwrap_or_else(|| manager.config.default_checkpoint.clone());
+
+                (*chain_id, manager.sync(&checkpoint).await)
+            });
+
+        futures::future::join_all(tasks).await.into_iter().collect()
+    }
+
+    /// Advances every manager concurrently until `cancel` is cancelled, see
+    /// [`ConsensusStateManager::run`].
+    pub async fn run(&mut self, cancel: &CancellationToken) {
+        let tasks = self
+            .managers
+            .values_mut()
+            .map(|manager| manager.run(cancel));
+        futures::future::join_all(tasks).await;
+    }
+
+    /// Returns a stream of every finalized header verified from now on by any of the managers,
+    /// with the chain id of its manager. Like [`ConsensusStateManager::subscribe_finalized`], a
+    /// stream that lags behind misses the oldest headers of a chain. The managers added later
+    /// are not part of the stream.
+    pub fn subscribe_finalized(&self) -> BoxStream<'static, (u64, FinalizedHeader)> {
+        let streams = self.managers.iter().map(|(chain_id, manager)| {
+            let chain_id = *chain_id;
+            stream::unfold(manager.subscribe_finalized(), move |mut recv| async move {
+                loop {
+                    match recv.recv().await {
+                        Ok(header) => return Some(((chain_id, header), recv)),
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            })
+            .boxed()
+        });
+
+        stream::select_all(streams).boxed()
+    }
+
+    /// Returns the health of every manager, keyed by chain id.
+    pub fn health(&self) -> BTreeMap<u64, Health> {
+        self.managers
+            .iter()
+            .map(|(chain_id, manager)| (*chain_id, manager.health()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use config::networks;
+
+    use super::*;
+    use crate::rpc::mock_rpc::MockRpc;
+
+    fn config(chain_id: u64) -> Config {
+        let base_config = networks::mainnet();
+        let mut config = Config {
+            consensus_rpc: "testdata/".to_string(),
+            execution_rpc: String::new(),
+            chain: base_config.chain,
+            forks: base_config.forks,
+            checkpoint: Some(
+                hex::decode("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275")
+                    .unwrap(),
+            ),
+            data_dir: Some(PathBuf::from("/tmp/helios")),
+            ..Default::default()
+        };
+        config.chain.chain_id = chain_id;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_multi_chain_manager() {
+        let mut multi_chain =
+            MultiChainManager::<MockRpc>::from_configs([config(5), config(1)]).unwrap();
+        assert_eq!(vec![1, 5], multi_chain.chain_ids());
+        assert_eq!(
+            Some(PathBuf::from("/tmp/helios/5")),
+            multi_chain.get(5).unwrap().config.data_dir
+        );
+
+        let manager = multi_chain.remove(1).unwrap();
+        multi_chain.insert(manager.clone()).unwrap();
+        assert!(multi_chain.insert(manager).is_err());
+
+        let finalized = multi_chain.subscribe_finalized();
+        let results = multi_chain.sync_all().await;
+        assert!(results.values().all(|res| res.is_ok()));
+
+        let mut chain_ids = finalized
+            .take(2)
+            .map(|(chain_id, _)| chain_id)
+            .collect::<Vec<_>>()
+            .await;
+        chain_ids.sort();
+        assert_eq!(vec![1, 5], chain_ids);
+
+        let health = multi_chain.health();
+        assert_eq!(vec![&1, &5], health.keys().collect::<Vec<_>>());
+        assert_eq!(health[&1].finalized_slot, health[&5].finalized_slot);
+    }
+}
@@ -0,0 +1,233 @@
+//! An RPC that reads the responses of the beacon API from a local directory, so that the
+//! verification can run without any network access, e.g. inside a secure enclave that is fed
+//! the files by an untrusted host. The responses are verified like those of a beacon node.
+//!
+//! The files hold the JSON bodies of the beacon API responses, laid out as:
+//!
+//! ```text
+//! bootstrap/0x<block root>.json    or bootstrap.json
+//! updates/<period>.json            and/or updates.json, with the updates of any periods
+//! finality.json
+//! optimistic.json
+//! blocks/<slot>.json
+//! blob_sidecars/<slot>.json
+//! state_proofs/0x<state root>/<gindex>.json
+//! spec.json
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use common::errors::RpcError;
+use eyre::{eyre, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{dedup_updates, provider, responses::*, ConsensusRpc};
+use crate::{
+    types::{validator::StateProof, *},
+    utils::calc_sync_period,
+};
+
+#[derive(Debug, Clone)]
+pub struct FileRpc {
+    dir: PathBuf,
+}
+
+impl FileRpc {
+    fn read<R: DeserializeOwned>(&self, path: impl AsRef<Path>) -> Result<R> {
+        Ok(serde_json::from_value(self.read_json(path)?)?)
+    }
+
+    /// Reads the file at `path` of the directory, normalized with [`provider::normalize`].
+    fn read_json(&self, path: impl AsRef<Path>) -> Result<Value> {
+        let path = self.dir.join(path);
+        let bytes =
+            std::fs::read(&path).map_err(|err| eyre!("cannot read {}: {}", path.display(), err))?;
+
+        let mut value = serde_json::from_slice::<Value>(&bytes)?;
+        provider::normalize(&mut value);
+        Ok(value)
+    }
+
+    fn read_updates(&self, path: impl AsRef<Path>) -> Result<Vec<Update>> {
+        let value = provider::normalize_updates(self.read_json(path)?);
+        let res: UpdateResponse = serde_json::from_value(value)?;
+        Ok(res.into_iter().map(|update| update.data).collect())
+    }
+
+    /// Returns the periods of the files of `updates/`.
+    fn update_periods(&self) -> Result<Vec<u64>> {
+        let dir = self.dir.join("updates");
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut periods = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let period = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok());
+
+            if let Some(period) = period {
+                periods.push(period);
+            }
+        }
+
+        periods.sort_unstable();
+        Ok(periods)
+    }
+
+    fn exists(&self, path: impl AsRef<Path>) -> bool {
+        self.dir.join(path).exists()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ConsensusRpc for FileRpc {
+    fn new(path: &str) -> Self {
+        FileRpc {
+            dir: PathBuf::from(path),
+        }
+    }
+
+    async fn get_bootstrap(&self, block_root: &'_ [u8]) -> Result<Bootstrap> {
+        let path = PathBuf::from(format!("bootstrap/0x{}.json", hex::encode(block_root)));
+        let path = if self.exists(&path) {
+            path
+        } else {
+            PathBuf::from("bootstrap.json")
+        };
+
+        let res: BootstrapResponse = self.read(path).map_err(|e| RpcError::new("bootstrap", e))?;
+
+        Ok(res.data)
+    }
+
+    /// Returns the updates of the range that are in the directory. Periods without updates are
+    /// skipped, like a beacon node does for the periods that it does not have.
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>> {
+        let end_period = start_period.saturating_add(count);
+        let mut updates = Vec::new();
+
+        if self.exists("updates.json") {
+            updates.extend(
+                self.read_updates("updates.json")
+                    .map_err(|e| RpcError::new("updates", e))?,
+            );
+        }
+
+        for period in self.update_periods()? {
+            if (start_period..end_period).contains(&period) {
+                updates.extend(
+                    self.read_updates(format!("updates/{}.json", period))
+                        .map_err(|e| RpcError::new("updates", e))?,
+                );
+            }
+        }
+
+        updates.retain(|update| {
+            let period = calc_sync_period(update.attested_header.slot.into());
+            (start_period..end_period).contains(&period)
+        });
+
+        Ok(dedup_updates(updates))
+    }
+
+    async fn get_finality_update(&self) -> Result<FinalityUpdate> {
+        let res: FinalityUpdateResponse = self
+            .read("finality.json")
+            .map_err(|e| RpcError::new("finality_update", e))?;
+
+        Ok(res.data)
+    }
+
+    async fn get_optimistic_update(&self) -> Result<OptimisticUpdate> {
+        let res: OptimisticUpdateResponse = self
+            .read("optimistic.json")
+            .map_err(|e| RpcError::new("optimistic_update", e))?;
+
+        Ok(res.data)
+    }
+
+    async fn get_block(&self, slot: u64) -> Result<BeaconBlock> {
+        let mut value = self
+            .read_json(format!("blocks/{}.json", slot))
+            .map_err(|e| RpcError::new("blocks", e))?;
+        provider::retain_known_block_fields(&mut value);
+        let res: BeaconBlockResponse =
+            serde_json::from_value(value).map_err(|e| RpcError::new("blocks", e))?;
+
+        Ok(res.data.message)
+    }
+
+    async fn get_blob_sidecars(&self, slot: u64) -> Result<Vec<BlobSidecar>> {
+        let res: BlobSidecarsResponse = self
+            .read(format!("blob_sidecars/{}.json", slot))
+            .map_err(|e| RpcError::new("blob_sidecars", e))?;
+
+        Ok(res.data)
+    }
+
+    async fn get_genesis_header(&self) -> Result<Header> {
+        let res: HeaderResponse = self
+            .read("genesis/header.json")
+            .map_err(|e| RpcError::new("genesis_header", e))?;
+
+        Ok(res.data.header.message)
+    }
+
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee> {
+        let res: GenesisSyncCommitteeResponse = self
+            .read("genesis/state.json")
+            .map_err(|e| RpcError::new("genesis_state", e))?;
+
+        Ok(res.data.current_sync_committee)
+    }
+
+    async fn get_state_proof(&self, state_root: &'_ [u8], gindex: u64) -> Result<StateProof> {
+        let path = format!("state_proofs/0x{}/{}.json", hex::encode(state_root), gindex);
+        let res: StateProofResponse = self
+            .read(path)
+            .map_err(|e| RpcError::new("state_proof", e))?;
+
+        Ok(res.data)
+    }
+
+    async fn chain_id(&self) -> Result<u64> {
+        let res: SpecResponse = self
+            .read("spec.json")
+            .map_err(|e| RpcError::new("spec", e))?;
+
+        Ok(res.data.chain_id.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_rpc() {
+        let rpc = FileRpc::new("testdata/");
+
+        rpc.get_bootstrap(&[0u8; 32]).await.unwrap();
+        rpc.get_finality_update().await.unwrap();
+        rpc.get_optimistic_update().await.unwrap();
+        rpc.get_block(7109344).await.unwrap();
+
+        // The updates of the directory are filtered by period.
+        let updates = rpc.get_updates(863, 2).await.unwrap();
+        let periods = updates
+            .iter()
+            .map(|update| calc_sync_period(update.attested_header.slot.into()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![863, 864], periods);
+
+        assert!(rpc.get_updates(900, 10).await.unwrap().is_empty());
+        assert!(rpc.chain_id().await.is_err());
+    }
+}
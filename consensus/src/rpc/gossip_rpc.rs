@@ -0,0 +1,309 @@
+//! A wrapper of an RPC that also subscribes to the light client update topics of the peer to peer
+//! network with libp2p gossipsub, so that the client keeps up with the chain when the beacon API
+//! is down or lags behind. Every other request is served by the wrapped RPC.
+//!
+//! The gossiped updates are not verified here, they are verified by the client like the ones of
+//! the RPC. Beacon nodes drop the peers that do not answer their status requests, so the peers
+//! should accept the client as a trusted peer, e.g. with the `--trusted-peers` flag of Lighthouse.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use config::fork_schedule::ForkSchedule;
+use eyre::{eyre, Result};
+use futures::StreamExt;
+use libp2p::{
+    gossipsub::{self, IdentTopic, MessageAuthenticity, ValidationMode},
+    multiaddr::Protocol,
+    noise,
+    swarm::SwarmEvent,
+    tcp, yamux, Multiaddr, SwarmBuilder,
+};
+use tracing::{debug, info, warn};
+
+use super::{rate_limit::RateLimit, retry::RetryPolicy, ConsensusRpc, RpcCredentials, TlsConfig};
+use crate::types::{
+    gossip::{
+        decode_finality_update, decode_optimistic_update, message_id, topic, FINALITY_UPDATE_TOPIC,
+        GOSSIP_MAX_SIZE, GOSSIP_VERSIONS, OPTIMISTIC_UPDATE_TOPIC,
+    },
+    multiproof::StateMultiproof,
+    validator::StateProof,
+    *,
+};
+
+/// The latest updates received from the gossip.
+#[derive(Debug, Default)]
+struct Gossiped {
+    finality_update: Option<FinalityUpdate>,
+    optimistic_update: Option<OptimisticUpdate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GossipRpc<R: ConsensusRpc> {
+    inner: R,
+    gossiped: Arc<Mutex<Gossiped>>,
+}
+
+impl<R: ConsensusRpc> GossipRpc<R> {
+    /// Wraps `inner`, and receives the updates of the forks of `forks` from the gossip of
+    /// `peers`, which are multiaddrs such as `/ip4/10.0.0.1/tcp/9000/p2p/16Uiu2...`.
+    pub fn with_inner(inner: R, peers: &[String], forks: &ForkSchedule) -> Result<Self> {
+        let rpc = Self {
+            inner,
+            gossiped: Arc::default(),
+        };
+        rpc.start(peers, forks)?;
+
+        Ok(rpc)
+    }
+
+    /// Starts the swarm in the background. It stops once the RPC is dropped and the next message
+    /// is received.
+    fn start(&self, peers: &[String], forks: &ForkSchedule) -> Result<()> {
+        let config = gossipsub::ConfigBuilder::default()
+            .validation_mode(ValidationMode::Anonymous)
+            .max_transmit_size(GOSSIP_MAX_SIZE)
+            .heartbeat_interval(Duration::from_millis(700))
+            .message_id_fn(|message: &gossipsub::Message| {
+                gossipsub::MessageId::from(message_id(message.topic.as_str(), &message.data))
+            })
+            .build()
+            .map_err(|err| eyre!(err.to_string()))?;
+
+        let mut swarm = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                tcp::Config::default(),
+                noise::Config::new,
+                yamux::Config::default,
+            )?
+            .with_behaviour(|_| {
+                gossipsub::Behaviour::new(MessageAuthenticity::Anonymous, config)
+                    .map_err(Box::<dyn std::error::Error + Send + Sync>::from)
+            })?
+            .with_swarm_config(|config| {
+                config.with_idle_connection_timeout(Duration::from_secs(60))
+            })
+            .build();
+
+        // The topics of every scheduled fork, so that the gossip goes on across a fork.
+        for fork in forks.forks() {
+            if !GOSSIP_VERSIONS.contains(&fork.name) || fork.epoch == u64::MAX {
+                continue;
+            }
+
+            let fork_digest = forks.fork_digest_at_slot(fork.epoch * 32)?;
+            for name in [FINALITY_UPDATE_TOPIC, OPTIMISTIC_UPDATE_TOPIC] {
+                let topic = IdentTopic::new(topic(&fork_digest, name));
+                swarm.behaviour_mut().subscribe(&topic)?;
+            }
+        }
+
+        for peer in peers {
+            let address: Multiaddr = peer.parse()?;
+            if let Some(Protocol::P2p(peer_id)) = address.iter().last() {
+                swarm.behaviour_mut().add_explicit_peer(&peer_id);
+            }
+            swarm.dial(address)?;
+        }
+
+        let gossiped = Arc::downgrade(&self.gossiped);
+        tokio::spawn(async move {
+            loop {
+                let event = swarm.select_next_some().await;
+                let Some(gossiped) = gossiped.upgrade() else {
+                    break;
+                };
+
+                match event {
+                    SwarmEvent::Behaviour(gossipsub::Event::Message { message, .. }) => {
+                        receive(&gossiped, message.topic.as_str(), &message.data);
+                    }
+                    SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                        info!(target: "helios::gossip", peer = %peer_id, "connected to gossip peer");
+                    }
+                    SwarmEvent::OutgoingConnectionError { error, .. } => {
+                        warn!(target: "helios::gossip", error = %error, "failed to connect to gossip peer");
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Keeps the update of a message of `topic` if it is newer than the kept one.
+fn receive(gossiped: &Mutex<Gossiped>, topic: &str, data: &[u8]) {
+    let mut gossiped = gossiped.lock().unwrap();
+    let res = if topic.contains(FINALITY_UPDATE_TOPIC) {
+        decode_finality_update(data).map(|update| {
+            let slot = update.attested_header.slot.as_u64();
+            if gossiped
+                .finality_update
+                .as_ref()
+                .map_or(true, |kept| kept.attested_header.slot.as_u64() < slot)
+            {
+                gossiped.finality_update = Some(update);
+            }
+        })
+    } else {
+        decode_optimistic_update(data).map(|update| {
+            let slot = update.attested_header.slot.as_u64();
+            if gossiped
+                .optimistic_update
+                .as_ref()
+                .map_or(true, |kept| kept.attested_header.slot.as_u64() < slot)
+            {
+                gossiped.optimistic_update = Some(update);
+            }
+        })
+    };
+
+    if let Err(err) = res {
+        debug!(target: "helios::gossip", error = %err, topic, "ignoring gossip message");
+    }
+}
+
+/// Returns the newer of the update of the RPC and the gossiped one, by attested slot, or the
+/// gossiped one if the request failed.
+fn newest<T>(requested: Result<T>, gossiped: Option<T>, slot: impl Fn(&T) -> u64) -> Result<T> {
+    match (requested, gossiped) {
+        (Ok(requested), Some(gossiped)) if slot(&gossiped) > slot(&requested) => Ok(gossiped),
+        (Ok(requested), _) => Ok(requested),
+        (Err(_), Some(gossiped)) => Ok(gossiped),
+        (Err(err), None) => Err(err),
+    }
+}
+
+#[async_trait]
+impl<R: ConsensusRpc> ConsensusRpc for GossipRpc<R> {
+    /// Creates a wrapper that does not subscribe to the gossip until
+    /// [`ConsensusRpc::with_gossip`] is set.
+    fn new(path: &str) -> Self {
+        Self {
+            inner: R::new(path),
+            gossiped: Arc::default(),
+        }
+    }
+
+    fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.inner = self.inner.with_retry_policy(policy);
+        self
+    }
+
+    fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.inner = self.inner.with_rate_limit(limit);
+        self
+    }
+
+    fn with_credentials(mut self, credentials: RpcCredentials) -> Self {
+        self.inner = self.inner.with_credentials(credentials);
+        self
+    }
+
+    fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.inner = self.inner.with_tls(tls);
+        self
+    }
+
+    fn with_proxy(mut self, proxy: &str) -> Self {
+        self.inner = self.inner.with_proxy(proxy);
+        self
+    }
+
+    fn with_unknown_forks(mut self, tolerate: bool) -> Self {
+        self.inner = self.inner.with_unknown_forks(tolerate);
+        self
+    }
+
+    fn with_record_dir(mut self, dir: &Path) -> Self {
+        self.inner = self.inner.with_record_dir(dir);
+        self
+    }
+
+    /// Failures to subscribe are logged, and the requests are then only served by the wrapped RPC.
+    fn with_gossip(self, peers: &[String], forks: &ForkSchedule) -> Self {
+        if let Err(err) = self.start(peers, forks) {
+            warn!(target: "helios::gossip", error = %err, "failed to subscribe to the gossip");
+        }
+        self
+    }
+
+    async fn get_bootstrap(&self, block_root: &'_ [u8]) -> Result<Bootstrap> {
+        self.inner.get_bootstrap(block_root).await
+    }
+
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>> {
+        self.inner.get_updates(start_period, count).await
+    }
+
+    async fn get_finality_update(&self) -> Result<FinalityUpdate> {
+        let requested = self.inner.get_finality_update().await;
+        let gossiped = self.gossiped.lock().unwrap().finality_update.clone();
+        newest(requested, gossiped, |update| {
+            update.attested_header.slot.as_u64()
+        })
+    }
+
+    async fn get_optimistic_update(&self) -> Result<OptimisticUpdate> {
+        let requested = self.inner.get_optimistic_update().await;
+        let gossiped = self.gossiped.lock().unwrap().optimistic_update.clone();
+        newest(requested, gossiped, |update| {
+            update.attested_header.slot.as_u64()
+        })
+    }
+
+    async fn get_block(&self, slot: u64) -> Result<BeaconBlock> {
+        self.inner.get_block(slot).await
+    }
+
+    async fn get_blob_sidecars(&self, slot: u64) -> Result<Vec<BlobSidecar>> {
+        self.inner.get_blob_sidecars(slot).await
+    }
+
+    async fn get_genesis_header(&self) -> Result<Header> {
+        self.inner.get_genesis_header().await
+    }
+
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee> {
+        self.inner.get_genesis_sync_committee().await
+    }
+
+    async fn get_state_proof(&self, state_root: &'_ [u8], gindex: u64) -> Result<StateProof> {
+        self.inner.get_state_proof(state_root, gindex).await
+    }
+
+    async fn get_state_multiproof(
+        &self,
+        state_root: &'_ [u8],
+        gindices: &[u64],
+    ) -> Result<StateMultiproof> {
+        self.inner.get_state_multiproof(state_root, gindices).await
+    }
+
+    async fn chain_id(&self) -> Result<u64> {
+        self.inner.chain_id().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest() {
+        let slot = |slot: &u64| *slot;
+
+        assert_eq!(5, newest(Ok(5), Some(3), slot).unwrap());
+        assert_eq!(7, newest(Ok(5), Some(7), slot).unwrap());
+        assert_eq!(3, newest(Err(eyre!("rpc down")), Some(3), slot).unwrap());
+        assert!(newest(Err(eyre!("rpc down")), None, slot).is_err());
+    }
+}
@@ -1,87 +1,234 @@
-use std::{fs::read_to_string, path::PathBuf};
+//! A mock of the consensus RPC for deterministic tests, available with the `testing` feature.
+//!
+//! The responses are either given to the builder, which does not need any files:
+//!
+//! ```ignore
+//! let rpc = MockRpc::new()
+//!     .with_bootstrap(bootstrap)
+//!     .with_updates(updates)
+//!     .with_finality_update(finality_update);
+//! state_manager.set_rpc_client(rpc);
+//! ```
+//!
+//! or read from a directory of beacon API responses, which is what [`ConsensusRpc::new`] does,
+//! so that a mock can also be created by the clients from the `consensus_rpc` of their config.
+
+use std::{collections::HashMap, fs::read_to_string, path::PathBuf};
 
 use async_trait::async_trait;
-use eyre::Result;
-
-use super::ConsensusRpc;
-use crate::types::{BeaconBlock, Bootstrap, FinalityUpdate, OptimisticUpdate, Update};
+use eyre::{eyre, Result};
+
+use super::{responses::*, ConsensusRpc};
+use crate::{
+    types::{
+        validator::StateProof, BeaconBlock, BlobSidecar, Bootstrap, FinalityUpdate, Header,
+        OptimisticUpdate, SyncCommittee, Update,
+    },
+    utils::calc_sync_period,
+};
+
+#[derive(Debug, Clone, Default)]
 pub struct MockRpc {
-    testdata: PathBuf,
+    testdata: Option<PathBuf>,
+    bootstrap: Option<Bootstrap>,
+    updates: Option<Vec<Update>>,
+    finality_update: Option<FinalityUpdate>,
+    optimistic_update: Option<OptimisticUpdate>,
+    blocks: HashMap<u64, BeaconBlock>,
+    blob_sidecars: HashMap<u64, Vec<BlobSidecar>>,
+    state_proofs: HashMap<u64, StateProof>,
+    chain_id: Option<u64>,
+}
+
+impl MockRpc {
+    /// Creates a mock without any response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the responses that were not given to the builder from `path`.
+    pub fn with_testdata(mut self, path: &str) -> Self {
+        self.testdata = Some(PathBuf::from(path));
+        self
+    }
+
+    pub fn with_bootstrap(mut self, bootstrap: Bootstrap) -> Self {
+        self.bootstrap = Some(bootstrap);
+        self
+    }
+
+    /// Sets the updates, of which those of the requested periods are returned.
+    pub fn with_updates(mut self, updates: Vec<Update>) -> Self {
+        self.updates = Some(updates);
+        self
+    }
+
+    pub fn with_finality_update(mut self, update: FinalityUpdate) -> Self {
+        self.finality_update = Some(update);
+        self
+    }
+
+    pub fn with_optimistic_update(mut self, update: OptimisticUpdate) -> Self {
+        self.optimistic_update = Some(update);
+        self
+    }
+
+    /// Adds a block, which is returned for its slot.
+    pub fn with_block(mut self, block: BeaconBlock) -> Self {
+        self.blocks.insert(block.slot.as_u64(), block);
+        self
+    }
+
+    pub fn with_blob_sidecars(mut self, slot: u64, sidecars: Vec<BlobSidecar>) -> Self {
+        self.blob_sidecars.insert(slot, sidecars);
+        self
+    }
+
+    /// Adds the proof of the leaf at `gindex`, which is returned for any state root.
+    pub fn with_state_proof(mut self, gindex: u64, proof: StateProof) -> Self {
+        self.state_proofs.insert(gindex, proof);
+        self
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    fn read(&self, path: &str) -> Result<String> {
+        let testdata = self
+            .testdata
+            .as_ref()
+            .ok_or_else(|| eyre!("the mock has no response for {}", path))?;
+
+        Ok(read_to_string(testdata.join(path))?)
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl ConsensusRpc for MockRpc {
     fn new(path: &str) -> Self {
-        MockRpc {
-            testdata: PathBuf::from(path),
-        }
+        MockRpc::new().with_testdata(path)
     }
 
     async fn get_bootstrap(&self, _block_root: &'_ [u8]) -> Result<Bootstrap> {
-        let res = read_to_string(self.testdata.join("bootstrap.json"))?;
+        if let Some(bootstrap) = &self.bootstrap {
+            return Ok(bootstrap.clone());
+        }
+
+        let res = self.read("bootstrap.json")?;
         let bootstrap: BootstrapResponse = serde_json::from_str(&res)?;
         Ok(bootstrap.data)
     }
 
-    async fn get_updates(&self, _period: u64, _count: u8) -> Result<Vec<Update>> {
-        let res = read_to_string(self.testdata.join("updates.json"))?;
+    /// The updates of the builder are filtered by period, the updates of the directory are all
+    /// returned whatever the requested periods.
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>> {
+        if let Some(updates) = &self.updates {
+            let periods = start_period..start_period.saturating_add(count);
+            return Ok(updates
+                .iter()
+                .filter(|update| {
+                    periods.contains(&calc_sync_period(update.attested_header.slot.into()))
+                })
+                .cloned()
+                .collect());
+        }
+
+        let res = self.read("updates.json")?;
         let updates: UpdateResponse = serde_json::from_str(&res)?;
         Ok(updates.into_iter().map(|update| update.data).collect())
     }
 
     async fn get_finality_update(&self) -> Result<FinalityUpdate> {
-        let res = read_to_string(self.testdata.join("finality.json"))?;
+        if let Some(update) = &self.finality_update {
+            return Ok(update.clone());
+        }
+
+        let res = self.read("finality.json")?;
         let finality: FinalityUpdateResponse = serde_json::from_str(&res)?;
         Ok(finality.data)
     }
 
     async fn get_optimistic_update(&self) -> Result<OptimisticUpdate> {
-        let res = read_to_string(self.testdata.join("optimistic.json"))?;
+        if let Some(update) = &self.optimistic_update {
+            return Ok(update.clone());
+        }
+
+        let res = self.read("optimistic.json")?;
         let optimistic: OptimisticUpdateResponse = serde_json::from_str(&res)?;
         Ok(optimistic.data)
     }
 
     async fn get_block(&self, slot: u64) -> Result<BeaconBlock> {
-        let path = self.testdata.join(format!("blocks/{}.json", slot));
-        let res = read_to_string(path)?;
+        if let Some(block) = self.blocks.get(&slot) {
+            return Ok(block.clone());
+        }
+
+        let res = self.read(&format!("blocks/{}.json", slot))?;
         let block: BeaconBlockResponse = serde_json::from_str(&res)?;
         Ok(block.data.message)
     }
 
-    async fn chain_id(&self) -> Result<u64> {
-        eyre::bail!("not implemented")
+    async fn get_blob_sidecars(&self, slot: u64) -> Result<Vec<BlobSidecar>> {
+        if let Some(sidecars) = self.blob_sidecars.get(&slot) {
+            return Ok(sidecars.clone());
+        }
+
+        let res = self.read(&format!("blob_sidecars/{}.json", slot))?;
+        let sidecars: BlobSidecarsResponse = serde_json::from_str(&res)?;
+        Ok(sidecars.data)
     }
-}
 
-#[derive(serde::Deserialize, Debug)]
-struct BeaconBlockResponse {
-    data: BeaconBlockData,
-}
+    async fn get_genesis_header(&self) -> Result<Header> {
+        let res = self.read("genesis/header.json")?;
+        let header: HeaderResponse = serde_json::from_str(&res)?;
+        Ok(header.data.header.message)
+    }
 
-#[derive(serde::Deserialize, Debug)]
-struct BeaconBlockData {
-    message: BeaconBlock,
-}
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee> {
+        let res = self.read("genesis/state.json")?;
+        let state: GenesisSyncCommitteeResponse = serde_json::from_str(&res)?;
+        Ok(state.data.current_sync_committee)
+    }
 
-type UpdateResponse = Vec<UpdateData>;
+    async fn get_state_proof(&self, _state_root: &'_ [u8], gindex: u64) -> Result<StateProof> {
+        if let Some(proof) = self.state_proofs.get(&gindex) {
+            return Ok(proof.clone());
+        }
 
-#[derive(serde::Deserialize, Debug)]
-struct UpdateData {
-    data: Update,
-}
+        let res = self.read(&format!("state_proofs/{}.json", gindex))?;
+        let proof: StateProofResponse = serde_json::from_str(&res)?;
+        Ok(proof.data)
+    }
 
-#[derive(serde::Deserialize, Debug)]
-struct FinalityUpdateResponse {
-    data: FinalityUpdate,
+    async fn chain_id(&self) -> Result<u64> {
+        self.chain_id
+            .ok_or_else(|| eyre!("the mock has no chain id"))
+    }
 }
 
-#[derive(serde::Deserialize, Debug)]
-struct OptimisticUpdateResponse {
-    data: OptimisticUpdate,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_rpc_builder() {
+        let testdata = <MockRpc as ConsensusRpc>::new("testdata/");
+        let updates = testdata.get_updates(0, 1).await.unwrap();
+        let bootstrap = testdata.get_bootstrap(&[]).await.unwrap();
 
-#[derive(serde::Deserialize, Debug)]
-struct BootstrapResponse {
-    data: Bootstrap,
+        let rpc = MockRpc::new()
+            .with_bootstrap(bootstrap)
+            .with_updates(updates)
+            .with_chain_id(1);
+
+        rpc.get_bootstrap(&[]).await.unwrap();
+        assert_eq!(2, rpc.get_updates(863, 2).await.unwrap().len());
+        assert_eq!(1, rpc.chain_id().await.unwrap());
+
+        // Responses that were not given to the builder are missing.
+        assert!(rpc.get_finality_update().await.is_err());
+    }
 }
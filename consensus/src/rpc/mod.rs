@@ -1,19 +1,168 @@
+pub mod file_rpc;
+#[cfg(all(feature = "p2p", not(target_arch = "wasm32")))]
+pub mod gossip_rpc;
+#[cfg(any(test, feature = "testing"))]
 pub mod mock_rpc;
 pub mod nimbus_rpc;
+pub mod portal_rpc;
+pub mod provider;
+pub mod rate_limit;
+pub mod recording_rpc;
+pub(crate) mod responses;
+pub mod retry;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tls;
+
+use std::{cmp, collections::BTreeMap, path::Path};
 
 use async_trait::async_trait;
+pub use config::{fork_schedule::ForkSchedule, RpcCredentials, TlsConfig};
 use eyre::Result;
+use futures::future::try_join_all;
+use rate_limit::RateLimit;
+use retry::RetryPolicy;
 
-use crate::types::{BeaconBlock, Bootstrap, FinalityUpdate, OptimisticUpdate, Update};
+use crate::{
+    constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES,
+    types::{
+        multiproof::StateMultiproof, validator::StateProof, BeaconBlock, BlobSidecar, Bootstrap,
+        FinalityUpdate, Header, OptimisticUpdate, SyncCommittee, Update,
+    },
+    utils::calc_sync_period,
+};
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 pub trait ConsensusRpc: Send + Sync {
     fn new(path: &str) -> Self;
+    /// Sets the retry policy of the requests. RPCs that do not send requests ignore it.
+    fn with_retry_policy(self, _policy: RetryPolicy) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Limits the rate of the requests. RPCs that do not send requests ignore it.
+    fn with_rate_limit(self, _limit: RateLimit) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Sends `credentials` with every request. RPCs that do not send requests ignore them.
+    fn with_credentials(self, _credentials: RpcCredentials) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Sets the TLS configuration of the requests. RPCs that do not send requests, and the
+    /// browser, where TLS is handled by the browser itself, ignore it.
+    fn with_tls(self, _tls: TlsConfig) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Sends the requests through `proxy`. RPCs that do not send requests, and the browser,
+    /// ignore it.
+    fn with_proxy(self, _proxy: &str) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Parses the responses of forks that are not modeled yet with the types of the latest known
+    /// fork, instead of failing with [`ConsensusError::UnsupportedFork`]. Responses that do not
+    /// parse still fail with it. RPCs that do not send requests ignore it.
+    ///
+    /// [`ConsensusError::UnsupportedFork`]: crate::errors::ConsensusError::UnsupportedFork
+    fn with_unknown_forks(self, _tolerate: bool) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Records the responses to `dir`. Only [`recording_rpc::RecordingRpc`] records them.
+    fn with_record_dir(self, _dir: &Path) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+    /// Also receives the finality and optimistic updates of the forks of `forks` from the gossip
+    /// of `peers`. Only the `GossipRpc` of the `p2p` feature subscribes to the gossip.
+    fn with_gossip(self, _peers: &[String], _forks: &ForkSchedule) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
     async fn get_bootstrap(&self, block_root: &'_ [u8]) -> Result<Bootstrap>;
-    async fn get_updates(&self, period: u64, count: u8) -> Result<Vec<Update>>;
+    /// Returns the updates of the `count` periods from `start_period`, in period order. Ranges
+    /// longer than [`MAX_REQUEST_LIGHT_CLIENT_UPDATES`] are fetched in several requests.
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>>;
     async fn get_finality_update(&self) -> Result<FinalityUpdate>;
     async fn get_optimistic_update(&self) -> Result<OptimisticUpdate>;
     async fn get_block(&self, slot: u64) -> Result<BeaconBlock>;
+    async fn get_blob_sidecars(&self, slot: u64) -> Result<Vec<BlobSidecar>>;
+    /// Returns the header of the genesis block, which is not verified.
+    async fn get_genesis_header(&self) -> Result<Header>;
+    /// Returns the current sync committee of the genesis state, which is not verified.
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee>;
+    /// Returns the proof of the leaf at `gindex` of the beacon state with the given root.
+    async fn get_state_proof(&self, state_root: &'_ [u8], gindex: u64) -> Result<StateProof>;
+    /// Returns the multiproof of the leaves at `gindices` of the beacon state with the given
+    /// root. RPCs that cannot serve multiproofs build it from the single proofs of the leaves.
+    async fn get_state_multiproof(
+        &self,
+        state_root: &'_ [u8],
+        gindices: &[u64],
+    ) -> Result<StateMultiproof> {
+        let proofs_fut = gindices
+            .iter()
+            .map(|gindex| self.get_state_proof(state_root, *gindex));
+        let proofs = try_join_all(proofs_fut).await?;
+
+        StateMultiproof::from_proofs(gindices, &proofs)
+    }
     async fn chain_id(&self) -> Result<u64>;
 }
+
+/// Splits the `count` periods from `start_period` into `(start_period, count)` requests of at most
+/// [`MAX_REQUEST_LIGHT_CLIENT_UPDATES`] periods.
+pub(crate) fn update_batches(start_period: u64, count: u64) -> impl Iterator<Item = (u64, u64)> {
+    let end_period = start_period.saturating_add(count);
+    (start_period..end_period)
+        .step_by(MAX_REQUEST_LIGHT_CLIENT_UPDATES as usize)
+        .map(move |period| {
+            let count = cmp::min(MAX_REQUEST_LIGHT_CLIENT_UPDATES, end_period - period);
+            (period, count)
+        })
+}
+
+/// Keeps the first update of every period, in period order, so that the responses of
+/// overlapping requests can be stitched together.
+pub(crate) fn dedup_updates(updates: Vec<Update>) -> Vec<Update> {
+    let mut by_period: BTreeMap<u64, Update> = BTreeMap::new();
+    for update in updates {
+        let period = calc_sync_period(update.attested_header.slot.into());
+        by_period.entry(period).or_insert(update);
+    }
+
+    by_period.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_batches() {
+        let batches = update_batches(10, 300).collect::<Vec<_>>();
+        assert_eq!(vec![(10, 128), (138, 128), (266, 44)], batches);
+
+        assert_eq!(vec![(5, 1)], update_batches(5, 1).collect::<Vec<_>>());
+        assert_eq!(0, update_batches(5, 0).count());
+    }
+}
@@ -1,27 +1,200 @@
-use std::cmp;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use common::errors::RpcError;
 use eyre::Result;
-use retri::{retry, BackoffSettings};
 use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::warn;
 
-use super::ConsensusRpc;
-use crate::{constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES, types::*};
+use super::{
+    dedup_updates,
+    provider::{self, ApiError},
+    rate_limit::{RateLimit, RateLimiter},
+    responses::*,
+    retry::{with_retry, RetryPolicy, Transient},
+    update_batches, ConsensusRpc, RpcCredentials,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use super::{tls, TlsConfig};
+use crate::{
+    errors::ConsensusError,
+    types::{multiproof::StateMultiproof, validator::StateProof, *},
+};
+
+/// The value of the credentials in the URLs of errors.
+const REDACTED: &str = "REDACTED";
 
 #[derive(Debug, Clone, Default)]
 pub struct NimbusRpc {
     rpc: String,
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    credentials: RpcCredentials,
+    /// Whether the responses of unknown forks are parsed as the latest known fork.
+    tolerate_unknown_forks: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    tls: Option<Arc<TlsConfig>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<String>,
+    /// The error of building the client of [`Self::tls`] and [`Self::proxy`], which fails every
+    /// request.
+    #[cfg(not(target_arch = "wasm32"))]
+    client_error: Option<String>,
 }
 
-async fn get<R: DeserializeOwned>(req: &str) -> Result<R> {
-    let bytes = retry(
-        || async { Ok::<_, eyre::Report>(reqwest::get(req).await?.bytes().await?) },
-        BackoffSettings::default(),
-    )
-    .await?;
+/// The errors of a request to the endpoint.
+#[derive(Debug, thiserror::Error)]
+enum RequestError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Api(#[from] ApiError),
+}
 
-    Ok(serde_json::from_slice::<R>(&bytes)?)
+impl Transient for RequestError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RequestError::Http(err) => err.is_transient(),
+            RequestError::Api(err) => err.is_transient(),
+        }
+    }
+}
+
+impl NimbusRpc {
+    async fn get<R: DeserializeOwned>(&self, req: &str) -> Result<R> {
+        Ok(serde_json::from_value(self.get_json(req).await?)?)
+    }
+
+    /// Fetches the light client data or block of `req`, and parses it. A response of a fork that
+    /// is not modeled fails with [`ConsensusError::UnsupportedFork`], with the response, unless
+    /// unknown forks are tolerated and it parses as a known fork.
+    async fn get_versioned<R: DeserializeOwned>(&self, req: &str, method: &str) -> Result<R> {
+        let mut value = self
+            .get_json(req)
+            .await
+            .map_err(|e| RpcError::new(method, e))?;
+        let mut known = provider::LIGHT_CLIENT_VERSIONS.as_slice();
+        if method == "updates" {
+            value = provider::normalize_updates(value);
+        } else if method == "blocks" {
+            provider::retain_known_block_fields(&mut value);
+            known = provider::BLOCK_VERSIONS.as_slice();
+        }
+
+        let Some(version) = provider::unknown_fork(&value, known) else {
+            return Ok(serde_json::from_value(value).map_err(|e| RpcError::new(method, e))?);
+        };
+
+        let raw = serde_json::to_vec(&value)?;
+        if !self.tolerate_unknown_forks {
+            return Err(ConsensusError::UnsupportedFork(version, raw).into());
+        }
+
+        warn!(target: "helios::consensus", method, version = %version, "parsing a response of an unknown fork");
+        serde_json::from_value(value)
+            .map_err(|_| ConsensusError::UnsupportedFork(version, raw).into())
+    }
+
+    /// Fetches the JSON of `req`, normalized with [`provider::normalize`].
+    async fn get_json(&self, req: &str) -> Result<Value> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(err) = &self.client_error {
+            return Err(eyre::eyre!("invalid rpc client configuration: {}", err));
+        }
+
+        let (bytes, _certificate) = with_retry(&self.retry_policy, || async move {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            self.send(req).await
+        })
+        .await?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(tls) = &self.tls {
+            tls::verify_pinned_certificate(tls, _certificate.as_deref())?;
+        }
+
+        let mut value = serde_json::from_slice::<Value>(&bytes)?;
+        provider::normalize(&mut value);
+        Ok(value)
+    }
+
+    /// Sends a request to `req`, and returns the body of the response with the certificate of
+    /// the server, when it is needed to check the pinned certificates.
+    async fn send(&self, req: &str) -> Result<(bytes::Bytes, Option<Vec<u8>>), RequestError> {
+        let mut request = self.client.get(req).query(&self.credentials.query);
+        for (name, value) in &self.credentials.headers {
+            request = request.header(name, value);
+        }
+
+        let res = request.send().await.map_err(|err| self.redact(err))?;
+        let status = res.status();
+        if status.is_client_error() || status.is_server_error() {
+            let body = res.bytes().await.unwrap_or_default();
+            return Err(ApiError::new(status, &body).into());
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let certificate = tls::peer_certificate(&res);
+        #[cfg(target_arch = "wasm32")]
+        let certificate = None;
+
+        let bytes = res.bytes().await.map_err(|err| self.redact(err))?;
+        Ok((bytes, certificate))
+    }
+
+    /// Rebuilds the HTTP client with the TLS configuration and the proxy of the endpoint.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn rebuild_client(&mut self) {
+        let build = || {
+            let mut builder = reqwest::Client::builder();
+            if let Some(tls) = &self.tls {
+                builder = tls::configure(builder, tls)?;
+            }
+            if let Some(proxy) = &self.proxy {
+                builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+            }
+
+            Ok::<_, eyre::Report>(builder.build()?)
+        };
+
+        match build() {
+            Ok(client) => {
+                self.client = client;
+                self.client_error = None;
+            }
+            Err(err) => self.client_error = Some(err.to_string()),
+        }
+    }
+
+    /// Removes the credentials from the URL of `err`, which is part of its message.
+    fn redact(&self, mut err: reqwest::Error) -> reqwest::Error {
+        if let Some(url) = err.url_mut() {
+            let _ = url.set_password(None);
+
+            if !self.credentials.query.is_empty() {
+                let pairs = url
+                    .query_pairs()
+                    .map(|(key, value)| {
+                        let value = if self.credentials.query.contains_key(key.as_ref()) {
+                            REDACTED.to_string()
+                        } else {
+                            value.into_owned()
+                        };
+                        (key.into_owned(), value)
+                    })
+                    .collect::<Vec<_>>();
+
+                url.query_pairs_mut().clear().extend_pairs(pairs);
+            }
+        }
+
+        err
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -30,9 +203,54 @@ impl ConsensusRpc for NimbusRpc {
     fn new(rpc: &str) -> Self {
         NimbusRpc {
             rpc: rpc.to_string(),
+            client: reqwest::Client::new(),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            credentials: RpcCredentials::default(),
+            tolerate_unknown_forks: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            tls: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            client_error: None,
         }
     }
 
+    fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    fn with_credentials(mut self, credentials: RpcCredentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(Arc::new(tls));
+        self.rebuild_client();
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self.rebuild_client();
+        self
+    }
+
+    fn with_unknown_forks(mut self, tolerate: bool) -> Self {
+        self.tolerate_unknown_forks = tolerate;
+        self
+    }
+
     async fn get_bootstrap(&self, block_root: &'_ [u8]) -> Result<Bootstrap> {
         let root_hex = hex::encode(block_root);
         let req = format!(
@@ -40,95 +258,134 @@ impl ConsensusRpc for NimbusRpc {
             self.rpc, root_hex
         );
 
-        let res: BootstrapResponse = get(&req).await.map_err(|e| RpcError::new("bootstrap", e))?;
+        let res: BootstrapResponse = self.get_versioned(&req, "bootstrap").await?;
 
         Ok(res.data)
     }
 
-    async fn get_updates(&self, period: u64, count: u8) -> Result<Vec<Update>> {
-        let count = cmp::min(count, MAX_REQUEST_LIGHT_CLIENT_UPDATES);
-        let req = format!(
-            "{}/eth/v1/beacon/light_client/updates?start_period={}&count={}",
-            self.rpc, period, count
-        );
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>> {
+        let mut updates = Vec::new();
+        for (period, count) in update_batches(start_period, count) {
+            let req = format!(
+                "{}/eth/v1/beacon/light_client/updates?start_period={}&count={}",
+                self.rpc, period, count
+            );
+
+            let res: UpdateResponse = self.get_versioned(&req, "updates").await?;
+            let is_partial = (res.len() as u64) < count;
+            updates.extend(res.into_iter().map(|d| d.data));
 
-        let res: UpdateResponse = get(&req).await.map_err(|e| RpcError::new("updates", e))?;
+            // The node has no updates past the last one it returned.
+            if is_partial {
+                break;
+            }
+        }
 
-        Ok(res.into_iter().map(|d| d.data).collect())
+        Ok(dedup_updates(updates))
     }
 
     async fn get_finality_update(&self) -> Result<FinalityUpdate> {
         let req = format!("{}/eth/v1/beacon/light_client/finality_update", self.rpc);
-        let res: FinalityUpdateResponse = get(&req)
-            .await
-            .map_err(|e| RpcError::new("finality_update", e))?;
+        let res: FinalityUpdateResponse = self.get_versioned(&req, "finality_update").await?;
 
         Ok(res.data)
     }
 
     async fn get_optimistic_update(&self) -> Result<OptimisticUpdate> {
         let req = format!("{}/eth/v1/beacon/light_client/optimistic_update", self.rpc);
-        let res: OptimisticUpdateResponse = get(&req)
-            .await
-            .map_err(|e| RpcError::new("optimistic_update", e))?;
+        let res: OptimisticUpdateResponse = self.get_versioned(&req, "optimistic_update").await?;
 
         Ok(res.data)
     }
 
     async fn get_block(&self, slot: u64) -> Result<BeaconBlock> {
         let req = format!("{}/eth/v2/beacon/blocks/{}", self.rpc, slot);
-        let res: BeaconBlockResponse = get(&req).await.map_err(|e| RpcError::new("blocks", e))?;
+        let res: BeaconBlockResponse = self.get_versioned(&req, "blocks").await?;
 
         Ok(res.data.message)
     }
 
-    async fn chain_id(&self) -> Result<u64> {
-        let req = format!("{}/eth/v1/config/spec", self.rpc);
-        let res: SpecResponse = get(&req).await.map_err(|e| RpcError::new("spec", e))?;
+    async fn get_blob_sidecars(&self, slot: u64) -> Result<Vec<BlobSidecar>> {
+        let req = format!("{}/eth/v1/beacon/blob_sidecars/{}", self.rpc, slot);
+        let res: BlobSidecarsResponse = self
+            .get(&req)
+            .await
+            .map_err(|e| RpcError::new("blob_sidecars", e))?;
 
-        Ok(res.data.chain_id.into())
+        Ok(res.data)
     }
-}
 
-#[derive(serde::Deserialize, Debug)]
-struct BeaconBlockResponse {
-    data: BeaconBlockData,
-}
+    async fn get_genesis_header(&self) -> Result<Header> {
+        let req = format!("{}/eth/v1/beacon/headers/genesis", self.rpc);
+        let res: HeaderResponse = self
+            .get(&req)
+            .await
+            .map_err(|e| RpcError::new("genesis_header", e))?;
 
-#[derive(serde::Deserialize, Debug)]
-struct BeaconBlockData {
-    message: BeaconBlock,
-}
+        Ok(res.data.header.message)
+    }
+
+    /// The sync committee is read from the genesis state, of which the other fields are ignored.
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee> {
+        let req = format!("{}/eth/v2/debug/beacon/states/genesis", self.rpc);
+        let res: GenesisSyncCommitteeResponse = self
+            .get(&req)
+            .await
+            .map_err(|e| RpcError::new("genesis_state", e))?;
 
-type UpdateResponse = Vec<UpdateData>;
+        Ok(res.data.current_sync_committee)
+    }
 
-#[derive(serde::Deserialize, Debug)]
-struct UpdateData {
-    data: Update,
-}
+    /// State proofs are not part of the standard beacon API, they are served by proof-serving
+    /// beacon nodes, such as Lodestar, under `/eth/v0/beacon/proof/state`.
+    async fn get_state_proof(&self, state_root: &'_ [u8], gindex: u64) -> Result<StateProof> {
+        let req = format!(
+            "{}/eth/v0/beacon/proof/state/0x{}?gindex={}",
+            self.rpc,
+            hex::encode(state_root),
+            gindex
+        );
+        let res: StateProofResponse = self
+            .get(&req)
+            .await
+            .map_err(|e| RpcError::new("state_proof", e))?;
 
-#[derive(serde::Deserialize, Debug)]
-struct FinalityUpdateResponse {
-    data: FinalityUpdate,
-}
+        Ok(res.data)
+    }
 
-#[derive(serde::Deserialize, Debug)]
-struct OptimisticUpdateResponse {
-    data: OptimisticUpdate,
-}
+    /// Multiproofs are served on the same endpoint as the state proofs, with several indices.
+    async fn get_state_multiproof(
+        &self,
+        state_root: &'_ [u8],
+        gindices: &[u64],
+    ) -> Result<StateMultiproof> {
+        let gindices_param = gindices
+            .iter()
+            .map(|gindex| gindex.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let req = format!(
+            "{}/eth/v0/beacon/proof/state/0x{}?gindices={}",
+            self.rpc,
+            hex::encode(state_root),
+            gindices_param
+        );
+        let res: StateMultiproofResponse = self
+            .get(&req)
+            .await
+            .map_err(|e| RpcError::new("state_multiproof", e))?;
 
-#[derive(serde::Deserialize, Debug)]
-struct BootstrapResponse {
-    data: Bootstrap,
-}
+        if res.data.gindices != gindices {
+            return Err(RpcError::new("state_multiproof", "unexpected generalized indices").into());
+        }
 
-#[derive(serde::Deserialize, Debug)]
-struct SpecResponse {
-    data: Spec,
-}
+        Ok(res.data)
+    }
 
-#[derive(serde::Deserialize, Debug)]
-struct Spec {
-    #[serde(rename = "DEPOSIT_NETWORK_ID")]
-    chain_id: primitives::U64,
+    async fn chain_id(&self) -> Result<u64> {
+        let req = format!("{}/eth/v1/config/spec", self.rpc);
+        let res: SpecResponse = self.get(&req).await.map_err(|e| RpcError::new("spec", e))?;
+
+        Ok(res.data.chain_id.into())
+    }
 }
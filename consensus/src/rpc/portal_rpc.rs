@@ -0,0 +1,271 @@
+//! An RPC that fetches the light client data from the beacon subnetwork of the
+//! [Portal network](https://github.com/ethereum/portal-network-specs/blob/master/beacon-chain/beacon-network.md),
+//! through the JSON-RPC API of a Portal client such as Trin, Fluffy or Ultralight, so that the
+//! client can sync when the centralized beacon API providers are unavailable.
+//!
+//! The Portal network only stores the light client data, so the blocks, the blobs and the state
+//! proofs are not served. The finality and optimistic updates are looked up from the latest slots
+//! seen by the RPC, since their content keys hold the slot from which an update is wanted. Only
+//! the SSZ layouts of Capella and Deneb are decoded, see [`light_client_ssz`].
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use common::errors::RpcError;
+use eyre::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    dedup_updates,
+    rate_limit::{RateLimit, RateLimiter},
+    retry::{with_retry, RetryPolicy},
+    update_batches, ConsensusRpc,
+};
+use crate::{
+    errors::ConsensusError,
+    types::{light_client_ssz, multiproof::StateMultiproof, validator::StateProof, *},
+};
+
+const BOOTSTRAP_SELECTOR: u8 = 0x10;
+const UPDATES_BY_RANGE_SELECTOR: u8 = 0x11;
+const FINALITY_UPDATE_SELECTOR: u8 = 0x12;
+const OPTIMISTIC_UPDATE_SELECTOR: u8 = 0x13;
+
+/// The length of the fork digest that prefixes every content value.
+const FORK_DIGEST_LEN: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct PortalRpc {
+    rpc: String,
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// The latest finalized slot seen, from which the next finality update is looked up.
+    finalized_slot: Arc<AtomicU64>,
+    /// The latest signature slot seen, from which the next optimistic update is looked up.
+    signature_slot: Arc<AtomicU64>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    result: Option<ContentResult>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize)]
+struct ContentResult {
+    content: String,
+}
+
+#[derive(Debug, Deserialize, thiserror::Error)]
+#[error("portal error {code}: {message}")]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+impl PortalRpc {
+    /// Looks up the content of `content_key` in the network, and returns its value without the
+    /// fork digest prefix.
+    async fn get_content(&self, content_key: &[u8], method: &str) -> Result<Vec<u8>> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "portal_beaconGetContent",
+            "params": [format!("0x{}", hex::encode(content_key))],
+        });
+
+        let res = with_retry(&self.retry_policy, || async {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            self.client
+                .post(&self.rpc)
+                .json(&body)
+                .send()
+                .await?
+                .json::<JsonRpcResponse>()
+                .await
+        })
+        .await
+        .map_err(|e| RpcError::new(method, e))?;
+
+        let result = match (res.result, res.error) {
+            (Some(result), _) => result,
+            (None, Some(err)) => return Err(RpcError::new(method, err).into()),
+            (None, None) => return Err(RpcError::new(method, "empty response").into()),
+        };
+
+        let content = hex::decode(result.content.trim_start_matches("0x"))?;
+        strip_fork_digest(content)
+    }
+
+    fn see_finalized_slot(&self, slot: u64) {
+        self.finalized_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+}
+
+/// Removes the fork digest that prefixes a content value. The data is verified by the client, so
+/// the digest is not checked.
+fn strip_fork_digest(mut content: Vec<u8>) -> Result<Vec<u8>> {
+    if content.len() < FORK_DIGEST_LEN {
+        return Err(
+            ConsensusError::InvalidLightClientSsz("missing fork digest".to_string()).into(),
+        );
+    }
+
+    Ok(content.split_off(FORK_DIGEST_LEN))
+}
+
+fn content_key(selector: u8, fields: &[u64]) -> Vec<u8> {
+    let mut key = vec![selector];
+    for field in fields {
+        key.extend_from_slice(&field.to_le_bytes());
+    }
+    key
+}
+
+fn not_served(method: &str) -> eyre::Report {
+    RpcError::new(method, "not served by the portal network").into()
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ConsensusRpc for PortalRpc {
+    /// `rpc` is the URL of the JSON-RPC API of the Portal client.
+    fn new(rpc: &str) -> Self {
+        PortalRpc {
+            rpc: rpc.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    async fn get_bootstrap(&self, block_root: &'_ [u8]) -> Result<Bootstrap> {
+        let mut key = vec![BOOTSTRAP_SELECTOR];
+        key.extend_from_slice(block_root);
+
+        let content = self.get_content(&key, "bootstrap").await?;
+        let bootstrap = light_client_ssz::decode_bootstrap(&content)?;
+        self.see_finalized_slot(bootstrap.header.slot.as_u64());
+
+        Ok(bootstrap)
+    }
+
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>> {
+        let mut updates = Vec::new();
+        for (period, count) in update_batches(start_period, count) {
+            let key = content_key(UPDATES_BY_RANGE_SELECTOR, &[period, count]);
+            let content = self.get_content(&key, "updates").await?;
+
+            let items = light_client_ssz::split_list(&content)?;
+            let is_partial = (items.len() as u64) < count;
+            for item in items {
+                let update = light_client_ssz::decode_update(&strip_fork_digest(item.to_vec())?)?;
+                self.see_finalized_slot(update.finalized_header.slot.as_u64());
+                updates.push(update);
+            }
+
+            if is_partial {
+                break;
+            }
+        }
+
+        Ok(dedup_updates(updates))
+    }
+
+    async fn get_finality_update(&self) -> Result<FinalityUpdate> {
+        let slot = self.finalized_slot.load(Ordering::Relaxed);
+        let key = content_key(FINALITY_UPDATE_SELECTOR, &[slot]);
+        let content = self.get_content(&key, "finality_update").await?;
+
+        let update = light_client_ssz::decode_finality_update(&content)?;
+        self.see_finalized_slot(update.finalized_header.slot.as_u64());
+        Ok(update)
+    }
+
+    async fn get_optimistic_update(&self) -> Result<OptimisticUpdate> {
+        let slot = self.signature_slot.load(Ordering::Relaxed);
+        let key = content_key(OPTIMISTIC_UPDATE_SELECTOR, &[slot]);
+        let content = self.get_content(&key, "optimistic_update").await?;
+
+        let update = light_client_ssz::decode_optimistic_update(&content)?;
+        self.signature_slot
+            .fetch_max(update.signature_slot.as_u64(), Ordering::Relaxed);
+        Ok(update)
+    }
+
+    async fn get_block(&self, _slot: u64) -> Result<BeaconBlock> {
+        Err(not_served("blocks"))
+    }
+
+    async fn get_blob_sidecars(&self, _slot: u64) -> Result<Vec<BlobSidecar>> {
+        Err(not_served("blob_sidecars"))
+    }
+
+    async fn get_genesis_header(&self) -> Result<Header> {
+        Err(not_served("genesis_header"))
+    }
+
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee> {
+        Err(not_served("genesis_state"))
+    }
+
+    async fn get_state_proof(&self, _state_root: &'_ [u8], _gindex: u64) -> Result<StateProof> {
+        Err(not_served("state_proof"))
+    }
+
+    async fn get_state_multiproof(
+        &self,
+        _state_root: &'_ [u8],
+        _gindices: &[u64],
+    ) -> Result<StateMultiproof> {
+        Err(not_served("state_multiproof"))
+    }
+
+    async fn chain_id(&self) -> Result<u64> {
+        Err(not_served("spec"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_key() {
+        assert_eq!(
+            [
+                vec![0x11],
+                5u64.to_le_bytes().to_vec(),
+                2u64.to_le_bytes().to_vec()
+            ]
+            .concat(),
+            content_key(UPDATES_BY_RANGE_SELECTOR, &[5, 2])
+        );
+        assert_eq!(vec![0x13], content_key(OPTIMISTIC_UPDATE_SELECTOR, &[]));
+    }
+
+    #[test]
+    fn test_strip_fork_digest() {
+        assert_eq!(
+            vec![5, 6],
+            strip_fork_digest(vec![1, 2, 3, 4, 5, 6]).unwrap()
+        );
+        assert!(strip_fork_digest(vec![1, 2]).is_err());
+    }
+}
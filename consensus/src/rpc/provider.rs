@@ -0,0 +1,291 @@
+//! Normalization of the responses of the beacon API implementations, which differ in how they
+//! encode integers, fork names, lists of updates and errors, and in the fields that they add
+//! ahead of the spec, so that the types of this crate parse the responses of every provider.
+
+use std::str::FromStr;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use super::retry::{is_transient_status, Transient};
+use crate::types::{
+    BeaconBlockBodyBellatrix, BeaconBlockBodyCapella, BeaconBlockBodyDeneb, BeaconBlockBodyElectra,
+    BeaconBlockType, ExecutionPayloadBellatrix, ExecutionPayloadCapella, ExecutionPayloadDeneb,
+};
+
+/// The versions of the light client data that the types of this crate parse. The branches of the
+/// Electra light client data are deeper, so Electra is not one of them.
+pub const LIGHT_CLIENT_VERSIONS: [&str; 4] = ["altair", "bellatrix", "capella", "deneb"];
+
+/// The versions of the blocks that the types of this crate parse.
+pub const BLOCK_VERSIONS: [&str; 5] = ["altair", "bellatrix", "capella", "deneb", "electra"];
+
+/// The maximum length of the message of an [`ApiError`] with a plain text body.
+const MAX_ERROR_MESSAGE_LEN: usize = 256;
+
+/// Normalizes a response: integers that are JSON numbers are turned into the decimal strings of
+/// the spec, and fork names are lowercased.
+pub fn normalize(value: &mut Value) {
+    match value {
+        Value::Number(number) => *value = Value::String(number.to_string()),
+        Value::Array(items) => items.iter_mut().for_each(normalize),
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                match field {
+                    Value::String(version) if key == "version" => {
+                        *version = version.to_lowercase();
+                    }
+                    field => normalize(field),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the version of a response, or of one of the items of a list response, that is not one
+/// of the `known` versions.
+pub fn unknown_fork(value: &Value, known: &[&str]) -> Option<String> {
+    match value {
+        Value::Array(items) => items.iter().find_map(|item| unknown_fork(item, known)),
+        Value::Object(fields) => fields
+            .get("version")
+            .and_then(Value::as_str)
+            .filter(|version| !known.contains(version))
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Returns the updates of a response of `/eth/v1/beacon/light_client/updates` as a list of
+/// `{ "data": update }`, whether it is a list of versioned updates, a list of bare updates or an
+/// object with a `data` list.
+pub fn normalize_updates(value: Value) -> Value {
+    let updates = match value {
+        Value::Array(updates) => updates,
+        Value::Object(mut fields) => match fields.remove("data") {
+            Some(Value::Array(updates)) => updates,
+            Some(data) => {
+                fields.insert("data".to_string(), data);
+                return Value::Object(fields);
+            }
+            None => return Value::Object(fields),
+        },
+        value => return value,
+    };
+
+    let updates = updates
+        .into_iter()
+        .map(|update| {
+            if update.get("data").is_some() {
+                update
+            } else {
+                json!({ "data": update })
+            }
+        })
+        .collect();
+
+    Value::Array(updates)
+}
+
+/// Removes the fields of the body of a block response that the types of its fork do not define.
+/// The bodies are parsed with `deny_unknown_fields` to tell the forks apart, so extra fields of
+/// a provider would otherwise fail the parsing. Blocks of unknown forks are left as they are.
+pub fn retain_known_block_fields(value: &mut Value) {
+    let fork = value
+        .get("version")
+        .and_then(Value::as_str)
+        .and_then(|version| BeaconBlockType::from_str(version).ok());
+
+    let (Some(fork), Some(body)) = (fork, value.pointer_mut("/data/message/body")) else {
+        return;
+    };
+
+    retain_known_fields(body, &body_schema(fork));
+}
+
+/// Returns the JSON of an empty body of `fork`, of which the fields are the known ones.
+fn body_schema(fork: BeaconBlockType) -> Value {
+    let (body, payload) = match fork {
+        BeaconBlockType::Bellatrix => (
+            serde_json::to_value(BeaconBlockBodyBellatrix::default()),
+            serde_json::to_value(ExecutionPayloadBellatrix::default()),
+        ),
+        BeaconBlockType::Capella => (
+            serde_json::to_value(BeaconBlockBodyCapella::default()),
+            serde_json::to_value(ExecutionPayloadCapella::default()),
+        ),
+        BeaconBlockType::Deneb => (
+            serde_json::to_value(BeaconBlockBodyDeneb::default()),
+            serde_json::to_value(ExecutionPayloadDeneb::default()),
+        ),
+        BeaconBlockType::Electra => (
+            serde_json::to_value(BeaconBlockBodyElectra::default()),
+            serde_json::to_value(ExecutionPayloadDeneb::default()),
+        ),
+    };
+
+    // The default payload of a body is a Bellatrix one, whatever the fork of the body.
+    let mut body = body.expect("an empty body serializes");
+    body["execution_payload"] = payload.expect("an empty payload serializes");
+    body
+}
+
+fn retain_known_fields(value: &mut Value, schema: &Value) {
+    if let (Value::Object(fields), Value::Object(known)) = (value, schema) {
+        fields.retain(|key, _| known.contains_key(key));
+        for (key, field) in fields.iter_mut() {
+            retain_known_fields(field, &known[key]);
+        }
+    }
+}
+
+/// An error response of the beacon API. Providers return `{ "code", "message" }`,
+/// `{ "statusCode", "error", "message" }` or plain text bodies, of which the message is kept.
+#[derive(Debug, Error)]
+#[error("beacon api error {status}: {message}")]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, body: &[u8]) -> Self {
+        let message = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Object(fields)) => ["message", "error"]
+                .iter()
+                .find_map(|key| fields.get(*key).and_then(Value::as_str))
+                .map(str::to_string),
+            _ => None,
+        };
+
+        let message = message.unwrap_or_else(|| {
+            String::from_utf8_lossy(body)
+                .trim()
+                .chars()
+                .take(MAX_ERROR_MESSAGE_LEN)
+                .collect()
+        });
+
+        Self { status, message }
+    }
+}
+
+impl Transient for ApiError {
+    fn is_transient(&self) -> bool {
+        is_transient_status(self.status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, path::PathBuf};
+
+    use super::*;
+    use crate::types::{BeaconBlock, BeaconBlockBody};
+
+    #[test]
+    fn test_normalize() {
+        let mut value =
+            json!({ "version": "Deneb", "data": { "slot": 7109344, "items": [1, "2"] } });
+        normalize(&mut value);
+
+        assert_eq!(
+            json!({ "version": "deneb", "data": { "slot": "7109344", "items": ["1", "2"] } }),
+            value
+        );
+    }
+
+    #[test]
+    fn test_unknown_fork() {
+        assert_eq!(
+            None,
+            unknown_fork(
+                &json!({ "version": "deneb", "data": {} }),
+                &LIGHT_CLIENT_VERSIONS
+            )
+        );
+        assert_eq!(
+            None,
+            unknown_fork(&json!({ "data": {} }), &LIGHT_CLIENT_VERSIONS)
+        );
+        assert_eq!(
+            Some("electra".to_string()),
+            unknown_fork(
+                &json!({ "version": "electra", "data": {} }),
+                &LIGHT_CLIENT_VERSIONS
+            )
+        );
+        assert_eq!(
+            Some("electra".to_string()),
+            unknown_fork(
+                &json!([
+                    { "version": "deneb", "data": {} },
+                    { "version": "electra", "data": {} }
+                ]),
+                &LIGHT_CLIENT_VERSIONS
+            )
+        );
+        assert_eq!(
+            None,
+            unknown_fork(
+                &json!({ "version": "electra", "data": {} }),
+                &BLOCK_VERSIONS
+            )
+        );
+        assert_eq!(
+            Some("fulu".to_string()),
+            unknown_fork(&json!({ "version": "fulu", "data": {} }), &BLOCK_VERSIONS)
+        );
+    }
+
+    #[test]
+    fn test_normalize_updates() {
+        let update = json!({ "signature_slot": "1" });
+        let expected = json!([{ "data": update }]);
+
+        assert_eq!(expected, normalize_updates(json!([update])));
+        assert_eq!(expected, normalize_updates(json!({ "data": [update] })));
+        assert_eq!(
+            json!([{ "version": "deneb", "data": update }]),
+            normalize_updates(json!([{ "version": "deneb", "data": update }]))
+        );
+    }
+
+    #[test]
+    fn test_retain_known_block_fields() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/blocks/7109344.json");
+        let mut value: Value = serde_json::from_str(&read_to_string(path).unwrap()).unwrap();
+        value["data"]["message"]["body"]["execution_requests"] = json!([]);
+        value["data"]["message"]["body"]["execution_payload"]["deposit_receipts"] = json!([]);
+
+        let parse =
+            |value: &Value| serde_json::from_value::<BeaconBlock>(value["data"]["message"].clone());
+        assert!(parse(&value).is_err());
+
+        retain_known_block_fields(&mut value);
+        let block = parse(&value).unwrap();
+        assert!(matches!(block.body, BeaconBlockBody::Capella(_)));
+    }
+
+    #[test]
+    fn test_api_error() {
+        let status = StatusCode::NOT_FOUND;
+
+        let error = ApiError::new(status, br#"{"code":404,"message":"Block not found"}"#);
+        assert_eq!("Block not found", error.message);
+
+        let error = ApiError::new(
+            status,
+            br#"{"statusCode":404,"error":"Not Found","message":"No block"}"#,
+        );
+        assert_eq!("No block", error.message);
+
+        let error = ApiError::new(status, b" not found\n");
+        assert_eq!("not found", error.message);
+
+        assert!(!error.is_transient());
+        assert!(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, b"").is_transient());
+    }
+}
@@ -0,0 +1,115 @@
+//! Client-side rate limiting of the requests to a consensus RPC endpoint, so that multi-period
+//! syncs stay under the request-per-second limits of public providers.
+
+use std::{cmp, sync::Mutex, time::Duration};
+
+pub use config::RateLimit;
+use zduny_wasm_timer::{Delay, Instant};
+
+/// The longest wait for a token, so that the deadline of the timer never overflows. The rates of
+/// [`config::Config::validate`] never wait that long.
+const MAX_WAIT: Duration = Duration::from_secs(60 * 60);
+
+/// A token bucket, with one token per request. Callers that find the bucket empty reserve a
+/// token that is refilled later, so concurrent requests are spread out in order.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        let bucket = Bucket {
+            tokens: burst(&limit),
+            last_refill: Instant::now(),
+        };
+
+        Self {
+            limit,
+            bucket: Mutex::new(bucket),
+        }
+    }
+
+    /// Waits until a request can be sent under the rate limit.
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            // The timer only fails when its runtime is gone, in which case there is no point in
+            // holding the request back.
+            let _ = Delay::new(wait).await;
+        }
+    }
+
+    /// Takes a token at `now`, and returns how long to wait until it is refilled.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let rate = self.limit.requests_per_second.max(f64::MIN_POSITIVE);
+
+        let now = cmp::max(now, bucket.last_refill);
+        let elapsed = (now - bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst(&self.limit));
+        bucket.last_refill = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::try_from_secs_f64(-bucket.tokens / rate)
+                .unwrap_or(MAX_WAIT)
+                .min(MAX_WAIT)
+        }
+    }
+}
+
+fn burst(limit: &RateLimit) -> f64 {
+    cmp::max(limit.burst, 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_second: 2.0,
+            burst: 2,
+        });
+        let start = limiter.bucket.lock().unwrap().last_refill;
+
+        // The burst is sent at once, the next requests are spread at the rate.
+        assert_eq!(Duration::ZERO, limiter.reserve(start));
+        assert_eq!(Duration::ZERO, limiter.reserve(start));
+        assert_eq!(Duration::from_millis(500), limiter.reserve(start));
+        assert_eq!(Duration::from_millis(1000), limiter.reserve(start));
+
+        // The reserved tokens are refilled first.
+        let later = start + Duration::from_millis(1500);
+        assert_eq!(Duration::ZERO, limiter.reserve(later));
+
+        // The bucket does not refill past the burst.
+        let idle = start + Duration::from_secs(60);
+        assert_eq!(Duration::ZERO, limiter.reserve(idle));
+        assert_eq!(Duration::ZERO, limiter.reserve(idle));
+        assert_eq!(Duration::from_millis(500), limiter.reserve(idle));
+    }
+
+    #[test]
+    fn test_max_wait() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_second: 0.0,
+            burst: 1,
+        });
+        let start = limiter.bucket.lock().unwrap().last_refill;
+
+        assert_eq!(Duration::ZERO, limiter.reserve(start));
+        assert_eq!(MAX_WAIT, limiter.reserve(start));
+    }
+}
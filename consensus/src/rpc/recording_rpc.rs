@@ -0,0 +1,264 @@
+//! A wrapper of an RPC that records its responses to a directory, in the layout of
+//! [`FileRpc`](super::file_rpc::FileRpc), so that a sync or a verification that failed in
+//! production can be replayed deterministically from the recording.
+//!
+//! The finality and optimistic updates are overwritten by every request, so a replay serves the
+//! last recorded ones. Failures to write the recording are logged, and do not fail the requests.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use eyre::Result;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use super::{rate_limit::RateLimit, retry::RetryPolicy, ConsensusRpc, RpcCredentials, TlsConfig};
+use crate::{
+    types::{validator::StateProof, *},
+    utils::calc_sync_period,
+};
+
+#[derive(Debug, Clone)]
+pub struct RecordingRpc<R: ConsensusRpc> {
+    inner: R,
+    dir: Option<PathBuf>,
+}
+
+impl<R: ConsensusRpc> RecordingRpc<R> {
+    /// Wraps `inner`, of which the responses are recorded to `dir`.
+    pub fn with_inner(inner: R, dir: &Path) -> Self {
+        Self {
+            inner,
+            dir: Some(dir.to_path_buf()),
+        }
+    }
+
+    fn record(&self, path: impl AsRef<Path>, value: impl Serialize) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        let path = dir.join(path);
+        let res = serde_json::to_vec_pretty(&value)
+            .map_err(eyre::Report::from)
+            .and_then(|bytes| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                Ok(fs::write(&path, bytes)?)
+            });
+
+        if let Err(err) = res {
+            warn!(
+                target: "helios::consensus",
+                err = %err,
+                path = %path.display(),
+                "failed to record rpc response"
+            );
+        }
+    }
+
+    fn record_data(&self, path: impl AsRef<Path>, data: impl Serialize) {
+        self.record(path, json!({ "data": data }));
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<R: ConsensusRpc> ConsensusRpc for RecordingRpc<R> {
+    /// Creates a wrapper that does not record until [`ConsensusRpc::with_record_dir`] is set.
+    fn new(path: &str) -> Self {
+        Self {
+            inner: R::new(path),
+            dir: None,
+        }
+    }
+
+    fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.inner = self.inner.with_retry_policy(policy);
+        self
+    }
+
+    fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.inner = self.inner.with_rate_limit(limit);
+        self
+    }
+
+    fn with_credentials(mut self, credentials: RpcCredentials) -> Self {
+        self.inner = self.inner.with_credentials(credentials);
+        self
+    }
+
+    fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.inner = self.inner.with_tls(tls);
+        self
+    }
+
+    fn with_proxy(mut self, proxy: &str) -> Self {
+        self.inner = self.inner.with_proxy(proxy);
+        self
+    }
+
+    fn with_unknown_forks(mut self, tolerate: bool) -> Self {
+        self.inner = self.inner.with_unknown_forks(tolerate);
+        self
+    }
+
+    fn with_record_dir(mut self, dir: &Path) -> Self {
+        self.dir = Some(dir.to_path_buf());
+        self
+    }
+
+    async fn get_bootstrap(&self, block_root: &'_ [u8]) -> Result<Bootstrap> {
+        let bootstrap = self.inner.get_bootstrap(block_root).await?;
+        let path = format!("bootstrap/0x{}.json", hex::encode(block_root));
+        self.record_data(path, &bootstrap);
+
+        Ok(bootstrap)
+    }
+
+    async fn get_updates(&self, start_period: u64, count: u64) -> Result<Vec<Update>> {
+        let updates = self.inner.get_updates(start_period, count).await?;
+
+        let mut by_period: BTreeMap<u64, Vec<Value>> = BTreeMap::new();
+        for update in &updates {
+            let period = calc_sync_period(update.attested_header.slot.into());
+            by_period
+                .entry(period)
+                .or_default()
+                .push(json!({ "data": update }));
+        }
+        for (period, updates) in by_period {
+            self.record(format!("updates/{}.json", period), updates);
+        }
+
+        Ok(updates)
+    }
+
+    async fn get_finality_update(&self) -> Result<FinalityUpdate> {
+        let update = self.inner.get_finality_update().await?;
+        self.record_data("finality.json", &update);
+
+        Ok(update)
+    }
+
+    async fn get_optimistic_update(&self) -> Result<OptimisticUpdate> {
+        let update = self.inner.get_optimistic_update().await?;
+        self.record_data("optimistic.json", &update);
+
+        Ok(update)
+    }
+
+    async fn get_block(&self, slot: u64) -> Result<BeaconBlock> {
+        let block = self.inner.get_block(slot).await?;
+
+        // The body of a block is not serialized with it.
+        let version = match block.body {
+            BeaconBlockBody::Bellatrix(_) => "bellatrix",
+            BeaconBlockBody::Capella(_) => "capella",
+            BeaconBlockBody::Deneb(_) => "deneb",
+            BeaconBlockBody::Electra(_) => "electra",
+        };
+        let mut message = serde_json::to_value(&block)?;
+        message["body"] = serde_json::to_value(&block.body)?;
+        self.record(
+            format!("blocks/{}.json", slot),
+            json!({ "version": version, "data": { "message": message } }),
+        );
+
+        Ok(block)
+    }
+
+    async fn get_blob_sidecars(&self, slot: u64) -> Result<Vec<BlobSidecar>> {
+        let sidecars = self.inner.get_blob_sidecars(slot).await?;
+        self.record_data(format!("blob_sidecars/{}.json", slot), &sidecars);
+
+        Ok(sidecars)
+    }
+
+    async fn get_genesis_header(&self) -> Result<Header> {
+        let header = self.inner.get_genesis_header().await?;
+        self.record(
+            "genesis/header.json",
+            json!({ "data": { "header": { "message": header } } }),
+        );
+
+        Ok(header)
+    }
+
+    /// Only the sync committee of the genesis state is recorded.
+    async fn get_genesis_sync_committee(&self) -> Result<SyncCommittee> {
+        let committee = self.inner.get_genesis_sync_committee().await?;
+        self.record(
+            "genesis/state.json",
+            json!({ "data": { "current_sync_committee": committee } }),
+        );
+
+        Ok(committee)
+    }
+
+    async fn get_state_proof(&self, state_root: &'_ [u8], gindex: u64) -> Result<StateProof> {
+        let proof = self.inner.get_state_proof(state_root, gindex).await?;
+        let path = format!("state_proofs/0x{}/{}.json", hex::encode(state_root), gindex);
+        self.record_data(path, &proof);
+
+        Ok(proof)
+    }
+
+    async fn chain_id(&self) -> Result<u64> {
+        let chain_id = self.inner.chain_id().await?;
+        self.record_data(
+            "spec.json",
+            json!({ "DEPOSIT_NETWORK_ID": chain_id.to_string() }),
+        );
+
+        Ok(chain_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz_rs::prelude::*;
+
+    use super::*;
+    use crate::rpc::{file_rpc::FileRpc, mock_rpc::MockRpc};
+
+    fn root(mut value: impl Merkleized) -> Node {
+        value.hash_tree_root().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("helios-recording-{}", std::process::id()));
+        let recording = RecordingRpc::with_inner(MockRpc::new().with_testdata("testdata/"), &dir);
+
+        let block_root = [1u8; 32];
+        let bootstrap = recording.get_bootstrap(&block_root).await.unwrap();
+        let updates = recording.get_updates(862, 6).await.unwrap();
+        let finality = recording.get_finality_update().await.unwrap();
+        let block = recording.get_block(7109344).await.unwrap();
+
+        let replay = FileRpc::new(dir.to_str().unwrap());
+        let replayed = replay.get_bootstrap(&block_root).await.unwrap();
+        assert_eq!(root(bootstrap.header), root(replayed.header));
+
+        let replayed = replay.get_updates(862, 6).await.unwrap();
+        assert_eq!(updates.len(), replayed.len());
+
+        let replayed = replay.get_finality_update().await.unwrap();
+        assert_eq!(
+            root(finality.finalized_header),
+            root(replayed.finalized_header)
+        );
+
+        let replayed = replay.get_block(7109344).await.unwrap();
+        assert_eq!(root(block), root(replayed));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
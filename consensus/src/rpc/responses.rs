@@ -0,0 +1,86 @@
+//! The responses of the beacon API, shared by the RPCs that read them.
+
+use crate::types::{multiproof::StateMultiproof, validator::StateProof, *};
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct BeaconBlockResponse {
+    pub(crate) data: BeaconBlockData,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct BeaconBlockData {
+    pub(crate) message: BeaconBlock,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct HeaderResponse {
+    pub(crate) data: HeaderData,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct HeaderData {
+    pub(crate) header: SignedHeader,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct SignedHeader {
+    pub(crate) message: Header,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct GenesisSyncCommitteeResponse {
+    pub(crate) data: GenesisSyncCommitteeData,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct GenesisSyncCommitteeData {
+    pub(crate) current_sync_committee: SyncCommittee,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct StateProofResponse {
+    pub(crate) data: StateProof,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct StateMultiproofResponse {
+    pub(crate) data: StateMultiproof,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct BlobSidecarsResponse {
+    pub(crate) data: Vec<BlobSidecar>,
+}
+
+pub(crate) type UpdateResponse = Vec<UpdateData>;
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct UpdateData {
+    pub(crate) data: Update,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct FinalityUpdateResponse {
+    pub(crate) data: FinalityUpdate,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct OptimisticUpdateResponse {
+    pub(crate) data: OptimisticUpdate,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct BootstrapResponse {
+    pub(crate) data: Bootstrap,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct SpecResponse {
+    pub(crate) data: Spec,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct Spec {
+    #[serde(rename = "DEPOSIT_NETWORK_ID")]
+    pub(crate) chain_id: primitives::U64,
+}
@@ -0,0 +1,107 @@
+//! Retries of the requests to the consensus RPC, so that rate limits and transient failures of
+//! public beacon nodes, such as `429` and `503` responses, do not fail the sync.
+
+use std::{future::Future, time::Duration};
+
+pub use config::RetryPolicy;
+use eyre::Result;
+use rand::Rng;
+use reqwest::StatusCode;
+use tracing::debug;
+use zduny_wasm_timer::Delay;
+
+/// The errors of a request that may succeed when it is retried.
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+}
+
+/// Whether a request that failed with `status` may succeed when retried.
+pub fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+impl Transient for reqwest::Error {
+    fn is_transient(&self) -> bool {
+        match self.status() {
+            Some(status) => is_transient_status(status),
+            None => self.is_timeout() || self.is_request() || self.is_body(),
+        }
+    }
+}
+
+/// Sends a request with `send` until it succeeds, fails with an error that is not transient, or
+/// `policy` runs out of attempts.
+pub async fn with_retry<T, E, F, Fut>(policy: &RetryPolicy, mut send: F) -> Result<T>
+where
+    E: Transient + std::error::Error + Send + Sync + 'static,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        let err = match send().await {
+            Ok(res) => return Ok(res),
+            Err(err) => err,
+        };
+
+        if attempt >= policy.max_attempts || !err.is_transient() {
+            return Err(err.into());
+        }
+
+        let backoff = jittered_backoff(policy, attempt, rand::thread_rng().gen());
+        debug!(
+            target: "helios::consensus",
+            err = %err,
+            attempt,
+            backoff_ms = backoff.as_millis() as u64,
+            "rpc request failed, retrying"
+        );
+
+        Delay::new(backoff).await?;
+        attempt += 1;
+    }
+}
+
+/// The backoff of `policy` after `attempt`, of which the fraction `random * jitter` is cut.
+fn jittered_backoff(policy: &RetryPolicy, attempt: u32, random: f64) -> Duration {
+    let jitter = policy.jitter.clamp(0.0, 1.0) * random.clamp(0.0, 1.0);
+    policy.backoff(attempt).mul_f64(1.0 - jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jittered_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            jitter: 0.5,
+        };
+
+        assert_eq!(
+            Duration::from_millis(100),
+            jittered_backoff(&policy, 1, 0.0)
+        );
+        assert_eq!(
+            Duration::from_millis(400),
+            jittered_backoff(&policy, 3, 0.0)
+        );
+        assert_eq!(
+            Duration::from_millis(200),
+            jittered_backoff(&policy, 3, 1.0)
+        );
+        // The backoff is capped before the jitter is applied.
+        assert_eq!(
+            Duration::from_millis(1000),
+            jittered_backoff(&policy, 10, 0.0)
+        );
+        assert_eq!(
+            Duration::from_millis(1000),
+            jittered_backoff(&policy, 100, 0.0)
+        );
+    }
+}
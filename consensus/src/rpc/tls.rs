@@ -0,0 +1,92 @@
+//! Custom root certificates and certificate pinning of the consensus RPC endpoints.
+//!
+//! Pins are checked against the certificate of every response, before its body is used, with
+//! the TLS stack of `reqwest`. The request itself is sent before the check, so pinning protects
+//! the data that is read from the endpoint rather than the credentials that are sent to it.
+
+use std::fs;
+
+pub use config::TlsConfig;
+use eyre::{eyre, Result};
+use reqwest::{tls::TlsInfo, Certificate, ClientBuilder, Response};
+use sha2::{Digest, Sha256};
+
+/// Configures `builder` to trust the root certificates of `tls`, and to keep the certificates
+/// of the responses when some are pinned.
+pub fn configure(mut builder: ClientBuilder, tls: &TlsConfig) -> Result<ClientBuilder> {
+    builder = builder.tls_info(!tls.pinned_certificates.is_empty());
+
+    for path in &tls.root_certificates {
+        let pem = fs::read(path)
+            .map_err(|err| eyre!("cannot read certificate {}: {}", path.display(), err))?;
+        builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+
+    Ok(builder)
+}
+
+/// Returns the DER encoding of the certificate that the server of `res` presented.
+pub fn peer_certificate(res: &Response) -> Option<Vec<u8>> {
+    res.extensions()
+        .get::<TlsInfo>()
+        .and_then(TlsInfo::peer_certificate)
+        .map(<[u8]>::to_vec)
+}
+
+/// Checks that `certificate` is one of the pinned certificates of `tls`, if any.
+pub fn verify_pinned_certificate(tls: &TlsConfig, certificate: Option<&[u8]>) -> Result<()> {
+    if tls.pinned_certificates.is_empty() {
+        return Ok(());
+    }
+
+    let certificate =
+        certificate.ok_or_else(|| eyre!("the endpoint did not present a certificate"))?;
+    let fingerprint = hex::encode(Sha256::digest(certificate));
+
+    let is_pinned = tls
+        .pinned_certificates
+        .iter()
+        .any(|pin| normalize_fingerprint(pin) == fingerprint);
+
+    if !is_pinned {
+        return Err(eyre!("certificate {} is not pinned", fingerprint));
+    }
+
+    Ok(())
+}
+
+/// Normalizes a hex fingerprint, such as the `AB:CD:...` output of `openssl x509 -fingerprint`.
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .trim()
+        .trim_start_matches("0x")
+        .replace(':', "")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_pinned_certificate() {
+        let certificate = b"certificate".as_slice();
+        let fingerprint = hex::encode(Sha256::digest(certificate)).to_uppercase();
+        let colon_separated = fingerprint
+            .as_bytes()
+            .chunks(2)
+            .map(|byte| std::str::from_utf8(byte).unwrap())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        let tls = TlsConfig {
+            root_certificates: vec![],
+            pinned_certificates: vec![colon_separated],
+        };
+        verify_pinned_certificate(&tls, Some(certificate)).unwrap();
+        assert!(verify_pinned_certificate(&tls, Some(b"other".as_slice())).is_err());
+        assert!(verify_pinned_certificate(&tls, None).is_err());
+
+        verify_pinned_certificate(&TlsConfig::default(), None).unwrap();
+    }
+}
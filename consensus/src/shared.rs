@@ -0,0 +1,250 @@
+//! A handle to a [`ConsensusStateManager`] that is shared by several services, such as a proof
+//! generator, a relayer and an RPC server, so that they use one synced instance concurrently
+//! without locking it themselves.
+//!
+//! The accessors take a read lock, which is held while their proofs are fetched from the RPC, so
+//! that they run concurrently. Syncing and advancing take the write lock, and wait for the reads
+//! in progress; the reads that come after wait for the advance, so that they never see a
+//! partially applied update.
+
+use std::sync::Arc;
+
+use common::types::Block;
+use config::Config;
+use eyre::Result;
+use tokio::{
+    select,
+    sync::{broadcast, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::{
+    header_cache::VerifiedHeader,
+    light_client_data::LightClientData,
+    rpc::ConsensusRpc,
+    types::{
+        multiproof::StateMultiproof,
+        validator::{StateProof, ValidatorProof},
+        BeaconBlock, Bytes32, FinalizedHeader, Header, Health,
+    },
+    ConsensusStateManager,
+};
+
+/// A cloneable handle to a [`ConsensusStateManager`]. Every clone uses the same manager.
+pub struct SharedConsensusState<R: ConsensusRpc> {
+    inner: Arc<RwLock<ConsensusStateManager<R>>>,
+}
+
+impl<R: ConsensusRpc> Clone for SharedConsensusState<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<R: ConsensusRpc> SharedConsensusState<R> {
+    pub fn new(manager: ConsensusStateManager<R>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(manager)),
+        }
+    }
+
+    /// Locks the manager for reading, for the reads that have no accessor. The lock should not be
+    /// held for long, since the advances wait for it.
+    pub async fn read(&self) -> RwLockReadGuard<'_, ConsensusStateManager<R>> {
+        self.inner.read().await
+    }
+
+    /// Locks the manager for writing, e.g. to apply updates received from elsewhere with
+    /// [`ConsensusStateManager::advance_state`].
+    pub async fn write(&self) -> RwLockWriteGuard<'_, ConsensusStateManager<R>> {
+        self.inner.write().await
+    }
+
+    pub async fn sync(&self, checkpoint: &[u8]) -> Result<()> {
+        self.inner.write().await.sync(checkpoint).await
+    }
+
+    pub async fn advance(&self) -> Result<()> {
+        self.inner.write().await.advance().await
+    }
+
+    /// Same as [`ConsensusStateManager::run`], but the manager is only locked during the
+    /// advances, so that it is read in between.
+    pub async fn run(&self, cancel: &CancellationToken) {
+        let mut failures = 0;
+
+        loop {
+            let delay = {
+                let manager = self.inner.read().await;
+                if failures == 0 {
+                    manager.duration_until_next_advance()
+                } else {
+                    manager.config.advance.backoff(failures)
+                }
+            };
+
+            select! {
+                res = zduny_wasm_timer::Delay::new(delay) => res.unwrap(),
+                _ = cancel.cancelled() => return,
+            }
+
+            let mut manager = self.inner.write().await;
+            manager.reload_pending();
+            let res = match manager.catch_up_periods().await {
+                Ok(()) => manager.advance().await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = res {
+                failures += 1;
+                warn!(target: "helios::consensus", failures, "advance error: {}", err);
+                continue;
+            }
+            failures = 0;
+            manager.prune();
+
+            let res = manager.send_blocks().await;
+            if let Err(err) = res {
+                warn!(target: "helios::consensus", "send error: {}", err);
+            }
+        }
+    }
+
+    /// See [`ConsensusStateManager::reload`].
+    pub async fn reload(&self, config: &Config) -> Result<()> {
+        self.inner.write().await.reload(config)
+    }
+
+    pub async fn health(&self) -> Health {
+        self.inner.read().await.health()
+    }
+
+    pub async fn get_finalized_header(&self) -> Header {
+        self.inner.read().await.get_finalized_header()
+    }
+
+    pub async fn get_optimistic_header(&self) -> Header {
+        self.inner.read().await.get_optimistic_header()
+    }
+
+    pub async fn get_safe_header(&self) -> Header {
+        self.inner.read().await.get_safe_header()
+    }
+
+    pub async fn get_verified_header(&self, slot: u64) -> Option<VerifiedHeader> {
+        self.inner.read().await.get_verified_header(slot)
+    }
+
+    pub async fn get_verified_header_by_root(&self, block_root: &[u8]) -> Option<VerifiedHeader> {
+        self.inner
+            .read()
+            .await
+            .get_verified_header_by_root(block_root)
+    }
+
+    pub async fn get_execution_block(&self, slot: u64) -> Result<Block> {
+        self.inner.read().await.get_execution_block(slot).await
+    }
+
+    pub async fn get_verified_block_by_number(&self, block_number: u64) -> Result<Block> {
+        self.inner
+            .read()
+            .await
+            .get_verified_block_by_number(block_number)
+            .await
+    }
+
+    pub async fn get_verified_beacon_block(&self, slot: u64) -> Result<BeaconBlock> {
+        self.inner
+            .read()
+            .await
+            .get_verified_beacon_block(slot)
+            .await
+    }
+
+    pub async fn get_finalized_state_root(&self) -> Result<Bytes32> {
+        self.inner.read().await.get_finalized_state_root().await
+    }
+
+    pub async fn get_validator_proof(&self, validator_index: u64) -> Result<ValidatorProof> {
+        self.inner
+            .read()
+            .await
+            .get_validator_proof(validator_index)
+            .await
+    }
+
+    pub async fn get_state_field_proof(&self, gindex: u64) -> Result<StateProof> {
+        self.inner.read().await.get_state_field_proof(gindex).await
+    }
+
+    pub async fn get_state_multiproof(&self, gindices: &[u64]) -> Result<StateMultiproof> {
+        self.inner.read().await.get_state_multiproof(gindices).await
+    }
+
+    /// See [`ConsensusStateManager::subscribe_finalized`].
+    pub async fn subscribe_finalized(&self) -> broadcast::Receiver<FinalizedHeader> {
+        self.inner.read().await.subscribe_finalized()
+    }
+
+    /// See [`ConsensusStateManager::light_client_data`].
+    pub async fn light_client_data(&self) -> LightClientData {
+        self.inner.read().await.light_client_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::networks;
+
+    use super::*;
+    use crate::rpc::mock_rpc::MockRpc;
+
+    #[tokio::test]
+    async fn test_shared_consensus_state() {
+        let base_config = networks::mainnet();
+        let config = Config {
+            consensus_rpc: String::new(),
+            execution_rpc: String::new(),
+            chain: base_config.chain,
+            forks: base_config.forks,
+            ..Default::default()
+        };
+        let manager = ConsensusStateManager::<MockRpc>::new(
+            "testdata/",
+            None,
+            None,
+            None,
+            config.into(),
+            None,
+        );
+        let shared = SharedConsensusState::new(manager);
+
+        let checkpoint =
+            hex::decode("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275")
+                .unwrap();
+        let mut finalized_recv = shared.subscribe_finalized().await;
+        shared.clone().sync(&checkpoint).await.unwrap();
+
+        let header = finalized_recv.try_recv().unwrap();
+        let (finalized, optimistic, health) = tokio::join!(
+            shared.get_finalized_header(),
+            shared.get_optimistic_header(),
+            shared.health(),
+        );
+        assert_eq!(header.slot, finalized.slot.as_u64());
+        assert!(optimistic.slot.as_u64() >= finalized.slot.as_u64());
+        assert_eq!(health.finalized_slot, finalized.slot.as_u64());
+
+        // The reads are not blocked by one another.
+        let guard = shared.read().await;
+        assert_eq!(finalized.slot, shared.get_finalized_header().await.slot);
+        drop(guard);
+        assert!(shared
+            .get_verified_header(finalized.slot.as_u64())
+            .await
+            .is_some());
+    }
+}
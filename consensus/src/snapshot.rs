@@ -0,0 +1,100 @@
+//! Signed snapshots of the verified state of a [`ConsensusStateManager`], so that the machine that
+//! synced it hands it off to others, such as the other dWallet validators, which then do not sync
+//! from a checkpoint themselves.
+//!
+//! A snapshot is the state exported by [`ConsensusStateManager::export_state`], with a detached
+//! BLS signature by a key of the operator. The state of a snapshot is not verified again when it
+//! is imported: it is trusted as much as the key that signed it, like a checkpoint is trusted as
+//! much as its source.
+//!
+//! [`ConsensusStateManager`]: crate::ConsensusStateManager
+//! [`ConsensusStateManager::export_state`]: crate::ConsensusStateManager::export_state
+
+use eyre::Result;
+use milagro_bls::{PublicKey, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::ConsensusError,
+    types::{
+        encoding::{decode_versioned, encode_versioned},
+        BLSPubKey, SignatureBytes,
+    },
+};
+
+/// Prefixes the signed messages, so that the signature of a snapshot is not the one of another
+/// message signed by the same key.
+pub const SNAPSHOT_DOMAIN: &[u8] = b"HELIOS_STATE_SNAPSHOT_V1";
+
+/// An exported state, with the signature of the operator that exported it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedStateSnapshot {
+    /// The state, see [`ConsensusStateManager::export_state`].
+    ///
+    /// [`ConsensusStateManager::export_state`]: crate::ConsensusStateManager::export_state
+    pub state: Vec<u8>,
+    /// The signature of the [`signing_root`] of the state.
+    pub signature: SignatureBytes,
+}
+
+impl SignedStateSnapshot {
+    /// Signs `state` with the BLS secret key `secret_key`, of 32 bytes.
+    pub fn sign(state: Vec<u8>, secret_key: &[u8]) -> Result<Self> {
+        let secret_key =
+            SecretKey::from_bytes(secret_key).map_err(|_| ConsensusError::InvalidSnapshotKey)?;
+        let signature = Signature::new(&signing_root(&state), &secret_key);
+
+        Ok(Self {
+            state,
+            signature: SignatureBytes::try_from(signature.as_bytes().as_slice())?,
+        })
+    }
+
+    /// Fails unless the state was signed by the BLS public key `public_key`, of 48 bytes.
+    pub fn verify(&self, public_key: &[u8]) -> Result<()> {
+        let public_key =
+            PublicKey::from_bytes(public_key).map_err(|_| ConsensusError::InvalidSnapshotKey)?;
+        let signature = Signature::from_bytes(&self.signature)
+            .map_err(|_| ConsensusError::InvalidSnapshotSignature)?;
+
+        if signature.verify(&signing_root(&self.state), &public_key) {
+            Ok(())
+        } else {
+            Err(ConsensusError::InvalidSnapshotSignature.into())
+        }
+    }
+
+    /// Serializes the snapshot, so that it is distributed as a single file.
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    /// Deserializes a snapshot that was serialized with [`Self::serialize_to_bytes`].
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// Returns the public key of the BLS secret key `secret_key`, to give it to the machines that
+/// import the snapshots signed by it.
+pub fn public_key(secret_key: &[u8]) -> Result<BLSPubKey> {
+    let secret_key =
+        SecretKey::from_bytes(secret_key).map_err(|_| ConsensusError::InvalidSnapshotKey)?;
+
+    BLSPubKey::try_from(
+        PublicKey::from_secret_key(&secret_key)
+            .as_bytes()
+            .as_slice(),
+    )
+}
+
+/// Returns the message signed for `state`: the SHA-256 hash of [`SNAPSHOT_DOMAIN`] followed by
+/// the state.
+pub fn signing_root(state: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(SNAPSHOT_DOMAIN)
+        .chain_update(state)
+        .finalize()
+        .into()
+}
@@ -0,0 +1,135 @@
+//! Rolling statistics of the health of the sync, so that operators notice a degraded consensus
+//! RPC or a struggling network before the client stops advancing: the sync committee
+//! participation of the recent updates, and the lag of the finalized header behind the wall clock.
+//! Their thresholds are the ones of [`SyncHealthConfig`].
+
+use std::collections::VecDeque;
+
+use config::SyncHealthConfig;
+
+use crate::{
+    constants::SYNC_COMMITTEE_SIZE,
+    types::{ParticipationStats, SyncHealthWarning},
+};
+
+/// Tracks the participation of the recent updates, and whether each threshold is currently
+/// crossed, so that a warning is only raised when the sync degrades.
+#[derive(Debug, Clone, Default)]
+pub struct SyncHealthMonitor {
+    /// The participants of the recent updates, from the oldest to the most recent.
+    participation: VecDeque<u64>,
+    low_participation: bool,
+    finality_lagging: bool,
+}
+
+impl SyncHealthMonitor {
+    /// Records the participants of an update, and drops the oldest ones out of `window`.
+    pub fn record_participation(&mut self, participants: u64, window: usize) {
+        self.participation.push_back(participants);
+        while self.participation.len() > window {
+            self.participation.pop_front();
+        }
+    }
+
+    pub fn participation(&self) -> Option<ParticipationStats> {
+        let latest = *self.participation.back()?;
+        let rate = |participants: u64| participants as f64 / SYNC_COMMITTEE_SIZE as f64;
+        let total = self.participation.iter().sum::<u64>();
+        let min = self.participation.iter().copied().min().unwrap_or(latest);
+
+        Some(ParticipationStats {
+            updates: self.participation.len(),
+            mean: rate(total) / self.participation.len() as f64,
+            min: rate(min),
+            latest: rate(latest),
+        })
+    }
+
+    /// Returns the warnings of the thresholds of `config` that were crossed since the last check,
+    /// given the current number of slots between the wall clock and the finalized header.
+    pub fn check(
+        &mut self,
+        config: &SyncHealthConfig,
+        finality_lag: u64,
+    ) -> Vec<SyncHealthWarning> {
+        let mut warnings = Vec::new();
+
+        if let Some(stats) = self.participation() {
+            let low = stats.mean < config.min_participation;
+            if low && !self.low_participation {
+                warnings.push(SyncHealthWarning::LowParticipation {
+                    participation: stats.mean,
+                    threshold: config.min_participation,
+                });
+            }
+            self.low_participation = low;
+        }
+
+        let lagging = finality_lag > config.max_finality_lag;
+        if lagging && !self.finality_lagging {
+            warnings.push(SyncHealthWarning::FinalityLag {
+                lag: finality_lag,
+                threshold: config.max_finality_lag,
+            });
+        }
+        self.finality_lagging = lagging;
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_participation_window() {
+        let mut monitor = SyncHealthMonitor::default();
+        assert_eq!(None, monitor.participation());
+
+        for participants in [128, 512, 384, 256] {
+            monitor.record_participation(participants, 3);
+        }
+        let stats = monitor.participation().unwrap();
+        assert_eq!(3, stats.updates);
+        assert_eq!(0.75, stats.mean);
+        assert_eq!(0.5, stats.min);
+        assert_eq!(0.5, stats.latest);
+    }
+
+    #[test]
+    fn test_warnings_on_degradation() {
+        let config = SyncHealthConfig {
+            participation_window: 2,
+            min_participation: 0.8,
+            max_finality_lag: 96,
+        };
+        let mut monitor = SyncHealthMonitor::default();
+        monitor.record_participation(512, config.participation_window);
+        assert!(monitor.check(&config, 64).is_empty());
+
+        monitor.record_participation(256, config.participation_window);
+        assert_eq!(
+            vec![
+                SyncHealthWarning::LowParticipation {
+                    participation: 0.75,
+                    threshold: 0.8,
+                },
+                SyncHealthWarning::FinalityLag {
+                    lag: 100,
+                    threshold: 96,
+                },
+            ],
+            monitor.check(&config, 100)
+        );
+        // The warnings are not raised again while the sync is degraded.
+        assert!(monitor.check(&config, 200).is_empty());
+
+        for _ in 0..2 {
+            monitor.record_participation(512, config.participation_window);
+        }
+        assert!(monitor.check(&config, 64).is_empty());
+        monitor.record_participation(0, config.participation_window);
+        assert_eq!(1, monitor.check(&config, 64).len());
+    }
+}
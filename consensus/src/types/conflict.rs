@@ -0,0 +1,110 @@
+//! Evidence of two verified updates that finalize different headers at the same slot. It can only
+//! happen if the consensus RPCs serve different chains, or if the sync committee signed a
+//! conflicting finality, which is a safety fault of the chain.
+
+use eyre::Result;
+use ssz_rs::prelude::*;
+
+use super::{Bytes32, GenericUpdate, Header};
+
+/// A verified update that finalizes another header than the one already verified at its slot.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FinalityConflict {
+    pub slot: u64,
+    /// The finalized header that was verified first, and kept.
+    pub existing_header: Header,
+    pub existing_root: Bytes32,
+    pub conflicting_root: Bytes32,
+    /// The update finalizing the conflicting header, with the sync committee signature over it.
+    pub update: GenericUpdate,
+}
+
+impl FinalityConflict {
+    /// Returns the conflict if the finalized header of `update` is not `existing_header`.
+    pub fn detect(existing_header: &Header, update: &GenericUpdate) -> Result<Option<Self>> {
+        let Some(finalized_header) = &update.finalized_header else {
+            return Ok(None);
+        };
+
+        if finalized_header.slot != existing_header.slot {
+            return Ok(None);
+        }
+
+        let existing_root = existing_header.clone().hash_tree_root()?;
+        let conflicting_root = finalized_header.clone().hash_tree_root()?;
+        if existing_root == conflicting_root {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            slot: finalized_header.slot.as_u64(),
+            existing_header: existing_header.clone(),
+            existing_root: Bytes32::try_from(existing_root.as_ref())?,
+            conflicting_root: Bytes32::try_from(conflicting_root.as_ref())?,
+            update: update.clone(),
+        }))
+    }
+
+    pub fn conflicting_header(&self) -> &Header {
+        self.update
+            .finalized_header
+            .as_ref()
+            .expect("a conflict has a finalized header")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SyncAggregate;
+
+    fn update(finalized_header: Header) -> GenericUpdate {
+        GenericUpdate {
+            attested_header: Header::default(),
+            sync_aggregate: SyncAggregate::default(),
+            signature_slot: 0,
+            next_sync_committee: None,
+            next_sync_committee_branch: None,
+            finalized_header: Some(finalized_header),
+            finality_branch: None,
+        }
+    }
+
+    #[test]
+    fn test_detect_conflict() {
+        let existing = Header {
+            slot: 64.into(),
+            ..Default::default()
+        };
+        let conflicting = Header {
+            slot: 64.into(),
+            proposer_index: 1.into(),
+            ..Default::default()
+        };
+
+        assert!(
+            FinalityConflict::detect(&existing, &update(existing.clone()))
+                .unwrap()
+                .is_none()
+        );
+
+        let conflict = FinalityConflict::detect(&existing, &update(conflicting.clone()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(64, conflict.slot);
+        assert_ne!(conflict.existing_root, conflict.conflicting_root);
+        assert_eq!(
+            conflicting.proposer_index,
+            conflict.conflicting_header().proposer_index
+        );
+
+        // Headers of other slots do not conflict.
+        let later = Header {
+            slot: 96.into(),
+            ..Default::default()
+        };
+        assert!(FinalityConflict::detect(&existing, &update(later))
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,132 @@
+//! The difference between two verified light client states, to audit what a state transition
+//! accepted.
+
+use consensus_core::sync::calc_sync_period;
+use eyre::Result;
+use ssz_rs::prelude::*;
+
+use super::{
+    encoding::{decode_versioned, encode_versioned, sync_committee_root},
+    Bytes32, Header, SyncCommittee,
+};
+use crate::errors::ConsensusError;
+
+/// What a transition from a state with the finalized header `old_finalized_header` to a state
+/// with the finalized header `new_finalized_header` accepted.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StateDiff {
+    pub old_finalized_header: Header,
+    pub old_finalized_root: Bytes32,
+    pub new_finalized_header: Header,
+    pub new_finalized_root: Bytes32,
+    /// The sync committee periods the new state advanced to, in order.
+    pub advanced_periods: Vec<u64>,
+    pub old_sync_committee_root: Bytes32,
+    pub new_sync_committee_root: Bytes32,
+    /// The execution blocks finalized by the transition, if the execution payloads of both
+    /// finalized headers were fetched.
+    pub execution: Option<ExecutionRange>,
+}
+
+/// The execution blocks after `from_block` up to and including `to_block`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionRange {
+    pub from_block: u64,
+    pub from_block_hash: Bytes32,
+    pub to_block: u64,
+    pub to_block_hash: Bytes32,
+}
+
+impl StateDiff {
+    /// Fails with [`ConsensusError::StateRegression`] if the new finalized header is older than
+    /// the old one.
+    pub fn new(
+        old_finalized_header: &Header,
+        old_sync_committee: &SyncCommittee,
+        new_finalized_header: &Header,
+        new_sync_committee: &SyncCommittee,
+    ) -> Result<Self> {
+        let old_slot = old_finalized_header.slot.as_u64();
+        let new_slot = new_finalized_header.slot.as_u64();
+        if new_slot < old_slot {
+            return Err(ConsensusError::StateRegression(old_slot, new_slot).into());
+        }
+
+        let old_period = calc_sync_period(old_slot);
+        let new_period = calc_sync_period(new_slot);
+
+        Ok(Self {
+            old_finalized_header: old_finalized_header.clone(),
+            old_finalized_root: header_root(old_finalized_header)?,
+            new_finalized_header: new_finalized_header.clone(),
+            new_finalized_root: header_root(new_finalized_header)?,
+            advanced_periods: (old_period + 1..=new_period).collect(),
+            old_sync_committee_root: sync_committee_root(old_sync_committee)?,
+            new_sync_committee_root: sync_committee_root(new_sync_committee)?,
+            execution: None,
+        })
+    }
+
+    pub fn slots_advanced(&self) -> u64 {
+        self.new_finalized_header.slot.as_u64() - self.old_finalized_header.slot.as_u64()
+    }
+
+    /// Whether the transition accepted nothing.
+    pub fn is_empty(&self) -> bool {
+        self.old_finalized_root == self.new_finalized_root
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    /// Deserializes a diff that was serialized with [`StateDiff::serialize_to_bytes`].
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+fn header_root(header: &Header) -> Result<Bytes32> {
+    let root = header.clone().hash_tree_root()?;
+    Ok(Bytes32::try_from(root.as_ref())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(slot: u64) -> Header {
+        Header {
+            slot: slot.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_state_diff() {
+        let committee = SyncCommittee::default();
+        let diff =
+            StateDiff::new(&header(8191), &committee, &header(8192 * 3), &committee).unwrap();
+
+        assert_eq!(vec![1, 2, 3], diff.advanced_periods);
+        assert_eq!(8192 * 3 - 8191, diff.slots_advanced());
+        assert!(!diff.is_empty());
+        assert_eq!(diff.old_sync_committee_root, diff.new_sync_committee_root);
+
+        let bytes = diff.serialize_to_bytes().unwrap();
+        let decoded = StateDiff::deserialize_from_bytes(&bytes).unwrap();
+        assert_eq!(diff.new_finalized_root, decoded.new_finalized_root);
+        assert_eq!(diff.advanced_periods, decoded.advanced_periods);
+        assert_eq!(bytes, decoded.serialize_to_bytes().unwrap());
+
+        let same = StateDiff::new(&header(100), &committee, &header(100), &committee).unwrap();
+        assert!(same.is_empty());
+        assert!(same.advanced_periods.is_empty());
+
+        let err = StateDiff::new(&header(100), &committee, &header(99), &committee).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::StateRegression(100, 99).to_string()
+        );
+    }
+}
@@ -0,0 +1,471 @@
+//! BCS encodings of the light client types, used to pass them through dWallet transactions.
+//!
+//! The beacon API types accept both the wrapped and unwrapped light client header formats, which
+//! requires a self describing format such as JSON. The `*Serde` types below hold the same data
+//! with plain serde representations, so that they can be encoded with BCS.
+//!
+//! Every encoding is prefixed with a version byte, so that future format changes (new forks, new
+//! fields) can be decoded unambiguously. Encodings may be compressed to fit larger payloads in a
+//! transaction, in which case the version byte has [`COMPRESSED_FLAG`] set and is followed by the
+//! [`Compression`] of the payload.
+
+use std::sync::Arc;
+
+use eyre::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use ssz_rs::Merkleized;
+
+use super::{
+    primitives::U64, AggregateUpdates, Bootstrap, Bytes32, FinalityUpdate, Header,
+    OptimisticUpdate, SyncAggregate, SyncCommittee, Update,
+};
+use crate::errors::ConsensusError;
+
+/// The version of the encodings produced by this module.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Set on the version byte of the compressed encodings.
+pub const COMPRESSED_FLAG: u8 = 0x80;
+
+const COMPRESSED_ENCODING_VERSION: u8 = ENCODING_VERSION | COMPRESSED_FLAG;
+
+/// The maximum size of a decompressed payload, so that decoding an untrusted payload cannot
+/// exhaust the memory.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+/// The compression of an encoding. Zstd needs the `zstd` feature, since it is implemented in C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Compression {
+    #[default]
+    None,
+    Snappy,
+    Zstd,
+}
+
+impl Compression {
+    /// The byte identifying the compression in the header of an encoding.
+    pub fn id(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Snappy => 1,
+            Self::Zstd => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Snappy),
+            2 => Ok(Self::Zstd),
+            _ => Err(ConsensusError::UnsupportedCompression(id).into()),
+        }
+    }
+
+    fn compress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Snappy => Ok(snap::raw::Encoder::new().compress_vec(bytes)?),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Ok(zstd::bulk::compress(
+                bytes,
+                zstd::DEFAULT_COMPRESSION_LEVEL,
+            )?),
+            #[cfg(not(feature = "zstd"))]
+            Self::Zstd => Err(ConsensusError::UnsupportedCompression(self.id()).into()),
+        }
+    }
+
+    fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Snappy => {
+                let size = snap::raw::decompress_len(bytes)?;
+                if size > MAX_DECOMPRESSED_SIZE {
+                    return Err(ConsensusError::DecompressedTooLarge(MAX_DECOMPRESSED_SIZE).into());
+                }
+                Ok(snap::raw::Decoder::new().decompress_vec(bytes)?)
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                use std::io::Read;
+
+                // The decompressed size is not trusted, the output is bounded while decoding.
+                let mut decompressed = Vec::new();
+                zstd::stream::read::Decoder::new(bytes)?
+                    .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+                    .read_to_end(&mut decompressed)?;
+                if decompressed.len() > MAX_DECOMPRESSED_SIZE {
+                    return Err(ConsensusError::DecompressedTooLarge(MAX_DECOMPRESSED_SIZE).into());
+                }
+                Ok(decompressed)
+            }
+            #[cfg(not(feature = "zstd"))]
+            Self::Zstd => Err(ConsensusError::UnsupportedCompression(self.id()).into()),
+        }
+    }
+}
+
+/// Encodes `value` with BCS, prefixed by [`ENCODING_VERSION`].
+pub fn encode_versioned<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    encode_versioned_with(value, Compression::None)
+}
+
+/// Encodes `value` with BCS and compresses it with `compression`. Uncompressed encodings are the
+/// ones of [`encode_versioned`].
+pub fn encode_versioned_with<T: Serialize>(value: &T, compression: Compression) -> Result<Vec<u8>> {
+    let encoded = bcs::to_bytes(value)?;
+
+    let mut bytes = match compression {
+        Compression::None => vec![ENCODING_VERSION],
+        _ => vec![COMPRESSED_ENCODING_VERSION, compression.id()],
+    };
+    bytes.extend(compression.compress(&encoded)?);
+    Ok(bytes)
+}
+
+/// Decodes a value that was encoded with [`encode_versioned`] or [`encode_versioned_with`].
+pub fn decode_versioned<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    match bytes.split_first() {
+        Some((&ENCODING_VERSION, encoded)) => Ok(bcs::from_bytes(encoded)?),
+        Some((&COMPRESSED_ENCODING_VERSION, compressed)) => {
+            let (id, compressed) = compressed
+                .split_first()
+                .ok_or(ConsensusError::MissingCompression)?;
+            let encoded = Compression::from_id(*id)?.decompress(compressed)?;
+            Ok(bcs::from_bytes(&encoded)?)
+        }
+        Some((version, _)) => Err(ConsensusError::UnsupportedEncodingVersion(*version).into()),
+        None => Err(ConsensusError::MissingEncodingVersion.into()),
+    }
+}
+
+impl Bootstrap {
+    /// Serializes the bootstrap, so that it can be passed through dWallet transactions.
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(&BootstrapSerde::from(self))
+    }
+
+    /// Deserializes a bootstrap that was serialized with [`Bootstrap::serialize_to_bytes`].
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(decode_versioned::<BootstrapSerde>(bytes)?.into())
+    }
+}
+
+/// The BCS representation of [`Bootstrap`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BootstrapSerde {
+    pub header: Header,
+    pub current_sync_committee: Arc<SyncCommittee>,
+    pub current_sync_committee_branch: Vec<Bytes32>,
+}
+
+impl From<&Bootstrap> for BootstrapSerde {
+    fn from(bootstrap: &Bootstrap) -> Self {
+        Self {
+            header: bootstrap.header.clone(),
+            current_sync_committee: bootstrap.current_sync_committee.clone(),
+            current_sync_committee_branch: bootstrap.current_sync_committee_branch.clone(),
+        }
+    }
+}
+
+impl From<BootstrapSerde> for Bootstrap {
+    fn from(bootstrap: BootstrapSerde) -> Self {
+        Self {
+            header: bootstrap.header,
+            current_sync_committee: bootstrap.current_sync_committee,
+            current_sync_committee_branch: bootstrap.current_sync_committee_branch,
+        }
+    }
+}
+
+/// The BCS representation of [`Update`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateSerde {
+    pub attested_header: Header,
+    pub next_sync_committee: Arc<SyncCommittee>,
+    pub next_sync_committee_branch: Vec<Bytes32>,
+    pub finalized_header: Header,
+    pub finality_branch: Vec<Bytes32>,
+    pub sync_aggregate: SyncAggregate,
+    pub signature_slot: U64,
+}
+
+impl From<&Update> for UpdateSerde {
+    fn from(update: &Update) -> Self {
+        Self {
+            attested_header: update.attested_header.clone(),
+            next_sync_committee: update.next_sync_committee.clone(),
+            next_sync_committee_branch: update.next_sync_committee_branch.clone(),
+            finalized_header: update.finalized_header.clone(),
+            finality_branch: update.finality_branch.clone(),
+            sync_aggregate: update.sync_aggregate.clone(),
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+impl From<UpdateSerde> for Update {
+    fn from(update: UpdateSerde) -> Self {
+        Self {
+            attested_header: update.attested_header,
+            next_sync_committee: update.next_sync_committee,
+            next_sync_committee_branch: update.next_sync_committee_branch,
+            finalized_header: update.finalized_header,
+            finality_branch: update.finality_branch,
+            sync_aggregate: update.sync_aggregate,
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+/// The BCS representation of [`FinalityUpdate`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FinalityUpdateSerde {
+    pub attested_header: Header,
+    pub finalized_header: Header,
+    pub finality_branch: Vec<Bytes32>,
+    pub sync_aggregate: SyncAggregate,
+    pub signature_slot: U64,
+}
+
+impl From<&FinalityUpdate> for FinalityUpdateSerde {
+    fn from(update: &FinalityUpdate) -> Self {
+        Self {
+            attested_header: update.attested_header.clone(),
+            finalized_header: update.finalized_header.clone(),
+            finality_branch: update.finality_branch.clone(),
+            sync_aggregate: update.sync_aggregate.clone(),
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+impl From<FinalityUpdateSerde> for FinalityUpdate {
+    fn from(update: FinalityUpdateSerde) -> Self {
+        Self {
+            attested_header: update.attested_header,
+            finalized_header: update.finalized_header,
+            finality_branch: update.finality_branch,
+            sync_aggregate: update.sync_aggregate,
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+/// The BCS representation of [`OptimisticUpdate`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OptimisticUpdateSerde {
+    pub attested_header: Header,
+    pub sync_aggregate: SyncAggregate,
+    pub signature_slot: U64,
+}
+
+impl From<&OptimisticUpdate> for OptimisticUpdateSerde {
+    fn from(update: &OptimisticUpdate) -> Self {
+        Self {
+            attested_header: update.attested_header.clone(),
+            sync_aggregate: update.sync_aggregate.clone(),
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+impl From<OptimisticUpdateSerde> for OptimisticUpdate {
+    fn from(update: OptimisticUpdateSerde) -> Self {
+        Self {
+            attested_header: update.attested_header,
+            sync_aggregate: update.sync_aggregate,
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+/// The BCS representation of [`AggregateUpdates`], i.e. every update dWallet needs to advance the
+/// light client state.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdatesResponse {
+    pub updates: Vec<UpdateSerde>,
+    pub finality_update: FinalityUpdateSerde,
+    pub optimistic_update: OptimisticUpdateSerde,
+}
+
+impl UpdatesResponse {
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    /// Same as [`UpdatesResponse::serialize_to_bytes`], but compressed with `compression`.
+    pub fn serialize_to_bytes_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        encode_versioned_with(self, compression)
+    }
+
+    /// Deserializes updates that were serialized with [`UpdatesResponse::serialize_to_bytes`], or
+    /// with [`UpdatesResponse::serialize_to_bytes_compressed`]. Fails with
+    /// [`ConsensusError::UnsupportedEncodingVersion`] for encodings of another version.
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+impl From<&AggregateUpdates> for UpdatesResponse {
+    fn from(updates: &AggregateUpdates) -> Self {
+        Self {
+            updates: updates.updates.iter().map(UpdateSerde::from).collect(),
+            finality_update: (&updates.finality_update).into(),
+            optimistic_update: (&updates.optimistic_update).into(),
+        }
+    }
+}
+
+impl From<UpdatesResponse> for AggregateUpdates {
+    fn from(response: UpdatesResponse) -> Self {
+        Self {
+            updates: response.updates.into_iter().map(Update::from).collect(),
+            finality_update: response.finality_update.into(),
+            optimistic_update: response.optimistic_update.into(),
+        }
+    }
+}
+
+/// The root of `committee`, by which a [`DeltaUpdatesResponse`] refers to a committee the
+/// receiver already knows.
+pub fn sync_committee_root(committee: &SyncCommittee) -> Result<Bytes32> {
+    let root = committee.clone().hash_tree_root()?;
+    Ok(Bytes32::try_from(root.as_ref())?)
+}
+
+/// The next sync committee of a [`DeltaUpdateSerde`], either in full or by its root.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum SyncCommitteeRef {
+    Full(Arc<SyncCommittee>),
+    Root(Bytes32),
+}
+
+/// An [`UpdateSerde`] whose next sync committee may be a reference.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeltaUpdateSerde {
+    pub attested_header: Header,
+    pub next_sync_committee: SyncCommitteeRef,
+    pub next_sync_committee_branch: Vec<Bytes32>,
+    pub finalized_header: Header,
+    pub finality_branch: Vec<Bytes32>,
+    pub sync_aggregate: SyncAggregate,
+    pub signature_slot: U64,
+}
+
+/// An [`UpdatesResponse`] in which the sync committees known to the receiver, and the ones
+/// repeated within the response, are replaced with their root. A committee is about 24KB, while
+/// its root is 32 bytes.
+///
+/// The references are only resolved to committees, not trusted: the resolved updates are verified
+/// as any other, and the branch of an update proves its committee.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeltaUpdatesResponse {
+    pub updates: Vec<DeltaUpdateSerde>,
+    pub finality_update: FinalityUpdateSerde,
+    pub optimistic_update: OptimisticUpdateSerde,
+}
+
+impl UpdatesResponse {
+    /// Encodes the updates as a delta against the committees whose roots are in `known`, e.g. the
+    /// committees of the light client state of the receiver.
+    pub fn to_delta(&self, known: &[Bytes32]) -> Result<DeltaUpdatesResponse> {
+        let mut sent: Vec<Bytes32> = Vec::new();
+        let updates = self
+            .updates
+            .iter()
+            .map(|update| {
+                let root = sync_committee_root(&update.next_sync_committee)?;
+                let next_sync_committee = if known.contains(&root) || sent.contains(&root) {
+                    SyncCommitteeRef::Root(root)
+                } else {
+                    sent.push(root);
+                    SyncCommitteeRef::Full(update.next_sync_committee.clone())
+                };
+
+                Ok(DeltaUpdateSerde {
+                    attested_header: update.attested_header.clone(),
+                    next_sync_committee,
+                    next_sync_committee_branch: update.next_sync_committee_branch.clone(),
+                    finalized_header: update.finalized_header.clone(),
+                    finality_branch: update.finality_branch.clone(),
+                    sync_aggregate: update.sync_aggregate.clone(),
+                    signature_slot: update.signature_slot,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DeltaUpdatesResponse {
+            updates,
+            finality_update: self.finality_update.clone(),
+            optimistic_update: self.optimistic_update.clone(),
+        })
+    }
+}
+
+impl DeltaUpdatesResponse {
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    /// Same as [`DeltaUpdatesResponse::serialize_to_bytes`], but compressed with `compression`.
+    pub fn serialize_to_bytes_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        encode_versioned_with(self, compression)
+    }
+
+    /// Deserializes updates that were serialized with
+    /// [`DeltaUpdatesResponse::serialize_to_bytes`], compressed or not.
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+
+    /// Replaces the committee references with the committees of `known`, or with the committees
+    /// sent earlier in the response. Fails with [`ConsensusError::UnknownSyncCommittee`] if a
+    /// reference is to neither.
+    pub fn resolve(self, known: &[Arc<SyncCommittee>]) -> Result<UpdatesResponse> {
+        let mut committees = known
+            .iter()
+            .map(|committee| Ok((sync_committee_root(committee)?, committee.clone())))
+            .collect::<Result<Vec<_>>>()?;
+
+        let updates = self
+            .updates
+            .into_iter()
+            .map(|update| {
+                let next_sync_committee = match update.next_sync_committee {
+                    SyncCommitteeRef::Full(committee) => {
+                        committees.push((sync_committee_root(&committee)?, committee.clone()));
+                        committee
+                    }
+                    SyncCommitteeRef::Root(root) => committees
+                        .iter()
+                        .find(|(known_root, _)| *known_root == root)
+                        .map(|(_, committee)| committee.clone())
+                        .ok_or_else(|| {
+                            ConsensusError::UnknownSyncCommittee(format!(
+                                "0x{}",
+                                hex::encode(&root)
+                            ))
+                        })?,
+                };
+
+                Ok(UpdateSerde {
+                    attested_header: update.attested_header,
+                    next_sync_committee,
+                    next_sync_committee_branch: update.next_sync_committee_branch,
+                    finalized_header: update.finalized_header,
+                    finality_branch: update.finality_branch,
+                    sync_aggregate: update.sync_aggregate,
+                    signature_slot: update.signature_slot,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(UpdatesResponse {
+            updates,
+            finality_update: self.finality_update,
+            optimistic_update: self.optimistic_update,
+        })
+    }
+}
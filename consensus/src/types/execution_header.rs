@@ -0,0 +1,161 @@
+//! The execution block header of a beacon block, rebuilt from the fields of its execution
+//! payload, so that the `block_hash` of the payload is checked instead of taken at face value.
+//!
+//! The payload carries the lists of the transactions and withdrawals rather than the roots of
+//! their tries, which are rebuilt, and the header of a Deneb block also commits to the parent root
+//! of the beacon block, and the one of an Electra block to its execution requests, see
+//! [EIP-7685](https://eips.ethereum.org/EIPS/eip-7685).
+
+use ethers::{
+    types::{Address, Bloom, Bytes, H256, H64, U256},
+    utils::{keccak256, rlp::RlpStream},
+};
+use eyre::Result;
+use sha2::{Digest, Sha256};
+use triehash_ethereum::ordered_trie_root;
+
+use super::{BeaconBlock, BeaconBlockBody, ExecutionPayload, ExecutionRequests, Withdrawal};
+
+/// The hash of the RLP encoding of an empty list, which is the ommers hash of every block since
+/// the merge.
+const EMPTY_OMMERS_HASH: &str = "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347";
+
+/// Returns the hash of the execution block header of `block`, as computed from its payload.
+pub fn execution_block_hash(block: &BeaconBlock) -> Result<H256> {
+    let payload = block.body.execution_payload();
+    let withdrawals = payload.withdrawals().ok();
+    let deneb = match payload {
+        ExecutionPayload::Deneb(payload) => Some(payload),
+        _ => None,
+    };
+    let requests = match &block.body {
+        BeaconBlockBody::Electra(body) => Some(&body.execution_requests),
+        _ => None,
+    };
+
+    let len = 16
+        + usize::from(withdrawals.is_some())
+        + 3 * usize::from(deneb.is_some())
+        + usize::from(requests.is_some());
+
+    let mut stream = RlpStream::new_list(len);
+    stream.append(&H256::from_slice(payload.parent_hash()));
+    stream.append(&H256::from_slice(&hex::decode(EMPTY_OMMERS_HASH)?));
+    stream.append(&Address::from_slice(payload.fee_recipient()));
+    stream.append(&H256::from_slice(payload.state_root()));
+    stream.append(&transactions_root(payload));
+    stream.append(&H256::from_slice(payload.receipts_root()));
+    stream.append(&Bloom::from_slice(payload.logs_bloom()));
+    stream.append(&U256::zero());
+    stream.append(&payload.block_number().as_u64());
+    stream.append(&payload.gas_limit().as_u64());
+    stream.append(&payload.gas_used().as_u64());
+    stream.append(&payload.timestamp().as_u64());
+    stream.append(&Bytes::from(payload.extra_data().to_vec()));
+    stream.append(&H256::from_slice(payload.prev_randao()));
+    stream.append(&H64::zero());
+    stream.append(&U256::from_little_endian(
+        &payload.base_fee_per_gas().to_bytes_le(),
+    ));
+
+    if let Some(withdrawals) = withdrawals {
+        stream.append(&withdrawals_root(withdrawals.iter()));
+    }
+    if let Some(payload) = deneb {
+        stream.append(&payload.blob_gas_used.as_u64());
+        stream.append(&payload.excess_blob_gas.as_u64());
+        stream.append(&H256::from_slice(&block.parent_root));
+    }
+    if let Some(requests) = requests {
+        stream.append(&requests_hash(requests)?);
+    }
+
+    Ok(H256::from(keccak256(stream.out())))
+}
+
+/// Returns the root of the trie of the transactions of `payload`, by index.
+pub fn transactions_root(payload: &ExecutionPayload) -> H256 {
+    let transactions = payload.transactions().decode();
+    let root = ordered_trie_root(transactions.iter().map(|tx| tx.as_slice()));
+    H256::from_slice(&root.to_fixed_bytes())
+}
+
+/// Returns the root of the trie of the RLP encoded `withdrawals`, by index.
+pub fn withdrawals_root<'a>(withdrawals: impl IntoIterator<Item = &'a Withdrawal>) -> H256 {
+    let encoded = withdrawals.into_iter().map(|withdrawal| {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&withdrawal.index.as_u64());
+        stream.append(&withdrawal.validator_index.as_u64());
+        stream.append(&Address::from_slice(&withdrawal.address));
+        stream.append(&withdrawal.amount.as_u64());
+        stream.out().to_vec()
+    });
+    let root = ordered_trie_root(encoded);
+    H256::from_slice(&root.to_fixed_bytes())
+}
+
+/// Returns the hash of the execution requests of an Electra block: the hash of the hashes of the
+/// requests of each type that has any, prefixed with the type.
+fn requests_hash(requests: &ExecutionRequests) -> Result<H256> {
+    let lists = [
+        ssz_rs::serialize(&requests.deposits)?,
+        ssz_rs::serialize(&requests.withdrawals)?,
+        ssz_rs::serialize(&requests.consolidations)?,
+    ];
+
+    let mut hasher = Sha256::new();
+    for (request_type, data) in lists.iter().enumerate() {
+        if !data.is_empty() {
+            let hash = Sha256::new()
+                .chain_update([request_type as u8])
+                .chain_update(data)
+                .finalize();
+            hasher.update(hash);
+        }
+    }
+
+    Ok(H256::from_slice(&hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn block(slot: u64) -> BeaconBlock {
+        let res = fs::read_to_string(format!("testdata/blocks/{slot}.json")).unwrap();
+        let res: serde_json::Value = serde_json::from_str(&res).unwrap();
+        serde_json::from_value(res["data"]["message"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_execution_block_hash() {
+        for slot in [7109344, 7109431] {
+            let block = block(slot);
+            let payload = block.body.execution_payload();
+            assert_eq!(
+                H256::from_slice(payload.block_hash()),
+                execution_block_hash(&block).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_tampered_payload() {
+        let mut block = block(7109344);
+        match &mut block.body {
+            BeaconBlockBody::Capella(body) => match &mut body.execution_payload {
+                ExecutionPayload::Capella(payload) => payload.gas_used = 1.into(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+
+        let payload = block.body.execution_payload();
+        assert_ne!(
+            H256::from_slice(payload.block_hash()),
+            execution_block_hash(&block).unwrap()
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Bootstraps from the genesis of a network, for the devnets that have no recent checkpoint. The
+//! genesis header is trusted like a checkpoint: its root must be the genesis block root of the
+//! config. The sync committee of the genesis state is then proven against the `state_root` of the
+//! header, and the genesis validators root of the state is checked against the config.
+
+use std::sync::Arc;
+
+use consensus_core::beacon_state::{state_field_gindex, BeaconStateField};
+use eyre::Result;
+use ssz_rs::prelude::*;
+
+use super::{validator::StateProof, Bootstrap, Bytes32, Header, SyncCommittee};
+use crate::errors::ConsensusError;
+
+/// The generalized index of the `genesis_validators_root` of a beacon state.
+pub fn genesis_validators_root_gindex() -> u64 {
+    state_field_gindex(BeaconStateField::GenesisValidatorsRoot)
+}
+
+/// The generalized index of the `current_sync_committee` of a beacon state.
+pub fn current_sync_committee_gindex() -> u64 {
+    state_field_gindex(BeaconStateField::CurrentSyncCommittee)
+}
+
+/// Builds the bootstrap of the genesis block `header` from the sync committee of the genesis
+/// state. Fails unless `header` is at slot 0 and has the root `genesis_block_root`, `root_proof`
+/// proves that the genesis validators root of its state is `genesis_root`, and `committee_proof`
+/// proves `sync_committee`.
+pub fn genesis_bootstrap(
+    header: Header,
+    mut sync_committee: SyncCommittee,
+    root_proof: &StateProof,
+    committee_proof: &StateProof,
+    genesis_root: &[u8],
+    genesis_block_root: &[u8],
+) -> Result<Bootstrap> {
+    if header.slot.as_u64() != 0 {
+        return Err(
+            ConsensusError::InvalidGenesis("the header is not at slot 0".to_string()).into(),
+        );
+    }
+
+    let block_root = genesis_checkpoint(&header)?;
+    if block_root != genesis_block_root {
+        return Err(ConsensusError::InvalidGenesis(format!(
+            "unexpected genesis block root 0x{}",
+            hex::encode(block_root)
+        ))
+        .into());
+    }
+
+    root_proof.verify(&header.state_root, genesis_validators_root_gindex())?;
+    if root_proof.leaf.as_slice() != genesis_root {
+        return Err(ConsensusError::InvalidGenesis(format!(
+            "unexpected genesis validators root 0x{}",
+            hex::encode(root_proof.leaf.as_slice())
+        ))
+        .into());
+    }
+
+    committee_proof.verify(&header.state_root, current_sync_committee_gindex())?;
+    let committee_root = sync_committee.hash_tree_root()?;
+    if committee_proof.leaf.as_slice() != committee_root.as_ref() {
+        return Err(ConsensusError::InvalidCurrentSyncCommitteeProof.into());
+    }
+
+    Ok(Bootstrap {
+        header,
+        current_sync_committee: Arc::new(sync_committee),
+        current_sync_committee_branch: committee_proof.branch.clone(),
+    })
+}
+
+/// Returns the root of the genesis block `header`, which is the checkpoint of its bootstrap.
+pub fn genesis_checkpoint(header: &Header) -> Result<Vec<u8>> {
+    Ok(header.clone().hash_tree_root()?.as_ref().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::types::withdrawals::merkle_branch;
+
+    const STATE_DEPTH: usize = 5;
+
+    fn to_bytes32(nodes: Vec<Node>) -> Vec<Bytes32> {
+        nodes
+            .iter()
+            .map(|node| Bytes32::try_from(node.as_ref()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_genesis_bootstrap() {
+        let genesis_root = [7u8; 32];
+        let mut sync_committee = SyncCommittee::default();
+        let committee_root = sync_committee.hash_tree_root().unwrap();
+
+        let root_index = (genesis_validators_root_gindex() - (1 << STATE_DEPTH)) as usize;
+        let committee_index = (current_sync_committee_gindex() - (1 << STATE_DEPTH)) as usize;
+        let mut fields = vec![Node::default(); 1 << STATE_DEPTH];
+        fields[root_index] = Node::try_from(genesis_root.as_slice()).unwrap();
+        fields[committee_index] = committee_root;
+
+        let root_branch = merkle_branch(fields.clone(), STATE_DEPTH, root_index);
+        let committee_branch = merkle_branch(fields.clone(), STATE_DEPTH, committee_index);
+        let root_proof = StateProof {
+            leaf: Bytes32::try_from(genesis_root.as_slice()).unwrap(),
+            branch: to_bytes32(root_branch),
+        };
+        let committee_proof = StateProof {
+            leaf: Bytes32::try_from(committee_root.as_ref()).unwrap(),
+            branch: to_bytes32(committee_branch),
+        };
+
+        let mut state_root = fields.clone();
+        while state_root.len() > 1 {
+            state_root = state_root
+                .chunks(2)
+                .map(|pair| {
+                    let mut node = pair[0].as_ref().to_vec();
+                    node.extend_from_slice(pair[1].as_ref());
+                    let hash = Sha256::digest(&node);
+                    Node::try_from(hash.as_slice()).unwrap()
+                })
+                .collect();
+        }
+        let header = Header {
+            state_root: Bytes32::try_from(state_root[0].as_ref()).unwrap(),
+            ..Default::default()
+        };
+        let block_root = genesis_checkpoint(&header).unwrap();
+
+        let bootstrap = genesis_bootstrap(
+            header.clone(),
+            sync_committee.clone(),
+            &root_proof,
+            &committee_proof,
+            &genesis_root,
+            &block_root,
+        )
+        .unwrap();
+        assert_eq!(
+            committee_proof.branch,
+            bootstrap.current_sync_committee_branch
+        );
+
+        // The state of another network has another genesis validators root.
+        assert!(genesis_bootstrap(
+            header.clone(),
+            sync_committee.clone(),
+            &root_proof,
+            &committee_proof,
+            &[8u8; 32],
+            &block_root,
+        )
+        .is_err());
+
+        // The genesis header served by the RPC must be the trusted one.
+        assert!(genesis_bootstrap(
+            header.clone(),
+            sync_committee.clone(),
+            &root_proof,
+            &committee_proof,
+            &genesis_root,
+            &[9u8; 32],
+        )
+        .is_err());
+
+        // The header of a later block is not a genesis header.
+        let mut later = header.clone();
+        later.slot = 1u64.into();
+        let later_root = genesis_checkpoint(&later).unwrap();
+        assert!(genesis_bootstrap(
+            later,
+            sync_committee.clone(),
+            &root_proof,
+            &committee_proof,
+            &genesis_root,
+            &later_root,
+        )
+        .is_err());
+
+        // The sync committee must be the proven one.
+        sync_committee.aggregate_pubkey = vec![1u8; 48].try_into().unwrap();
+        assert!(genesis_bootstrap(
+            header,
+            sync_committee,
+            &root_proof,
+            &committee_proof,
+            &genesis_root,
+            &block_root,
+        )
+        .is_err());
+    }
+}
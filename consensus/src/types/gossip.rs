@@ -0,0 +1,97 @@
+//! The gossip topics and messages of the light client updates of the peer to peer network, see
+//! the [light client p2p interface](https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/light-client/p2p-interface.md).
+//!
+//! The messages are snappy compressed SSZ, see [`light_client_ssz`].
+
+use eyre::Result;
+use sha2::{Digest, Sha256};
+
+use super::{light_client_ssz, FinalityUpdate, OptimisticUpdate};
+use crate::errors::ConsensusError;
+
+pub const FINALITY_UPDATE_TOPIC: &str = "light_client_finality_update";
+pub const OPTIMISTIC_UPDATE_TOPIC: &str = "light_client_optimistic_update";
+
+/// The forks of which the gossiped updates are decoded. Their light client headers carry an
+/// execution header, and Electra lengthens the finality branch.
+pub const GOSSIP_VERSIONS: [&str; 2] = ["capella", "deneb"];
+
+/// The maximum size of a gossip message, compressed or not.
+pub const GOSSIP_MAX_SIZE: usize = 10 * 1024 * 1024;
+
+const MESSAGE_DOMAIN_INVALID_SNAPPY: [u8; 4] = [0, 0, 0, 0];
+const MESSAGE_DOMAIN_VALID_SNAPPY: [u8; 4] = [1, 0, 0, 0];
+
+/// The topic of the gossip `name`, such as [`FINALITY_UPDATE_TOPIC`], for the fork with the
+/// digest `fork_digest`.
+pub fn topic(fork_digest: &[u8; 4], name: &str) -> String {
+    format!("/eth2/{}/{}/ssz_snappy", hex::encode(fork_digest), name)
+}
+
+/// The id of a message of `topic`, with which the peers deduplicate the messages they relay.
+pub fn message_id(topic: &str, data: &[u8]) -> Vec<u8> {
+    let (domain, data) = match decompress(data) {
+        Ok(decompressed) => (MESSAGE_DOMAIN_VALID_SNAPPY, decompressed),
+        Err(_) => (MESSAGE_DOMAIN_INVALID_SNAPPY, data.to_vec()),
+    };
+
+    Sha256::new()
+        .chain_update(domain)
+        .chain_update((topic.len() as u64).to_le_bytes())
+        .chain_update(topic.as_bytes())
+        .chain_update(data)
+        .finalize()[..20]
+        .to_vec()
+}
+
+/// Decodes a gossiped `LightClientFinalityUpdate`. The update is not verified.
+pub fn decode_finality_update(data: &[u8]) -> Result<FinalityUpdate> {
+    light_client_ssz::decode_finality_update(&decompress(data)?)
+}
+
+/// Decodes a gossiped `LightClientOptimisticUpdate`. The update is not verified.
+pub fn decode_optimistic_update(data: &[u8]) -> Result<OptimisticUpdate> {
+    light_client_ssz::decode_optimistic_update(&decompress(data)?)
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    if snap::raw::decompress_len(data)? > GOSSIP_MAX_SIZE {
+        return Err(ConsensusError::DecompressedTooLarge(GOSSIP_MAX_SIZE).into());
+    }
+
+    Ok(snap::raw::Decoder::new().decompress_vec(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::light_client_ssz::tests::{finality_update, optimistic_update};
+
+    #[test]
+    fn test_decode_gossiped_updates() {
+        let compress = |bytes: &[u8]| snap::raw::Encoder::new().compress_vec(bytes).unwrap();
+
+        let update = decode_finality_update(&compress(&finality_update(100, 64))).unwrap();
+        assert_eq!(64, update.finalized_header.slot.as_u64());
+        let update = decode_optimistic_update(&compress(&optimistic_update(100))).unwrap();
+        assert_eq!(100, update.attested_header.slot.as_u64());
+
+        // The messages are always compressed.
+        assert!(decode_optimistic_update(&optimistic_update(100)).is_err());
+    }
+
+    #[test]
+    fn test_topic_and_message_id() {
+        assert_eq!(
+            "/eth2/6a95a1a9/light_client_finality_update/ssz_snappy",
+            topic(&[0x6a, 0x95, 0xa1, 0xa9], FINALITY_UPDATE_TOPIC)
+        );
+
+        let topic = topic(&[0; 4], OPTIMISTIC_UPDATE_TOPIC);
+        let data = snap::raw::Encoder::new().compress_vec(b"update").unwrap();
+        let id = message_id(&topic, &data);
+        assert_eq!(20, id.len());
+        // The id of a valid message is the one of its decompressed data.
+        assert_ne!(id, message_id(&topic, b"update"));
+    }
+}
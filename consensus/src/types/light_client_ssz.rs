@@ -0,0 +1,329 @@
+//! SSZ decoding of the light client data of the Capella and Deneb forks, as gossiped on the peer to
+//! peer network and stored in the Portal network.
+//!
+//! Their light client headers carry an execution header, of which only the beacon part is kept,
+//! as for the responses of the beacon API. The data is not verified.
+
+use std::sync::Arc;
+
+use eyre::Result;
+use ssz_rs::prelude::*;
+
+use super::{
+    Bootstrap, Bytes32, FinalityUpdate, Header, OptimisticUpdate, SyncAggregate, SyncCommittee,
+    Update,
+};
+use crate::errors::ConsensusError;
+
+const OFFSET_LEN: usize = 4;
+const HEADER_LEN: usize = 112;
+const SYNC_COMMITTEE_LEN: usize = 513 * 48;
+const SYNC_COMMITTEE_BRANCH_LEN: usize = 5;
+const FINALITY_BRANCH_LEN: usize = 6;
+const SYNC_AGGREGATE_LEN: usize = 160;
+
+/// Reads the fixed part of a container, field by field.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, at: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.at..self.at + len)
+            .ok_or_else(|| invalid("container too short"))?;
+        self.at += len;
+        Ok(bytes)
+    }
+
+    fn offset(&mut self) -> Result<usize> {
+        let offset = u32::from_le_bytes(self.take(OFFSET_LEN)?.try_into()?) as usize;
+        if offset > self.bytes.len() {
+            return Err(invalid("offset out of bounds"));
+        }
+
+        Ok(offset)
+    }
+
+    fn branch(&mut self, len: usize) -> Result<Vec<Bytes32>> {
+        Ok(self
+            .take(len * 32)?
+            .chunks(32)
+            .map(Bytes32::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    /// Checks that the first variable field starts at `offset`, where the fixed part ends.
+    fn end_fixed_part(&self, offset: usize) -> Result<()> {
+        if offset != self.at {
+            return Err(invalid("invalid first offset"));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn decode_bootstrap(bytes: &[u8]) -> Result<Bootstrap> {
+    let mut reader = Reader::new(bytes);
+    let header_offset = reader.offset()?;
+    let current_sync_committee = SyncCommittee::deserialize(reader.take(SYNC_COMMITTEE_LEN)?)?;
+    let current_sync_committee_branch = reader.branch(SYNC_COMMITTEE_BRANCH_LEN)?;
+    reader.end_fixed_part(header_offset)?;
+
+    Ok(Bootstrap {
+        header: decode_header(&bytes[header_offset..])?,
+        current_sync_committee: Arc::new(current_sync_committee),
+        current_sync_committee_branch,
+    })
+}
+
+pub fn decode_update(bytes: &[u8]) -> Result<Update> {
+    let mut reader = Reader::new(bytes);
+    let attested_offset = reader.offset()?;
+    let next_sync_committee = SyncCommittee::deserialize(reader.take(SYNC_COMMITTEE_LEN)?)?;
+    let next_sync_committee_branch = reader.branch(SYNC_COMMITTEE_BRANCH_LEN)?;
+    let finalized_offset = reader.offset()?;
+    let finality_branch = reader.branch(FINALITY_BRANCH_LEN)?;
+    let sync_aggregate = SyncAggregate::deserialize(reader.take(SYNC_AGGREGATE_LEN)?)?;
+    let signature_slot = reader.u64()?;
+    reader.end_fixed_part(attested_offset)?;
+
+    Ok(Update {
+        attested_header: decode_header(variable_field(bytes, attested_offset, finalized_offset)?)?,
+        next_sync_committee: Arc::new(next_sync_committee),
+        next_sync_committee_branch,
+        finalized_header: decode_header(&bytes[finalized_offset..])?,
+        finality_branch,
+        sync_aggregate,
+        signature_slot: signature_slot.into(),
+    })
+}
+
+pub fn decode_finality_update(bytes: &[u8]) -> Result<FinalityUpdate> {
+    let mut reader = Reader::new(bytes);
+    let attested_offset = reader.offset()?;
+    let finalized_offset = reader.offset()?;
+    let finality_branch = reader.branch(FINALITY_BRANCH_LEN)?;
+    let sync_aggregate = SyncAggregate::deserialize(reader.take(SYNC_AGGREGATE_LEN)?)?;
+    let signature_slot = reader.u64()?;
+    reader.end_fixed_part(attested_offset)?;
+
+    Ok(FinalityUpdate {
+        attested_header: decode_header(variable_field(bytes, attested_offset, finalized_offset)?)?,
+        finalized_header: decode_header(&bytes[finalized_offset..])?,
+        finality_branch,
+        sync_aggregate,
+        signature_slot: signature_slot.into(),
+    })
+}
+
+pub fn decode_optimistic_update(bytes: &[u8]) -> Result<OptimisticUpdate> {
+    let mut reader = Reader::new(bytes);
+    let attested_offset = reader.offset()?;
+    let sync_aggregate = SyncAggregate::deserialize(reader.take(SYNC_AGGREGATE_LEN)?)?;
+    let signature_slot = reader.u64()?;
+    reader.end_fixed_part(attested_offset)?;
+
+    Ok(OptimisticUpdate {
+        attested_header: decode_header(&bytes[attested_offset..])?,
+        sync_aggregate,
+        signature_slot: signature_slot.into(),
+    })
+}
+
+/// Splits an SSZ list of variable size items, such as the updates of a range of periods.
+pub fn split_list(bytes: &[u8]) -> Result<Vec<&[u8]>> {
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut reader = Reader::new(bytes);
+    let first = reader.offset()?;
+    if first == 0 || first % OFFSET_LEN != 0 {
+        return Err(invalid("invalid list offset"));
+    }
+
+    let mut offsets = vec![first];
+    for _ in 1..first / OFFSET_LEN {
+        offsets.push(reader.offset()?);
+    }
+    offsets.push(bytes.len());
+
+    offsets
+        .windows(2)
+        .map(|window| variable_field(bytes, window[0], window[1]))
+        .collect()
+}
+
+fn variable_field(bytes: &[u8], start: usize, end: usize) -> Result<&[u8]> {
+    bytes
+        .get(start..end)
+        .ok_or_else(|| invalid("invalid offsets"))
+}
+
+/// Decodes the beacon header of a light client header, which is its first field.
+fn decode_header(bytes: &[u8]) -> Result<Header> {
+    if bytes.len() < HEADER_LEN {
+        return Err(invalid("light client header too short"));
+    }
+
+    Ok(Header::deserialize(&bytes[..HEADER_LEN])?)
+}
+
+fn invalid(reason: &str) -> eyre::Report {
+    ConsensusError::InvalidLightClientSsz(reason.to_string()).into()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn header(slot: u64) -> Header {
+        Header {
+            slot: slot.into(),
+            proposer_index: 7u64.into(),
+            ..Default::default()
+        }
+    }
+
+    /// A light client header with an execution part, of which only the beacon header is decoded.
+    pub(crate) fn light_client_header(slot: u64) -> Vec<u8> {
+        let mut bytes = ssz_rs::serialize(&header(slot)).unwrap();
+        bytes.extend_from_slice(&((HEADER_LEN + OFFSET_LEN + 128) as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0xab; 128 + 600]);
+        bytes
+    }
+
+    fn sync_aggregate() -> Vec<u8> {
+        ssz_rs::serialize(&SyncAggregate::default()).unwrap()
+    }
+
+    pub(crate) fn finality_update(attested_slot: u64, finalized_slot: u64) -> Vec<u8> {
+        let attested = light_client_header(attested_slot);
+        let fixed_len = 2 * OFFSET_LEN + FINALITY_BRANCH_LEN * 32 + SYNC_AGGREGATE_LEN + 8;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(fixed_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&((fixed_len + attested.len()) as u32).to_le_bytes());
+        bytes.extend_from_slice(&[1; FINALITY_BRANCH_LEN * 32]);
+        bytes.extend_from_slice(&sync_aggregate());
+        bytes.extend_from_slice(&(attested_slot + 1).to_le_bytes());
+        bytes.extend_from_slice(&attested);
+        bytes.extend_from_slice(&light_client_header(finalized_slot));
+        bytes
+    }
+
+    pub(crate) fn optimistic_update(attested_slot: u64) -> Vec<u8> {
+        let fixed_len = OFFSET_LEN + SYNC_AGGREGATE_LEN + 8;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(fixed_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&sync_aggregate());
+        bytes.extend_from_slice(&(attested_slot + 1).to_le_bytes());
+        bytes.extend_from_slice(&light_client_header(attested_slot));
+        bytes
+    }
+
+    pub(crate) fn update(attested_slot: u64, finalized_slot: u64) -> Vec<u8> {
+        let attested = light_client_header(attested_slot);
+        let fixed_len = OFFSET_LEN
+            + SYNC_COMMITTEE_LEN
+            + SYNC_COMMITTEE_BRANCH_LEN * 32
+            + OFFSET_LEN
+            + FINALITY_BRANCH_LEN * 32
+            + SYNC_AGGREGATE_LEN
+            + 8;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(fixed_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&ssz_rs::serialize(&SyncCommittee::default()).unwrap());
+        bytes.extend_from_slice(&[2; SYNC_COMMITTEE_BRANCH_LEN * 32]);
+        bytes.extend_from_slice(&((fixed_len + attested.len()) as u32).to_le_bytes());
+        bytes.extend_from_slice(&[1; FINALITY_BRANCH_LEN * 32]);
+        bytes.extend_from_slice(&sync_aggregate());
+        bytes.extend_from_slice(&(attested_slot + 1).to_le_bytes());
+        bytes.extend_from_slice(&attested);
+        bytes.extend_from_slice(&light_client_header(finalized_slot));
+        bytes
+    }
+
+    pub(crate) fn bootstrap(slot: u64) -> Vec<u8> {
+        let fixed_len = OFFSET_LEN + SYNC_COMMITTEE_LEN + SYNC_COMMITTEE_BRANCH_LEN * 32;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(fixed_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&ssz_rs::serialize(&SyncCommittee::default()).unwrap());
+        bytes.extend_from_slice(&[2; SYNC_COMMITTEE_BRANCH_LEN * 32]);
+        bytes.extend_from_slice(&light_client_header(slot));
+        bytes
+    }
+
+    #[test]
+    fn test_decode_finality_update() {
+        let mut bytes = finality_update(100, 64);
+        let update = decode_finality_update(&bytes).unwrap();
+        assert_eq!(100, update.attested_header.slot.as_u64());
+        assert_eq!(7, update.attested_header.proposer_index.as_u64());
+        assert_eq!(64, update.finalized_header.slot.as_u64());
+        assert_eq!(FINALITY_BRANCH_LEN, update.finality_branch.len());
+        assert_eq!(101, update.signature_slot.as_u64());
+
+        bytes.truncate(OFFSET_LEN * 2 + 10);
+        assert!(decode_finality_update(&bytes).is_err());
+        // An optimistic update does not decode as a finality update.
+        assert!(decode_finality_update(&optimistic_update(100)).is_err());
+    }
+
+    #[test]
+    fn test_decode_optimistic_update() {
+        let update = decode_optimistic_update(&optimistic_update(100)).unwrap();
+        assert_eq!(100, update.attested_header.slot.as_u64());
+        assert_eq!(101, update.signature_slot.as_u64());
+    }
+
+    #[test]
+    fn test_decode_update_and_bootstrap() {
+        let update = decode_update(&update(100, 64)).unwrap();
+        assert_eq!(100, update.attested_header.slot.as_u64());
+        assert_eq!(64, update.finalized_header.slot.as_u64());
+        assert_eq!(
+            SYNC_COMMITTEE_BRANCH_LEN,
+            update.next_sync_committee_branch.len()
+        );
+
+        let bootstrap = decode_bootstrap(&bootstrap(32)).unwrap();
+        assert_eq!(32, bootstrap.header.slot.as_u64());
+        assert_eq!(
+            SYNC_COMMITTEE_BRANCH_LEN,
+            bootstrap.current_sync_committee_branch.len()
+        );
+    }
+
+    #[test]
+    fn test_split_list() {
+        let items: [&[u8]; 3] = [b"first", b"", b"third"];
+        let mut bytes = Vec::new();
+        let mut offset = items.len() * OFFSET_LEN;
+        for item in items {
+            bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += item.len();
+        }
+        for item in items {
+            bytes.extend_from_slice(item);
+        }
+
+        assert_eq!(items.to_vec(), split_list(&bytes).unwrap());
+        assert!(split_list(&[]).unwrap().is_empty());
+        assert!(split_list(&[3, 0, 0, 0]).is_err());
+    }
+}
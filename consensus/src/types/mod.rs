@@ -1,4 +1,4 @@
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, sync::Arc};
 
 use eyre::Result;
 use ssz_rs::prelude::*;
@@ -6,17 +6,34 @@ use superstruct::superstruct;
 
 use self::{
     primitives::{ByteList, ByteVector, U64},
+    transactions::{LazyTransactions, MAX_BYTES_PER_TRANSACTION},
     utils::{header_deserialize, superstruct_ssz, u256_deserialize},
 };
+use crate::constants::{BYTES_PER_BLOB, SYNC_COMMITTEE_SIZE};
+pub mod conflict;
+pub mod diff;
+pub mod encoding;
+pub mod execution_header;
+pub mod genesis;
+pub mod gossip;
+pub mod light_client_ssz;
+pub mod multiproof;
+pub mod payload_proof;
 pub mod primitives;
+pub mod transactions;
 pub(crate) mod utils;
+pub mod validator;
+pub mod withdrawals;
 
 pub type Address = ByteVector<20>;
 pub type Bytes32 = ByteVector<32>;
 pub type LogsBloom = ByteVector<256>;
 pub type BLSPubKey = ByteVector<48>;
 pub type SignatureBytes = ByteVector<96>;
-pub type Transaction = ByteList<1073741824>;
+pub type KzgCommitment = ByteVector<48>;
+pub type KzgProof = ByteVector<48>;
+pub type Blob = ByteVector<BYTES_PER_BLOB>;
+pub type Transaction = ByteList<MAX_BYTES_PER_TRANSACTION>;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
 pub struct BeaconBlock {
@@ -29,10 +46,12 @@ pub struct BeaconBlock {
 }
 
 /// Represents the different types of Beacon blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum BeaconBlockType {
     Bellatrix,
     Capella,
     Deneb,
+    Electra,
 }
 
 impl Display for BeaconBlockType {
@@ -41,6 +60,7 @@ impl Display for BeaconBlockType {
             BeaconBlockType::Bellatrix => "bellatrix".to_string(),
             BeaconBlockType::Capella => "capella".to_string(),
             BeaconBlockType::Deneb => "deneb".to_string(),
+            BeaconBlockType::Electra => "electra".to_string(),
         };
         write!(f, "{}", str)
     }
@@ -55,6 +75,7 @@ impl FromStr for BeaconBlockType {
             "bellatrix" => Ok(BeaconBlockType::Bellatrix),
             "capella" => Ok(BeaconBlockType::Capella),
             "deneb" => Ok(BeaconBlockType::Deneb),
+            "electra" => Ok(BeaconBlockType::Electra),
             _ => Err(()),
         }
     }
@@ -65,6 +86,7 @@ pub enum BeaconBlockBodyWrapper {
     Bellatrix(BeaconBlockBodyBellatrix),
     Capella(BeaconBlockBodyCapella),
     Deneb(BeaconBlockBodyDeneb),
+    Electra(BeaconBlockBodyElectra),
 }
 
 impl BeaconBlockBodyWrapper {
@@ -93,12 +115,15 @@ impl BeaconBlockBodyWrapper {
             BeaconBlockBodyWrapper::Bellatrix(body) => BeaconBlockBody::Bellatrix(body),
             BeaconBlockBodyWrapper::Capella(body) => BeaconBlockBody::Capella(body),
             BeaconBlockBodyWrapper::Deneb(body) => BeaconBlockBody::Deneb(body),
+            BeaconBlockBodyWrapper::Electra(body) => BeaconBlockBody::Electra(body),
         }
     }
 }
 
+/// The body of a beacon block. The payload of an Electra body is a Deneb payload, whose layout
+/// Electra did not change.
 #[superstruct(
-    variants(Bellatrix, Capella, Deneb),
+    variants(Bellatrix, Capella, Deneb, Electra),
     variant_attributes(
         derive(
             serde::Deserialize,
@@ -118,17 +143,27 @@ pub struct BeaconBlockBody {
     eth1_data: Eth1Data,
     graffiti: Bytes32,
     proposer_slashings: List<ProposerSlashing, 16>,
+    #[superstruct(only(Bellatrix, Capella, Deneb))]
     attester_slashings: List<AttesterSlashing, 2>,
+    #[superstruct(only(Electra))]
+    #[serde(rename = "attester_slashings")]
+    attester_slashings_electra: List<AttesterSlashingElectra, 1>,
+    #[superstruct(only(Bellatrix, Capella, Deneb))]
     attestations: List<Attestation, 128>,
+    #[superstruct(only(Electra))]
+    #[serde(rename = "attestations")]
+    attestations_electra: List<AttestationElectra, 8>,
     deposits: List<Deposit, 16>,
     voluntary_exits: List<SignedVoluntaryExit, 16>,
     sync_aggregate: SyncAggregate,
     #[serde(skip_serializing, default)]
     pub execution_payload: ExecutionPayload,
-    #[superstruct(only(Capella, Deneb))]
+    #[superstruct(only(Capella, Deneb, Electra))]
     bls_to_execution_changes: List<SignedBlsToExecutionChange, 16>,
-    #[superstruct(only(Deneb))]
-    blob_kzg_commitments: List<ByteVector<48>, 4096>,
+    #[superstruct(only(Deneb, Electra))]
+    pub blob_kzg_commitments: List<KzgCommitment, 4096>,
+    #[superstruct(only(Electra))]
+    pub execution_requests: ExecutionRequests,
 }
 
 impl Default for BeaconBlockBody {
@@ -137,7 +172,43 @@ impl Default for BeaconBlockBody {
     }
 }
 
-superstruct_ssz!(BeaconBlockBody);
+superstruct_ssz!(BeaconBlockBody, Bellatrix, Capella, Deneb, Electra);
+
+/// The requests of the execution layer to the consensus layer of an Electra block, see
+/// [EIP-7685](https://eips.ethereum.org/EIPS/eip-7685).
+#[derive(Default, Clone, Debug, SimpleSerialize, serde::Deserialize, serde::Serialize)]
+pub struct ExecutionRequests {
+    pub deposits: List<DepositRequest, 8192>,
+    pub withdrawals: List<WithdrawalRequest, 16>,
+    pub consolidations: List<ConsolidationRequest, 2>,
+}
+
+/// A deposit made on the execution layer, see [EIP-6110](https://eips.ethereum.org/EIPS/eip-6110).
+#[derive(Default, Clone, Debug, SimpleSerialize, serde::Deserialize, serde::Serialize)]
+pub struct DepositRequest {
+    pub pubkey: BLSPubKey,
+    pub withdrawal_credentials: Bytes32,
+    pub amount: U64,
+    pub signature: SignatureBytes,
+    pub index: U64,
+}
+
+/// A withdrawal or exit triggered from the execution layer, see
+/// [EIP-7002](https://eips.ethereum.org/EIPS/eip-7002).
+#[derive(Default, Clone, Debug, SimpleSerialize, serde::Deserialize, serde::Serialize)]
+pub struct WithdrawalRequest {
+    pub source_address: Address,
+    pub validator_pubkey: BLSPubKey,
+    pub amount: U64,
+}
+
+/// A consolidation of two validators, see [EIP-7251](https://eips.ethereum.org/EIPS/eip-7251).
+#[derive(Default, Clone, Debug, SimpleSerialize, serde::Deserialize, serde::Serialize)]
+pub struct ConsolidationRequest {
+    pub source_address: Address,
+    pub source_pubkey: BLSPubKey,
+    pub target_pubkey: BLSPubKey,
+}
 
 #[derive(Default, Clone, Debug, SimpleSerialize, serde::Deserialize, serde::Serialize)]
 pub struct SignedBlsToExecutionChange {
@@ -171,9 +242,11 @@ impl ExecutionPayloadWrapper {
                     ExecutionPayloadCapella,
                 >(&json)?))
             }
-            BeaconBlockType::Deneb => Ok(ExecutionPayloadWrapper::Deneb(serde_json::from_slice::<
-                ExecutionPayloadDeneb,
-            >(&json)?)),
+            BeaconBlockType::Deneb | BeaconBlockType::Electra => {
+                Ok(ExecutionPayloadWrapper::Deneb(serde_json::from_slice::<
+                    ExecutionPayloadDeneb,
+                >(&json)?))
+            }
         }
     }
 
@@ -218,9 +291,9 @@ pub struct ExecutionPayload {
     #[serde(deserialize_with = "u256_deserialize")]
     pub base_fee_per_gas: U256,
     pub block_hash: Bytes32,
-    pub transactions: List<Transaction, 1048576>,
+    pub transactions: LazyTransactions,
     #[superstruct(only(Capella, Deneb))]
-    withdrawals: List<Withdrawal, 16>,
+    pub withdrawals: List<Withdrawal, 16>,
     #[superstruct(only(Deneb))]
     blob_gas_used: U64,
     #[superstruct(only(Deneb))]
@@ -233,14 +306,14 @@ impl Default for ExecutionPayload {
     }
 }
 
-superstruct_ssz!(ExecutionPayload);
+superstruct_ssz!(ExecutionPayload, Bellatrix, Capella, Deneb);
 
 #[derive(Default, Clone, Debug, SimpleSerialize, serde::Deserialize, serde::Serialize)]
 pub struct Withdrawal {
-    index: U64,
-    validator_index: U64,
-    address: Address,
-    amount: U64,
+    pub index: U64,
+    pub validator_index: U64,
+    pub address: Address,
+    pub amount: U64,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
@@ -250,18 +323,18 @@ pub struct ProposerSlashing {
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
-struct SignedBeaconBlockHeader {
-    message: BeaconBlockHeader,
-    signature: SignatureBytes,
+pub struct SignedBeaconBlockHeader {
+    pub message: BeaconBlockHeader,
+    pub signature: SignatureBytes,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
-struct BeaconBlockHeader {
-    slot: U64,
-    proposer_index: U64,
-    parent_root: Bytes32,
-    state_root: Bytes32,
-    body_root: Bytes32,
+pub struct BeaconBlockHeader {
+    pub slot: U64,
+    pub proposer_index: U64,
+    pub parent_root: Bytes32,
+    pub state_root: Bytes32,
+    pub body_root: Bytes32,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
@@ -270,6 +343,21 @@ pub struct AttesterSlashing {
     attestation_2: IndexedAttestation,
 }
 
+/// An attester slashing of an Electra block, of which the attestations span every committee of
+/// a slot.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
+pub struct AttesterSlashingElectra {
+    attestation_1: IndexedAttestationElectra,
+    attestation_2: IndexedAttestationElectra,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
+struct IndexedAttestationElectra {
+    attesting_indices: List<U64, 131072>,
+    data: AttestationData,
+    signature: SignatureBytes,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
 struct IndexedAttestation {
     attesting_indices: List<U64, 2048>,
@@ -284,6 +372,15 @@ pub struct Attestation {
     signature: SignatureBytes,
 }
 
+/// An attestation of an Electra block, which aggregates the committees of `committee_bits`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
+pub struct AttestationElectra {
+    aggregation_bits: Bitlist<131072>,
+    data: AttestationData,
+    signature: SignatureBytes,
+    committee_bits: Bitvector<64>,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
 struct AttestationData {
     slot: U64,
@@ -332,11 +429,11 @@ pub struct Eth1Data {
     block_hash: Bytes32,
 }
 
-#[derive(serde::Deserialize, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct Bootstrap {
     #[serde(deserialize_with = "header_deserialize")]
     pub header: Header,
-    pub current_sync_committee: SyncCommittee,
+    pub current_sync_committee: Arc<SyncCommittee>,
     pub current_sync_committee_branch: Vec<Bytes32>,
 }
 
@@ -344,7 +441,7 @@ pub struct Bootstrap {
 pub struct Update {
     #[serde(deserialize_with = "header_deserialize")]
     pub attested_header: Header,
-    pub next_sync_committee: SyncCommittee,
+    pub next_sync_committee: Arc<SyncCommittee>,
     pub next_sync_committee_branch: Vec<Bytes32>,
     #[serde(deserialize_with = "header_deserialize")]
     pub finalized_header: Header,
@@ -353,7 +450,7 @@ pub struct Update {
     pub signature_slot: U64,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Debug, Default)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
 pub struct FinalityUpdate {
     #[serde(deserialize_with = "header_deserialize")]
     pub attested_header: Header,
@@ -364,7 +461,7 @@ pub struct FinalityUpdate {
     pub signature_slot: U64,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct OptimisticUpdate {
     #[serde(deserialize_with = "header_deserialize")]
     pub attested_header: Header,
@@ -372,6 +469,17 @@ pub struct OptimisticUpdate {
     pub signature_slot: U64,
 }
 
+/// A blob of a Deneb block, as served by `/eth/v1/beacon/blob_sidecars`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, SimpleSerialize, Clone)]
+pub struct BlobSidecar {
+    pub index: U64,
+    pub blob: Blob,
+    pub kzg_commitment: KzgCommitment,
+    pub kzg_proof: KzgProof,
+    pub signed_block_header: SignedBeaconBlockHeader,
+    pub kzg_commitment_inclusion_proof: Vector<Bytes32, 17>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, SimpleSerialize)]
 pub struct Header {
     pub slot: U64,
@@ -393,11 +501,14 @@ pub struct SyncAggregate {
     pub sync_committee_signature: SignatureBytes,
 }
 
+/// The fields shared by [`Update`], [`FinalityUpdate`] and [`OptimisticUpdate`]. The next sync
+/// committee is shared with the update it was built from rather than copied.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct GenericUpdate {
     pub attested_header: Header,
     pub sync_aggregate: SyncAggregate,
     pub signature_slot: u64,
-    pub next_sync_committee: Option<SyncCommittee>,
+    pub next_sync_committee: Option<Arc<SyncCommittee>>,
     pub next_sync_committee_branch: Option<Vec<Bytes32>>,
     pub finalized_header: Option<Header>,
     pub finality_branch: Option<Vec<Bytes32>>,
@@ -445,6 +556,95 @@ impl From<&OptimisticUpdate> for GenericUpdate {
     }
 }
 
+/// Summary of a successfully verified update, so that integrators can apply acceptance policies of
+/// their own (e.g. a minimum participation) on top of the light client verification.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Number of sync committee members that signed the attested header.
+    pub participation: u64,
+    pub attested_slot: u64,
+    pub signature_slot: u64,
+    /// The finalized slot, if the update carried a finalized header.
+    pub finalized_slot: Option<u64>,
+    /// Whether a finality branch was verified against the attested header.
+    pub has_finality_proof: bool,
+    /// Whether a next sync committee branch was verified against the attested header.
+    pub has_next_sync_committee_proof: bool,
+}
+
+impl VerificationReport {
+    /// The share of the sync committee that signed the attested header, between 0 and 1.
+    pub fn participation_ratio(&self) -> f64 {
+        self.participation as f64 / SYNC_COMMITTEE_SIZE as f64
+    }
+}
+
+/// A newly verified finalized header, as sent to the subscribers of
+/// `ConsensusStateManager::subscribe_finalized`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct FinalizedHeader {
+    pub slot: u64,
+    /// The hash tree root of `header`, i.e. the beacon block root.
+    pub block_root: Bytes32,
+    pub header: Header,
+    /// The hash of the execution block, if its payload could be fetched and verified.
+    pub execution_block_hash: Option<Bytes32>,
+    /// The state root of the execution block, if its payload could be fetched and verified.
+    pub execution_state_root: Option<Bytes32>,
+}
+
+/// A checkpoint that was found too far behind the wall clock to sync forward from, so that the
+/// client bootstraps from a fresh checkpoint instead, see `Config::stale_checkpoint_age`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StaleCheckpoint {
+    pub checkpoint: Vec<u8>,
+    /// The slot of the header of the checkpoint.
+    pub slot: u64,
+    /// The age of the header of the checkpoint, in seconds.
+    pub age: u64,
+}
+
+/// A warning of a degraded sync, raised when a threshold of `Config::sync_health` is crossed. It
+/// is raised again only once the sync recovered and the threshold is crossed again.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum SyncHealthWarning {
+    /// The mean sync committee participation of the recent updates is below the threshold.
+    LowParticipation { participation: f64, threshold: f64 },
+    /// The finalized header is more slots behind the wall clock than the threshold.
+    FinalityLag { lag: u64, threshold: u64 },
+}
+
+/// The sync committee participation of the recent finality and optimistic updates, as fractions of
+/// the committee.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct ParticipationStats {
+    /// The number of updates of the window.
+    pub updates: usize,
+    pub mean: f64,
+    pub min: f64,
+    /// The participation of the latest update.
+    pub latest: f64,
+}
+
+/// The status of a consensus state manager, for health and readiness probes.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Health {
+    /// Whether the state was bootstrapped and its optimistic header is recent.
+    pub synced: bool,
+    pub finalized_slot: u64,
+    pub optimistic_slot: u64,
+    /// The number of slots between the wall clock and the finalized header.
+    pub finalized_lag: u64,
+    /// The number of slots between the wall clock and the optimistic header.
+    pub optimistic_lag: u64,
+    /// The unix timestamp, in seconds, of the last sync or advance that succeeded.
+    pub last_success: Option<u64>,
+    /// The error of the last sync or advance, if it failed.
+    pub last_error: Option<String>,
+    /// The participation of the recent updates, once one was applied.
+    pub participation: Option<ParticipationStats>,
+}
+
 /// Holds an aggregate of all update types that are necessary to verify and apply a new Ethereum
 /// state.
 #[derive(Debug)]
@@ -476,6 +676,11 @@ impl BeaconBlockBody {
                 beacon_block_body_deneb.execution_payload = payload;
                 BeaconBlockBody::Deneb(beacon_block_body_deneb)
             }
+            BeaconBlockBody::Electra(beacon_block_body) => {
+                let mut beacon_block_body_electra: BeaconBlockBodyElectra = beacon_block_body;
+                beacon_block_body_electra.execution_payload = payload;
+                BeaconBlockBody::Electra(beacon_block_body_electra)
+            }
         }
     }
 
@@ -485,6 +690,12 @@ impl BeaconBlockBody {
             BeaconBlockBody::Bellatrix(_) => BeaconBlockType::Bellatrix,
             BeaconBlockBody::Capella(_) => BeaconBlockType::Capella,
             BeaconBlockBody::Deneb(_) => BeaconBlockType::Deneb,
+            BeaconBlockBody::Electra(_) => BeaconBlockType::Electra,
         }
     }
+
+    /// Returns the execution requests of the body, which are empty before Electra.
+    pub fn execution_requests_or_default(&self) -> ExecutionRequests {
+        self.execution_requests().cloned().unwrap_or_default()
+    }
 }
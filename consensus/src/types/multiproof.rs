@@ -0,0 +1,146 @@
+//! SSZ multiproofs of several leaves of a beacon state, which share the nodes of their branches,
+//! so that composite claims, such as the fields of a validator, ship as one compact proof.
+
+use consensus_core::merkle::{is_valid_multiproof, multiproof_helper_indices};
+use eyre::Result;
+
+use super::{
+    encoding::{decode_versioned, encode_versioned},
+    validator::StateProof,
+    Bytes32,
+};
+use crate::errors::ConsensusError;
+
+/// The proof of the `leaves` at the generalized indices `gindices` of a beacon state. The nodes
+/// of `proof` are at the indices of [`multiproof_helper_indices`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StateMultiproof {
+    pub gindices: Vec<u64>,
+    pub leaves: Vec<Bytes32>,
+    pub proof: Vec<Bytes32>,
+}
+
+impl StateMultiproof {
+    /// Builds the multiproof of the leaves at `gindices` from their single proofs, in the same
+    /// order. The proofs are not verified.
+    pub fn from_proofs(gindices: &[u64], proofs: &[StateProof]) -> Result<Self> {
+        // The generalized indices start at 1, the root.
+        if gindices.len() != proofs.len() || gindices.contains(&0) {
+            return Err(ConsensusError::InvalidStateMultiproof.into());
+        }
+
+        let helpers = multiproof_helper_indices(gindices);
+        let proof = helpers
+            .iter()
+            .map(|helper| {
+                // Every helper is the sibling of a node on the path of one of the leaves.
+                gindices
+                    .iter()
+                    .zip(proofs)
+                    .find_map(|(gindex, proof)| {
+                        let depth =
+                            (0..gindex.ilog2()).find(|depth| (gindex >> depth) ^ 1 == *helper)?;
+                        proof.branch.get(depth as usize).cloned()
+                    })
+                    .ok_or(ConsensusError::InvalidStateMultiproof)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            gindices: gindices.to_vec(),
+            leaves: proofs.iter().map(|proof| proof.leaf.clone()).collect(),
+            proof,
+        })
+    }
+
+    /// Verifies every leaf against `state_root`, which must be checked against a verified header
+    /// by the caller.
+    pub fn verify(&self, state_root: &Bytes32) -> Result<()> {
+        let to_array = |bytes: &Bytes32| -> Result<[u8; 32]> { Ok(bytes.as_slice().try_into()?) };
+        let leaves = self
+            .leaves
+            .iter()
+            .map(to_array)
+            .collect::<Result<Vec<_>>>()?;
+        let proof = self
+            .proof
+            .iter()
+            .map(to_array)
+            .collect::<Result<Vec<_>>>()?;
+
+        if !is_valid_multiproof(&leaves, &self.gindices, &proof, &to_array(state_root)?) {
+            return Err(ConsensusError::InvalidStateMultiproof.into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the proven leaf at `gindex`, if the multiproof has it.
+    pub fn leaf(&self, gindex: u64) -> Option<&Bytes32> {
+        let index = self.gindices.iter().position(|proven| *proven == gindex)?;
+        self.leaves.get(index)
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    fn bytes32(byte: u8) -> Bytes32 {
+        Bytes32::try_from([byte; 32].as_slice()).unwrap()
+    }
+
+    fn hash(left: &Bytes32, right: &Bytes32) -> Bytes32 {
+        let node: [u8; 32] = Sha256::new()
+            .chain_update(left.as_slice())
+            .chain_update(right.as_slice())
+            .finalize()
+            .into();
+        Bytes32::try_from(node.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_state_multiproof() {
+        let leaves = [bytes32(1), bytes32(2), bytes32(3), bytes32(4)];
+        let left = hash(&leaves[0], &leaves[1]);
+        let right = hash(&leaves[2], &leaves[3]);
+        let root = hash(&left, &right);
+
+        let proofs = [
+            StateProof {
+                leaf: leaves[0].clone(),
+                branch: vec![leaves[1].clone(), right.clone()],
+            },
+            StateProof {
+                leaf: leaves[2].clone(),
+                branch: vec![leaves[3].clone(), left],
+            },
+        ];
+
+        let multiproof = StateMultiproof::from_proofs(&[4, 6], &proofs).unwrap();
+        assert_eq!(vec![leaves[3].clone(), leaves[1].clone()], multiproof.proof);
+        assert_eq!(Some(&leaves[2]), multiproof.leaf(6));
+        assert_eq!(None, multiproof.leaf(5));
+        multiproof.verify(&root).unwrap();
+
+        let bytes = multiproof.serialize_to_bytes().unwrap();
+        let mut decoded = StateMultiproof::deserialize_from_bytes(&bytes).unwrap();
+        decoded.verify(&root).unwrap();
+
+        decoded.leaves[1] = bytes32(5);
+        assert!(decoded.verify(&root).is_err());
+        assert!(multiproof.verify(&right).is_err());
+        assert!(StateMultiproof::from_proofs(&[4], &proofs).is_err());
+        assert!(StateMultiproof::from_proofs(&[4, 0], &proofs).is_err());
+    }
+}
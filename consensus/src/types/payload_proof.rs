@@ -0,0 +1,174 @@
+//! Proofs linking the `state_root` and `block_hash` of an execution payload to the `body_root` of
+//! its beacon header, so that a verifier holding only the header, e.g. an on-chain light client,
+//! can check them itself.
+
+use eyre::Result;
+use ssz_rs::prelude::*;
+
+use super::{
+    withdrawals::{
+        body_field_roots, merkle_branch, payload_field_roots, BODY_DEPTH, EXECUTION_PAYLOAD_INDEX,
+    },
+    BeaconBlock, BeaconBlockType, Bytes32, Header,
+};
+use crate::{
+    errors::ConsensusError,
+    utils::{branch_to_nodes, bytes32_to_node},
+};
+
+/// The indices of the `state_root` and `block_hash` of an execution payload.
+const STATE_ROOT_INDEX: usize = 2;
+const BLOCK_HASH_INDEX: usize = 12;
+
+/// The proof that the execution payload of the beacon block of `header` has the given
+/// `state_root` and `block_hash`. The branches start with the sibling of the leaf and end with the
+/// sibling of the `execution_payload` of the body.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionPayloadProof {
+    pub fork: BeaconBlockType,
+    pub header: Header,
+    pub state_root: Bytes32,
+    pub state_root_branch: Vec<Bytes32>,
+    pub block_hash: Bytes32,
+    pub block_hash_branch: Vec<Bytes32>,
+}
+
+impl ExecutionPayloadProof {
+    /// Builds the proof of the execution payload of `block`.
+    pub fn new(block: &BeaconBlock) -> Result<Self> {
+        let mut block = block.clone();
+
+        let body_roots = body_field_roots(&mut block.body)?;
+        let mut payload = block.body.execution_payload().clone();
+        let (fork, payload_depth, payload_roots) = payload_field_roots(&mut payload)?;
+
+        let body_branch = merkle_branch(body_roots, BODY_DEPTH, EXECUTION_PAYLOAD_INDEX);
+        let branch = |index| -> Result<Vec<Bytes32>> {
+            merkle_branch(payload_roots.clone(), payload_depth, index)
+                .iter()
+                .chain(&body_branch)
+                .map(|node| Ok(Bytes32::try_from(node.as_ref())?))
+                .collect()
+        };
+
+        let header = Header {
+            slot: block.slot,
+            proposer_index: block.proposer_index,
+            parent_root: block.parent_root.clone(),
+            state_root: block.state_root.clone(),
+            body_root: Bytes32::try_from(block.body.hash_tree_root()?.as_ref())?,
+        };
+
+        let proof = Self {
+            fork,
+            header,
+            state_root: payload.state_root().clone(),
+            state_root_branch: branch(STATE_ROOT_INDEX)?,
+            block_hash: payload.block_hash().clone(),
+            block_hash_branch: branch(BLOCK_HASH_INDEX)?,
+        };
+
+        // The field roots are listed by hand, check them against the body root.
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Verifies both branches against the `body_root` of the header. The header itself must be
+    /// checked against a verified header by the caller.
+    pub fn verify(&self) -> Result<()> {
+        let fields = [
+            (&self.state_root, &self.state_root_branch, STATE_ROOT_INDEX),
+            (&self.block_hash, &self.block_hash_branch, BLOCK_HASH_INDEX),
+        ];
+
+        let root = bytes32_to_node(&self.header.body_root)?;
+        for (leaf, branch, field_index) in fields {
+            let (depth, index) = field_position(&self.fork, field_index);
+            if branch.len() != depth {
+                return Err(ConsensusError::InvalidPayloadProof.into());
+            }
+
+            let leaf = bytes32_to_node(leaf)?;
+            let branch = branch_to_nodes(branch.clone())?;
+            if !consensus_core::merkle::is_valid_branch(&leaf, &branch, depth, index, &root) {
+                return Err(ConsensusError::InvalidPayloadProof.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The generalized index of the `state_root` of the execution payload in the tree of the body.
+    pub fn state_root_gindex(&self) -> u64 {
+        field_gindex(&self.fork, STATE_ROOT_INDEX)
+    }
+
+    /// The generalized index of the `block_hash` of the execution payload in the tree of the body.
+    pub fn block_hash_gindex(&self) -> u64 {
+        field_gindex(&self.fork, BLOCK_HASH_INDEX)
+    }
+}
+
+/// Returns the depth and index of the payload field at `field_index` in the tree of a body.
+fn field_position(fork: &BeaconBlockType, field_index: usize) -> (usize, usize) {
+    let payload_depth = match fork {
+        BeaconBlockType::Bellatrix | BeaconBlockType::Capella => 4,
+        BeaconBlockType::Deneb | BeaconBlockType::Electra => 5,
+    };
+
+    let index = (EXECUTION_PAYLOAD_INDEX << payload_depth) | field_index;
+    (BODY_DEPTH + payload_depth, index)
+}
+
+fn field_gindex(fork: &BeaconBlockType, field_index: usize) -> u64 {
+    let (depth, index) = field_position(fork, field_index);
+    ((1 << depth) | index) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        BeaconBlockBody, BeaconBlockBodyDeneb, ExecutionPayload, ExecutionPayloadDeneb,
+    };
+
+    #[test]
+    fn test_execution_payload_proof() {
+        let payload = ExecutionPayloadDeneb {
+            state_root: vec![1; 32].try_into().unwrap(),
+            block_hash: vec![2; 32].try_into().unwrap(),
+            ..Default::default()
+        };
+        let block = BeaconBlock {
+            slot: 100.into(),
+            body: BeaconBlockBody::Deneb(BeaconBlockBodyDeneb {
+                execution_payload: ExecutionPayload::Deneb(payload),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let proof = ExecutionPayloadProof::new(&block).unwrap();
+        assert_eq!(
+            proof.header.clone().hash_tree_root().unwrap(),
+            block.clone().hash_tree_root().unwrap()
+        );
+        assert_eq!(&[1; 32], proof.state_root.as_slice());
+        assert_eq!((1 << 9) | (9 << 5) | 2, proof.state_root_gindex());
+
+        let mut tampered = proof.clone();
+        tampered.state_root = tampered.block_hash.clone();
+        assert!(tampered.verify().is_err());
+
+        // The branch of a field does not prove another field.
+        let mut tampered = proof;
+        tampered.block_hash_branch = tampered.state_root_branch.clone();
+        assert!(tampered.verify().is_err());
+
+        // Bellatrix payloads are proven too.
+        ExecutionPayloadProof::new(&BeaconBlock::default())
+            .unwrap()
+            .verify()
+            .unwrap();
+    }
+}
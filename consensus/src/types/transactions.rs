@@ -0,0 +1,245 @@
+use serde::de::Error;
+use ssz_rs::prelude::*;
+
+use super::{Bytes32, Transaction};
+
+pub const MAX_TRANSACTIONS_PER_PAYLOAD: usize = 1048576;
+pub const MAX_BYTES_PER_TRANSACTION: usize = 1073741824;
+
+pub type TransactionList = List<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD>;
+
+/// The transactions of an [`super::ExecutionPayload`].
+///
+/// Transactions received from the RPC are kept hex encoded and only decoded when they are
+/// explicitly requested with [`LazyTransactions::decode`]. Verifying a payload against its header
+/// only needs the hash tree root of the list, which is computed one transaction at a time without
+/// materializing the whole list, and is cached afterwards.
+#[derive(Debug, Clone)]
+pub struct LazyTransactions {
+    inner: Inner,
+}
+
+#[derive(Debug, Clone)]
+enum Inner {
+    /// Hex encoded transactions, validated on deserialization, and their hash tree root once
+    /// computed.
+    Encoded {
+        transactions: Vec<String>,
+        root: Option<Node>,
+    },
+    Decoded(TransactionList),
+}
+
+impl LazyTransactions {
+    pub fn len(&self) -> usize {
+        match &self.inner {
+            Inner::Encoded { transactions, .. } => transactions.len(),
+            Inner::Decoded(transactions) => transactions.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the transactions were already decoded.
+    pub fn is_decoded(&self) -> bool {
+        matches!(self.inner, Inner::Decoded(_))
+    }
+
+    /// Decodes the transactions. The decoded list is not kept, use
+    /// [`LazyTransactions::materialize`] to decode the transactions only once.
+    pub fn decode(&self) -> TransactionList {
+        match &self.inner {
+            Inner::Encoded { transactions, .. } => {
+                let transactions = transactions
+                    .iter()
+                    .map(|tx| decode_transaction(tx.as_str()))
+                    .collect::<Vec<_>>();
+                TransactionList::try_from(transactions)
+                    .expect("transaction count is validated on deserialization")
+            }
+            Inner::Decoded(transactions) => transactions.clone(),
+        }
+    }
+
+    /// Decodes the transactions in place and returns them.
+    pub fn materialize(&mut self) -> &TransactionList {
+        if !self.is_decoded() {
+            self.inner = Inner::Decoded(self.decode());
+        }
+
+        match &self.inner {
+            Inner::Decoded(transactions) => transactions,
+            Inner::Encoded { .. } => unreachable!(),
+        }
+    }
+}
+
+fn decode_transaction(tx: &str) -> Transaction {
+    let bytes = hex::decode(tx.strip_prefix("0x").unwrap_or(tx))
+        .expect("transactions are validated on deserialization");
+    Transaction::try_from(bytes).expect("transaction size is validated on deserialization")
+}
+
+fn is_valid_transaction(tx: &str) -> bool {
+    let hex = tx.strip_prefix("0x").unwrap_or(tx);
+    hex.len() % 2 == 0
+        && hex.len() / 2 <= MAX_BYTES_PER_TRANSACTION
+        && hex.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+impl Default for LazyTransactions {
+    fn default() -> Self {
+        TransactionList::default().into()
+    }
+}
+
+impl From<TransactionList> for LazyTransactions {
+    fn from(transactions: TransactionList) -> Self {
+        Self {
+            inner: Inner::Decoded(transactions),
+        }
+    }
+}
+
+impl Merkleized for LazyTransactions {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        match &mut self.inner {
+            Inner::Decoded(transactions) => transactions.hash_tree_root(),
+            Inner::Encoded {
+                root: Some(root), ..
+            } => Ok(*root),
+            Inner::Encoded { transactions, root } => {
+                // The root of a list of composite elements only depends on the roots of its
+                // elements, and the root of a `Bytes32` is the value itself.
+                let roots = transactions
+                    .iter()
+                    .map(|tx| {
+                        let tx_root = decode_transaction(tx.as_str()).hash_tree_root()?;
+                        Ok(Bytes32::try_from(tx_root.as_ref())
+                            .expect("a node is always 32 bytes long"))
+                    })
+                    .collect::<Result<Vec<_>, MerkleizationError>>()?;
+
+                let mut roots = List::<Bytes32, MAX_TRANSACTIONS_PER_PAYLOAD>::try_from(roots)
+                    .expect("transaction count is validated on deserialization");
+                let list_root = roots.hash_tree_root()?;
+                *root = Some(list_root);
+                Ok(list_root)
+            }
+        }
+    }
+}
+
+impl Sized for LazyTransactions {
+    fn is_variable_size() -> bool {
+        TransactionList::is_variable_size()
+    }
+
+    fn size_hint() -> usize {
+        TransactionList::size_hint()
+    }
+}
+
+impl Serialize for LazyTransactions {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        match &self.inner {
+            Inner::Decoded(transactions) => transactions.serialize(buffer),
+            Inner::Encoded { .. } => self.decode().serialize(buffer),
+        }
+    }
+}
+
+impl Deserialize for LazyTransactions {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError>
+    where
+        Self: std::marker::Sized,
+    {
+        Ok(TransactionList::deserialize(encoding)?.into())
+    }
+}
+
+impl SimpleSerialize for LazyTransactions {}
+
+impl serde::Serialize for LazyTransactions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match &self.inner {
+            Inner::Encoded { transactions, .. } => {
+                serde::Serialize::serialize(transactions, serializer)
+            }
+            Inner::Decoded(transactions) => serde::Serialize::serialize(transactions, serializer),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LazyTransactions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let transactions: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
+
+        if transactions.len() > MAX_TRANSACTIONS_PER_PAYLOAD {
+            return Err(D::Error::custom("too many transactions"));
+        }
+
+        if !transactions
+            .iter()
+            .all(|tx| is_valid_transaction(tx.as_str()))
+        {
+            return Err(D::Error::custom("invalid transaction encoding"));
+        }
+
+        Ok(Self {
+            inner: Inner::Encoded {
+                transactions,
+                root: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz_rs::prelude::*;
+
+    use super::LazyTransactions;
+
+    const TRANSACTIONS: &str = r#"["0x02f87001", "0xf86c0a8502540be400", "0x"]"#;
+
+    #[test]
+    fn test_lazy_transactions_root_matches_decoded() {
+        let mut lazy: LazyTransactions = serde_json::from_str(TRANSACTIONS).unwrap();
+        assert!(!lazy.is_decoded());
+        assert_eq!(lazy.len(), 3);
+
+        let mut decoded = lazy.decode();
+        assert_eq!(decoded[0].as_slice(), &[0x02, 0xf8, 0x70, 0x01]);
+        assert_eq!(
+            lazy.hash_tree_root().unwrap(),
+            decoded.hash_tree_root().unwrap()
+        );
+        // Computing the root must not decode the transactions in place.
+        assert!(!lazy.is_decoded());
+
+        lazy.materialize();
+        assert!(lazy.is_decoded());
+        assert_eq!(
+            lazy.hash_tree_root().unwrap(),
+            decoded.hash_tree_root().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lazy_transactions_invalid_encoding() {
+        let res = serde_json::from_str::<LazyTransactions>(r#"["0x0"]"#);
+        assert!(res.is_err());
+
+        let res = serde_json::from_str::<LazyTransactions>(r#"["0xzz"]"#);
+        assert!(res.is_err());
+    }
+}
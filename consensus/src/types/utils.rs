@@ -8,7 +8,7 @@ use ethers::{
 use serde::de::Error;
 use ssz_rs::prelude::*;
 
-use super::{ExecutionPayload, Header};
+use super::{execution_header::withdrawals_root, ExecutionPayload, Header};
 
 pub fn u256_deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
 where
@@ -46,13 +46,11 @@ struct Beacon {
 }
 
 macro_rules! superstruct_ssz {
-    ($type:tt) => {
+    ($type:tt, $($variant:ident),+ $(,)?) => {
         impl ssz_rs::Merkleized for $type {
             fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
                 match self {
-                    $type::Bellatrix(inner) => inner.hash_tree_root(),
-                    $type::Capella(inner) => inner.hash_tree_root(),
-                    $type::Deneb(inner) => inner.hash_tree_root(),
+                    $($type::$variant(inner) => inner.hash_tree_root(),)+
                 }
             }
         }
@@ -70,9 +68,7 @@ macro_rules! superstruct_ssz {
         impl ssz_rs::Serialize for $type {
             fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
                 match self {
-                    $type::Bellatrix(inner) => inner.serialize(buffer),
-                    $type::Capella(inner) => inner.serialize(buffer),
-                    $type::Deneb(inner) => inner.serialize(buffer),
+                    $($type::$variant(inner) => inner.serialize(buffer),)+
                 }
             }
         }
@@ -100,6 +96,7 @@ impl From<ExecutionPayload> for Block {
 
         let txs = value
             .transactions()
+            .decode()
             .iter()
             .enumerate()
             .map(|(i, tx)| {
@@ -154,6 +151,11 @@ impl From<ExecutionPayload> for Block {
             size: 0.into(),
             transactions_root: H256::default(),
             uncles: vec![],
+            parent_beacon_block_root: None,
+            withdrawals_root: value
+                .withdrawals()
+                .ok()
+                .map(|withdrawals| withdrawals_root(withdrawals.iter())),
         }
     }
 }
@@ -0,0 +1,301 @@
+//! Proofs of the balance, withdrawal credentials and exit status of a validator against the
+//! `state_root` of a verified header, built from the state proofs of the consensus RPC.
+
+use consensus_core::{
+    beacon_state::{
+        balance_from_chunk, balance_gindex, validator_field_gindex, verify_beacon_state_field,
+        ValidatorField, FAR_FUTURE_EPOCH,
+    },
+    merkle::is_valid_gindex_branch,
+};
+use eyre::Result;
+
+use super::Bytes32;
+use crate::errors::ConsensusError;
+
+/// The proof of the leaf at a generalized index of a beacon state.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StateProof {
+    pub leaf: Bytes32,
+    pub branch: Vec<Bytes32>,
+}
+
+impl StateProof {
+    /// Verifies that the leaf is at `gindex` of the beacon state with `state_root`, which must be
+    /// checked against a verified header by the caller.
+    pub fn verify(&self, state_root: &Bytes32, gindex: u64) -> Result<()> {
+        let branch = self
+            .branch
+            .iter()
+            .map(to_array)
+            .collect::<Result<Vec<_>>>()?;
+
+        let leaf = to_array(&self.leaf)?;
+        if !verify_beacon_state_field(&to_array(state_root)?, gindex, &leaf, &branch) {
+            return Err(ConsensusError::InvalidStateFieldProof(gindex).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// The fields of a validator that are proven by a [`ValidatorProof`], in the order of its
+/// proofs. The proof of the balance comes last.
+pub const VALIDATOR_PROOF_FIELDS: [ValidatorField; 6] = [
+    ValidatorField::WithdrawalCredentials,
+    ValidatorField::EffectiveBalance,
+    ValidatorField::Slashed,
+    ValidatorField::ActivationEpoch,
+    ValidatorField::ExitEpoch,
+    ValidatorField::WithdrawableEpoch,
+];
+
+/// The state of a validator, with the proofs of every field against `state_root`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ValidatorProof {
+    pub slot: u64,
+    pub state_root: Bytes32,
+    pub validator_index: u64,
+    pub withdrawal_credentials: Bytes32,
+    pub effective_balance: u64,
+    pub balance: u64,
+    pub slashed: bool,
+    pub activation_epoch: u64,
+    pub exit_epoch: u64,
+    pub withdrawable_epoch: u64,
+    /// The proofs of [`VALIDATOR_PROOF_FIELDS`], then of the balance.
+    pub proofs: Vec<StateProof>,
+}
+
+impl ValidatorProof {
+    /// The generalized indices of the proofs of the validator at `validator_index`.
+    pub fn gindices(validator_index: u64) -> Vec<u64> {
+        VALIDATOR_PROOF_FIELDS
+            .iter()
+            .map(|field| validator_field_gindex(validator_index, *field))
+            .chain([balance_gindex(validator_index)])
+            .collect()
+    }
+
+    /// Verifies `proofs` against `state_root`, and decodes the fields of the validator from their
+    /// leaves.
+    pub fn new(
+        slot: u64,
+        state_root: Bytes32,
+        validator_index: u64,
+        proofs: Vec<StateProof>,
+    ) -> Result<Self> {
+        let gindices = Self::gindices(validator_index);
+        if proofs.len() != gindices.len() {
+            return Err(ConsensusError::InvalidStateProof(validator_index).into());
+        }
+
+        let root = to_array(&state_root)?;
+        for (proof, gindex) in proofs.iter().zip(gindices) {
+            let branch = proof
+                .branch
+                .iter()
+                .map(to_array)
+                .collect::<Result<Vec<_>>>()?;
+
+            if !is_valid_gindex_branch(&to_array(&proof.leaf)?, &branch, gindex, &root) {
+                return Err(ConsensusError::InvalidStateProof(validator_index).into());
+            }
+        }
+
+        let leaf = |field: ValidatorField| {
+            let position = VALIDATOR_PROOF_FIELDS
+                .iter()
+                .position(|proven| *proven == field)
+                .expect("the field is proven");
+            to_array(&proofs[position].leaf)
+        };
+        let uint = |field| Ok::<_, eyre::Report>(u64::from_le_bytes(leaf(field)?[..8].try_into()?));
+        let balance_chunk = to_array(&proofs[VALIDATOR_PROOF_FIELDS.len()].leaf)?;
+
+        Ok(Self {
+            slot,
+            state_root,
+            validator_index,
+            withdrawal_credentials: Bytes32::try_from(
+                leaf(ValidatorField::WithdrawalCredentials)?.as_slice(),
+            )?,
+            effective_balance: uint(ValidatorField::EffectiveBalance)?,
+            balance: balance_from_chunk(&balance_chunk, validator_index),
+            slashed: leaf(ValidatorField::Slashed)?[0] == 1,
+            activation_epoch: uint(ValidatorField::ActivationEpoch)?,
+            exit_epoch: uint(ValidatorField::ExitEpoch)?,
+            withdrawable_epoch: uint(ValidatorField::WithdrawableEpoch)?,
+            proofs,
+        })
+    }
+
+    /// Verifies the proofs against `state_root`, and that they prove the fields of the
+    /// validator. The state root itself must be checked against a verified header by the caller.
+    pub fn verify(&self) -> Result<()> {
+        let proven = Self::new(
+            self.slot,
+            self.state_root.clone(),
+            self.validator_index,
+            self.proofs.clone(),
+        )?;
+
+        let is_valid = proven.withdrawal_credentials == self.withdrawal_credentials
+            && proven.effective_balance == self.effective_balance
+            && proven.balance == self.balance
+            && proven.slashed == self.slashed
+            && proven.activation_epoch == self.activation_epoch
+            && proven.exit_epoch == self.exit_epoch
+            && proven.withdrawable_epoch == self.withdrawable_epoch;
+
+        if !is_valid {
+            return Err(ConsensusError::InvalidStateProof(self.validator_index).into());
+        }
+
+        Ok(())
+    }
+
+    pub fn has_initiated_exit(&self) -> bool {
+        self.exit_epoch != FAR_FUTURE_EPOCH
+    }
+
+    pub fn is_exited(&self, epoch: u64) -> bool {
+        self.exit_epoch <= epoch
+    }
+}
+
+fn to_array(bytes: &Bytes32) -> Result<[u8; 32]> {
+    Ok(bytes.as_slice().try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use consensus_core::beacon_state::{state_field_gindex, BeaconStateField};
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    /// Returns the root of the subtree at `gindex` of a tree with the given leaves. Empty subtrees
+    /// are a zero node, which is enough to build consistent proofs.
+    fn node(gindex: u64, leaves: &[(u64, [u8; 32])]) -> [u8; 32] {
+        if let Some((_, leaf)) = leaves
+            .iter()
+            .find(|(leaf_gindex, _)| *leaf_gindex == gindex)
+        {
+            return *leaf;
+        }
+
+        let depth = gindex.ilog2();
+        let has_leaves = leaves.iter().any(|(leaf_gindex, _)| {
+            let leaf_depth = leaf_gindex.ilog2();
+            leaf_depth > depth && leaf_gindex >> (leaf_depth - depth) == gindex
+        });
+        if !has_leaves {
+            return [0u8; 32];
+        }
+
+        Sha256::new()
+            .chain_update(node(gindex * 2, leaves))
+            .chain_update(node(gindex * 2 + 1, leaves))
+            .finalize()
+            .into()
+    }
+
+    fn build_proof(validator_index: u64, leaves: Vec<[u8; 32]>) -> ValidatorProof {
+        let leaves = ValidatorProof::gindices(validator_index)
+            .into_iter()
+            .zip(leaves)
+            .collect::<Vec<_>>();
+
+        let proofs = leaves
+            .iter()
+            .map(|(gindex, leaf)| {
+                let mut branch = Vec::new();
+                let mut gindex = *gindex;
+                while gindex > 1 {
+                    branch.push(Bytes32::try_from(node(gindex ^ 1, &leaves).as_slice()).unwrap());
+                    gindex /= 2;
+                }
+
+                StateProof {
+                    leaf: Bytes32::try_from(leaf.as_slice()).unwrap(),
+                    branch,
+                }
+            })
+            .collect();
+
+        let state_root = Bytes32::try_from(node(1, &leaves).as_slice()).unwrap();
+        ValidatorProof::new(100, state_root, validator_index, proofs).unwrap()
+    }
+
+    fn uint_leaf(value: u64) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        leaf[..8].copy_from_slice(&value.to_le_bytes());
+        leaf
+    }
+
+    #[test]
+    fn test_validator_proof() {
+        let mut balance_chunk = [0u8; 32];
+        balance_chunk[24..].copy_from_slice(&31_000_000_000u64.to_le_bytes());
+
+        let proof = build_proof(
+            7,
+            vec![
+                [1u8; 32],
+                uint_leaf(32_000_000_000),
+                [0u8; 32],
+                uint_leaf(10),
+                uint_leaf(FAR_FUTURE_EPOCH),
+                uint_leaf(FAR_FUTURE_EPOCH),
+                balance_chunk,
+            ],
+        );
+
+        assert_eq!([1u8; 32], proof.withdrawal_credentials.as_slice());
+        assert_eq!(32_000_000_000, proof.effective_balance);
+        assert_eq!(31_000_000_000, proof.balance);
+        assert!(!proof.slashed);
+        assert_eq!(10, proof.activation_epoch);
+        assert!(!proof.has_initiated_exit());
+        proof.verify().unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.balance += 1;
+        assert!(tampered.verify().is_err());
+
+        // The proofs of a validator do not prove another one.
+        let mut tampered = proof.clone();
+        tampered.validator_index = 6;
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = proof;
+        tampered.proofs.pop();
+        assert!(tampered.verify().is_err());
+    }
+
+    #[test]
+    fn test_state_proof() {
+        let gindex = state_field_gindex(BeaconStateField::GenesisTime);
+        let leaves = [(gindex, uint_leaf(1_606_824_023))];
+        let state_root = Bytes32::try_from(node(1, &leaves).as_slice()).unwrap();
+
+        let branch = (0..gindex.ilog2())
+            .map(|depth| Bytes32::try_from(node((gindex >> depth) ^ 1, &leaves).as_slice()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let proof = StateProof {
+            leaf: Bytes32::try_from(leaves[0].1.as_slice()).unwrap(),
+            branch,
+        };
+
+        proof.verify(&state_root, gindex).unwrap();
+        let err = proof
+            .verify(&state_root, state_field_gindex(BeaconStateField::Slot))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::InvalidStateFieldProof(34).to_string()
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Inclusion proofs of the `withdrawals` of Capella and Deneb execution payloads, against the
+//! root of their beacon block, so that a withdrawal can be proven from a verified header.
+//!
+//! `ssz_rs` does not generate proofs, so the branch is built from the roots of the fields of the
+//! block, the body and the payload, see the
+//! [SSZ merkleization](https://github.com/ethereum/consensus-specs/blob/dev/ssz/simple-serialize.md#merkleization).
+
+use eyre::Result;
+use sha2::{Digest, Sha256};
+use ssz_rs::prelude::*;
+
+use super::{BeaconBlock, BeaconBlockBody, BeaconBlockType, Bytes32, ExecutionPayload, Withdrawal};
+use crate::{
+    errors::ConsensusError,
+    utils::{branch_to_nodes, bytes32_to_node},
+};
+
+/// The depth of a beacon block, and the index of its `body_root`.
+const BLOCK_DEPTH: usize = 3;
+const BODY_ROOT_INDEX: usize = 4;
+/// The depth of a block body, which is the same up to Electra, and the index of its
+/// `execution_payload`.
+pub(super) const BODY_DEPTH: usize = 4;
+pub(super) const EXECUTION_PAYLOAD_INDEX: usize = 9;
+/// The index of the `withdrawals` of a Capella, Deneb or Electra execution payload.
+const WITHDRAWALS_INDEX: usize = 14;
+/// `log2(MAX_WITHDRAWALS_PER_PAYLOAD)`
+const WITHDRAWALS_DEPTH: usize = 4;
+
+/// The proof that `withdrawal` is at `index` of the withdrawals of the beacon block `block_root`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WithdrawalProof {
+    pub fork: BeaconBlockType,
+    pub slot: u64,
+    pub block_root: Bytes32,
+    pub index: u64,
+    pub withdrawal: Withdrawal,
+    pub branch: Vec<Bytes32>,
+}
+
+impl WithdrawalProof {
+    /// Builds the proof of the withdrawal at `index` of the execution payload of `block`.
+    pub fn new(block: &BeaconBlock, index: usize) -> Result<Self> {
+        let mut block = block.clone();
+        let slot = block.slot.as_u64();
+
+        let body_roots = body_field_roots(&mut block.body)?;
+        let mut payload = block.body.execution_payload().clone();
+        let (fork, payload_depth, payload_roots) = payload_field_roots(&mut payload)?;
+
+        let withdrawals = payload
+            .withdrawals()
+            .map_err(|_| ConsensusError::NoWithdrawals(slot))?;
+        let withdrawal = withdrawals
+            .get(index)
+            .cloned()
+            .ok_or(ConsensusError::WithdrawalNotFound(slot, index as u64))?;
+        let withdrawal_roots = withdrawals
+            .iter()
+            .map(|withdrawal| withdrawal.clone().hash_tree_root())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut length = [0u8; 32];
+        length[..8].copy_from_slice(&(withdrawals.len() as u64).to_le_bytes());
+
+        let block_roots = vec![
+            block.slot.hash_tree_root()?,
+            block.proposer_index.hash_tree_root()?,
+            block.parent_root.hash_tree_root()?,
+            block.state_root.hash_tree_root()?,
+            block.body.hash_tree_root()?,
+        ];
+
+        let mut branch = merkle_branch(withdrawal_roots, WITHDRAWALS_DEPTH, index);
+        branch.push(Node::try_from(length.as_slice())?);
+        branch.extend(merkle_branch(
+            payload_roots,
+            payload_depth,
+            WITHDRAWALS_INDEX,
+        ));
+        branch.extend(merkle_branch(
+            body_roots,
+            BODY_DEPTH,
+            EXECUTION_PAYLOAD_INDEX,
+        ));
+        branch.extend(merkle_branch(block_roots, BLOCK_DEPTH, BODY_ROOT_INDEX));
+
+        let proof = Self {
+            fork,
+            slot,
+            block_root: Bytes32::try_from(block.hash_tree_root()?.as_ref())?,
+            index: index as u64,
+            withdrawal,
+            branch: branch
+                .iter()
+                .map(|node| Bytes32::try_from(node.as_ref()))
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        // The field roots are listed by hand, check them against the block root.
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Verifies the proof against `block_root`. The block root itself must be checked against a
+    /// verified header by the caller.
+    pub fn verify(&self) -> Result<()> {
+        let (depth, index) = withdrawal_position(&self.fork, self.slot, self.index)?;
+        if self.branch.len() != depth {
+            return Err(ConsensusError::InvalidWithdrawalProof.into());
+        }
+
+        let leaf = self.withdrawal.clone().hash_tree_root()?;
+        let branch = branch_to_nodes(self.branch.clone())?;
+        let root = bytes32_to_node(&self.block_root)?;
+
+        if !consensus_core::merkle::is_valid_branch(&leaf, &branch, depth, index, &root) {
+            return Err(ConsensusError::InvalidWithdrawalProof.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the depth and index of the withdrawal at `index` in the tree of a beacon block.
+fn withdrawal_position(
+    fork: &BeaconBlockType,
+    slot: u64,
+    index: u64,
+) -> Result<(usize, usize), ConsensusError> {
+    let payload_depth = match fork {
+        BeaconBlockType::Bellatrix => return Err(ConsensusError::NoWithdrawals(slot)),
+        BeaconBlockType::Capella => 4,
+        BeaconBlockType::Deneb | BeaconBlockType::Electra => 5,
+    };
+
+    if index >= 1 << WITHDRAWALS_DEPTH {
+        return Err(ConsensusError::WithdrawalNotFound(slot, index));
+    }
+
+    let mut position = BODY_ROOT_INDEX;
+    position = (position << BODY_DEPTH) | EXECUTION_PAYLOAD_INDEX;
+    position = (position << payload_depth) | WITHDRAWALS_INDEX;
+    // The root of the withdrawals is hashed with their length, which is on the right.
+    position <<= 1;
+    position = (position << WITHDRAWALS_DEPTH) | index as usize;
+
+    let depth = BLOCK_DEPTH + BODY_DEPTH + payload_depth + 1 + WITHDRAWALS_DEPTH;
+    Ok((depth, position))
+}
+
+macro_rules! field_roots {
+    ($container:expr, $($field:ident),+ $(,)?) => {
+        vec![$($container.$field.hash_tree_root()?),+]
+    };
+}
+
+pub(super) fn body_field_roots(body: &mut BeaconBlockBody) -> Result<Vec<Node>> {
+    Ok(match body {
+        BeaconBlockBody::Bellatrix(body) => field_roots!(
+            body,
+            randao_reveal,
+            eth1_data,
+            graffiti,
+            proposer_slashings,
+            attester_slashings,
+            attestations,
+            deposits,
+            voluntary_exits,
+            sync_aggregate,
+            execution_payload,
+        ),
+        BeaconBlockBody::Capella(body) => field_roots!(
+            body,
+            randao_reveal,
+            eth1_data,
+            graffiti,
+            proposer_slashings,
+            attester_slashings,
+            attestations,
+            deposits,
+            voluntary_exits,
+            sync_aggregate,
+            execution_payload,
+            bls_to_execution_changes,
+        ),
+        BeaconBlockBody::Deneb(body) => field_roots!(
+            body,
+            randao_reveal,
+            eth1_data,
+            graffiti,
+            proposer_slashings,
+            attester_slashings,
+            attestations,
+            deposits,
+            voluntary_exits,
+            sync_aggregate,
+            execution_payload,
+            bls_to_execution_changes,
+            blob_kzg_commitments,
+        ),
+        BeaconBlockBody::Electra(body) => field_roots!(
+            body,
+            randao_reveal,
+            eth1_data,
+            graffiti,
+            proposer_slashings,
+            attester_slashings_electra,
+            attestations_electra,
+            deposits,
+            voluntary_exits,
+            sync_aggregate,
+            execution_payload,
+            bls_to_execution_changes,
+            blob_kzg_commitments,
+            execution_requests,
+        ),
+    })
+}
+
+/// Returns the fork of `payload`, its depth and the roots of its fields.
+pub(super) fn payload_field_roots(
+    payload: &mut ExecutionPayload,
+) -> Result<(BeaconBlockType, usize, Vec<Node>)> {
+    Ok(match payload {
+        ExecutionPayload::Bellatrix(payload) => (
+            BeaconBlockType::Bellatrix,
+            4,
+            field_roots!(
+                payload,
+                parent_hash,
+                fee_recipient,
+                state_root,
+                receipts_root,
+                logs_bloom,
+                prev_randao,
+                block_number,
+                gas_limit,
+                gas_used,
+                timestamp,
+                extra_data,
+                base_fee_per_gas,
+                block_hash,
+                transactions,
+            ),
+        ),
+        ExecutionPayload::Capella(payload) => (
+            BeaconBlockType::Capella,
+            4,
+            field_roots!(
+                payload,
+                parent_hash,
+                fee_recipient,
+                state_root,
+                receipts_root,
+                logs_bloom,
+                prev_randao,
+                block_number,
+                gas_limit,
+                gas_used,
+                timestamp,
+                extra_data,
+                base_fee_per_gas,
+                block_hash,
+                transactions,
+                withdrawals,
+            ),
+        ),
+        ExecutionPayload::Deneb(payload) => (
+            BeaconBlockType::Deneb,
+            5,
+            field_roots!(
+                payload,
+                parent_hash,
+                fee_recipient,
+                state_root,
+                receipts_root,
+                logs_bloom,
+                prev_randao,
+                block_number,
+                gas_limit,
+                gas_used,
+                timestamp,
+                extra_data,
+                base_fee_per_gas,
+                block_hash,
+                transactions,
+                withdrawals,
+                blob_gas_used,
+                excess_blob_gas,
+            ),
+        ),
+    })
+}
+
+/// Returns the branch of the leaf at `index` of the tree of `leaves`, padded with zero leaves to
+/// the given depth. The branch starts with the sibling of the leaf.
+pub(super) fn merkle_branch(mut layer: Vec<Node>, depth: usize, mut index: usize) -> Vec<Node> {
+    layer.resize(1 << depth, Node::default());
+
+    let mut branch = Vec::with_capacity(depth);
+    for _ in 0..depth {
+        branch.push(layer[index ^ 1]);
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        index /= 2;
+    }
+
+    branch
+}
+
+fn hash_pair(left: &Node, right: &Node) -> Node {
+    let hash = Sha256::new()
+        .chain_update(left.as_ref())
+        .chain_update(right.as_ref())
+        .finalize();
+
+    Node::try_from(hash.as_slice()).expect("a sha256 hash is 32 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        BeaconBlockBodyCapella, BeaconBlockBodyDeneb, BeaconBlockBodyElectra, ConsolidationRequest,
+        ExecutionPayloadCapella, ExecutionPayloadDeneb, ExecutionRequests, WithdrawalRequest,
+    };
+
+    fn withdrawal(index: u64) -> Withdrawal {
+        Withdrawal {
+            index: index.into(),
+            validator_index: (index * 10).into(),
+            address: vec![index as u8; 20].try_into().unwrap(),
+            amount: 32_000_000_000u64.into(),
+        }
+    }
+
+    fn capella_block(withdrawals: Vec<Withdrawal>) -> BeaconBlock {
+        let payload = ExecutionPayloadCapella {
+            withdrawals: List::try_from(withdrawals).unwrap(),
+            ..Default::default()
+        };
+        let body = BeaconBlockBodyCapella {
+            execution_payload: ExecutionPayload::Capella(payload),
+            ..Default::default()
+        };
+
+        BeaconBlock {
+            slot: 100.into(),
+            body: BeaconBlockBody::Capella(body),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_withdrawal_proof() {
+        let block = capella_block((0..5).map(withdrawal).collect());
+
+        let proof = WithdrawalProof::new(&block, 3).unwrap();
+        assert_eq!(3, proof.withdrawal.index.as_u64());
+        proof.verify().unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.withdrawal.amount = 1u64.into();
+        assert!(tampered.verify().is_err());
+
+        // The branch of a withdrawal does not prove another position.
+        let mut tampered = proof;
+        tampered.index = 2;
+        assert!(tampered.verify().is_err());
+
+        assert!(WithdrawalProof::new(&block, 5).is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_proof_deneb() {
+        let payload = ExecutionPayloadDeneb {
+            withdrawals: List::try_from(vec![withdrawal(7)]).unwrap(),
+            ..Default::default()
+        };
+        let block = BeaconBlock {
+            body: BeaconBlockBody::Deneb(BeaconBlockBodyDeneb {
+                execution_payload: ExecutionPayload::Deneb(payload),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        WithdrawalProof::new(&block, 0).unwrap().verify().unwrap();
+
+        // Blocks before Capella have no withdrawals.
+        assert!(WithdrawalProof::new(&BeaconBlock::default(), 0).is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_proof_electra() {
+        let payload = ExecutionPayloadDeneb {
+            withdrawals: List::try_from(vec![withdrawal(3), withdrawal(4)]).unwrap(),
+            ..Default::default()
+        };
+        let requests = ExecutionRequests {
+            withdrawals: List::try_from(vec![WithdrawalRequest {
+                amount: 1u64.into(),
+                ..Default::default()
+            }])
+            .unwrap(),
+            consolidations: List::try_from(vec![ConsolidationRequest::default()]).unwrap(),
+            ..Default::default()
+        };
+        let block = BeaconBlock {
+            body: BeaconBlockBody::Electra(BeaconBlockBodyElectra {
+                execution_payload: ExecutionPayload::Deneb(payload),
+                execution_requests: requests,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // The proof is checked against the root of the whole body, execution requests included.
+        WithdrawalProof::new(&block, 1).unwrap().verify().unwrap();
+
+        let requests = block.body.execution_requests_or_default();
+        assert_eq!(1, requests.withdrawals.len());
+        assert_eq!(1, requests.consolidations.len());
+        assert!(BeaconBlock::default()
+            .body
+            .execution_requests_or_default()
+            .deposits
+            .is_empty());
+    }
+}
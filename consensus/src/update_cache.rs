@@ -0,0 +1,159 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use eyre::Result;
+use sha2::{Digest, Sha256};
+use ssz_rs::prelude::*;
+
+use crate::types::{SyncAggregate, SyncCommittee};
+
+/// Number of verified sync aggregates kept by a [`VerifiedUpdateCache`] created with `Default`.
+pub const DEFAULT_UPDATE_CACHE_CAPACITY: usize = 64;
+
+/// The content address of a verified sync aggregate signature.
+pub type UpdateKey = [u8; 32];
+
+/// Returns the key of the signature of `sync_aggregate` by `sync_committee` over `signing_root`.
+///
+/// The signing root commits to the attested header and to the fork domain of the signature slot,
+/// and the committee is identified by its aggregate public key, which is proven with the
+/// committee. The same update is thus only found again if it is checked against the same
+/// committee and network.
+pub fn update_key(
+    signing_root: &Node,
+    sync_aggregate: &SyncAggregate,
+    sync_committee: &SyncCommittee,
+) -> Result<UpdateKey> {
+    let aggregate_root = sync_aggregate.clone().hash_tree_root()?;
+
+    Ok(Sha256::new()
+        .chain_update(signing_root.as_ref())
+        .chain_update(aggregate_root.as_ref())
+        .chain_update(sync_committee.aggregate_pubkey.as_slice())
+        .finalize()
+        .into())
+}
+
+/// A bounded cache of the sync aggregate signatures that were recently found valid, so that an
+/// update received from several sources (e.g. polling and an event stream) is only checked once.
+/// When full, the oldest signature is evicted.
+#[derive(Debug)]
+pub struct VerifiedUpdateCache {
+    capacity: usize,
+    inner: Mutex<VerifiedUpdateCacheInner>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct VerifiedUpdateCacheInner {
+    keys: HashSet<UpdateKey>,
+    /// Keys ordered from the oldest to the most recent.
+    order: VecDeque<UpdateKey>,
+}
+
+impl VerifiedUpdateCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(VerifiedUpdateCacheInner::default()),
+        }
+    }
+
+    /// Records a valid signature. Callers must only insert the keys of verified signatures.
+    pub fn insert(&self, key: UpdateKey) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.keys.insert(key) {
+            return;
+        }
+        inner.order.push_back(key);
+
+        while inner.keys.len() > self.capacity {
+            let Some(evicted) = inner.order.pop_front() else {
+                break;
+            };
+            inner.keys.remove(&evicted);
+        }
+    }
+
+    pub fn contains(&self, key: &UpdateKey) -> bool {
+        self.inner.lock().unwrap().keys.contains(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap() = VerifiedUpdateCacheInner::default();
+    }
+}
+
+impl Default for VerifiedUpdateCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_UPDATE_CACHE_CAPACITY)
+    }
+}
+
+impl Clone for VerifiedUpdateCache {
+    fn clone(&self) -> Self {
+        Self {
+            capacity: self.capacity,
+            inner: Mutex::new(self.inner.lock().unwrap().clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SignatureBytes;
+
+    fn key(signature: u8) -> UpdateKey {
+        let sync_aggregate = SyncAggregate {
+            sync_committee_signature: SignatureBytes::try_from(vec![signature; 96]).unwrap(),
+            ..Default::default()
+        };
+
+        update_key(&Node::default(), &sync_aggregate, &SyncCommittee::default()).unwrap()
+    }
+
+    #[test]
+    fn test_update_key() {
+        assert_eq!(key(1), key(1));
+        assert_ne!(key(1), key(2));
+
+        // The same aggregate of another committee is another signature.
+        let committee = SyncCommittee {
+            aggregate_pubkey: vec![1; 48].try_into().unwrap(),
+            ..Default::default()
+        };
+        let other = update_key(&Node::default(), &SyncAggregate::default(), &committee).unwrap();
+        let sync_aggregate = SyncAggregate::default();
+        let default =
+            update_key(&Node::default(), &sync_aggregate, &SyncCommittee::default()).unwrap();
+        assert_ne!(other, default);
+    }
+
+    #[test]
+    fn test_update_cache_evicts_oldest() {
+        let cache = VerifiedUpdateCache::new(2);
+        cache.insert(key(1));
+        cache.insert(key(2));
+        cache.insert(key(1));
+        cache.insert(key(3));
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&key(1)));
+        assert!(cache.contains(&key(2)));
+        assert!(cache.contains(&key(3)));
+    }
+}
@@ -1,20 +1,12 @@
+pub use consensus_core::sync::calc_sync_period;
 use eyre::Result;
-use milagro_bls::{AggregateSignature, PublicKey};
+use milagro_bls::PublicKey;
 use ssz_rs::prelude::*;
 
 use crate::types::{Bytes32, Header, SignatureBytes};
 
-pub fn calc_sync_period(slot: u64) -> u64 {
-    let epoch = slot / 32; // 32 slots per epoch
-    epoch / 256 // 256 epochs per sync committee
-}
-
 pub fn is_aggregate_valid(sig_bytes: &SignatureBytes, msg: &[u8], pks: &[&PublicKey]) -> bool {
-    let sig_res = AggregateSignature::from_bytes(sig_bytes);
-    match sig_res {
-        Ok(sig) => sig.fast_aggregate_verify(msg, pks),
-        Err(_) => false,
-    }
+    consensus_core::bls::is_aggregate_valid(sig_bytes, msg, pks)
 }
 
 pub fn is_proof_valid<L: Merkleized>(
@@ -29,31 +21,19 @@ pub fn is_proof_valid<L: Merkleized>(
         let state_root = bytes32_to_node(&attested_header.state_root)?;
         let branch = branch_to_nodes(branch.to_vec())?;
 
-        let is_valid = is_valid_merkle_branch(&leaf_hash, branch.iter(), depth, index, &state_root);
+        let is_valid =
+            consensus_core::merkle::is_valid_branch(&leaf_hash, &branch, depth, index, &state_root);
         Ok(is_valid)
     })();
 
     res.unwrap_or_default()
 }
 
-#[derive(SimpleSerialize, Default, Debug)]
-struct SigningData {
-    object_root: Bytes32,
-    domain: Bytes32,
-}
-
-#[derive(SimpleSerialize, Default, Debug)]
-struct ForkData {
-    current_version: Vector<u8, 4>,
-    genesis_validator_root: Bytes32,
-}
-
 pub fn compute_signing_root(object_root: Bytes32, domain: Bytes32) -> Result<Node> {
-    let mut data = SigningData {
-        object_root,
-        domain,
-    };
-    Ok(data.hash_tree_root()?)
+    Ok(consensus_core::domain::compute_signing_root(
+        &bytes32_to_array(&object_root)?,
+        &bytes32_to_array(&domain)?,
+    )?)
 }
 
 pub fn compute_domain(
@@ -61,22 +41,12 @@ pub fn compute_domain(
     fork_version: Vector<u8, 4>,
     genesis_root: Bytes32,
 ) -> Result<Bytes32> {
-    let fork_data_root = compute_fork_data_root(fork_version, genesis_root)?;
-    let start = domain_type;
-    let end = &fork_data_root.as_ref()[..28];
-    let d = [start, end].concat();
-    Ok(d.to_vec().try_into().unwrap())
-}
-
-fn compute_fork_data_root(
-    current_version: Vector<u8, 4>,
-    genesis_validator_root: Bytes32,
-) -> Result<Node> {
-    let mut fork_data = ForkData {
-        current_version,
-        genesis_validator_root,
-    };
-    Ok(fork_data.hash_tree_root()?)
+    let domain = consensus_core::domain::compute_domain(
+        domain_type.try_into()?,
+        fork_version.as_slice().try_into()?,
+        &bytes32_to_array(&genesis_root)?,
+    )?;
+    Bytes32::try_from(domain.as_slice())
 }
 
 pub fn branch_to_nodes(branch: Vec<Bytes32>) -> Result<Vec<Node>> {
@@ -89,3 +59,7 @@ pub fn branch_to_nodes(branch: Vec<Bytes32>) -> Result<Vec<Node>> {
 pub fn bytes32_to_node(bytes: &Bytes32) -> Result<Node> {
     Ok(Node::try_from(bytes.as_slice())?)
 }
+
+fn bytes32_to_array(bytes: &Bytes32) -> Result<[u8; 32]> {
+    Ok(bytes.as_slice().try_into()?)
+}
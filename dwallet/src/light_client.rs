@@ -11,9 +11,14 @@
 /// structures respectively.
 use anyhow::anyhow;
 use client::{Client, ClientBuilder};
+use common::types::BlockTag;
 use config::Network;
 use consensus::database::ConfigDB;
 use ethers::prelude::{Address, EIP1186ProofResponse};
+use execution::{
+    ens::EnsResolverLayout,
+    types::{AccountProofs, BeaconRootProof, CallOpts, Erc20Balance, ProofBundle},
+};
 
 /// Interface of the Ethereum light client for dWallet network.
 pub struct EthLightClientWrapper {
@@ -80,6 +85,88 @@ impl EthLightClientWrapper {
         Ok(eth_lc)
     }
 
+    /// Executes `eth_call` locally on top of the finalized state.
+    /// Every account and storage slot the call touches is fetched with `eth_getProof` and verified
+    /// against the finalized state root, so the execution RPC is not trusted for the result.
+    pub async fn call_finalized(&self, opts: &CallOpts) -> Result<Vec<u8>, anyhow::Error> {
+        self.client
+            .call(opts, BlockTag::Finalized)
+            .await
+            .map_err(|e| anyhow!("failed to execute call: {}", e))
+    }
+
+    /// Returns the ERC-20 balance of `holder` in `token` at the finalized block.
+    /// The balance is verified with a storage proof against the finalized state root, and is
+    /// returned together with the proof so that it can be forwarded to dWallet.
+    /// `balances_slot` is the storage slot of the `balances` mapping of the token contract.
+    pub async fn get_erc20_balance(
+        &self,
+        token: &Address,
+        holder: &Address,
+        balances_slot: u64,
+    ) -> Result<Erc20Balance, anyhow::Error> {
+        self.client
+            .get_erc20_balance(token, holder, balances_slot)
+            .await
+            .map_err(|e| anyhow!("failed to get ERC-20 balance: {}", e))
+    }
+
+    /// Resolves the address of the ENS `name` through the ENS public resolver.
+    /// The registry and resolver records are read from storage proven against the finalized
+    /// state root, instead of trusting the result of an `eth_call`.
+    pub async fn resolve_ens(&self, name: &str) -> Result<Option<Address>, anyhow::Error> {
+        self.client
+            .resolve_ens(name, &EnsResolverLayout::default())
+            .await
+            .map_err(|e| anyhow!("failed to resolve ENS name: {}", e))
+    }
+
+    /// Get the proofs of the storage slots of several messages of `contract_addr` with a single
+    /// `eth_getProof` call. The proofs are verified against the finalized state root, and
+    /// returned as one bundle, see [`ProofBundle::serialize_to_bytes`].
+    pub async fn get_proofs_batch(
+        &self,
+        contract_addr: &Address,
+        proof_parameters: &[ProofRequestParameters],
+    ) -> Result<ProofBundle, anyhow::Error> {
+        let slots = proof_parameters
+            .iter()
+            .map(|parameters| {
+                execution::get_message_storage_slot(
+                    parameters.message.clone(),
+                    parameters.dwallet_id.clone(),
+                    parameters.data_slot,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("failed to calculate message storage slot: {}", e))?;
+
+        self.client
+            .get_proof_bundle(contract_addr, &slots, BlockTag::Finalized)
+            .await
+            .map_err(|e| anyhow!("failed to get proofs: {}", e))
+    }
+
+    /// Get the proof that the EIP-4788 beacon roots contract stores the parent beacon block root
+    /// of the finalized block.
+    pub async fn get_beacon_root_proof(&self) -> Result<BeaconRootProof, anyhow::Error> {
+        self.client
+            .get_beacon_root_proof()
+            .await
+            .map_err(|e| anyhow!("failed to get beacon root proof: {}", e))
+    }
+
+    /// Get the proofs of several accounts, verified against the same finalized state root.
+    pub async fn create_account_proofs(
+        &self,
+        addresses: &[Address],
+    ) -> Result<AccountProofs, anyhow::Error> {
+        self.client
+            .create_account_proofs(addresses)
+            .await
+            .map_err(|e| anyhow!("failed to create account proofs: {}", e))
+    }
+
     /// Get the Merkle Tree Proof (EIP1186Proof) for the client parameters.
     pub async fn get_proofs(
         self: &mut EthLightClientWrapper,
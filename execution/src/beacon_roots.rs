@@ -0,0 +1,55 @@
+//! Storage layout of the EIP-4788 beacon roots contract, which exposes the root of the parent
+//! beacon block of every recent execution block to contracts.
+//!
+//! The contract keeps a ring buffer of [`HISTORY_BUFFER_LENGTH`] entries. The timestamp of a
+//! block is stored at slot `timestamp % HISTORY_BUFFER_LENGTH`, and the parent beacon block root
+//! at that slot plus [`HISTORY_BUFFER_LENGTH`], see
+//! [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788#specification).
+
+use ethers::types::{H256, U256};
+
+/// The address of the beacon roots contract, deployed at the same address on every network.
+pub const BEACON_ROOTS_ADDRESS: &str = "0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02";
+
+/// The number of entries of the ring buffer of the beacon roots contract.
+pub const HISTORY_BUFFER_LENGTH: u64 = 8191;
+
+/// The storage slot of the timestamp of the block with the given `timestamp`.
+pub fn timestamp_slot(timestamp: u64) -> H256 {
+    u64_to_h256(timestamp % HISTORY_BUFFER_LENGTH)
+}
+
+/// The storage slot of the parent beacon block root of the block with the given `timestamp`.
+pub fn root_slot(timestamp: u64) -> H256 {
+    u64_to_h256(timestamp % HISTORY_BUFFER_LENGTH + HISTORY_BUFFER_LENGTH)
+}
+
+fn u64_to_h256(value: u64) -> H256 {
+    let mut bytes = [0u8; 32];
+    U256::from(value).to_big_endian(&mut bytes);
+    H256::from(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_roots_slots() {
+        let timestamp = 1710338135;
+        let index = timestamp % HISTORY_BUFFER_LENGTH;
+
+        assert_eq!(
+            U256::from(index),
+            U256::from(timestamp_slot(timestamp).as_bytes())
+        );
+        assert_eq!(
+            U256::from(index + HISTORY_BUFFER_LENGTH),
+            U256::from(root_slot(timestamp).as_bytes())
+        );
+        assert_eq!(
+            timestamp_slot(timestamp),
+            timestamp_slot(timestamp + HISTORY_BUFFER_LENGTH)
+        );
+    }
+}
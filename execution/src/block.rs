@@ -0,0 +1,259 @@
+//! Cross-checks of the blocks returned by the execution RPC against the blocks built from
+//! verified execution payloads.
+//!
+//! A payload does not carry every field of its execution header, such as the transactions root,
+//! so those are taken from the RPC block, and the hash of its RLP encoded header is recomputed to
+//! check them against the verified block hash. The transactions root is only in the verified block
+//! when the consensus client rebuilt it from the transactions of the payload, see
+//! `Config::verify_transactions_root`, and is then checked directly, like the withdrawals root
+//! that is rebuilt from the withdrawals of every Capella payload.
+
+use common::types::{Block, Transactions};
+use ethers::{
+    types::{Block as RpcBlock, H256},
+    utils::{keccak256, rlp::RlpStream},
+};
+use eyre::Result;
+
+use crate::errors::ExecutionError;
+
+/// Checks `rpc_block` against the verified block `block`, and returns `block` completed with the
+/// transactions root of `rpc_block` if it has none, which is covered by the recomputed header hash.
+pub fn verify_block(block: &Block, rpc_block: &RpcBlock<H256>) -> Result<Block> {
+    let number = block.number.as_u64();
+    let check = |matches: bool, field: &str| {
+        if matches {
+            Ok(())
+        } else {
+            Err(ExecutionError::BlockMismatch(number, field.to_string()))
+        }
+    };
+
+    check(rpc_block.hash == Some(block.hash), "hash")?;
+    check(rpc_block.number == Some(block.number), "number")?;
+    check(rpc_block.parent_hash == block.parent_hash, "parentHash")?;
+    check(rpc_block.author == Some(block.miner), "miner")?;
+    check(rpc_block.state_root == block.state_root, "stateRoot")?;
+    check(
+        rpc_block.receipts_root == block.receipts_root,
+        "receiptsRoot",
+    )?;
+    check(
+        rpc_block.logs_bloom.map(|bloom| bloom.as_bytes().to_vec())
+            == Some(block.logs_bloom.to_vec()),
+        "logsBloom",
+    )?;
+    check(
+        rpc_block.gas_limit == block.gas_limit.as_u64().into(),
+        "gasLimit",
+    )?;
+    check(
+        rpc_block.gas_used == block.gas_used.as_u64().into(),
+        "gasUsed",
+    )?;
+    check(
+        rpc_block.timestamp == block.timestamp.as_u64().into(),
+        "timestamp",
+    )?;
+    check(rpc_block.extra_data == block.extra_data, "extraData")?;
+    check(rpc_block.mix_hash == Some(block.mix_hash), "mixHash")?;
+    check(
+        rpc_block.base_fee_per_gas == Some(block.base_fee_per_gas),
+        "baseFeePerGas",
+    )?;
+    if block.parent_beacon_block_root.is_some() {
+        check(
+            rpc_block.parent_beacon_block_root == block.parent_beacon_block_root,
+            "parentBeaconBlockRoot",
+        )?;
+    }
+    if block.withdrawals_root.is_some() {
+        check(
+            rpc_block.withdrawals_root == block.withdrawals_root,
+            "withdrawalsRoot",
+        )?;
+    }
+    // The root of an empty trie is not zero, so a zero root was not rebuilt.
+    let rebuilt_transactions_root = !block.transactions_root.is_zero();
+    if rebuilt_transactions_root {
+        check(
+            rpc_block.transactions_root == block.transactions_root,
+            "transactionsRoot",
+        )?;
+    }
+
+    // The fields the payload does not carry are only trusted through the header hash.
+    check(header_hash(rpc_block) == block.hash, "header hash")?;
+
+    // Every transaction of the verified block has to be at its index in the RPC block.
+    if let Transactions::Full(txs) = &block.transactions {
+        for tx in txs {
+            let index = tx.transaction_index.unwrap_or_default().as_usize();
+            check(
+                rpc_block.transactions.get(index) == Some(&tx.hash),
+                "transactions",
+            )?;
+        }
+    }
+
+    let mut block = block.clone();
+    if !rebuilt_transactions_root {
+        block.transactions_root = rpc_block.transactions_root;
+    }
+    Ok(block)
+}
+
+/// Returns the hash of the RLP encoding of the header of `block`. Missing fields are encoded as
+/// their default, which yields a different hash.
+pub fn header_hash(block: &RpcBlock<H256>) -> H256 {
+    H256::from(keccak256(encode_header(block)))
+}
+
+/// Returns the RLP encoding of the header of `block`, see [`header_hash`].
+pub fn encode_header(block: &RpcBlock<H256>) -> Vec<u8> {
+    let optional_fields = [
+        block.base_fee_per_gas.is_some(),
+        block.withdrawals_root.is_some(),
+        block.blob_gas_used.is_some(),
+        block.excess_blob_gas.is_some(),
+        block.parent_beacon_block_root.is_some(),
+    ];
+    let len = 15 + optional_fields.iter().filter(|is_set| **is_set).count();
+
+    let mut stream = RlpStream::new_list(len);
+    stream.append(&block.parent_hash);
+    stream.append(&block.uncles_hash);
+    stream.append(&block.author.unwrap_or_default());
+    stream.append(&block.state_root);
+    stream.append(&block.transactions_root);
+    stream.append(&block.receipts_root);
+    stream.append(&block.logs_bloom.unwrap_or_default());
+    stream.append(&block.difficulty);
+    stream.append(&block.number.unwrap_or_default());
+    stream.append(&block.gas_limit);
+    stream.append(&block.gas_used);
+    stream.append(&block.timestamp);
+    stream.append(&block.extra_data);
+    stream.append(&block.mix_hash.unwrap_or_default());
+    stream.append(&block.nonce.unwrap_or_default());
+
+    if let Some(base_fee_per_gas) = block.base_fee_per_gas {
+        stream.append(&base_fee_per_gas);
+    }
+    if let Some(withdrawals_root) = block.withdrawals_root {
+        stream.append(&withdrawals_root);
+    }
+    if let Some(blob_gas_used) = block.blob_gas_used {
+        stream.append(&blob_gas_used);
+    }
+    if let Some(excess_blob_gas) = block.excess_blob_gas {
+        stream.append(&excess_blob_gas);
+    }
+    if let Some(parent_beacon_block_root) = block.parent_beacon_block_root {
+        stream.append(&parent_beacon_block_root);
+    }
+
+    stream.out().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Address, Bloom, H64, U256};
+
+    use super::*;
+
+    fn rpc_block() -> RpcBlock<H256> {
+        RpcBlock {
+            number: Some(100.into()),
+            author: Some(Address::repeat_byte(1)),
+            state_root: H256::repeat_byte(2),
+            transactions_root: H256::repeat_byte(3),
+            logs_bloom: Some(Bloom::zero()),
+            gas_limit: 30_000_000.into(),
+            timestamp: 1_700_000_000.into(),
+            mix_hash: Some(H256::repeat_byte(4)),
+            nonce: Some(H64::zero()),
+            base_fee_per_gas: Some(U256::from(7)),
+            withdrawals_root: Some(H256::repeat_byte(5)),
+            transactions: vec![H256::repeat_byte(6)],
+            ..Default::default()
+        }
+    }
+
+    fn verified_block(rpc_block: &mut RpcBlock<H256>) -> Block {
+        let hash = header_hash(rpc_block);
+        rpc_block.hash = Some(hash);
+
+        Block {
+            number: 100.into(),
+            hash,
+            miner: rpc_block.author.unwrap(),
+            state_root: rpc_block.state_root,
+            logs_bloom: vec![0; 256].into(),
+            gas_limit: 30_000_000.into(),
+            timestamp: 1_700_000_000.into(),
+            mix_hash: rpc_block.mix_hash.unwrap(),
+            base_fee_per_gas: 7.into(),
+            transactions: Transactions::Full(vec![]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let mut rpc = rpc_block();
+        let block = verified_block(&mut rpc);
+
+        let verified = verify_block(&block, &rpc).unwrap();
+        assert_eq!(rpc.transactions_root, verified.transactions_root);
+
+        // A field that is in the payload is compared directly.
+        let mut tampered = rpc.clone();
+        tampered.state_root = H256::zero();
+        let err = verify_block(&block, &tampered).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::BlockMismatch(100, "stateRoot".to_string()).to_string()
+        );
+
+        // A field that is not in the payload is caught by the header hash.
+        let mut tampered = rpc;
+        tampered.transactions_root = H256::zero();
+        let err = verify_block(&block, &tampered).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::BlockMismatch(100, "header hash".to_string()).to_string()
+        );
+    }
+
+    #[test]
+    fn test_verify_rebuilt_transactions_root() {
+        let mut rpc = rpc_block();
+        let mut block = verified_block(&mut rpc);
+        block.transactions_root = rpc.transactions_root;
+        verify_block(&block, &rpc).unwrap();
+
+        block.transactions_root = H256::repeat_byte(7);
+        let err = verify_block(&block, &rpc).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::BlockMismatch(100, "transactionsRoot".to_string()).to_string()
+        );
+    }
+
+    #[test]
+    fn test_verify_withdrawals_root() {
+        let mut rpc = rpc_block();
+        let mut block = verified_block(&mut rpc);
+        block.withdrawals_root = rpc.withdrawals_root;
+        let verified = verify_block(&block, &rpc).unwrap();
+        assert_eq!(rpc.withdrawals_root, verified.withdrawals_root);
+
+        block.withdrawals_root = Some(H256::repeat_byte(7));
+        let err = verify_block(&block, &rpc).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::BlockMismatch(100, "withdrawalsRoot".to_string()).to_string()
+        );
+    }
+}
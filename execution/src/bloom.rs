@@ -0,0 +1,111 @@
+//! Pre-screening of log queries with the `logs_bloom` of verified blocks, to skip the blocks that
+//! cannot contain a matching log before fetching and proving their receipts.
+
+use ethers::{
+    types::{Filter, ValueOrArray, H256},
+    utils::keccak256,
+};
+
+/// The length of a logs bloom, in bytes.
+pub const BLOOM_LENGTH: usize = 256;
+
+/// Whether a block with `logs_bloom` may contain a log matching the address and topics of
+/// `filter`. A bloom of an unexpected length cannot rule anything out.
+pub fn bloom_may_match(filter: &Filter, logs_bloom: &[u8]) -> bool {
+    if logs_bloom.len() != BLOOM_LENGTH {
+        return true;
+    }
+
+    let address_matches = match &filter.address {
+        Some(ValueOrArray::Value(address)) => contains(logs_bloom, address.as_bytes()),
+        Some(ValueOrArray::Array(addresses)) if !addresses.is_empty() => addresses
+            .iter()
+            .any(|address| contains(logs_bloom, address.as_bytes())),
+        _ => true,
+    };
+
+    address_matches
+        && filter.topics.iter().all(|topic| match topic {
+            Some(ValueOrArray::Value(Some(topic))) => contains(logs_bloom, topic.as_bytes()),
+            Some(ValueOrArray::Array(topics)) => topic_matches(logs_bloom, topics),
+            _ => true,
+        })
+}
+
+/// A position with several topics matches any of them, and a wildcard matches anything.
+fn topic_matches(logs_bloom: &[u8], topics: &[Option<H256>]) -> bool {
+    topics.is_empty()
+        || topics.iter().any(|topic| match topic {
+            Some(topic) => contains(logs_bloom, topic.as_bytes()),
+            None => true,
+        })
+}
+
+/// Whether the three bits of `input` are set in `logs_bloom`.
+fn contains(logs_bloom: &[u8], input: &[u8]) -> bool {
+    bloom_bits(input)
+        .iter()
+        .all(|(index, mask)| logs_bloom[*index] & mask != 0)
+}
+
+/// Returns the byte index and mask of the three bits set by `input`, as in the yellow paper.
+fn bloom_bits(input: &[u8]) -> [(usize, u8); 3] {
+    let hash = keccak256(input);
+    [0, 2, 4].map(|i| {
+        let bit = (((hash[i] as usize) << 8) | hash[i + 1] as usize) % (BLOOM_LENGTH * 8);
+        (BLOOM_LENGTH - 1 - bit / 8, 1 << (bit % 8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::Address;
+
+    use super::*;
+
+    fn bloom_of(inputs: &[&[u8]]) -> Vec<u8> {
+        let mut bloom = vec![0; BLOOM_LENGTH];
+        for input in inputs {
+            for (index, mask) in bloom_bits(input) {
+                bloom[index] |= mask;
+            }
+        }
+        bloom
+    }
+
+    #[test]
+    fn test_bloom_may_match() {
+        let address = Address::repeat_byte(1);
+        let topic = H256::repeat_byte(2);
+        let bloom = bloom_of(&[address.as_bytes(), topic.as_bytes()]);
+
+        assert!(bloom_may_match(&Filter::new(), &bloom));
+        assert!(bloom_may_match(
+            &Filter::new().address(address).topic0(topic),
+            &bloom
+        ));
+        assert!(bloom_may_match(
+            &Filter::new().address(vec![Address::zero(), address]),
+            &bloom
+        ));
+
+        assert!(!bloom_may_match(
+            &Filter::new().address(Address::zero()),
+            &bloom
+        ));
+        assert!(!bloom_may_match(
+            &Filter::new().address(address).topic1(H256::zero()),
+            &bloom
+        ));
+        assert!(!bloom_may_match(
+            &Filter::new().address(address),
+            &vec![0; BLOOM_LENGTH]
+        ));
+
+        // A bloom that is not known cannot rule the block out.
+        assert!(bloom_may_match(
+            &Filter::new().address(Address::zero()),
+            &[]
+        ));
+    }
+}
@@ -0,0 +1,430 @@
+//! Broadcasting of raw transactions to several execution RPCs, confirmed once their receipt is
+//! proven against the receipts root of a block of the finalized chain.
+
+use std::sync::Arc;
+
+use common::types::{Block, BlockTag};
+use ethers::{
+    types::{TransactionReceipt, H256},
+    utils::keccak256,
+};
+use eyre::Result;
+use futures::future::join_all;
+use tokio::{
+    select,
+    sync::{mpsc, watch},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::{block::header_hash, errors::ExecutionError, rpc::ExecutionRpc, ExecutionClient};
+
+/// The progress of a transaction sent with [`Broadcaster::send_raw_transaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastEvent {
+    /// The transaction was sent to the endpoint at `endpoint` of the broadcaster.
+    Sent {
+        endpoint: usize,
+        error: Option<String>,
+    },
+    /// The receipt of the transaction is proven against the verified block `block_number`, which
+    /// is not finalized yet.
+    Included { block_number: u64 },
+    /// The receipt of the transaction is proven against the block `block_number`, which is
+    /// finalized.
+    Confirmed { block_number: u64 },
+}
+
+/// Sends raw transactions to the execution RPC of the client and to additional endpoints, so that
+/// a single censoring endpoint cannot hold a transaction back.
+pub struct Broadcaster<R: ExecutionRpc> {
+    execution: Arc<ExecutionClient<R>>,
+    endpoints: Vec<R>,
+}
+
+impl<R: ExecutionRpc> Broadcaster<R> {
+    /// The transactions are sent to the RPC of `execution` and to every RPC of `endpoints`.
+    pub fn new(execution: Arc<ExecutionClient<R>>, endpoints: &[String]) -> Result<Self> {
+        let mut rpcs = vec![execution.rpc.clone()];
+        for endpoint in endpoints {
+            rpcs.push(R::new(endpoint)?);
+        }
+
+        Ok(Self {
+            execution,
+            endpoints: rpcs,
+        })
+    }
+
+    /// Sends `bytes` to every endpoint, then waits for the finalized blocks received on
+    /// `finalized_block_recv` until the block the receipt of the transaction is proven against is
+    /// finalized. The progress is sent to `events`, if any.
+    ///
+    /// The receipt is proven against a block kept by the state, and the block is then proven to be
+    /// an ancestor of a finalized block. Fails if no endpoint accepted the transaction, or once
+    /// `cancel` is cancelled.
+    pub async fn send_raw_transaction(
+        &self,
+        bytes: &[u8],
+        mut finalized_block_recv: watch::Receiver<Option<Block>>,
+        events: Option<mpsc::Sender<BroadcastEvent>>,
+        cancel: CancellationToken,
+    ) -> Result<TransactionReceipt> {
+        let emit = |event: BroadcastEvent| {
+            let events = events.clone();
+            async move {
+                if let Some(events) = events {
+                    // The progress is best effort, a dropped receiver does not stop the wait.
+                    let _ = events.send(event).await;
+                }
+            }
+        };
+
+        let tx_hash = H256::from(keccak256(bytes));
+        let results = join_all(
+            self.endpoints
+                .iter()
+                .map(|endpoint| endpoint.send_raw_transaction(bytes)),
+        )
+        .await;
+
+        let mut errors = Vec::new();
+        for (endpoint, result) in results.into_iter().enumerate() {
+            let error = match result {
+                Ok(hash) if hash != tx_hash => {
+                    Some(format!("unexpected transaction hash {hash:?}"))
+                }
+                Ok(_) => None,
+                Err(err) => Some(err.to_string()),
+            };
+            if let Some(error) = &error {
+                warn!(target: "helios::execution", endpoint, error = %error, "failed to broadcast transaction");
+                errors.push(error.clone());
+            }
+
+            emit(BroadcastEvent::Sent { endpoint, error }).await;
+        }
+        if errors.len() == self.endpoints.len() {
+            return Err(ExecutionError::BroadcastFailed(errors.join(", ")).into());
+        }
+
+        // The receipt proven against a verified block, with the number and hash of the block.
+        let mut included: Option<(u64, H256, TransactionReceipt)> = None;
+        loop {
+            if included.is_none() {
+                match self.prove_receipt(&tx_hash).await {
+                    Ok(Some((block_number, block_hash, receipt))) => {
+                        emit(BroadcastEvent::Included { block_number }).await;
+                        included = Some((block_number, block_hash, receipt));
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        debug!(target: "helios::execution", error = %err, "transaction receipt not proven yet");
+                    }
+                }
+            }
+
+            let finalized = finalized_block_recv.borrow_and_update().clone();
+            if let (Some((block_number, block_hash, receipt)), Some(finalized)) =
+                (&included, finalized)
+            {
+                if *block_number <= finalized.number.as_u64() {
+                    let ancestor = self
+                        .execution
+                        .get_verified_ancestor(&finalized, *block_number)
+                        .await;
+                    match ancestor {
+                        Ok(ancestor) if header_hash(&ancestor) == *block_hash => {
+                            let block_number = *block_number;
+                            emit(BroadcastEvent::Confirmed { block_number }).await;
+                            return Ok(receipt.clone());
+                        }
+                        // The block of the receipt was reorged out, prove the new receipt.
+                        Ok(_) => included = None,
+                        Err(err) => {
+                            debug!(target: "helios::execution", error = %err, "failed to check the finality of the transaction block");
+                        }
+                    }
+                }
+            }
+
+            let changed = select! {
+                changed = finalized_block_recv.changed() => changed,
+                _ = cancel.cancelled() => return Err(ExecutionError::BroadcastCancelled(tx_hash).into()),
+            };
+            if changed.is_err() {
+                return Err(ExecutionError::BroadcastCancelled(tx_hash).into());
+            }
+        }
+    }
+
+    /// Returns the receipt of `tx_hash`, proven against the receipts root of its block, with the
+    /// number and hash of the block. The block must still be kept by the state.
+    async fn prove_receipt(
+        &self,
+        tx_hash: &H256,
+    ) -> Result<Option<(u64, H256, TransactionReceipt)>> {
+        let Some(receipt) = self.execution.get_transaction_receipt(tx_hash).await? else {
+            return Ok(None);
+        };
+
+        let block_number = receipt.block_number.unwrap_or_default().as_u64();
+        let block = self
+            .execution
+            .get_block(BlockTag::Number(block_number), false)
+            .await?;
+        if receipt.block_hash != Some(block.hash) {
+            return Err(ExecutionError::ReceiptRootMismatch(tx_hash.to_string()).into());
+        }
+
+        Ok(Some((block_number, block.hash, receipt)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use common::types::Transactions;
+    use ethers::types::Block as RpcBlock;
+    use tempfile::TempDir;
+    use tokio::sync::mpsc::{channel, Receiver};
+
+    use super::*;
+    use crate::{execution::compute_receipts_root, rpc::mock_rpc::MockRpc, state::State};
+
+    const TX: &[u8] = b"raw transaction";
+
+    fn tx_hash() -> H256 {
+        H256::from(keccak256(TX))
+    }
+
+    /// Writes the files of a mock RPC to `dir` that accepts [`TX`] and serves its receipt in a
+    /// block. `extra_data` tells the blocks of competing forks apart. Returns the verified block.
+    fn write_chain(dir: &Path, extra_data: &[u8]) -> Block {
+        let receipt = fs::read_to_string("testdata/receipt.json").unwrap();
+        let mut receipt: TransactionReceipt = serde_json::from_str(&receipt).unwrap();
+        receipt.transaction_hash = tx_hash();
+
+        let mut rpc_block = RpcBlock::<H256> {
+            number: receipt.block_number,
+            receipts_root: compute_receipts_root(&[receipt.clone()]).unwrap(),
+            transactions: vec![tx_hash()],
+            extra_data: extra_data.to_vec().into(),
+            ..Default::default()
+        };
+        let hash = header_hash(&rpc_block);
+        rpc_block.hash = Some(hash);
+        receipt.block_hash = Some(hash);
+
+        let write = |file: &str, value: String| fs::write(dir.join(file), value).unwrap();
+        write("block.json", serde_json::to_string(&rpc_block).unwrap());
+        write("receipt.json", serde_json::to_string(&receipt).unwrap());
+        write("tx_hash.json", serde_json::to_string(&tx_hash()).unwrap());
+
+        Block {
+            number: receipt.block_number.unwrap(),
+            hash,
+            receipts_root: rpc_block.receipts_root,
+            transactions: Transactions::Hashes(vec![tx_hash()]),
+            ..Default::default()
+        }
+    }
+
+    struct TestBroadcaster {
+        broadcaster: Broadcaster<MockRpc>,
+        state: State,
+        // The state stops tracking blocks once a sender is dropped.
+        _block_send: mpsc::Sender<Block>,
+        _state_finalized_send: watch::Sender<Option<Block>>,
+    }
+
+    /// Returns a broadcaster to the mock RPC at `dir` and to the additional `endpoints`.
+    fn broadcaster(dir: &Path, endpoints: &[&Path]) -> TestBroadcaster {
+        let (block_send, block_recv) = channel(256);
+        let (state_finalized_send, finalized_recv) = watch::channel(None);
+        let state = State::new(block_recv, finalized_recv, 64);
+
+        let execution = ExecutionClient::new(dir.to_str().unwrap(), state.clone()).unwrap();
+        let endpoints = endpoints
+            .iter()
+            .map(|endpoint| endpoint.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        TestBroadcaster {
+            broadcaster: Broadcaster::new(Arc::new(execution), &endpoints).unwrap(),
+            state,
+            _block_send: block_send,
+            _state_finalized_send: state_finalized_send,
+        }
+    }
+
+    fn drain(events: &mut Receiver<BroadcastEvent>) -> Vec<BroadcastEvent> {
+        let mut drained = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            drained.push(event);
+        }
+        drained
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_failed() {
+        // The endpoints serve no transaction hash, so they fail.
+        let dir = TempDir::new().unwrap();
+        let endpoint = TempDir::new().unwrap();
+        let test = broadcaster(dir.path(), &[endpoint.path()]);
+
+        let (_, finalized_recv) = watch::channel(None);
+        let (events_send, mut events) = channel(16);
+        let res = test
+            .broadcaster
+            .send_raw_transaction(
+                TX,
+                finalized_recv,
+                Some(events_send),
+                CancellationToken::new(),
+            )
+            .await;
+
+        let err = res.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::BroadcastFailed(_))
+        ));
+        let events = drain(&mut events);
+        assert_eq!(2, events.len());
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, BroadcastEvent::Sent { error: Some(_), .. })));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_unexpected_hash() {
+        let dir = TempDir::new().unwrap();
+        let block = write_chain(dir.path(), b"");
+        let endpoint = TempDir::new().unwrap();
+        fs::write(
+            endpoint.path().join("tx_hash.json"),
+            serde_json::to_string(&H256::zero()).unwrap(),
+        )
+        .unwrap();
+        let test = broadcaster(dir.path(), &[endpoint.path()]);
+        test.state.push_block(block.clone()).await;
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let (_, finalized_recv) = watch::channel(None);
+        let (events_send, mut events) = channel(16);
+        let res = test
+            .broadcaster
+            .send_raw_transaction(TX, finalized_recv, Some(events_send), cancel)
+            .await;
+
+        // The other endpoint accepted the transaction, so it is still waited for.
+        let err = res.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::BroadcastCancelled(hash)) if *hash == tx_hash()
+        ));
+        assert_eq!(
+            vec![
+                BroadcastEvent::Sent {
+                    endpoint: 0,
+                    error: None
+                },
+                BroadcastEvent::Sent {
+                    endpoint: 1,
+                    error: Some(format!("unexpected transaction hash {:?}", H256::zero())),
+                },
+                BroadcastEvent::Included {
+                    block_number: block.number.as_u64()
+                },
+            ],
+            drain(&mut events)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_confirmed() {
+        let dir = TempDir::new().unwrap();
+        let block = write_chain(dir.path(), b"");
+        let test = broadcaster(dir.path(), &[]);
+        test.state.push_block(block.clone()).await;
+        let block_number = block.number.as_u64();
+
+        let (finalized_send, finalized_recv) = watch::channel(None);
+        let (events_send, mut events) = channel(16);
+        let send = test.broadcaster.send_raw_transaction(
+            TX,
+            finalized_recv,
+            Some(events_send),
+            CancellationToken::new(),
+        );
+        let finalize = async {
+            let sent = events.recv().await.unwrap();
+            assert!(matches!(sent, BroadcastEvent::Sent { error: None, .. }));
+            assert_eq!(
+                BroadcastEvent::Included { block_number },
+                events.recv().await.unwrap()
+            );
+            finalized_send.send(Some(block.clone())).unwrap();
+        };
+
+        let (res, _) = tokio::join!(send, finalize);
+        let receipt = res.unwrap();
+        assert_eq!(tx_hash(), receipt.transaction_hash);
+        assert_eq!(Some(block.hash), receipt.block_hash);
+        assert_eq!(
+            vec![BroadcastEvent::Confirmed { block_number }],
+            drain(&mut events)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reorg() {
+        let dir = TempDir::new().unwrap();
+        let block = write_chain(dir.path(), b"");
+        let test = broadcaster(dir.path(), &[]);
+        test.state.push_block(block.clone()).await;
+        let block_number = block.number.as_u64();
+
+        let (finalized_send, finalized_recv) = watch::channel(None);
+        let (events_send, mut events) = channel(16);
+        let send = test.broadcaster.send_raw_transaction(
+            TX,
+            finalized_recv,
+            Some(events_send),
+            CancellationToken::new(),
+        );
+        let reorg = async {
+            events.recv().await.unwrap();
+            assert_eq!(
+                BroadcastEvent::Included { block_number },
+                events.recv().await.unwrap()
+            );
+
+            // The block of the receipt is replaced by the one of another fork, which is finalized.
+            let finalized = write_chain(dir.path(), b"fork");
+            test.state.push_block(finalized.clone()).await;
+
+            // The first finalized block only resets the receipt, which is proven again against
+            // the finalized fork on a later one.
+            loop {
+                finalized_send.send_replace(Some(finalized.clone()));
+                tokio::task::yield_now().await;
+                if let Ok(event) = events.try_recv() {
+                    break (event, finalized);
+                }
+            }
+        };
+
+        let (res, (event, finalized)) = tokio::join!(send, reorg);
+        assert_eq!(BroadcastEvent::Included { block_number }, event);
+        assert_eq!(Some(finalized.hash), res.unwrap().block_hash);
+        assert_ne!(block.hash, finalized.hash);
+        assert_eq!(
+            vec![BroadcastEvent::Confirmed { block_number }],
+            drain(&mut events)
+        );
+    }
+}
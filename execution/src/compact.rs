@@ -0,0 +1,393 @@
+//! A compact encoding of verified account proofs, designed to be decoded and verified cheaply by
+//! Move contracts.
+//!
+//! Every field has a fixed width, except the trie nodes, which are stored once in a shared table
+//! and referenced by their index, since the account proofs of a batch share most of their upper
+//! nodes. Integers are little endian, as read by `sui::bcs::peel_u*`, hashes and addresses are raw
+//! bytes.
+//!
+//! ```text
+//! version       u8
+//! block_number  u64
+//! state_root    [u8; 32]
+//! node_count    u32, then node_count x (len: u32, node: [u8; len])
+//! account_count u32, then account_count x
+//!     address       [u8; 20]
+//!     nonce         u64
+//!     balance       u256
+//!     storage_hash  [u8; 32]
+//!     code_hash     [u8; 32]
+//!     proof_len     u32, then proof_len x node index: u32
+//!     storage_count u32, then storage_count x
+//!         key       [u8; 32]
+//!         value     u256
+//!         proof_len u32, then proof_len x node index: u32
+//! ```
+
+use std::collections::HashMap;
+
+use ethers::types::{Address, Bytes, EIP1186ProofResponse, StorageProof, H256, U256};
+use eyre::Result;
+
+use crate::{
+    errors::ExecutionError,
+    proofs::verify_account_proof,
+    types::{AccountProofs, ProofBundle},
+};
+
+/// The version of the compact encoding, written as its first byte.
+pub const COMPACT_PROOF_VERSION: u8 = 1;
+
+/// Account proofs at the same block, with their trie nodes deduplicated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactProof {
+    pub block_number: u64,
+    pub state_root: H256,
+    /// The trie nodes of every proof, referenced by their index.
+    pub nodes: Vec<Bytes>,
+    pub accounts: Vec<CompactAccount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactAccount {
+    pub address: Address,
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_hash: H256,
+    pub code_hash: H256,
+    pub account_proof: Vec<u32>,
+    pub storage: Vec<CompactStorage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactStorage {
+    pub key: H256,
+    pub value: U256,
+    pub proof: Vec<u32>,
+}
+
+impl CompactProof {
+    /// Builds the compact form of `proofs`, which must all be for `state_root`.
+    pub fn new(block_number: u64, state_root: H256, proofs: &[EIP1186ProofResponse]) -> Self {
+        let mut nodes = Vec::new();
+        let mut indices = HashMap::new();
+        let mut index_of = |node: &Bytes| {
+            *indices.entry(node.clone()).or_insert_with(|| {
+                nodes.push(node.clone());
+                (nodes.len() - 1) as u32
+            })
+        };
+
+        let accounts = proofs
+            .iter()
+            .map(|proof| CompactAccount {
+                address: proof.address,
+                nonce: proof.nonce.as_u64(),
+                balance: proof.balance,
+                storage_hash: proof.storage_hash,
+                code_hash: proof.code_hash,
+                account_proof: proof.account_proof.iter().map(&mut index_of).collect(),
+                storage: proof
+                    .storage_proof
+                    .iter()
+                    .map(|storage_proof| {
+                        let mut key = [0u8; 32];
+                        storage_proof.key.to_big_endian(&mut key);
+
+                        CompactStorage {
+                            key: H256::from(key),
+                            value: storage_proof.value,
+                            proof: storage_proof.proof.iter().map(&mut index_of).collect(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            block_number,
+            state_root,
+            nodes,
+            accounts,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![COMPACT_PROOF_VERSION];
+        out.extend_from_slice(&self.block_number.to_le_bytes());
+        out.extend_from_slice(self.state_root.as_bytes());
+
+        write_len(&mut out, self.nodes.len());
+        for node in self.nodes.iter() {
+            write_len(&mut out, node.len());
+            out.extend_from_slice(node);
+        }
+
+        write_len(&mut out, self.accounts.len());
+        for account in self.accounts.iter() {
+            out.extend_from_slice(account.address.as_bytes());
+            out.extend_from_slice(&account.nonce.to_le_bytes());
+            write_u256(&mut out, account.balance);
+            out.extend_from_slice(account.storage_hash.as_bytes());
+            out.extend_from_slice(account.code_hash.as_bytes());
+            write_indices(&mut out, &account.account_proof);
+
+            write_len(&mut out, account.storage.len());
+            for storage in account.storage.iter() {
+                out.extend_from_slice(storage.key.as_bytes());
+                write_u256(&mut out, storage.value);
+                write_indices(&mut out, &storage.proof);
+            }
+        }
+
+        out
+    }
+
+    /// Decodes a proof encoded with [`CompactProof::encode`]. The proof is not verified.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ExecutionError> {
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let version = reader.u8()?;
+        if version != COMPACT_PROOF_VERSION {
+            return Err(invalid(format!("unsupported version {version}")));
+        }
+
+        let block_number = reader.u64()?;
+        let state_root = reader.h256()?;
+
+        let node_count = reader.u32()?;
+        let mut nodes = Vec::new();
+        for _ in 0..node_count {
+            let len = reader.u32()? as usize;
+            nodes.push(Bytes::from(reader.take(len)?.to_vec()));
+        }
+
+        let account_count = reader.u32()?;
+        let mut accounts = Vec::new();
+        for _ in 0..account_count {
+            let address = Address::from_slice(reader.take(20)?);
+            let nonce = reader.u64()?;
+            let balance = reader.u256()?;
+            let storage_hash = reader.h256()?;
+            let code_hash = reader.h256()?;
+            let account_proof = reader.indices()?;
+
+            let storage_count = reader.u32()?;
+            let mut storage = Vec::new();
+            for _ in 0..storage_count {
+                storage.push(CompactStorage {
+                    key: reader.h256()?,
+                    value: reader.u256()?,
+                    proof: reader.indices()?,
+                });
+            }
+
+            accounts.push(CompactAccount {
+                address,
+                nonce,
+                balance,
+                storage_hash,
+                code_hash,
+                account_proof,
+                storage,
+            });
+        }
+
+        if reader.offset != bytes.len() {
+            return Err(invalid("trailing bytes".to_string()));
+        }
+
+        Ok(Self {
+            block_number,
+            state_root,
+            nodes,
+            accounts,
+        })
+    }
+
+    /// Expands the proofs back to their `eth_getProof` form.
+    pub fn to_proofs(&self) -> Result<Vec<EIP1186ProofResponse>, ExecutionError> {
+        let resolve = |indices: &[u32]| {
+            indices
+                .iter()
+                .map(|index| {
+                    self.nodes
+                        .get(*index as usize)
+                        .cloned()
+                        .ok_or_else(|| invalid(format!("unknown node {index}")))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        self.accounts
+            .iter()
+            .map(|account| {
+                let storage_proof = account
+                    .storage
+                    .iter()
+                    .map(|storage| {
+                        Ok(StorageProof {
+                            key: U256::from(storage.key.as_bytes()),
+                            value: storage.value,
+                            proof: resolve(&storage.proof)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, ExecutionError>>()?;
+
+                Ok(EIP1186ProofResponse {
+                    address: account.address,
+                    balance: account.balance,
+                    code_hash: account.code_hash,
+                    nonce: account.nonce.into(),
+                    storage_hash: account.storage_hash,
+                    account_proof: resolve(&account.account_proof)?,
+                    storage_proof,
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies every proof against the state root. The state root itself must be checked
+    /// against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        self.to_proofs()?.iter().try_for_each(|proof| {
+            verify_account_proof(&proof.address, &[], proof, &self.state_root)
+        })
+    }
+}
+
+impl From<&ProofBundle> for CompactProof {
+    fn from(bundle: &ProofBundle) -> Self {
+        Self::new(
+            bundle.block_number,
+            bundle.state_root,
+            std::slice::from_ref(&bundle.proof),
+        )
+    }
+}
+
+impl From<&AccountProofs> for CompactProof {
+    fn from(proofs: &AccountProofs) -> Self {
+        Self::new(proofs.block_number, proofs.state_root, &proofs.proofs)
+    }
+}
+
+fn invalid(reason: String) -> ExecutionError {
+    ExecutionError::InvalidCompactProof(reason)
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn write_u256(out: &mut Vec<u8>, value: U256) {
+    let mut bytes = [0u8; 32];
+    value.to_little_endian(&mut bytes);
+    out.extend_from_slice(&bytes);
+}
+
+fn write_indices(out: &mut Vec<u8>, indices: &[u32]) {
+    write_len(out, indices.len());
+    for index in indices {
+        out.extend_from_slice(&index.to_le_bytes());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ExecutionError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid(format!("truncated at byte {}", self.offset)))?;
+
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ExecutionError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ExecutionError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ExecutionError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn u256(&mut self) -> Result<U256, ExecutionError> {
+        Ok(U256::from_little_endian(self.take(32)?))
+    }
+
+    fn h256(&mut self) -> Result<H256, ExecutionError> {
+        Ok(H256::from_slice(self.take(32)?))
+    }
+
+    fn indices(&mut self) -> Result<Vec<u32>, ExecutionError> {
+        let len = self.u32()?;
+        (0..len).map(|_| self.u32()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof(address: u8, account_proof: Vec<Bytes>) -> EIP1186ProofResponse {
+        EIP1186ProofResponse {
+            address: Address::repeat_byte(address),
+            balance: U256::from(1_000_000u64),
+            nonce: 7u64.into(),
+            account_proof,
+            storage_proof: vec![StorageProof {
+                key: U256::from(3),
+                value: U256::MAX,
+                proof: vec![Bytes::from(vec![0xc0, address])],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn compact_proof_roundtrip() {
+        let shared = Bytes::from(vec![0xaa; 40]);
+        let proofs = vec![
+            proof(1, vec![shared.clone(), Bytes::from(vec![1])]),
+            proof(2, vec![shared.clone(), Bytes::from(vec![2])]),
+        ];
+
+        let compact = CompactProof::new(10, H256::repeat_byte(9), &proofs);
+        // The shared node is only stored once.
+        assert_eq!(5, compact.nodes.len());
+
+        let decoded = CompactProof::decode(&compact.encode()).unwrap();
+        assert_eq!(compact, decoded);
+        assert_eq!(proofs, decoded.to_proofs().unwrap());
+    }
+
+    #[test]
+    fn compact_proof_decode_invalid() {
+        let compact = CompactProof::new(10, H256::zero(), &[proof(1, vec![])]);
+        let mut bytes = compact.encode();
+
+        assert!(CompactProof::decode(&bytes[..bytes.len() - 1]).is_err());
+
+        bytes.push(0);
+        assert!(CompactProof::decode(&bytes).is_err());
+
+        bytes[0] = COMPACT_PROOF_VERSION + 1;
+        assert!(CompactProof::decode(&bytes).is_err());
+
+        let mut compact = compact;
+        compact.accounts[0].storage[0].proof = vec![100];
+        assert!(compact.to_proofs().is_err());
+    }
+}
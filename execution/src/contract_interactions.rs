@@ -16,7 +16,7 @@
 
 use ethers::{
     abi::ethabi::{encode, Token},
-    types::H256,
+    types::{Address, H256},
     utils::keccak256,
 };
 use eyre::Error;
@@ -45,6 +45,14 @@ fn calculate_mapping_slot_for_key(key: H256, mapping_slot: u64) -> H256 {
     H256::from_slice(&keccak256(&hasher))
 }
 
+/// Calculates the storage slot of the value of an address key in a mapping, e.g. the balance of
+/// `key` in the `balances` mapping of an ERC-20 token, which is at `mapping_slot` in the contract
+/// storage layout.
+pub fn get_address_mapping_slot(key: &Address, mapping_slot: u64) -> H256 {
+    let encoded = encode(&[Token::Address(*key), Token::Uint(mapping_slot.into())]);
+    H256::from_slice(&keccak256(encoded))
+}
+
 /// Calculates the key for a given message and dWallet ID.
 /// In the smart contract, the key is calculated by hashing the message, and the dWallet ID
 /// together. The result is a H256 hash that represents the key.
@@ -116,6 +124,23 @@ mod tests {
         assert_eq!(expected_hash, calculate_mapping_slot_for_key(key, slot));
     }
 
+    #[test]
+    fn get_address_mapping_slot_valid() {
+        let key = Address::from_slice(&[0xab; 20]);
+        let mapping_slot = 3;
+
+        let expected_hash = {
+            let mut hasher = Vec::new();
+            hasher.extend([0u8; 12]);
+            hasher.extend([0xab; 20]);
+            hasher.extend([0u8; 31]);
+            hasher.push(3);
+            H256::from_slice(keccak256(&hasher).as_slice())
+        };
+
+        assert_eq!(expected_hash, get_address_mapping_slot(&key, mapping_slot));
+    }
+
     #[test]
     fn calculate_key_valid() {
         let message: Vec<u8> = vec![];
@@ -0,0 +1,185 @@
+//! Storage layouts used to resolve ENS names from proven storage slots, instead of trusting the
+//! result of an `eth_call` to the registry and the resolver.
+//!
+//! The registry keeps `mapping(bytes32 => Record) records` at slot 0, where a `Record` is
+//! `{ address owner; address resolver; uint64 ttl; }`, so the resolver of a node is stored in the
+//! second slot of its record. The current registry falls back to the old registry, which has the
+//! same layout, for the nodes that have no owner in its own records, i.e. the names that were not
+//! migrated.
+//! The resolver layout is described by [`EnsResolverLayout`], see the
+//! [ENS contracts](https://github.com/ensdomains/ens-contracts) for the layout of each resolver.
+
+use ethers::{
+    types::{Address, H256, U256},
+    utils::keccak256,
+};
+
+use crate::errors::ExecutionError;
+
+/// The address of the ENS registry, deployed at the same address on every network.
+pub const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+/// The address of the ENS registry deployed before the migration of 2020, which the current
+/// registry falls back to.
+pub const ENS_OLD_REGISTRY_ADDRESS: &str = "0x314159265dD8dbb310642f98f50C066173C1259b";
+
+/// The storage slot of the `records` mapping of the ENS registries.
+pub const ENS_REGISTRY_RECORDS_SLOT: u64 = 0;
+
+/// The SLIP-44 coin type of Ethereum addresses, used as the key of `addr` records.
+const COIN_TYPE_ETH: u64 = 60;
+
+/// The storage slots of the mappings of an ENS resolver that are needed to resolve an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnsResolverLayout {
+    /// The slot of `mapping(bytes32 => uint64) recordVersions`.
+    pub record_versions_slot: u64,
+    /// The slot of
+    /// `mapping(uint64 => mapping(bytes32 => mapping(uint256 => bytes))) versionable_addresses`.
+    pub addresses_slot: u64,
+}
+
+impl Default for EnsResolverLayout {
+    /// The layout of the ENS public resolver.
+    fn default() -> Self {
+        Self {
+            record_versions_slot: 0,
+            addresses_slot: 2,
+        }
+    }
+}
+
+/// Computes the ENS node of `name`, as defined in
+/// [EIP-137](https://eips.ethereum.org/EIPS/eip-137#namehash-algorithm).
+pub fn namehash(name: &str) -> H256 {
+    let node = name
+        .rsplit('.')
+        .filter(|label| !label.is_empty())
+        .fold([0u8; 32], |node, label| {
+            let mut hasher = node.to_vec();
+            hasher.extend_from_slice(&keccak256(label.as_bytes()));
+            keccak256(hasher)
+        });
+
+    H256::from(node)
+}
+
+/// Calculates the storage slot of the value of `key` in a mapping stored at `slot`.
+fn mapping_slot(key: H256, slot: H256) -> H256 {
+    let mut hasher = key.as_bytes().to_vec();
+    hasher.extend_from_slice(slot.as_bytes());
+    H256::from(keccak256(hasher))
+}
+
+fn u256_to_h256(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256::from(bytes)
+}
+
+/// The storage slot of the owner of `node` in an ENS registry, the first slot of its record.
+pub fn registry_owner_slot(node: H256) -> H256 {
+    mapping_slot(node, u256_to_h256(ENS_REGISTRY_RECORDS_SLOT.into()))
+}
+
+/// The storage slot of the resolver of `node` in an ENS registry.
+pub fn registry_resolver_slot(node: H256) -> H256 {
+    let record_slot = registry_owner_slot(node);
+    u256_to_h256(
+        U256::from(record_slot.as_bytes())
+            .overflowing_add(U256::one())
+            .0,
+    )
+}
+
+/// Returns the address stored in the lower bytes of a slot, such as the owner and the resolver of
+/// a registry record, which shares its slot with the TTL of the record.
+pub fn slot_address(value: U256) -> Address {
+    Address::from_slice(&u256_to_h256(value).as_bytes()[12..])
+}
+
+/// The storage slot of the record version of `node` in a resolver.
+pub fn resolver_version_slot(node: H256, layout: &EnsResolverLayout) -> H256 {
+    mapping_slot(node, u256_to_h256(layout.record_versions_slot.into()))
+}
+
+/// The storage slot of the Ethereum address of `node` in a resolver, for the given record version.
+pub fn resolver_address_slot(node: H256, version: u64, layout: &EnsResolverLayout) -> H256 {
+    let version_slot = mapping_slot(
+        u256_to_h256(version.into()),
+        u256_to_h256(layout.addresses_slot.into()),
+    );
+    let node_slot = mapping_slot(node, version_slot);
+    mapping_slot(u256_to_h256(COIN_TYPE_ETH.into()), node_slot)
+}
+
+/// Decodes an address from the value of the slot of a `bytes` storage variable.
+/// Values shorter than 32 bytes are stored in place, left aligned, with twice their length in the
+/// lowest byte of the slot. Returns `None` if no address is set.
+pub fn decode_address_record(value: U256) -> Result<Option<Address>, ExecutionError> {
+    let value = u256_to_h256(value);
+    let bytes = value.as_bytes();
+
+    match bytes[31] {
+        0 => Ok(None),
+        40 => Ok(Some(Address::from_slice(&bytes[..20]))),
+        _ => Err(ExecutionError::InvalidEnsRecord(format!("{value:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn namehash_valid() {
+        assert_eq!(H256::zero(), namehash(""));
+        assert_eq!(
+            H256::from_str("0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+                .unwrap(),
+            namehash("eth")
+        );
+        assert_eq!(
+            H256::from_str("0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+                .unwrap(),
+            namehash("foo.eth")
+        );
+    }
+
+    #[test]
+    fn registry_record_slots() {
+        let node = namehash("foo.eth");
+        let owner_slot = U256::from(registry_owner_slot(node).as_bytes());
+        assert_eq!(
+            owner_slot + 1,
+            U256::from(registry_resolver_slot(node).as_bytes())
+        );
+
+        let mut bytes = [0u8; 32];
+        bytes[4..12].copy_from_slice(&[0xff; 8]);
+        bytes[12..].copy_from_slice(&[0xab; 20]);
+        assert_eq!(
+            Address::from_slice(&[0xab; 20]),
+            slot_address(U256::from(bytes))
+        );
+    }
+
+    #[test]
+    fn decode_address_record_valid() {
+        assert_eq!(None, decode_address_record(U256::zero()).unwrap());
+
+        let mut bytes = [0u8; 32];
+        bytes[..20].copy_from_slice(&[0xab; 20]);
+        bytes[31] = 40;
+        assert_eq!(
+            Some(Address::from_slice(&[0xab; 20])),
+            decode_address_record(U256::from(bytes)).unwrap()
+        );
+
+        // A stored `bytes` value of another length is not an address.
+        bytes[31] = 42;
+        assert!(decode_address_record(U256::from(bytes)).is_err());
+    }
+}
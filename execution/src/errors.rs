@@ -1,7 +1,7 @@
 use bytes::Bytes;
 use ethers::{
     abi::AbiDecode,
-    types::{Address, U256},
+    types::{Address, H256, U256},
 };
 use eyre::Report;
 use thiserror::Error;
@@ -12,18 +12,32 @@ pub enum ExecutionError {
     InvalidAccountProof(Address),
     #[error("invalid storage proof for address: {0}, slot: {1}")]
     InvalidStorageProof(Address, U256),
+    #[error("missing storage proof for address: {0}, slot: {1}")]
+    MissingStorageProof(Address, U256),
     #[error("code hash mismatch for address: {0}, found: {1}, expected: {2}")]
     CodeHashMismatch(Address, String, String),
     #[error("receipt root mismatch for tx: {0}")]
     ReceiptRootMismatch(String),
+    #[error("receipts root mismatch for block: {0}")]
+    BlockReceiptsRootMismatch(u64),
     #[error("missing transaction for tx: {0}")]
     MissingTransaction(String),
     #[error("could not prove receipt for tx: {0}")]
     NoReceiptForTransaction(String),
     #[error("missing log for transaction: {0}, index: {1}")]
     MissingLog(String, U256),
+    #[error("block {0} has {1} logs matching the filter, but the rpc returned {2}")]
+    IncompleteLogs(u64, usize, usize),
     #[error("too many logs to prove: {0}, current limit is: {1}")]
     TooManyLogsToProve(usize, usize),
+    #[error("missing parent beacon block root for block: {0}")]
+    MissingParentBeaconBlockRoot(u64),
+    #[error("beacon roots contract does not store the parent beacon block root of block: {0}")]
+    BeaconRootMismatch(u64),
+    #[error("invalid compact proof: {0}")]
+    InvalidCompactProof(String),
+    #[error("invalid ENS address record: {0}")]
+    InvalidEnsRecord(String),
     #[error("execution rpc is for the incorrect network")]
     IncorrectRpcNetwork(),
     #[error("Invalid base gas fee helios {0} vs rpc endpoint {1} at block {2}")]
@@ -36,6 +50,24 @@ pub enum ExecutionError {
     EmptyExecutionPayload(),
     #[error("User query for block {0} but helios oldest block is {1}")]
     InvalidBlockRange(u64, u64),
+    #[error("rpc block {0} does not match the verified payload: {1}")]
+    BlockMismatch(u64, String),
+    #[error("block {0} is not finalized")]
+    BlockNotFinalized(u64),
+    #[error("block {0} is not within the ancestors of block {1}")]
+    AncestorOutOfRange(u64, u64),
+    #[error("no endpoint accepted the transaction: {0}")]
+    BroadcastFailed(String),
+    #[error("stopped waiting for the confirmation of transaction {0:?}")]
+    BroadcastCancelled(H256),
+    #[error("transaction {0:?} is not in a finalized block")]
+    TransactionNotFinalized(H256),
+    #[error("transactions root mismatch for block: {0}")]
+    BlockTransactionsRootMismatch(u64),
+    #[error("fewer than {1} execution rpcs agree on {0}")]
+    QuorumNotReached(String, usize),
+    #[error("execution rpcs disagree on {0}")]
+    QuorumDisagreement(String),
 }
 
 /// Errors that can occur during evm.rs calls
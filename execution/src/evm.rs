@@ -1,6 +1,6 @@
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 
-use common::types::BlockTag;
+use common::types::{Block, BlockTag};
 use ethers::types::transaction::eip2930::AccessListItem;
 use eyre::{Report, Result};
 use futures::future::join_all;
@@ -15,22 +15,35 @@ use tracing::trace;
 
 use super::ExecutionClient;
 use crate::{
-    constants::PARALLEL_QUERY_BATCH_SIZE, errors::EvmError, rpc::ExecutionRpc, types::CallOpts,
+    constants::PARALLEL_QUERY_BATCH_SIZE,
+    errors::EvmError,
+    rpc::ExecutionRpc,
+    types::{Account, CallOpts},
 };
 
 pub struct Evm<R: ExecutionRpc> {
     evm: EVM<ProofDB<R>>,
     chain_id: u64,
-    tag: BlockTag,
 }
 
 impl<R: ExecutionRpc> Evm<R> {
     pub fn new(execution: Arc<ExecutionClient<R>>, chain_id: u64, tag: BlockTag) -> Self {
+        Self::with_database(ProofDB::new(tag, execution), chain_id)
+    }
+
+    /// Creates an [`Evm`] that executes calls on top of the state of `block`. Every account and
+    /// storage slot is fetched with `eth_getProof` and verified against the state root of `block`,
+    /// so `block` must come from a verified source, e.g. the finalized execution payload of the
+    /// light client. This does not require the execution client to track blocks.
+    pub fn new_at_block(execution: Arc<ExecutionClient<R>>, chain_id: u64, block: Block) -> Self {
+        Self::with_database(ProofDB::new_at_block(block, execution), chain_id)
+    }
+
+    fn with_database(db: ProofDB<R>, chain_id: u64) -> Self {
         let mut evm: EVM<ProofDB<R>> = EVM::new();
-        let db = ProofDB::new(tag, execution);
         evm.database(db);
 
-        Evm { evm, chain_id, tag }
+        Evm { evm, chain_id }
     }
 
     pub async fn call(&mut self, opts: &CallOpts) -> Result<Vec<u8>, EvmError> {
@@ -56,7 +69,7 @@ impl<R: ExecutionRpc> Evm<R> {
     }
 
     async fn call_inner(&mut self, opts: &CallOpts) -> Result<ResultAndState, EvmError> {
-        let env = self.get_env(opts, self.tag).await;
+        let env = self.get_env(opts).await;
         _ = self
             .evm
             .db
@@ -83,7 +96,7 @@ impl<R: ExecutionRpc> Evm<R> {
         tx_res.map_err(|_| EvmError::Generic("evm error".to_string()))
     }
 
-    async fn get_env(&self, opts: &CallOpts, tag: BlockTag) -> Env {
+    async fn get_env(&self, opts: &CallOpts) -> Env {
         let mut env = Env::default();
         let to = convert_address(&opts.to.unwrap_or_default());
         let from = convert_address(&opts.from.unwrap_or_default());
@@ -107,8 +120,8 @@ impl<R: ExecutionRpc> Evm<R> {
             .db
             .as_ref()
             .unwrap()
-            .execution
-            .get_block(tag, false)
+            .state
+            .get_block()
             .await
             .unwrap();
 
@@ -123,17 +136,29 @@ impl<R: ExecutionRpc> Evm<R> {
 }
 
 struct ProofDB<R: ExecutionRpc> {
-    execution: Arc<ExecutionClient<R>>,
     state: EvmState<R>,
 }
 
 impl<R: ExecutionRpc> ProofDB<R> {
     pub fn new(tag: BlockTag, execution: Arc<ExecutionClient<R>>) -> Self {
-        let state = EvmState::new(execution.clone(), tag);
-        ProofDB { execution, state }
+        let state = EvmState::new(execution, CallBlock::Tag(tag));
+        ProofDB { state }
+    }
+
+    pub fn new_at_block(block: Block, execution: Arc<ExecutionClient<R>>) -> Self {
+        let state = EvmState::new(execution, CallBlock::Verified(Box::new(block)));
+        ProofDB { state }
     }
 }
 
+/// The block whose state a call is executed on.
+enum CallBlock {
+    /// A block tracked by the execution client.
+    Tag(BlockTag),
+    /// A block provided by the caller, that was verified by other means.
+    Verified(Box<Block>),
+}
+
 enum StateAccess {
     Basic(Address),
     BlockHash(u64),
@@ -144,13 +169,13 @@ struct EvmState<R: ExecutionRpc> {
     basic: HashMap<Address, AccountInfo>,
     block_hash: HashMap<u64, B256>,
     storage: HashMap<Address, HashMap<U256, U256>>,
-    block: BlockTag,
+    block: CallBlock,
     access: Option<StateAccess>,
     execution: Arc<ExecutionClient<R>>,
 }
 
 impl<R: ExecutionRpc> EvmState<R> {
-    pub fn new(execution: Arc<ExecutionClient<R>>, block: BlockTag) -> Self {
+    fn new(execution: Arc<ExecutionClient<R>>, block: CallBlock) -> Self {
         Self {
             execution,
             block,
@@ -161,15 +186,42 @@ impl<R: ExecutionRpc> EvmState<R> {
         }
     }
 
+    async fn get_block(&self) -> Result<Block> {
+        match &self.block {
+            CallBlock::Tag(tag) => self.execution.get_block(*tag, false).await,
+            CallBlock::Verified(block) => Ok((**block).clone()),
+        }
+    }
+
+    async fn get_account(
+        &self,
+        address: &ethers::types::Address,
+        slots: Option<&[ethers::types::H256]>,
+    ) -> Result<Account> {
+        match &self.block {
+            CallBlock::Tag(tag) => self.execution.get_account(address, slots, *tag).await,
+            CallBlock::Verified(block) => {
+                self.execution
+                    .get_account_at_block(address, slots, block)
+                    .await
+            }
+        }
+    }
+
+    /// The block tag to use for requests to the execution RPC.
+    fn rpc_tag(&self) -> BlockTag {
+        match &self.block {
+            CallBlock::Tag(tag) => *tag,
+            CallBlock::Verified(block) => BlockTag::Number(block.number.as_u64()),
+        }
+    }
+
     pub async fn update_state(&mut self) -> Result<()> {
         if let Some(access) = &self.access.take() {
             match access {
                 StateAccess::Basic(address) => {
                     let address_ethers = ethers::types::Address::from_slice(address.as_slice());
-                    let account = self
-                        .execution
-                        .get_account(&address_ethers, None, self.block)
-                        .await?;
+                    let account = self.get_account(&address_ethers, None).await?;
 
                     let bytecode = Bytecode::new_raw(account.code.into());
                     let code_hash = B256::from_slice(account.code_hash.as_bytes());
@@ -182,10 +234,7 @@ impl<R: ExecutionRpc> EvmState<R> {
                     let address_ethers = ethers::types::Address::from_slice(address.as_slice());
                     let slot_ethers = ethers::types::H256::from_slice(&slot.to_be_bytes::<32>());
                     let slots = [slot_ethers];
-                    let account = self
-                        .execution
-                        .get_account(&address_ethers, Some(&slots), self.block)
-                        .await?;
+                    let account = self.get_account(&address_ethers, Some(&slots)).await?;
 
                     let storage = self.storage.entry(*address).or_default();
                     let slot_ethers = &slot.as_le_bytes();
@@ -199,12 +248,19 @@ impl<R: ExecutionRpc> EvmState<R> {
                     storage.insert(*slot, value);
                 }
                 StateAccess::BlockHash(number) => {
-                    let block = self
-                        .execution
-                        .get_block(BlockTag::Number(*number), false)
-                        .await?;
-
-                    let hash = B256::from_slice(block.hash.as_bytes());
+                    let hash = match &self.block {
+                        CallBlock::Verified(block) if block.number.as_u64() == *number => {
+                            block.hash
+                        }
+                        _ => {
+                            self.execution
+                                .get_block(BlockTag::Number(*number), false)
+                                .await?
+                                .hash
+                        }
+                    };
+
+                    let hash = B256::from_slice(hash.as_bytes());
                     self.block_hash.insert(*number, hash);
                 }
             }
@@ -249,7 +305,7 @@ impl<R: ExecutionRpc> EvmState<R> {
         let mut list = self
             .execution
             .rpc
-            .create_access_list(opts, self.block)
+            .create_access_list(opts, self.rpc_tag())
             .await
             .map_err(EvmError::RpcError)?
             .0;
@@ -264,7 +320,7 @@ impl<R: ExecutionRpc> EvmState<R> {
             storage_keys: Vec::default(),
         };
 
-        let coinbase = self.execution.get_block(self.block, false).await?.miner;
+        let coinbase = self.get_block().await?.miner;
         let producer_access_entry = AccessListItem {
             address: coinbase,
             storage_keys: Vec::default(),
@@ -287,11 +343,8 @@ impl<R: ExecutionRpc> EvmState<R> {
         let mut account_map = HashMap::new();
         for chunk in list.chunks(PARALLEL_QUERY_BATCH_SIZE) {
             let account_chunk_futs = chunk.iter().map(|account| {
-                let account_fut = self.execution.get_account(
-                    &account.address,
-                    Some(account.storage_keys.as_slice()),
-                    self.block,
-                );
+                let account_fut =
+                    self.get_account(&account.address, Some(account.storage_keys.as_slice()));
                 async move { (account.address, account_fut.await) }
             });
 
@@ -3,15 +3,16 @@ use std::collections::HashMap;
 use common::{
     errors::BlockNotFoundError,
     types::{Block, BlockTag, Transactions},
-    utils::hex_str_to_bytes,
 };
 use ethers::{
-    abi::AbiEncode,
     prelude::{Address, EIP1186ProofResponse},
-    types::{Filter, Log, Transaction, TransactionReceipt, H256, U256},
+    types::{
+        Block as RpcBlock, FeeHistory, Filter, Log, Transaction, TransactionReceipt, ValueOrArray,
+        H256, U256,
+    },
     utils::{
         keccak256,
-        rlp::{encode, Encodable, RlpStream},
+        rlp::{Encodable, RlpStream},
     },
 };
 use eyre::Result;
@@ -20,12 +21,28 @@ use revm::primitives::KECCAK_EMPTY;
 use triehash_ethereum::ordered_trie_root;
 
 use super::{
-    proof::{encode_account, verify_proof},
+    beacon_roots::{root_slot, timestamp_slot, BEACON_ROOTS_ADDRESS},
+    block::{encode_header, header_hash, verify_block},
+    bloom::bloom_may_match,
+    contract_interactions::get_address_mapping_slot,
+    ens::{
+        decode_address_record, namehash, registry_owner_slot, registry_resolver_slot,
+        resolver_address_slot, resolver_version_slot, slot_address, EnsResolverLayout,
+        ENS_OLD_REGISTRY_ADDRESS, ENS_REGISTRY_ADDRESS,
+    },
+    fees::{gas_used_ratio, next_base_fee},
+    proofs::{trie::ordered_trie_proofs, verify_account_proof},
     rpc::ExecutionRpc,
-    types::Account,
+    types::{
+        Account, AccountProofs, BeaconRootProof, BlockReceiptsProof, CodeProof, Erc20Balance,
+        ProofBundle, ProvenNonce, ProvenStorage, TransactionCounts,
+    },
 };
 use crate::{errors::ExecutionError, state::State};
 
+/// The maximum number of headers walked back by [`ExecutionClient::get_verified_ancestor`].
+const MAX_ANCESTOR_DEPTH: u64 = 1024;
+
 // We currently limit the max number of logs to fetch,
 // to avoid blocking the client for too long.
 const MAX_SUPPORTED_LOGS_NUMBER: usize = 5;
@@ -66,84 +83,411 @@ impl<R: ExecutionRpc> ExecutionClient<R> {
         slots: Option<&[H256]>,
         tag: BlockTag,
     ) -> Result<Account> {
-        let slots = slots.unwrap_or(&[]);
         let block = self
             .state
             .get_block(tag)
             .await
             .ok_or(BlockNotFoundError::new(tag))?;
 
+        self.get_account_at_block(address, slots, &block).await
+    }
+
+    /// Same as [`Self::get_account`], but verifies the proofs against the state root of the given
+    /// block, which must come from a verified source such as the finalized execution payload of
+    /// the light client.
+    pub async fn get_account_at_block(
+        &self,
+        address: &Address,
+        slots: Option<&[H256]>,
+        block: &Block,
+    ) -> Result<Account> {
+        let slots = slots.unwrap_or(&[]);
+        let proof = self.get_verified_proof(address, slots, block).await?;
+
+        let slot_map = proof
+            .storage_proof
+            .iter()
+            .map(|storage_proof| (storage_proof.key, storage_proof.value))
+            .collect::<HashMap<_, _>>();
+
+        let code = self
+            .get_verified_code(address, proof.code_hash, block)
+            .await?;
+
+        Ok(Account {
+            balance: proof.balance,
+            nonce: proof.nonce.as_u64(),
+            code,
+            code_hash: proof.code_hash,
+            storage_hash: proof.storage_hash,
+            slots: slot_map,
+        })
+    }
+
+    /// Fetches the account and storage proofs of `address` at `block`, and verifies them against
+    /// the state root of the block. Fails if the RPC omits the proof of any of the `slots`.
+    pub async fn get_verified_proof(
+        &self,
+        address: &Address,
+        slots: &[H256],
+        block: &Block,
+    ) -> Result<EIP1186ProofResponse> {
         let proof = self
             .rpc
             .get_proof(address, slots, block.number.as_u64())
             .await?;
 
-        let account_path = keccak256(address.as_bytes()).to_vec();
-        let account_encoded = encode_account(&proof);
+        verify_account_proof(address, slots, &proof, &block.state_root)?;
 
-        let is_valid = verify_proof(
-            &proof.account_proof,
-            block.state_root.as_bytes(),
-            &account_path,
-            &account_encoded,
-        );
+        Ok(proof)
+    }
 
-        if !is_valid {
-            return Err(ExecutionError::InvalidAccountProof(*address).into());
-        }
+    /// Fetches the proofs of `address` and of all of its `slots` with a single `eth_getProof`
+    /// call, and verifies them against the state root of `block`, which must come from a verified
+    /// source.
+    pub async fn get_proof_bundle(
+        &self,
+        address: &Address,
+        slots: &[H256],
+        block: &Block,
+    ) -> Result<ProofBundle> {
+        let proof = self.get_verified_proof(address, slots, block).await?;
 
-        let mut slot_map = HashMap::new();
+        Ok(ProofBundle {
+            block_number: block.number.as_u64(),
+            block_hash: block.hash,
+            state_root: block.state_root,
+            address: *address,
+            slots: slots.to_vec(),
+            proof,
+        })
+    }
 
-        for storage_proof in proof.storage_proof {
-            let key = hex_str_to_bytes(&storage_proof.key.encode_hex())?;
-            let value = encode(&storage_proof.value).to_vec();
+    /// Returns the nonce of `address` at the finalized block, with its proof against the state
+    /// root of the block.
+    pub async fn get_proven_nonce(&self, address: &Address) -> Result<ProvenNonce> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
 
-            let key_hash = keccak256(key);
+        let proof = self.get_proof_bundle(address, &[], &block).await?;
+        Ok(ProvenNonce {
+            nonce: proof.proof.nonce.as_u64(),
+            proof,
+        })
+    }
 
-            let is_valid = verify_proof(
-                &storage_proof.proof,
-                proof.storage_hash.as_bytes(),
-                &key_hash,
-                &value,
-            );
+    /// Returns the value of `slot` of `address` at the finalized block, with its proof against
+    /// the state root of the block.
+    pub async fn get_proven_storage(&self, address: &Address, slot: H256) -> Result<ProvenStorage> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
 
-            if !is_valid {
-                return Err(
-                    ExecutionError::InvalidStorageProof(*address, storage_proof.key).into(),
-                );
-            }
+        let proof = self.get_proof_bundle(address, &[slot], &block).await?;
+        let value = proof.storage_value(&slot).ok_or_else(|| {
+            ExecutionError::MissingStorageProof(*address, U256::from(slot.as_bytes()))
+        })?;
+
+        Ok(ProvenStorage { slot, value, proof })
+    }
+
+    /// Returns the code of `address` at `block`, with the proof of the account whose code hash
+    /// the code hashes to.
+    pub async fn get_code_proof(&self, address: &Address, block: &Block) -> Result<CodeProof> {
+        let proof = self.get_proof_bundle(address, &[], block).await?;
+        let code = self
+            .get_verified_code(address, proof.proof.code_hash, block)
+            .await?;
+
+        Ok(CodeProof {
+            code: code.into(),
+            proof,
+        })
+    }
 
-            slot_map.insert(storage_proof.key, storage_proof.value);
+    /// Fetches the code of `address` at `block`, and checks it against the proven `code_hash`.
+    async fn get_verified_code(
+        &self,
+        address: &Address,
+        code_hash: H256,
+        block: &Block,
+    ) -> Result<Vec<u8>> {
+        if code_hash == H256::from_slice(KECCAK_EMPTY.as_slice()) {
+            return Ok(Vec::new());
         }
 
-        let code = if proof.code_hash == H256::from_slice(KECCAK_EMPTY.as_slice()) {
-            Vec::new()
-        } else {
-            let code = self.rpc.get_code(address, block.number.as_u64()).await?;
-            let code_hash = keccak256(&code).into();
-
-            if proof.code_hash != code_hash {
-                return Err(ExecutionError::CodeHashMismatch(
-                    *address,
-                    code_hash.to_string(),
-                    proof.code_hash.to_string(),
-                )
-                .into());
-            }
+        let code = self.rpc.get_code(address, block.number.as_u64()).await?;
+        let fetched_hash = keccak256(&code).into();
+        if fetched_hash != code_hash {
+            return Err(ExecutionError::CodeHashMismatch(
+                *address,
+                fetched_hash.to_string(),
+                code_hash.to_string(),
+            )
+            .into());
+        }
+
+        Ok(code)
+    }
+
+    /// Returns the proof that the beacon roots contract stores the parent beacon block root of
+    /// the finalized block.
+    pub async fn get_beacon_root_proof(&self) -> Result<BeaconRootProof> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+
+        self.get_beacon_root_proof_at_block(&block).await
+    }
+
+    /// Same as [`Self::get_beacon_root_proof`], for a block that must come from a verified
+    /// source. The parent beacon block root of the block is taken from its beacon block.
+    pub async fn get_beacon_root_proof_at_block(&self, block: &Block) -> Result<BeaconRootProof> {
+        let block_number = block.number.as_u64();
+        let parent_beacon_block_root = block
+            .parent_beacon_block_root
+            .ok_or(ExecutionError::MissingParentBeaconBlockRoot(block_number))?;
+
+        let timestamp = block.timestamp.as_u64();
+        let address: Address = BEACON_ROOTS_ADDRESS.parse()?;
+        let slots = [timestamp_slot(timestamp), root_slot(timestamp)];
+        let proof = self.get_verified_proof(&address, &slots, block).await?;
 
-            code
+        let beacon_root_proof = BeaconRootProof {
+            block_number,
+            timestamp,
+            state_root: block.state_root,
+            parent_beacon_block_root,
+            proof,
         };
+        beacon_root_proof.verify_values()?;
 
-        Ok(Account {
-            balance: proof.balance,
-            nonce: proof.nonce.as_u64(),
-            code,
-            code_hash: proof.code_hash,
-            storage_hash: proof.storage_hash,
-            slots: slot_map,
+        Ok(beacon_root_proof)
+    }
+
+    /// Returns the proofs of `addresses` at the finalized block, verified against the finalized
+    /// state root.
+    pub async fn create_account_proofs(&self, addresses: &[Address]) -> Result<AccountProofs> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+
+        self.create_account_proofs_at_block(addresses, &block).await
+    }
+
+    /// Same as [`Self::create_account_proofs`], but verifies the proofs against the state root of
+    /// the given block, which must come from a verified source.
+    pub async fn create_account_proofs_at_block(
+        &self,
+        addresses: &[Address],
+        block: &Block,
+    ) -> Result<AccountProofs> {
+        let proofs_fut = addresses
+            .iter()
+            .map(|address| self.get_verified_proof(address, &[], block));
+        let proofs = join_all(proofs_fut)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(AccountProofs {
+            block_number: block.number.as_u64(),
+            block_hash: block.hash,
+            state_root: block.state_root,
+            proofs,
+        })
+    }
+
+    /// Returns the nonces of `addresses` at the finalized block, proven against the finalized
+    /// state root.
+    pub async fn get_transaction_counts(&self, addresses: &[Address]) -> Result<TransactionCounts> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+
+        self.get_transaction_counts_at_block(addresses, &block)
+            .await
+    }
+
+    /// Same as [`Self::get_transaction_counts`], but proves the nonces against the state root of
+    /// the given block, which must come from a verified source.
+    pub async fn get_transaction_counts_at_block(
+        &self,
+        addresses: &[Address],
+        block: &Block,
+    ) -> Result<TransactionCounts> {
+        let proofs = self
+            .create_account_proofs_at_block(addresses, block)
+            .await?;
+        let nonces = proofs
+            .proofs
+            .iter()
+            .map(|proof| proof.nonce.as_u64())
+            .collect();
+
+        Ok(TransactionCounts { nonces, proofs })
+    }
+
+    /// Returns the ERC-20 balance of `holder` in `token` at the finalized block, verified with a
+    /// storage proof against the finalized state root.
+    /// `balances_slot` is the storage slot of the `balances` mapping in the token contract layout.
+    pub async fn get_erc20_balance(
+        &self,
+        token: &Address,
+        holder: &Address,
+        balances_slot: u64,
+    ) -> Result<Erc20Balance> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+
+        self.get_erc20_balance_at_block(token, holder, balances_slot, &block)
+            .await
+    }
+
+    /// Same as [`Self::get_erc20_balance`], but verifies the proof against the state root of the
+    /// given block, which must come from a verified source.
+    pub async fn get_erc20_balance_at_block(
+        &self,
+        token: &Address,
+        holder: &Address,
+        balances_slot: u64,
+        block: &Block,
+    ) -> Result<Erc20Balance> {
+        let slot = get_address_mapping_slot(holder, balances_slot);
+        let proof = self.get_verified_proof(token, &[slot], block).await?;
+
+        // The proof of the slot is checked to be present by `get_verified_proof`.
+        let key = U256::from(slot.as_bytes());
+        let balance = proof
+            .storage_proof
+            .iter()
+            .find(|storage_proof| storage_proof.key == key)
+            .map(|storage_proof| storage_proof.value)
+            .unwrap_or_default();
+
+        Ok(Erc20Balance {
+            token: *token,
+            holder: *holder,
+            balance,
+            slot,
+            block_number: block.number.as_u64(),
+            state_root: block.state_root,
+            proof,
         })
     }
 
+    /// Resolves the address of the ENS `name` at the finalized block.
+    /// The registry and resolver records are read from storage slots proven against the
+    /// finalized state root, from the old registry for the names without an owner in the current
+    /// one, as the registry itself does. Returns `None` if the name has no resolver or no address.
+    pub async fn resolve_ens(
+        &self,
+        name: &str,
+        layout: &EnsResolverLayout,
+    ) -> Result<Option<Address>> {
+        let block = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+
+        self.resolve_ens_at_block(name, layout, &block).await
+    }
+
+    /// Same as [`Self::resolve_ens`], but verifies the storage proofs against the state root of
+    /// the given block, which must come from a verified source.
+    pub async fn resolve_ens_at_block(
+        &self,
+        name: &str,
+        layout: &EnsResolverLayout,
+        block: &Block,
+    ) -> Result<Option<Address>> {
+        let node = namehash(name);
+        let registry: Address = ENS_REGISTRY_ADDRESS.parse()?;
+
+        let slots = [registry_owner_slot(node), registry_resolver_slot(node)];
+        let record = self
+            .get_verified_storage_values(&registry, &slots, block)
+            .await?;
+        let resolver = if slot_address(record[0]).is_zero() {
+            // The names that were not migrated are only in the old registry.
+            let old_registry: Address = ENS_OLD_REGISTRY_ADDRESS.parse()?;
+            self.get_verified_storage(&old_registry, slots[1], block)
+                .await?
+        } else {
+            record[1]
+        };
+
+        let resolver = slot_address(resolver);
+        if resolver.is_zero() {
+            return Ok(None);
+        }
+
+        let version = self
+            .get_verified_storage(&resolver, resolver_version_slot(node, layout), block)
+            .await?;
+        let address = self
+            .get_verified_storage(
+                &resolver,
+                resolver_address_slot(node, version.low_u64(), layout),
+                block,
+            )
+            .await?;
+
+        Ok(decode_address_record(address)?)
+    }
+
+    /// Returns the value of `slot` in the storage of `address`, proven against the state root of
+    /// `block`.
+    async fn get_verified_storage(
+        &self,
+        address: &Address,
+        slot: H256,
+        block: &Block,
+    ) -> Result<U256> {
+        let values = self
+            .get_verified_storage_values(address, &[slot], block)
+            .await?;
+        Ok(values[0])
+    }
+
+    /// Same as [`Self::get_verified_storage`] for each of `slots`, with a single proof.
+    async fn get_verified_storage_values(
+        &self,
+        address: &Address,
+        slots: &[H256],
+        block: &Block,
+    ) -> Result<Vec<U256>> {
+        let proof = self.get_verified_proof(address, slots, block).await?;
+
+        slots
+            .iter()
+            .map(|slot| {
+                let key = U256::from(slot.as_bytes());
+                proof
+                    .storage_proof
+                    .iter()
+                    .find(|storage_proof| storage_proof.key == key)
+                    .map(|storage_proof| storage_proof.value)
+                    .ok_or(ExecutionError::MissingStorageProof(*address, key).into())
+            })
+            .collect()
+    }
+
     pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<H256> {
         self.rpc.send_raw_transaction(bytes).await
     }
@@ -174,6 +518,130 @@ impl<R: ExecutionRpc> ExecutionClient<R> {
         Ok(block)
     }
 
+    /// Returns the finalized block `tag`, cross-checked against the block of the execution RPC,
+    /// which also provides its transactions root.
+    pub async fn get_verified_block(&self, tag: BlockTag, full_tx: bool) -> Result<Block> {
+        let block = self
+            .state
+            .get_block(tag)
+            .await
+            .ok_or(BlockNotFoundError::new(tag))?;
+
+        self.verify_finalized_block(block, full_tx).await
+    }
+
+    /// Like [`Self::get_verified_block`], for the block with the given hash.
+    pub async fn get_verified_block_by_hash(&self, hash: H256, full_tx: bool) -> Result<Block> {
+        let mut block = self.state.get_block_by_hash(hash).await;
+        if block.is_none() {
+            block = self
+                .state
+                .get_block(BlockTag::Finalized)
+                .await
+                .filter(|finalized| finalized.hash == hash);
+        }
+        let block = block.ok_or(eyre::eyre!("block not found"))?;
+
+        self.verify_finalized_block(block, full_tx).await
+    }
+
+    async fn verify_finalized_block(&self, block: Block, full_tx: bool) -> Result<Block> {
+        let number = block.number.as_u64();
+        let finalized = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+        if number > finalized.number.as_u64() {
+            return Err(ExecutionError::BlockNotFinalized(number).into());
+        }
+
+        let rpc_block = self.rpc.get_block(number).await?;
+        let mut block = verify_block(&block, &rpc_block)?;
+        if !full_tx {
+            block.transactions = Transactions::Hashes(block.transactions.hashes());
+        }
+
+        Ok(block)
+    }
+
+    /// Returns the base fees and gas used ratios of the `block_count` blocks up to `last_block`,
+    /// from the verified payloads kept by the state rather than from the execution RPC. The range
+    /// is shortened to the kept blocks. The rewards are left empty, since the payloads do not carry
+    /// the gas used by each transaction.
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        last_block: BlockTag,
+    ) -> Result<FeeHistory> {
+        let latest = self
+            .state
+            .latest_block_number()
+            .await
+            .ok_or(ExecutionError::EmptyExecutionPayload())?;
+        let oldest = self
+            .state
+            .oldest_block_number()
+            .await
+            .ok_or(ExecutionError::EmptyExecutionPayload())?;
+
+        let last_block = match last_block {
+            BlockTag::Number(number) => number.min(latest),
+            tag => self
+                .state
+                .get_block(tag)
+                .await
+                .ok_or(BlockNotFoundError::new(tag))?
+                .number
+                .as_u64(),
+        };
+        if last_block < oldest {
+            return Err(ExecutionError::InvalidBlockRange(last_block, oldest).into());
+        }
+
+        let first_block = (last_block + 1).saturating_sub(block_count).max(oldest);
+        let mut fee_history = FeeHistory {
+            base_fee_per_gas: Vec::new(),
+            gas_used_ratio: Vec::new(),
+            oldest_block: first_block.into(),
+            reward: Vec::new(),
+        };
+        if block_count == 0 {
+            return Ok(fee_history);
+        }
+
+        let mut next_fee = U256::zero();
+        for number in first_block..=last_block {
+            let block = self
+                .state
+                .get_block(BlockTag::Number(number))
+                .await
+                .ok_or(ExecutionError::BlockNotFoundError(number))?;
+
+            let gas_used = block.gas_used.as_u64();
+            let gas_limit = block.gas_limit.as_u64();
+            fee_history.base_fee_per_gas.push(block.base_fee_per_gas);
+            fee_history
+                .gas_used_ratio
+                .push(gas_used_ratio(gas_used, gas_limit));
+            next_fee = next_base_fee(block.base_fee_per_gas, gas_used, gas_limit);
+        }
+
+        // As with `eth_feeHistory`, the base fee of the block after the range is included.
+        fee_history.base_fee_per_gas.push(next_fee);
+        Ok(fee_history)
+    }
+
+    /// Returns the base fee of the block after the latest verified block.
+    pub async fn suggest_base_fee(&self) -> Result<U256> {
+        let block = self.get_block(BlockTag::Latest, false).await?;
+        Ok(next_base_fee(
+            block.base_fee_per_gas,
+            block.gas_used.as_u64(),
+            block.gas_limit.as_u64(),
+        ))
+    }
+
     pub async fn get_transaction_by_block_hash_and_index(
         &self,
         block_hash: H256,
@@ -203,6 +671,50 @@ impl<R: ExecutionRpc> ExecutionClient<R> {
             return Ok(None);
         };
 
+        let receipts = self.get_block_receipts(&block).await?;
+
+        if compute_receipts_root(&receipts)? != block.receipts_root || !receipts.contains(&receipt)
+        {
+            return Err(ExecutionError::ReceiptRootMismatch(tx_hash.to_string()).into());
+        }
+
+        Ok(Some(receipt))
+    }
+
+    /// Returns the block `number`, proven to be an ancestor of the verified block `block` by
+    /// recomputing the hashes of the headers of the execution RPC from `block` down to it.
+    pub async fn get_verified_ancestor(
+        &self,
+        block: &Block,
+        number: u64,
+    ) -> Result<RpcBlock<H256>> {
+        let block_number = block.number.as_u64();
+        if number > block_number || block_number - number > MAX_ANCESTOR_DEPTH {
+            return Err(ExecutionError::AncestorOutOfRange(number, block_number).into());
+        }
+
+        let mut expected_hash = block.hash;
+        for ancestor_number in (number..=block_number).rev() {
+            let ancestor = self.rpc.get_block(ancestor_number).await?;
+            if header_hash(&ancestor) != expected_hash {
+                return Err(ExecutionError::BlockMismatch(
+                    ancestor_number,
+                    "header hash".to_string(),
+                )
+                .into());
+            }
+
+            if ancestor_number == number {
+                return Ok(ancestor);
+            }
+            expected_hash = ancestor.parent_hash;
+        }
+
+        unreachable!("the range ends with the requested block")
+    }
+
+    /// Fetches the receipts of every transaction of `block` from the RPC, without verifying them.
+    async fn get_block_receipts(&self, block: &Block) -> Result<Vec<TransactionReceipt>> {
         let tx_hashes = block.transactions.hashes();
 
         let receipts_fut = tx_hashes.iter().map(|hash| async move {
@@ -211,21 +723,105 @@ impl<R: ExecutionRpc> ExecutionClient<R> {
         });
 
         let receipts = join_all(receipts_fut).await;
-        let receipts = receipts.into_iter().collect::<Result<Vec<_>>>()?;
-        let receipts_encoded: Vec<Vec<u8>> = receipts.iter().map(encode_receipt).collect();
+        receipts.into_iter().collect::<Result<Vec<_>>>()
+    }
+
+    pub async fn get_transaction(&self, hash: H256) -> Option<Transaction> {
+        self.state.get_transaction(hash).await
+    }
 
-        let expected_receipt_root = ordered_trie_root(receipts_encoded);
-        let expected_receipt_root = H256::from_slice(&expected_receipt_root.to_fixed_bytes());
+    /// Returns the transaction `hash` of a block kept by the state, once the block is proven to be
+    /// an ancestor of the finalized block and the transactions of its payload to rebuild the
+    /// transactions root of its header. Fails for the transactions that are only in blocks that
+    /// are not finalized yet, or were reorged out.
+    pub async fn get_verified_transaction(&self, hash: H256) -> Result<Transaction> {
+        let block = self
+            .state
+            .get_transaction_block(hash)
+            .await
+            .ok_or_else(|| ExecutionError::MissingTransaction(format!("{hash:?}")))?;
+        let header = self
+            .get_finalized_header(&block)
+            .await?
+            .ok_or(ExecutionError::TransactionNotFinalized(hash))?;
 
-        if expected_receipt_root != block.receipts_root || !receipts.contains(&receipt) {
-            return Err(ExecutionError::ReceiptRootMismatch(tx_hash.to_string()).into());
+        // The transactions root can only be rebuilt from the full transactions.
+        let number = block.number.as_u64();
+        let Transactions::Full(txs) = &block.transactions else {
+            return Err(ExecutionError::BlockTransactionsRootMismatch(number).into());
+        };
+        if compute_transactions_root(txs) != header.transactions_root {
+            return Err(ExecutionError::BlockTransactionsRootMismatch(number).into());
         }
 
-        Ok(Some(receipt))
+        // Every transaction of the trie is proven at its index, including this one.
+        txs.iter()
+            .find(|tx| tx.hash == hash && H256::from(keccak256(tx.rlp())) == hash)
+            .cloned()
+            .ok_or_else(|| ExecutionError::MissingTransaction(format!("{hash:?}")).into())
     }
 
-    pub async fn get_transaction(&self, hash: H256) -> Option<Transaction> {
-        self.state.get_transaction(hash).await
+    /// Returns the receipts of every transaction of the finalized block `tag`, with the header of
+    /// the block and the proof of each receipt against its receipts root, see
+    /// [`BlockReceiptsProof::verify`]. The receipts are fetched at once, and the receipts trie is
+    /// only built once for all the proofs.
+    pub async fn get_verified_block_receipts(&self, tag: BlockTag) -> Result<BlockReceiptsProof> {
+        let block = self
+            .state
+            .get_block(tag)
+            .await
+            .ok_or(BlockNotFoundError::new(tag))?;
+        let number = block.number.as_u64();
+        let header = self
+            .get_finalized_header(&block)
+            .await?
+            .ok_or(ExecutionError::BlockNotFinalized(number))?;
+
+        let receipts = self.rpc.get_block_receipts(number).await?;
+        let tx_hashes = block.transactions.hashes();
+        let matches = receipts.len() == tx_hashes.len()
+            && receipts.iter().zip(&tx_hashes).all(|(receipt, hash)| {
+                receipt.transaction_hash == *hash && receipt.block_hash == Some(block.hash)
+            });
+        if !matches {
+            return Err(ExecutionError::BlockReceiptsRootMismatch(number).into());
+        }
+
+        let encoded = receipts
+            .iter()
+            .map(encode_receipt)
+            .collect::<Result<Vec<_>>>()?;
+        let (receipts_root, receipt_proofs) = ordered_trie_proofs(&encoded);
+        if receipts_root != header.receipts_root {
+            return Err(ExecutionError::BlockReceiptsRootMismatch(number).into());
+        }
+
+        Ok(BlockReceiptsProof {
+            block_number: number,
+            block_hash: block.hash,
+            header: encode_header(&header).into(),
+            receipts,
+            receipt_proofs,
+        })
+    }
+
+    /// Returns the header of the verified block `block` from the execution RPC, once `block` is
+    /// proven to be an ancestor of the finalized block, or `None` if it is not finalized.
+    async fn get_finalized_header(&self, block: &Block) -> Result<Option<RpcBlock<H256>>> {
+        let finalized = self
+            .state
+            .get_block(BlockTag::Finalized)
+            .await
+            .ok_or(BlockNotFoundError::new(BlockTag::Finalized))?;
+
+        let number = block.number.as_u64();
+        if number > finalized.number.as_u64() {
+            return Ok(None);
+        }
+        let header = self.get_verified_ancestor(&finalized, number).await?;
+
+        // A block that was reorged out has another hash than the finalized one at its number.
+        Ok((header_hash(&header) == block.hash).then_some(header))
     }
 
     pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
@@ -255,6 +851,66 @@ impl<R: ExecutionRpc> ExecutionClient<R> {
         Ok(logs)
     }
 
+    /// Fetches the logs of `block` that match `filter`, and verifies them against the receipts
+    /// root of `block`, which must come from a verified source such as the finalized execution
+    /// payload of the light client. The receipts of the block are fetched once, and the logs must
+    /// be exactly the logs of the verified receipts that match `filter`, in order, so that the RPC
+    /// can neither add nor leave out any log. Nothing is fetched if the logs bloom of `block`
+    /// rules the filter out.
+    pub async fn get_logs_at_block(&self, filter: &Filter, block: &Block) -> Result<Vec<Log>> {
+        if !bloom_may_match(filter, &block.logs_bloom) {
+            return Ok(Vec::new());
+        }
+
+        let filter = filter.clone().at_block_hash(block.hash);
+        let logs = self.rpc.get_logs(&filter).await?;
+
+        // The receipts are fetched even if the RPC returns no log, which may be an omission.
+        let number = block.number.as_u64();
+        let receipts = self.get_block_receipts(block).await?;
+        if compute_receipts_root(&receipts)? != block.receipts_root {
+            return Err(ExecutionError::BlockReceiptsRootMismatch(number).into());
+        }
+
+        let expected = receipts
+            .iter()
+            .flat_map(|receipt| {
+                receipt
+                    .logs
+                    .iter()
+                    .map(|log| (receipt.transaction_hash, log))
+            })
+            .filter(|(_, log)| log_matches(&filter, log))
+            .collect::<Vec<_>>();
+        if expected.len() != logs.len() {
+            return Err(ExecutionError::IncompleteLogs(number, expected.len(), logs.len()).into());
+        }
+
+        for (log, (tx_hash, expected)) in logs.iter().zip(expected) {
+            if log.transaction_hash != Some(tx_hash) || log.rlp_bytes() != expected.rlp_bytes() {
+                let index = log.log_index.unwrap_or_default();
+                return Err(ExecutionError::MissingLog(tx_hash.to_string(), index).into());
+            }
+        }
+
+        Ok(logs)
+    }
+
+    /// Same as [`Self::get_logs_at_block`] for each of `blocks`, in order. Only the blocks whose
+    /// logs bloom may match `filter` are queried, which keeps wide scans cheap.
+    pub async fn get_logs_at_blocks(&self, filter: &Filter, blocks: &[Block]) -> Result<Vec<Log>> {
+        let logs_fut = blocks
+            .iter()
+            .filter(|block| bloom_may_match(filter, &block.logs_bloom))
+            .map(|block| self.get_logs_at_block(filter, block));
+
+        let logs = join_all(logs_fut)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        Ok(logs.into_iter().flatten().collect())
+    }
+
     pub async fn get_filter_changes(&self, filter_id: &U256) -> Result<Vec<Log>> {
         let logs = self.rpc.get_filter_changes(filter_id).await?;
         if logs.len() > MAX_SUPPORTED_LOGS_NUMBER {
@@ -309,41 +965,99 @@ impl<R: ExecutionRpc> ExecutionClient<R> {
                 .await?
                 .ok_or(ExecutionError::NoReceiptForTransaction(tx_hash.to_string()))?;
 
-            // Check if the receipt contains the desired log
-            // Encoding logs for comparison
-            let receipt_logs_encoded = receipt
-                .logs
-                .iter()
-                .map(|log| log.rlp_bytes())
-                .collect::<Vec<_>>();
-
-            let log_encoded = log.rlp_bytes();
-
-            if !receipt_logs_encoded.contains(&log_encoded) {
-                return Err(ExecutionError::MissingLog(
-                    tx_hash.to_string(),
-                    log.log_index.unwrap(),
-                )
-                .into());
-            }
+            verify_log_in_receipt(log, &receipt)?;
         }
         Ok(())
     }
 }
 
-fn encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
+/// Checks that `log` is one of the logs of the already proven `receipt`.
+fn verify_log_in_receipt(log: &Log, receipt: &TransactionReceipt) -> Result<()> {
+    // Encoding logs for comparison
+    let receipt_logs_encoded = receipt
+        .logs
+        .iter()
+        .map(|log| log.rlp_bytes())
+        .collect::<Vec<_>>();
+
+    let log_encoded = log.rlp_bytes();
+
+    if !receipt_logs_encoded.contains(&log_encoded) {
+        return Err(ExecutionError::MissingLog(
+            receipt.transaction_hash.to_string(),
+            log.log_index.unwrap_or_default(),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Whether `log` matches the address and topics of `filter`, as in [`bloom_may_match`], but
+/// exactly.
+fn log_matches(filter: &Filter, log: &Log) -> bool {
+    let address_matches = match &filter.address {
+        Some(ValueOrArray::Value(address)) => *address == log.address,
+        Some(ValueOrArray::Array(addresses)) if !addresses.is_empty() => {
+            addresses.contains(&log.address)
+        }
+        _ => true,
+    };
+
+    address_matches
+        && filter
+            .topics
+            .iter()
+            .enumerate()
+            .all(|(index, topic)| match topic {
+                Some(ValueOrArray::Value(Some(topic))) => log.topics.get(index) == Some(topic),
+                Some(ValueOrArray::Array(topics)) => {
+                    topics.is_empty()
+                        || topics.iter().any(|topic| match topic {
+                            Some(topic) => log.topics.get(index) == Some(topic),
+                            None => true,
+                        })
+                }
+                _ => true,
+            })
+}
+
+/// Returns the root of the trie of the signed encodings of `txs`, by index.
+fn compute_transactions_root(txs: &[Transaction]) -> H256 {
+    let txs_encoded = txs.iter().map(|tx| tx.rlp().to_vec());
+    let transactions_root = ordered_trie_root(txs_encoded);
+    H256::from_slice(&transactions_root.to_fixed_bytes())
+}
+
+pub(crate) fn compute_receipts_root(receipts: &[TransactionReceipt]) -> Result<H256> {
+    let receipts_encoded = receipts
+        .iter()
+        .map(encode_receipt)
+        .collect::<Result<Vec<_>>>()?;
+    let receipts_root = ordered_trie_root(receipts_encoded);
+    Ok(H256::from_slice(&receipts_root.to_fixed_bytes()))
+}
+
+/// Returns the consensus encoding of `receipt`. The receipts of the RPC without a status or a
+/// transaction type, such as the ones from before Byzantium, cannot be encoded, and fail as a
+/// mismatch of the receipt root.
+pub(crate) fn encode_receipt(receipt: &TransactionReceipt) -> Result<Vec<u8>> {
+    let mismatch =
+        || ExecutionError::ReceiptRootMismatch(format!("{:?}", receipt.transaction_hash));
+    let status = receipt.status.ok_or_else(mismatch)?;
+    let tx_type = receipt.transaction_type.ok_or_else(mismatch)?.as_u64();
+
     let mut stream = RlpStream::new();
     stream.begin_list(4);
-    stream.append(&receipt.status.unwrap());
+    stream.append(&status);
     stream.append(&receipt.cumulative_gas_used);
     stream.append(&receipt.logs_bloom);
     stream.append_list(&receipt.logs);
 
     let legacy_receipt_encoded = stream.out();
-    let tx_type = receipt.transaction_type.unwrap().as_u64();
 
-    match tx_type {
+    Ok(match tx_type {
         0 => legacy_receipt_encoded.to_vec(),
         _ => [&tx_type.to_be_bytes()[7..8], &legacy_receipt_encoded].concat(),
-    }
+    })
 }
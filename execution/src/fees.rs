@@ -0,0 +1,63 @@
+//! The base fee of the next block, derived from its parent as in
+//! [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559#specification).
+
+use ethers::types::U256;
+
+/// The ratio of the gas limit of a block to its gas target.
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// The bound of the change of the base fee from a block to the next one, as a denominator.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Returns the base fee of the child of a block with the given base fee, gas used and gas limit.
+pub fn next_base_fee(base_fee: U256, gas_used: u64, gas_limit: u64) -> U256 {
+    let gas_target = gas_limit / ELASTICITY_MULTIPLIER;
+    if gas_target == 0 || gas_used == gas_target {
+        return base_fee;
+    }
+
+    let denominator = U256::from(gas_target) * BASE_FEE_MAX_CHANGE_DENOMINATOR;
+    if gas_used > gas_target {
+        let delta = base_fee * (gas_used - gas_target) / denominator;
+        base_fee + delta.max(U256::one())
+    } else {
+        let delta = base_fee * (gas_target - gas_used) / denominator;
+        base_fee.saturating_sub(delta)
+    }
+}
+
+/// Returns the ratio of gas used to the gas limit of a block.
+pub fn gas_used_ratio(gas_used: u64, gas_limit: u64) -> f64 {
+    if gas_limit == 0 {
+        0.0
+    } else {
+        gas_used as f64 / gas_limit as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_base_fee() {
+        let base_fee = U256::from(1_000_000_000u64);
+        let gas_limit = 30_000_000;
+
+        assert_eq!(base_fee, next_base_fee(base_fee, 15_000_000, gas_limit));
+        // A full block raises the base fee by an eighth, an empty one lowers it by an eighth.
+        assert_eq!(
+            U256::from(1_125_000_000u64),
+            next_base_fee(base_fee, gas_limit, gas_limit)
+        );
+        assert_eq!(
+            U256::from(875_000_000u64),
+            next_base_fee(base_fee, 0, gas_limit)
+        );
+        // The base fee always increases above the target.
+        assert_eq!(
+            U256::from(8),
+            next_base_fee(U256::from(7), 15_000_001, gas_limit)
+        );
+    }
+}
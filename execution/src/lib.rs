@@ -1,14 +1,23 @@
 pub use contract_interactions::*;
 pub use execution::*;
-pub use proof::{verify_proof, *};
+pub use proofs::{verify_proof, *};
 
+pub mod beacon_roots;
+pub mod block;
+pub mod bloom;
+pub mod broadcast;
+pub mod compact;
 pub mod constants;
+pub mod ens;
 pub mod errors;
 pub mod evm;
+pub mod fees;
+pub mod proofs;
+pub mod quorum;
 pub mod rpc;
 pub mod state;
 pub mod types;
+pub mod watcher;
 
 mod contract_interactions;
 mod execution;
-mod proof;
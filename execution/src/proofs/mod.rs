@@ -0,0 +1,78 @@
+//! Verification of account and storage proofs against a state root.
+
+use common::utils::hex_str_to_bytes;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, EIP1186ProofResponse, H256, U256},
+    utils::{
+        keccak256,
+        rlp::{encode, RlpStream},
+    },
+};
+use eyre::Result;
+
+pub use self::mpt::verify_proof;
+use crate::errors::ExecutionError;
+
+pub mod mpt;
+pub mod trie;
+
+pub fn encode_account(proof: &EIP1186ProofResponse) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&proof.nonce);
+    stream.append(&proof.balance);
+    stream.append(&proof.storage_hash);
+    stream.append(&proof.code_hash);
+    let encoded = stream.out();
+    encoded.to_vec()
+}
+
+/// Verifies the account proof of `address` against `state_root`, and every storage proof against
+/// the storage root of the account. Fails if the proof of any of the `slots` is missing.
+pub fn verify_account_proof(
+    address: &Address,
+    slots: &[H256],
+    proof: &EIP1186ProofResponse,
+    state_root: &H256,
+) -> Result<()> {
+    let account_path = keccak256(address.as_bytes()).to_vec();
+    let account_encoded = encode_account(proof);
+
+    let is_valid = verify_proof(
+        &proof.account_proof,
+        state_root.as_bytes(),
+        &account_path,
+        &account_encoded,
+    );
+
+    if !is_valid {
+        return Err(ExecutionError::InvalidAccountProof(*address).into());
+    }
+
+    for storage_proof in proof.storage_proof.iter() {
+        let key = hex_str_to_bytes(&storage_proof.key.encode_hex())?;
+        let value = encode(&storage_proof.value).to_vec();
+
+        let key_hash = keccak256(key);
+
+        let is_valid = verify_proof(
+            &storage_proof.proof,
+            proof.storage_hash.as_bytes(),
+            &key_hash,
+            &value,
+        );
+
+        if !is_valid {
+            return Err(ExecutionError::InvalidStorageProof(*address, storage_proof.key).into());
+        }
+    }
+
+    for slot in slots {
+        let key = U256::from(slot.as_bytes());
+        if !proof.storage_proof.iter().any(|p| p.key == key) {
+            return Err(ExecutionError::MissingStorageProof(*address, key).into());
+        }
+    }
+
+    Ok(())
+}
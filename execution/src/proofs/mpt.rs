@@ -1,13 +1,41 @@
+//! Verification of Merkle-Patricia trie proofs, as returned by `eth_getProof`.
+//!
+//! A proof is the list of RLP encoded nodes from the root to the node holding the key, and the
+//! path is the key as stored in the trie. The state and storage tries are secure tries, whose
+//! paths are the keccak hash of the address or of the storage slot, see [`secure_path`].
+
 #![allow(clippy::needless_return)]
 
 use ethers::{
-    types::{Bytes, EIP1186ProofResponse},
+    types::{Bytes, H256},
     utils::{
         keccak256,
         rlp::{decode_list, RlpStream},
     },
 };
 
+/// The RLP encoding of an empty value, which stands for a missing key in an exclusion proof.
+pub const EMPTY_VALUE: [u8; 1] = [0x80];
+
+/// Returns the path of `key` in a secure trie.
+pub fn secure_path(key: &[u8]) -> [u8; 32] {
+    keccak256(key)
+}
+
+/// Verifies that `proof` proves the RLP encoded `value` at `path` in the trie with `root`. An
+/// empty account, like an empty value, can only be proven with an exclusion proof.
+pub fn verify_inclusion(root: &H256, path: &[u8], value: &[u8], proof: &[Bytes]) -> bool {
+    value != EMPTY_VALUE.as_slice() && verify_proof(proof, root.as_bytes(), path, value)
+}
+
+/// Verifies that `proof` proves that nothing is at `path` in the trie with `root`.
+pub fn verify_exclusion(root: &H256, path: &[u8], proof: &[Bytes]) -> bool {
+    verify_proof(proof, root.as_bytes(), path, &EMPTY_VALUE)
+}
+
+/// Verifies that `proof` proves `value` at `path` in the trie with `root`, or that nothing is at
+/// `path` if `value` is the RLP encoding of an empty slot or account. Prefer [`verify_inclusion`]
+/// and [`verify_exclusion`], which do not depend on the value to tell both proofs apart.
 pub fn verify_proof(proof: &[Bytes], root: &[u8], path: &[u8], value: &[u8]) -> bool {
     let mut expected_hash = root.to_vec();
     let mut path_offset = 0;
@@ -164,19 +192,9 @@ fn get_nibble(path: &[u8], offset: usize) -> u8 {
     }
 }
 
-pub fn encode_account(proof: &EIP1186ProofResponse) -> Vec<u8> {
-    let mut stream = RlpStream::new_list(4);
-    stream.append(&proof.nonce);
-    stream.append(&proof.balance);
-    stream.append(&proof.storage_hash);
-    stream.append(&proof.code_hash);
-    let encoded = stream.out();
-    encoded.to_vec()
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::proof::shared_prefix_length;
+    use super::*;
 
     #[tokio::test]
     async fn test_shared_prefix_length() {
@@ -197,4 +215,34 @@ mod tests {
         let shared_len = shared_prefix_length(&path, path_offset, &node_path);
         assert_eq!(shared_len, 7);
     }
+
+    /// A trie with a single leaf, whose node is the root.
+    fn single_leaf_trie(path: &[u8], value: &[u8]) -> (H256, Vec<Bytes>) {
+        let mut key = vec![0x20];
+        key.extend_from_slice(path);
+
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&key);
+        stream.append(&value.to_vec());
+        let node = Bytes::from(stream.out().to_vec());
+
+        (H256::from(keccak256(&node)), vec![node])
+    }
+
+    #[test]
+    fn test_verify_inclusion_and_exclusion() {
+        let path = secure_path(b"key");
+        let value = vec![0x83, 1, 2, 3];
+        let (root, proof) = single_leaf_trie(&path, &value);
+
+        assert!(verify_inclusion(&root, &path, &value, &proof));
+        assert!(!verify_inclusion(&root, &path, &[0x01], &proof));
+        assert!(!verify_inclusion(&H256::zero(), &path, &value, &proof));
+        assert!(!verify_exclusion(&root, &path, &proof));
+
+        // The leaf proves that another path is not in the trie.
+        let other_path = secure_path(b"other key");
+        assert!(verify_exclusion(&root, &other_path, &proof));
+        assert!(!verify_inclusion(&root, &other_path, &value, &proof));
+    }
 }
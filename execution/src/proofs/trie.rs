@@ -0,0 +1,170 @@
+//! Building of the ordered Merkle-Patricia tries of a block, such as its receipts trie, with the
+//! proof of every item, so that the items of a whole block are proven with a single pass.
+//!
+//! The path of an item is the RLP encoding of its index, see [`index_path`]. Every node of the
+//! proof of an item is hashed, as [`verify_inclusion`](super::mpt::verify_inclusion) expects, so
+//! the items must be encoded to at least 32 bytes, as the receipts are.
+
+use ethers::{
+    types::{Bytes, H256},
+    utils::{
+        keccak256,
+        rlp::{encode, RlpStream},
+    },
+};
+
+use super::mpt::EMPTY_VALUE;
+
+/// Returns the path of the item at `index` in an ordered trie.
+pub fn index_path(index: usize) -> Vec<u8> {
+    encode(&(index as u64)).to_vec()
+}
+
+/// Returns the root of the ordered trie of `values`, with the proof of each value, from the root
+/// to its leaf.
+pub fn ordered_trie_proofs(values: &[Vec<u8>]) -> (H256, Vec<Vec<Bytes>>) {
+    if values.is_empty() {
+        return (H256::from(keccak256(EMPTY_VALUE)), Vec::new());
+    }
+
+    let mut items = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (nibbles(&index_path(index)), index, value.as_slice()))
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut proofs = vec![Vec::new(); values.len()];
+    let root = build_node(&items, 0, &mut proofs);
+    // The nodes were pushed from the leaves up.
+    for proof in &mut proofs {
+        proof.reverse();
+    }
+
+    (H256::from(keccak256(root)), proofs)
+}
+
+/// Returns the RLP encoded node of the sorted `items` below the first `depth` nibbles of their
+/// paths, and pushes it, after the nodes below it, to the proof of each of them.
+fn build_node(
+    items: &[(Vec<u8>, usize, &[u8])],
+    depth: usize,
+    proofs: &mut [Vec<Bytes>],
+) -> Vec<u8> {
+    let stream = if let [(path, _, value)] = items {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&path[depth..], true));
+        stream.append(&value.to_vec());
+        stream
+    } else if let Some(prefix) = common_prefix(items, depth).filter(|len| *len > 0) {
+        let child = build_node(items, depth + prefix, proofs);
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&items[0].0[depth..depth + prefix], false));
+        append_reference(&mut stream, &child);
+        stream
+    } else {
+        let mut stream = RlpStream::new_list(17);
+        for nibble in 0..16 {
+            let start = items.partition_point(|(path, ..)| path[depth] < nibble);
+            let end = items.partition_point(|(path, ..)| path[depth] <= nibble);
+            if start == end {
+                stream.append_empty_data();
+            } else {
+                let child = build_node(&items[start..end], depth + 1, proofs);
+                append_reference(&mut stream, &child);
+            }
+        }
+        // No path is the prefix of another, so the branches hold no value.
+        stream.append_empty_data();
+        stream
+    };
+
+    let node = stream.out().to_vec();
+    for (_, index, _) in items {
+        proofs[*index].push(Bytes::from(node.clone()));
+    }
+
+    node
+}
+
+/// Returns the number of nibbles after `depth` that every path of `items` shares, if they are
+/// all longer than that.
+fn common_prefix(items: &[(Vec<u8>, usize, &[u8])], depth: usize) -> Option<usize> {
+    let first = &items[0].0;
+    let mut len = first.len() - depth;
+    for (path, ..) in &items[1..] {
+        len = len.min(
+            path[depth..]
+                .iter()
+                .zip(&first[depth..])
+                .take_while(|(a, b)| a == b)
+                .count(),
+        );
+    }
+
+    items
+        .iter()
+        .all(|(path, ..)| path.len() > depth + len)
+        .then_some(len)
+}
+
+/// Appends the reference of a child node: the node itself if it is shorter than a hash.
+fn append_reference(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&H256::from(keccak256(node)));
+    }
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xF])
+        .collect()
+}
+
+/// Returns the hex prefix encoding of `nibbles`, flagged as the path of a leaf or of an extension.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let (first, rest) = if nibbles.len() % 2 == 1 {
+        (((flag + 1) << 4) | nibbles[0], &nibbles[1..])
+    } else {
+        (flag << 4, nibbles)
+    };
+
+    let mut encoded = vec![first];
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use triehash_ethereum::ordered_trie_root;
+
+    use super::*;
+    use crate::proofs::mpt::verify_inclusion;
+
+    #[test]
+    fn test_ordered_trie_proofs() {
+        for count in [0, 1, 2, 17, 130, 300] {
+            let values = (0..count)
+                .map(|index: usize| [&index.to_be_bytes()[..], &[0xAA; 40][..]].concat())
+                .collect::<Vec<_>>();
+            let (root, proofs) = ordered_trie_proofs(&values);
+
+            let expected = ordered_trie_root(values.iter());
+            assert_eq!(H256::from_slice(&expected.to_fixed_bytes()), root);
+            assert_eq!(count, proofs.len());
+            for (index, (value, proof)) in values.iter().zip(&proofs).enumerate() {
+                assert!(verify_inclusion(&root, &index_path(index), value, proof));
+                assert!(!verify_inclusion(
+                    &root,
+                    &index_path(index + 1),
+                    value,
+                    proof
+                ));
+            }
+        }
+    }
+}
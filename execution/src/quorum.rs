@@ -0,0 +1,225 @@
+//! Agreement of several execution RPCs on the data that no proof covers, such as the pending state
+//! and gas estimates, so that a single faulty or malicious RPC cannot pick it.
+//!
+//! Such data is still not verified: a majority of the RPCs can agree on a wrong value. It is
+//! returned as a [`QuorumChecked`] value, so that it is never mistaken for verified data.
+
+use std::future::Future;
+
+use ethers::types::{Address, U256};
+use eyre::Result;
+use futures::future::join_all;
+use serde::Serialize;
+use tracing::debug;
+
+use crate::{errors::ExecutionError, rpc::ExecutionRpc, types::CallOpts, ExecutionClient};
+
+/// An unverified value, that at least the quorum of the execution RPCs returned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuorumChecked<T> {
+    pub value: T,
+    /// The number of RPCs that returned `value`.
+    pub agreeing: usize,
+    /// The number of RPCs that were queried, including the ones that failed.
+    pub queried: usize,
+}
+
+/// Queries the execution RPC of the client and additional endpoints for unproven data.
+pub struct QuorumRpc<R: ExecutionRpc> {
+    rpcs: Vec<R>,
+    quorum: usize,
+}
+
+impl<R: ExecutionRpc> QuorumRpc<R> {
+    /// The data is queried from the RPC of `execution` and from every RPC of `endpoints`, and
+    /// `quorum` of them, all of them by default, must return the same value.
+    pub fn new(
+        execution: &ExecutionClient<R>,
+        endpoints: &[String],
+        quorum: Option<usize>,
+    ) -> Result<Self> {
+        let mut rpcs = vec![execution.rpc.clone()];
+        for endpoint in endpoints {
+            rpcs.push(R::new(endpoint)?);
+        }
+
+        Ok(Self {
+            quorum: quorum.unwrap_or(rpcs.len()),
+            rpcs,
+        })
+    }
+
+    /// Returns the nonce of `address` in the pending state.
+    pub async fn get_pending_nonce(&self, address: &Address) -> Result<QuorumChecked<u64>> {
+        self.query("the pending nonce", |rpc| {
+            rpc.get_pending_transaction_count(address)
+        })
+        .await
+    }
+
+    /// Returns the gas used by the call `opts` against the pending state.
+    pub async fn estimate_gas(&self, opts: &CallOpts) -> Result<QuorumChecked<u64>> {
+        self.query("the gas estimate", |rpc| rpc.estimate_gas(opts))
+            .await
+    }
+
+    pub async fn max_priority_fee_per_gas(&self) -> Result<QuorumChecked<U256>> {
+        self.query("the priority fee", |rpc| rpc.max_priority_fee_per_gas())
+            .await
+    }
+
+    async fn query<'a, T, F, Fut>(&'a self, data: &str, request: F) -> Result<QuorumChecked<T>>
+    where
+        T: PartialEq,
+        F: Fn(&'a R) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let results = join_all(self.rpcs.iter().map(request)).await;
+        for (endpoint, result) in results.iter().enumerate() {
+            if let Err(err) = result {
+                debug!(target: "helios::execution", endpoint, error = %err, "failed to query {data}");
+            }
+        }
+
+        agreed(data, results, self.quorum)
+    }
+}
+
+/// Returns the value of the `results` of the RPCs that at least `quorum` of them returned.
+///
+/// The other RPCs may fail or return other values, but fails if two values reach the quorum, which
+/// only happens with a quorum of at most half of the RPCs.
+pub fn agreed<T: PartialEq>(
+    data: &str,
+    results: Vec<Result<T>>,
+    quorum: usize,
+) -> Result<QuorumChecked<T>> {
+    let queried = results.len();
+
+    // The distinct values, with the number of RPCs that returned each.
+    let mut values: Vec<(T, usize)> = Vec::new();
+    for value in results.into_iter().flatten() {
+        match values.iter_mut().find(|(other, _)| *other == value) {
+            Some((_, count)) => *count += 1,
+            None => values.push((value, 1)),
+        }
+    }
+
+    let mut agreed = values
+        .into_iter()
+        .filter(|(_, count)| *count >= quorum.max(1));
+    match (agreed.next(), agreed.next()) {
+        (Some((value, agreeing)), None) => Ok(QuorumChecked {
+            value,
+            agreeing,
+            queried,
+        }),
+        (Some(_), Some(_)) => Err(ExecutionError::QuorumDisagreement(data.to_string()).into()),
+        (None, _) => Err(ExecutionError::QuorumNotReached(data.to_string(), quorum).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use eyre::eyre;
+    use tempfile::TempDir;
+    use tokio::sync::{mpsc, watch};
+
+    use super::*;
+    use crate::{rpc::mock_rpc::MockRpc, state::State};
+
+    /// Writes the files of a mock RPC to `dir` that returns `nonce` and the same priority fee as
+    /// the others, and no gas estimate.
+    fn write_rpc(dir: &Path, nonce: u64) {
+        fs::write(dir.join("pending_nonce.json"), nonce.to_string()).unwrap();
+        fs::write(dir.join("priority_fee.json"), "\"0x3b9aca00\"").unwrap();
+    }
+
+    /// Returns a quorum of the mock RPCs at `dirs`, the first of which is the one of the client.
+    fn quorum_rpc(dirs: &[TempDir], quorum: Option<usize>) -> QuorumRpc<MockRpc> {
+        let (_block_send, block_recv) = mpsc::channel(1);
+        let (_finalized_send, finalized_recv) = watch::channel(None);
+        let state = State::new(block_recv, finalized_recv, 64);
+
+        let path = |dir: &TempDir| dir.path().to_str().unwrap().to_string();
+        let execution = ExecutionClient::new(&path(&dirs[0]), state).unwrap();
+        let endpoints = dirs[1..].iter().map(path).collect::<Vec<_>>();
+
+        QuorumRpc::new(&execution, &endpoints, quorum).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_quorum_rpc() {
+        let dirs = [
+            TempDir::new().unwrap(),
+            TempDir::new().unwrap(),
+            TempDir::new().unwrap(),
+        ];
+        write_rpc(dirs[0].path(), 5);
+        write_rpc(dirs[1].path(), 5);
+        write_rpc(dirs[2].path(), 6);
+
+        // All of the RPCs must agree by default.
+        let rpc = quorum_rpc(&dirs, None);
+        assert_eq!(
+            U256::from(1_000_000_000),
+            rpc.max_priority_fee_per_gas().await.unwrap().value
+        );
+        assert!(rpc.get_pending_nonce(&Address::zero()).await.is_err());
+
+        let rpc = quorum_rpc(&dirs, Some(2));
+        assert_eq!(
+            QuorumChecked {
+                value: 5,
+                agreeing: 2,
+                queried: 3,
+            },
+            rpc.get_pending_nonce(&Address::zero()).await.unwrap()
+        );
+
+        // The RPCs that fail do not count towards the quorum.
+        let opts = CallOpts {
+            from: None,
+            to: None,
+            gas: None,
+            gas_price: None,
+            value: None,
+            data: None,
+        };
+        let err = rpc.estimate_gas(&opts).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::QuorumNotReached("the gas estimate".to_string(), 2).to_string()
+        );
+    }
+
+    #[test]
+    fn test_agreed() {
+        let results = || vec![Ok(21_000), Err(eyre!("timeout")), Ok(21_000), Ok(22_000)];
+
+        let checked = agreed("the gas estimate", results(), 2).unwrap();
+        assert_eq!(
+            QuorumChecked {
+                value: 21_000,
+                agreeing: 2,
+                queried: 4,
+            },
+            checked
+        );
+
+        let err = agreed("the gas estimate", results(), 3).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::QuorumNotReached("the gas estimate".to_string(), 3).to_string()
+        );
+
+        let err = agreed("the gas estimate", results(), 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ExecutionError::QuorumDisagreement("the gas estimate".to_string()).to_string()
+        );
+    }
+}
@@ -7,14 +7,14 @@ use ethers::{
     providers::{FilterKind, HttpRateLimitRetryPolicy, Middleware, Provider, RetryClient},
     types::{
         transaction::{eip2718::TypedTransaction, eip2930::AccessList},
-        BlockId, BlockNumber, Bytes, EIP1186ProofResponse, Eip1559TransactionRequest, FeeHistory,
-        Filter, Log, Transaction, TransactionReceipt, H256, U256,
+        Block, BlockId, BlockNumber, Bytes, EIP1186ProofResponse, Eip1559TransactionRequest,
+        FeeHistory, Filter, Log, Transaction, TransactionReceipt, H256, U256,
     },
 };
 use eyre::Result;
 
 use super::ExecutionRpc;
-use crate::types::CallOpts;
+use crate::{errors::ExecutionError, types::CallOpts};
 
 pub struct HttpRpc {
     url: String,
@@ -66,14 +66,10 @@ impl ExecutionRpc for HttpRpc {
             BlockTag::Number(number) => BlockId::Number(BlockNumber::Number(number.into())),
         };
 
-        let mut raw_tx = Eip1559TransactionRequest::new();
-        raw_tx.to = Some(opts.to.unwrap_or_default().into());
-        raw_tx.from = opts.from;
-        raw_tx.value = opts.value;
+        let mut raw_tx = transaction_request(opts);
         raw_tx.gas = Some(opts.gas.unwrap_or(U256::from(100_000_000)));
         raw_tx.max_fee_per_gas = Some(U256::zero());
         raw_tx.max_priority_fee_per_gas = Some(U256::zero());
-        raw_tx.data = opts.data.as_ref().map(|data| data.to_owned());
 
         let tx = TypedTransaction::Eip1559(raw_tx);
         let list = self
@@ -96,6 +92,16 @@ impl ExecutionRpc for HttpRpc {
         Ok(code.to_vec())
     }
 
+    async fn get_block(&self, block: u64) -> Result<Block<H256>> {
+        let rpc_block = self
+            .provider
+            .get_block(block)
+            .await
+            .map_err(|e| RpcError::new("get_block", e))?;
+
+        Ok(rpc_block.ok_or(ExecutionError::BlockNotFoundError(block))?)
+    }
+
     async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<H256> {
         let bytes = Bytes::from(bytes.to_owned());
         let tx = self
@@ -117,6 +123,14 @@ impl ExecutionRpc for HttpRpc {
         Ok(receipt)
     }
 
+    async fn get_block_receipts(&self, block: u64) -> Result<Vec<TransactionReceipt>> {
+        Ok(self
+            .provider
+            .get_block_receipts(block)
+            .await
+            .map_err(|e| RpcError::new("get_block_receipts", e))?)
+    }
+
     async fn get_transaction(&self, tx_hash: &H256) -> Result<Option<Transaction>> {
         Ok(self
             .provider
@@ -195,4 +209,46 @@ impl ExecutionRpc for HttpRpc {
             .await
             .map_err(|e| RpcError::new("fee_history", e))?)
     }
+
+    async fn get_pending_transaction_count(&self, address: &Address) -> Result<u64> {
+        let block = Some(BlockId::Number(BlockNumber::Pending));
+        Ok(self
+            .provider
+            .get_transaction_count(*address, block)
+            .await
+            .map_err(|e| RpcError::new("get_transaction_count", e))?
+            .as_u64())
+    }
+
+    async fn estimate_gas(&self, opts: &CallOpts) -> Result<u64> {
+        let mut raw_tx = transaction_request(opts);
+        raw_tx.gas = opts.gas;
+
+        let tx = TypedTransaction::Eip1559(raw_tx);
+        let block = Some(BlockId::Number(BlockNumber::Pending));
+        Ok(self
+            .provider
+            .estimate_gas(&tx, block)
+            .await
+            .map_err(|e| RpcError::new("estimate_gas", e))?
+            .as_u64())
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> Result<U256> {
+        Ok(self
+            .provider
+            .request("eth_maxPriorityFeePerGas", ())
+            .await
+            .map_err(|e| RpcError::new("max_priority_fee_per_gas", e))?)
+    }
+}
+
+/// Returns the transaction of the call `opts`, without its gas and fees.
+fn transaction_request(opts: &CallOpts) -> Eip1559TransactionRequest {
+    let mut raw_tx = Eip1559TransactionRequest::new();
+    raw_tx.to = Some(opts.to.unwrap_or_default().into());
+    raw_tx.from = opts.from;
+    raw_tx.value = opts.value;
+    raw_tx.data = opts.data.as_ref().map(|data| data.to_owned());
+    raw_tx
 }
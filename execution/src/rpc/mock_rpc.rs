@@ -3,8 +3,8 @@ use std::{fs::read_to_string, path::PathBuf};
 use async_trait::async_trait;
 use common::{types::BlockTag, utils::hex_str_to_bytes};
 use ethers::types::{
-    transaction::eip2930::AccessList, Address, EIP1186ProofResponse, FeeHistory, Filter, Log,
-    Transaction, TransactionReceipt, H256, U256,
+    transaction::eip2930::AccessList, Address, Block, EIP1186ProofResponse, FeeHistory, Filter,
+    Log, Transaction, TransactionReceipt, H256, U256,
 };
 use eyre::{eyre, Result};
 
@@ -43,8 +43,14 @@ impl ExecutionRpc for MockRpc {
         hex_str_to_bytes(&code[0..code.len() - 1])
     }
 
+    async fn get_block(&self, _block: u64) -> Result<Block<H256>> {
+        let block = read_to_string(self.path.join("block.json"))?;
+        Ok(serde_json::from_str(&block)?)
+    }
+
     async fn send_raw_transaction(&self, _bytes: &[u8]) -> Result<H256> {
-        Err(eyre!("not implemented"))
+        let hash = read_to_string(self.path.join("tx_hash.json"))?;
+        Ok(serde_json::from_str(&hash)?)
     }
 
     async fn get_transaction_receipt(&self, _tx_hash: &H256) -> Result<Option<TransactionReceipt>> {
@@ -52,6 +58,11 @@ impl ExecutionRpc for MockRpc {
         Ok(serde_json::from_str(&receipt)?)
     }
 
+    async fn get_block_receipts(&self, _block: u64) -> Result<Vec<TransactionReceipt>> {
+        let receipt = read_to_string(self.path.join("receipt.json"))?;
+        Ok(vec![serde_json::from_str(&receipt)?])
+    }
+
     async fn get_transaction(&self, _tx_hash: &H256) -> Result<Option<Transaction>> {
         let tx = read_to_string(self.path.join("transaction.json"))?;
         Ok(serde_json::from_str(&tx)?)
@@ -96,4 +107,19 @@ impl ExecutionRpc for MockRpc {
         let fee_history = read_to_string(self.path.join("fee_history.json"))?;
         Ok(serde_json::from_str(&fee_history)?)
     }
+
+    async fn get_pending_transaction_count(&self, _address: &Address) -> Result<u64> {
+        let nonce = read_to_string(self.path.join("pending_nonce.json"))?;
+        Ok(serde_json::from_str(&nonce)?)
+    }
+
+    async fn estimate_gas(&self, _opts: &CallOpts) -> Result<u64> {
+        let gas = read_to_string(self.path.join("gas_estimate.json"))?;
+        Ok(serde_json::from_str(&gas)?)
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> Result<U256> {
+        let fee = read_to_string(self.path.join("priority_fee.json"))?;
+        Ok(serde_json::from_str(&fee)?)
+    }
 }
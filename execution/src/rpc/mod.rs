@@ -1,8 +1,8 @@
 use async_trait::async_trait;
 use common::types::BlockTag;
 use ethers::types::{
-    transaction::eip2930::AccessList, Address, EIP1186ProofResponse, FeeHistory, Filter, Log,
-    Transaction, TransactionReceipt, H256, U256,
+    transaction::eip2930::AccessList, Address, Block, EIP1186ProofResponse, FeeHistory, Filter,
+    Log, Transaction, TransactionReceipt, H256, U256,
 };
 use eyre::Result;
 
@@ -27,8 +27,10 @@ pub trait ExecutionRpc: Send + Clone + Sync + 'static {
 
     async fn create_access_list(&self, opts: &CallOpts, block: BlockTag) -> Result<AccessList>;
     async fn get_code(&self, address: &Address, block: u64) -> Result<Vec<u8>>;
+    async fn get_block(&self, block: u64) -> Result<Block<H256>>;
     async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<H256>;
     async fn get_transaction_receipt(&self, tx_hash: &H256) -> Result<Option<TransactionReceipt>>;
+    async fn get_block_receipts(&self, block: u64) -> Result<Vec<TransactionReceipt>>;
     async fn get_transaction(&self, tx_hash: &H256) -> Result<Option<Transaction>>;
     async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>>;
     async fn get_filter_changes(&self, filter_id: &U256) -> Result<Vec<Log>>;
@@ -43,4 +45,10 @@ pub trait ExecutionRpc: Send + Clone + Sync + 'static {
         last_block: u64,
         reward_percentiles: &[f64],
     ) -> Result<FeeHistory>;
+
+    /// The nonce of `address` in the pending state, which counts the transactions of the mempool.
+    async fn get_pending_transaction_count(&self, address: &Address) -> Result<u64>;
+    /// The gas used by the call `opts` against the pending state, as estimated by the node.
+    async fn estimate_gas(&self, opts: &CallOpts) -> Result<u64>;
+    async fn max_priority_fee_per_gas(&self) -> Result<U256>;
 }
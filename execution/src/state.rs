@@ -29,20 +29,25 @@ impl State {
         #[cfg(target_arch = "wasm32")]
         let run = wasm_bindgen_futures::spawn_local;
 
+        // The task stops once the consensus client is stopped, which closes both channels.
         run(async move {
             loop {
                 select! {
                     block = block_recv.recv() => {
-                        if let Some(block) = block {
-                            inner_ref.write().await.push_block(block);
-                        }
+                        let Some(block) = block else {
+                            break;
+                        };
+                        inner_ref.write().await.push_block(block);
                     },
-                    _ = finalized_block_recv.changed() => {
+                    changed = finalized_block_recv.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+
                         let block = finalized_block_recv.borrow_and_update().clone();
                         if let Some(block) = block {
                             inner_ref.write().await.push_finalized_block(block);
                         }
-
                     }
                 }
             }
@@ -100,6 +105,16 @@ impl State {
             .cloned()
     }
 
+    /// Returns the block that includes the transaction `hash`.
+    pub async fn get_transaction_block(&self, hash: H256) -> Option<Block> {
+        let inner = self.inner.read().await;
+        inner
+            .txs
+            .get(&hash)
+            .and_then(|loc| inner.blocks.get(&loc.block))
+            .cloned()
+    }
+
     pub async fn get_transaction_by_block_and_index(
         &self,
         block_hash: H256,
@@ -1,9 +1,23 @@
 use std::{collections::HashMap, fmt};
 
+use consensus::types::encoding::{
+    decode_versioned, encode_versioned, encode_versioned_with, Compression,
+};
 pub use ethers::types::Address;
-use ethers::types::{Bytes, H256, U256};
+use ethers::{
+    types::{Bytes, EIP1186ProofResponse, TransactionReceipt, H256, U256},
+    utils::{keccak256, rlp::Rlp},
+};
+use eyre::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    beacon_roots::{root_slot, timestamp_slot, BEACON_ROOTS_ADDRESS},
+    errors::ExecutionError,
+    execution::encode_receipt,
+    proofs::{mpt::verify_inclusion, trie::index_path, verify_account_proof},
+};
+
 #[derive(Default, Debug, Clone)]
 pub struct Account {
     pub balance: U256,
@@ -37,3 +51,351 @@ impl fmt::Debug for CallOpts {
             .finish()
     }
 }
+
+/// A verified ERC-20 balance, together with the proof it was verified with, so that it can be
+/// forwarded and verified again by dWallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc20Balance {
+    pub token: Address,
+    pub holder: Address,
+    pub balance: U256,
+    /// The storage slot of the balance of `holder` in the `token` contract.
+    pub slot: H256,
+    pub block_number: u64,
+    /// The state root the proof was verified against.
+    pub state_root: H256,
+    pub proof: EIP1186ProofResponse,
+}
+
+/// An account proof, with the block it was verified against, so that it can be verified again
+/// offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofBundle {
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub state_root: H256,
+    pub address: Address,
+    pub slots: Vec<H256>,
+    pub proof: EIP1186ProofResponse,
+}
+
+impl ProofBundle {
+    /// Verifies the proof against the state root of the bundle. The state root itself must be
+    /// checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        verify_account_proof(&self.address, &self.slots, &self.proof, &self.state_root)
+    }
+
+    /// Returns the proven value of `slot`, if the bundle has a proof for it.
+    pub fn storage_value(&self, slot: &H256) -> Option<U256> {
+        let key = U256::from(slot.as_bytes());
+        self.proof
+            .storage_proof
+            .iter()
+            .find(|storage_proof| storage_proof.key == key)
+            .map(|storage_proof| storage_proof.value)
+    }
+
+    /// Serializes the bundle with the versioned BCS encoding, so that it can be passed through
+    /// dWallet transactions.
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    /// Same as [`ProofBundle::serialize_to_bytes`], but compressed with `compression`.
+    pub fn serialize_to_bytes_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        encode_versioned_with(self, compression)
+    }
+
+    /// Deserializes a bundle that was serialized with [`ProofBundle::serialize_to_bytes`], or
+    /// with [`ProofBundle::serialize_to_bytes_compressed`].
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// The code of an account, with the proof of the account against the state root of a block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeProof {
+    pub code: Bytes,
+    pub proof: ProofBundle,
+}
+
+impl CodeProof {
+    /// Verifies the proof, and that the code hashes to the proven code hash. The state root of the
+    /// proof must be checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        self.proof.verify()?;
+
+        let code_hash = H256::from(keccak256(&self.code));
+        if code_hash != self.proof.proof.code_hash {
+            return Err(ExecutionError::CodeHashMismatch(
+                self.proof.address,
+                code_hash.to_string(),
+                self.proof.proof.code_hash.to_string(),
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Whether the account is a contract, i.e. has code.
+    pub fn is_contract(&self) -> bool {
+        !self.code.is_empty()
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// The nonce of an account, with its proof against the state root of the finalized block, to
+/// build a transaction from a proven nonce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenNonce {
+    pub nonce: u64,
+    pub proof: ProofBundle,
+}
+
+impl ProvenNonce {
+    /// Verifies the proof, and that it proves `nonce`. The state root of the proof must be
+    /// checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        self.proof.verify()?;
+        if self.proof.proof.nonce != self.nonce.into() {
+            return Err(ExecutionError::InvalidAccountProof(self.proof.address).into());
+        }
+
+        Ok(())
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// The value of a storage slot at the finalized block, with its proof against the state root of
+/// the block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenStorage {
+    pub slot: H256,
+    pub value: U256,
+    pub proof: ProofBundle,
+}
+
+impl ProvenStorage {
+    /// Verifies the proof, and that it proves `value` for `slot`. The state root of the proof must
+    /// be checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        self.proof.verify()?;
+
+        let key = U256::from(self.slot.as_bytes());
+        if self.proof.storage_value(&self.slot) != Some(self.value) {
+            return Err(ExecutionError::InvalidStorageProof(self.proof.address, key).into());
+        }
+
+        Ok(())
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// The proofs of several accounts, verified against the state root of the same block. The block
+/// is only described once, so that the bundle fits in a single dWallet transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProofs {
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub state_root: H256,
+    /// The proofs, in the order of the requested addresses.
+    pub proofs: Vec<EIP1186ProofResponse>,
+}
+
+impl AccountProofs {
+    /// Verifies every proof against the state root of the bundle. The state root itself must be
+    /// checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        self.proofs.iter().try_for_each(|proof| {
+            verify_account_proof(&proof.address, &[], proof, &self.state_root)
+        })
+    }
+
+    /// Serializes the proofs with the versioned BCS encoding.
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    /// Same as [`AccountProofs::serialize_to_bytes`], but compressed with `compression`.
+    pub fn serialize_to_bytes_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        encode_versioned_with(self, compression)
+    }
+
+    /// Deserializes proofs that were serialized with [`AccountProofs::serialize_to_bytes`], or
+    /// with [`AccountProofs::serialize_to_bytes_compressed`].
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// The nonces of several accounts at the finalized block, with their proofs against the state root
+/// of the block, to track the transaction counts of many addresses at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionCounts {
+    /// The nonces, in the order of the proofs.
+    pub nonces: Vec<u64>,
+    pub proofs: AccountProofs,
+}
+
+impl TransactionCounts {
+    /// Verifies the proofs, and that they prove `nonces`. The state root of the proofs must be
+    /// checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        self.proofs.verify()?;
+        for (index, proof) in self.proofs.proofs.iter().enumerate() {
+            if self.nonces.get(index).map(|nonce| U256::from(*nonce)) != Some(proof.nonce) {
+                return Err(ExecutionError::InvalidAccountProof(proof.address).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the nonce of `address`, if it is in the proofs.
+    pub fn nonce(&self, address: &Address) -> Option<u64> {
+        self.proofs
+            .proofs
+            .iter()
+            .position(|proof| proof.address == *address)
+            .and_then(|index| self.nonces.get(index).copied())
+    }
+
+    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode_versioned(bytes)
+    }
+}
+
+/// The proof that the EIP-4788 beacon roots contract stores `parent_beacon_block_root` for the
+/// block with `timestamp`, so that contracts reading the root can be bridged with the verified
+/// consensus state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeaconRootProof {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub state_root: H256,
+    pub parent_beacon_block_root: H256,
+    /// The proof of the timestamp and root slots of the beacon roots contract.
+    pub proof: EIP1186ProofResponse,
+}
+
+impl BeaconRootProof {
+    /// Verifies the proof against the state root, and that the proven slots hold the timestamp
+    /// and the parent beacon block root. The state root itself must be checked against a trusted
+    /// source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        let slots = [timestamp_slot(self.timestamp), root_slot(self.timestamp)];
+        verify_account_proof(&self.proof.address, &slots, &self.proof, &self.state_root)?;
+
+        self.verify_values()
+    }
+
+    /// Checks the values of the proven slots, without verifying the proof itself.
+    pub(crate) fn verify_values(&self) -> Result<()> {
+        let value = |slot: H256| {
+            let key = U256::from(slot.as_bytes());
+            self.proof
+                .storage_proof
+                .iter()
+                .find(|storage_proof| storage_proof.key == key)
+                .map(|storage_proof| storage_proof.value)
+                .ok_or(ExecutionError::MissingStorageProof(self.proof.address, key))
+        };
+
+        let is_valid = self.proof.address == BEACON_ROOTS_ADDRESS.parse::<Address>()?
+            && value(timestamp_slot(self.timestamp))? == U256::from(self.timestamp)
+            && value(root_slot(self.timestamp))?
+                == U256::from(self.parent_beacon_block_root.as_bytes());
+
+        if !is_valid {
+            return Err(ExecutionError::BeaconRootMismatch(self.block_number).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// The receipts of every transaction of a block, with the RLP encoded header of the block and the
+/// proof of each receipt against the receipts root of the header, so that whole blocks are
+/// indexed without trusting the execution RPC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReceiptsProof {
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub header: Bytes,
+    /// The receipts, in the order of the transactions of the block.
+    pub receipts: Vec<TransactionReceipt>,
+    /// The proofs of the receipts, in the same order.
+    pub receipt_proofs: Vec<Vec<Bytes>>,
+}
+
+impl BlockReceiptsProof {
+    /// Verifies that the header hashes to `block_hash`, that the proof of every receipt holds
+    /// against its receipts root, and that no receipt is missing: the last one used all the gas of
+    /// the block. The block hash itself must be checked against a trusted source by the caller.
+    pub fn verify(&self) -> Result<()> {
+        if H256::from(keccak256(&self.header)) != self.block_hash {
+            let field = "header hash".to_string();
+            return Err(ExecutionError::BlockMismatch(self.block_number, field).into());
+        }
+
+        let header = Rlp::new(&self.header);
+        let receipts_root: H256 = header.val_at(5)?;
+        let gas_used: U256 = header.val_at(10)?;
+        let cumulative_gas_used = self
+            .receipts
+            .last()
+            .map(|receipt| receipt.cumulative_gas_used)
+            .unwrap_or_default();
+        if cumulative_gas_used != gas_used || self.receipts.len() != self.receipt_proofs.len() {
+            return Err(ExecutionError::BlockReceiptsRootMismatch(self.block_number).into());
+        }
+
+        let proofs = self.receipts.iter().zip(&self.receipt_proofs);
+        for (index, (receipt, proof)) in proofs.enumerate() {
+            let path = index_path(index);
+            if !verify_inclusion(&receipts_root, &path, &encode_receipt(receipt)?, proof) {
+                let tx = format!("{:?}", receipt.transaction_hash);
+                return Err(ExecutionError::ReceiptRootMismatch(tx).into());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::HashMap, sync::Arc};
+
+use common::types::Block;
+use ethers::types::{Address, EIP1186ProofResponse, H256, U256};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    select,
+    sync::{mpsc, watch},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::{rpc::ExecutionRpc, ExecutionClient};
+
+/// Capacity of the channel returned by [`Watcher::spawn`].
+pub const WATCHER_CHANNEL_CAPACITY: usize = 64;
+
+/// An account, and the storage slots of it, tracked by a [`Watcher`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchTarget {
+    pub address: Address,
+    #[serde(default)]
+    pub slots: Vec<H256>,
+}
+
+/// A change of a watched value between two finalized blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEventKind {
+    BalanceChanged { old: U256, new: U256 },
+    NonceChanged { old: U256, new: U256 },
+    SlotChanged { slot: H256, old: U256, new: U256 },
+}
+
+/// A change of a watched account, with the proof of the new value. The proof was verified against
+/// the state root of the finalized block `block_number`.
+#[derive(Debug, Clone)]
+pub struct WatchEvent {
+    pub address: Address,
+    pub block_number: u64,
+    pub kind: WatchEventKind,
+    pub proof: Arc<EIP1186ProofResponse>,
+}
+
+#[derive(Debug, Clone)]
+struct WatchedValues {
+    balance: U256,
+    nonce: U256,
+    slots: HashMap<U256, U256>,
+}
+
+impl From<&EIP1186ProofResponse> for WatchedValues {
+    fn from(proof: &EIP1186ProofResponse) -> Self {
+        Self {
+            balance: proof.balance,
+            nonce: proof.nonce.as_u64().into(),
+            slots: proof
+                .storage_proof
+                .iter()
+                .map(|storage_proof| (storage_proof.key, storage_proof.value))
+                .collect(),
+        }
+    }
+}
+
+/// Tracks a set of accounts and storage slots, and proves them again at every finalized block.
+///
+/// The first block a target is proven at only records its values, changes are reported from the
+/// following blocks on.
+pub struct Watcher<R: ExecutionRpc> {
+    execution: Arc<ExecutionClient<R>>,
+    targets: Vec<WatchTarget>,
+    values: HashMap<Address, WatchedValues>,
+    last_block: Option<u64>,
+}
+
+impl<R: ExecutionRpc> Watcher<R> {
+    pub fn new(execution: Arc<ExecutionClient<R>>, targets: Vec<WatchTarget>) -> Self {
+        Self {
+            execution,
+            targets,
+            values: HashMap::new(),
+            last_block: None,
+        }
+    }
+
+    pub fn targets(&self) -> &[WatchTarget] {
+        &self.targets
+    }
+
+    /// Proves every target at `block`, which must come from a verified source, and returns the
+    /// changes since the previous block. Blocks that are not newer than the last checked block
+    /// are ignored.
+    pub async fn check_block(&mut self, block: &Block) -> Result<Vec<WatchEvent>> {
+        let block_number = block.number.as_u64();
+        if self.last_block.is_some_and(|last| block_number <= last) {
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        let mut values = HashMap::new();
+
+        for target in self.targets.iter() {
+            let proof = self
+                .execution
+                .get_verified_proof(&target.address, &target.slots, block)
+                .await?;
+            let new = WatchedValues::from(&proof);
+
+            if let Some(old) = self.values.get(&target.address) {
+                let proof = Arc::new(proof);
+                let event = |kind| WatchEvent {
+                    address: target.address,
+                    block_number,
+                    kind,
+                    proof: proof.clone(),
+                };
+
+                if old.balance != new.balance {
+                    events.push(event(WatchEventKind::BalanceChanged {
+                        old: old.balance,
+                        new: new.balance,
+                    }));
+                }
+
+                if old.nonce != new.nonce {
+                    events.push(event(WatchEventKind::NonceChanged {
+                        old: old.nonce,
+                        new: new.nonce,
+                    }));
+                }
+
+                for slot in target.slots.iter() {
+                    let key = U256::from(slot.as_bytes());
+                    let old_value = old.slots.get(&key).copied().unwrap_or_default();
+                    let new_value = new.slots.get(&key).copied().unwrap_or_default();
+
+                    if old_value != new_value {
+                        events.push(event(WatchEventKind::SlotChanged {
+                            slot: *slot,
+                            old: old_value,
+                            new: new_value,
+                        }));
+                    }
+                }
+            }
+
+            values.insert(target.address, new);
+        }
+
+        // Only commit the new values once every target was proven, so that a failed block is
+        // compared against the last complete one.
+        self.values = values;
+        self.last_block = Some(block_number);
+
+        Ok(events)
+    }
+
+    /// Checks every new finalized block received on `finalized_block_recv`, and sends the
+    /// changes to the returned channel. The watcher stops once the channel is dropped, or once
+    /// the sender of the finalized blocks is.
+    pub fn spawn(
+        self,
+        finalized_block_recv: watch::Receiver<Option<Block>>,
+    ) -> mpsc::Receiver<WatchEvent> {
+        self.spawn_with_cancellation(finalized_block_recv, CancellationToken::new())
+    }
+
+    /// Same as [`Self::spawn`], but the watcher also stops once `cancel` is cancelled. A block
+    /// that is being checked is not reported.
+    pub fn spawn_with_cancellation(
+        mut self,
+        mut finalized_block_recv: watch::Receiver<Option<Block>>,
+        cancel: CancellationToken,
+    ) -> mpsc::Receiver<WatchEvent> {
+        let (sender, receiver) = mpsc::channel(WATCHER_CHANNEL_CAPACITY);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let run = tokio::spawn;
+        #[cfg(target_arch = "wasm32")]
+        let run = wasm_bindgen_futures::spawn_local;
+
+        run(async move {
+            loop {
+                let changed = select! {
+                    changed = finalized_block_recv.changed() => changed,
+                    _ = cancel.cancelled() => return,
+                };
+                if changed.is_err() {
+                    return;
+                }
+
+                let block = finalized_block_recv.borrow_and_update().clone();
+                let Some(block) = block else {
+                    continue;
+                };
+
+                let checked = select! {
+                    checked = self.check_block(&block) => checked,
+                    _ = cancel.cancelled() => return,
+                };
+                let events = match checked {
+                    Ok(events) => events,
+                    Err(err) => {
+                        warn!(
+                            target: "helios::execution",
+                            error = %err,
+                            block = block.number.as_u64(),
+                            "failed to prove watched accounts"
+                        );
+                        continue;
+                    }
+                };
+
+                for event in events {
+                    if sender.send(event).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tokio::sync::{mpsc::channel, watch};
+
+    use super::*;
+    use crate::{rpc::mock_rpc::MockRpc, state::State};
+
+    fn get_watcher(address: Address) -> Watcher<MockRpc> {
+        let (_, block_recv) = channel(256);
+        let (_, finalized_recv) = watch::channel(None);
+        let state = State::new(block_recv, finalized_recv, 64);
+        let execution = ExecutionClient::new("testdata/", state).unwrap();
+
+        Watcher::new(
+            Arc::new(execution),
+            vec![WatchTarget {
+                address,
+                slots: Vec::new(),
+            }],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_watch_events() {
+        let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+        let mut watcher = get_watcher(address);
+        let block = Block {
+            number: 2.into(),
+            state_root: H256::from_str(
+                "0xaa02f5db2ee75e3da400d10f3c30e894b6016ce8a2501680380a907b6674ce0d",
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+
+        // The values proven at the previous block, before the account was funded and used.
+        watcher.values.insert(
+            address,
+            WatchedValues {
+                balance: U256::zero(),
+                nonce: U256::zero(),
+                slots: HashMap::new(),
+            },
+        );
+        watcher.last_block = Some(1);
+
+        let events = watcher.check_block(&block).await.unwrap();
+        let kinds = events
+            .iter()
+            .map(|event| event.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                WatchEventKind::BalanceChanged {
+                    old: U256::zero(),
+                    new: U256::from_str_radix("48c27395000", 16).unwrap(),
+                },
+                WatchEventKind::NonceChanged {
+                    old: U256::zero(),
+                    new: U256::one(),
+                },
+            ],
+            kinds
+        );
+        assert!(events
+            .iter()
+            .all(|event| event.address == address && event.block_number == 2));
+
+        // The new values are the base of the next block.
+        let mut block = block;
+        block.number = 3.into();
+        assert!(watcher.check_block(&block).await.unwrap().is_empty());
+    }
+}
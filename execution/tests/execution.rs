@@ -1,14 +1,29 @@
 #![allow(clippy::needless_return)]
 
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 use common::types::{Block, BlockTag, Transactions};
 use ethers::{
-    types::{Address, Filter, Transaction, H256, U256},
-    utils::rlp::{Decodable, Rlp},
+    types::{Address, Block as RpcBlock, Filter, Transaction, TransactionReceipt, H256, U256},
+    utils::{
+        keccak256,
+        rlp::{Decodable, Rlp},
+    },
+};
+use execution::{
+    block::encode_header,
+    compact::CompactProof,
+    errors::ExecutionError,
+    rpc::mock_rpc::MockRpc,
+    state::State,
+    types::{BlockReceiptsProof, CodeProof, ProofBundle, ProvenNonce, TransactionCounts},
+    watcher::{WatchTarget, Watcher},
+    ExecutionClient,
+};
+use tokio::sync::{
+    mpsc::{channel, Sender},
+    watch,
 };
-use execution::{rpc::mock_rpc::MockRpc, state::State, ExecutionClient};
-use tokio::sync::{mpsc::channel, watch};
 
 fn create_state() -> State {
     let (_, block_recv) = channel(256);
@@ -20,18 +35,53 @@ fn create_client(state: State) -> ExecutionClient<MockRpc> {
     ExecutionClient::new("testdata/", state).unwrap()
 }
 
-#[tokio::test]
-async fn test_get_account() {
-    let state = create_state();
-
-    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
-    let block = Block {
+/// Returns a block with the state root that the account proof of the mock RPC is verified against.
+fn verified_block(number: u64) -> Block {
+    Block {
+        number: number.into(),
         state_root: H256::from_str(
             "0xaa02f5db2ee75e3da400d10f3c30e894b6016ce8a2501680380a907b6674ce0d",
         )
         .unwrap(),
         ..Default::default()
-    };
+    }
+}
+
+/// A state whose finalized block is set by the test, see [`VerifiedState::finalize`].
+struct VerifiedState {
+    state: State,
+    // The state stops tracking blocks once a sender is dropped.
+    _block_send: Sender<Block>,
+    finalized_send: watch::Sender<Option<Block>>,
+}
+
+impl VerifiedState {
+    /// Finalizes `block`, and waits for the state to track it.
+    async fn finalize(&self, block: Block) {
+        self.finalized_send.send(Some(block)).unwrap();
+        while self.state.get_block(BlockTag::Finalized).await.is_none() {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+/// Returns a state without any finalized block yet.
+fn verified_state() -> VerifiedState {
+    let (block_send, block_recv) = channel(256);
+    let (finalized_send, finalized_recv) = watch::channel(None);
+    VerifiedState {
+        state: State::new(block_recv, finalized_recv, 64),
+        _block_send: block_send,
+        finalized_send,
+    }
+}
+
+#[tokio::test]
+async fn test_get_account() {
+    let state = create_state();
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
 
     state.push_block(block).await;
     let execution = create_client(state);
@@ -47,6 +97,264 @@ async fn test_get_account() {
     );
 }
 
+#[tokio::test]
+async fn test_get_account_at_block() {
+    // The block is not tracked by the state, only its state root is used.
+    let execution = create_client(create_state());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
+
+    let account = execution
+        .get_account_at_block(&address, None, &block)
+        .await
+        .unwrap();
+    assert_eq!(
+        account.balance,
+        U256::from_str_radix("48c27395000", 16).unwrap()
+    );
+
+    let account_res = execution
+        .get_account_at_block(&address, None, &Block::default())
+        .await;
+    assert!(account_res.is_err());
+}
+
+#[tokio::test]
+async fn test_get_erc20_balance_missing_storage_proof() {
+    let state = create_state();
+
+    let token = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let holder = Address::from_str("326c977e6efc84e512bb9c30f76e30c160ed06fb").unwrap();
+    let block = verified_block(0);
+
+    state.push_block(block.clone()).await;
+    let execution = create_client(state);
+
+    // The account proof is valid, but the RPC does not return the proof of the balance slot.
+    let balance_res = execution
+        .get_erc20_balance_at_block(&token, &holder, 0, &block)
+        .await;
+    assert!(balance_res.is_err());
+
+    // No block is finalized yet.
+    let balance_res = execution.get_erc20_balance(&token, &holder, 0).await;
+    assert!(balance_res.is_err());
+}
+
+#[tokio::test]
+async fn test_watcher() {
+    let execution = Arc::new(create_client(create_state()));
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let mut block = verified_block(1);
+
+    let mut watcher = Watcher::new(
+        execution,
+        vec![WatchTarget {
+            address,
+            slots: Vec::new(),
+        }],
+    );
+
+    // The first block only records the watched values.
+    let events = watcher.check_block(&block).await.unwrap();
+    assert!(events.is_empty());
+
+    block.number = 2.into();
+    let events = watcher.check_block(&block).await.unwrap();
+    assert!(events.is_empty());
+
+    // The proofs are verified against the state root of every block.
+    block.number = 3.into();
+    block.state_root = H256::default();
+    assert!(watcher.check_block(&block).await.is_err());
+}
+
+#[tokio::test]
+async fn test_proof_bundle_bcs_roundtrip() {
+    let execution = create_client(create_state());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
+
+    let bundle = execution
+        .get_proof_bundle(&address, &[], &block)
+        .await
+        .unwrap();
+    let bytes = bundle.serialize_to_bytes().unwrap();
+    let decoded = ProofBundle::deserialize_from_bytes(&bytes).unwrap();
+
+    assert_eq!(decoded.address, address);
+    assert_eq!(decoded.block_hash, block.hash);
+    assert_eq!(decoded.state_root, block.state_root);
+    assert_eq!(decoded.proof.balance, bundle.proof.balance);
+    decoded.verify().unwrap();
+
+    let mut tampered = decoded.clone();
+    tampered.proof.balance += U256::one();
+    assert!(tampered.verify().is_err());
+
+    let mut tampered = decoded;
+    tampered.state_root = H256::zero();
+    assert!(tampered.verify().is_err());
+}
+
+#[tokio::test]
+async fn test_get_code_proof() {
+    let execution = create_client(create_state());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
+
+    let proof = execution.get_code_proof(&address, &block).await.unwrap();
+    proof.verify().unwrap();
+
+    let bytes = proof.serialize_to_bytes().unwrap();
+    let mut decoded = CodeProof::deserialize_from_bytes(&bytes).unwrap();
+    decoded.verify().unwrap();
+    assert_eq!(proof.code, decoded.code);
+
+    decoded.code = vec![0xfe].into();
+    assert!(decoded.verify().is_err());
+}
+
+#[tokio::test]
+async fn test_get_proven_nonce() {
+    let verified = verified_state();
+    let execution = create_client(verified.state.clone());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    assert!(execution.get_proven_nonce(&address).await.is_err());
+
+    verified.finalize(verified_block(100)).await;
+
+    let nonce = execution.get_proven_nonce(&address).await.unwrap();
+    assert_eq!(100, nonce.proof.block_number);
+    assert_eq!(nonce.proof.proof.nonce.as_u64(), nonce.nonce);
+
+    let bytes = nonce.serialize_to_bytes().unwrap();
+    let mut decoded = ProvenNonce::deserialize_from_bytes(&bytes).unwrap();
+    decoded.verify().unwrap();
+
+    decoded.nonce += 1;
+    assert!(decoded.verify().is_err());
+}
+
+#[tokio::test]
+async fn test_get_proven_storage() {
+    let verified = verified_state();
+    let execution = create_client(verified.state.clone());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let slot = H256::zero();
+    // No block is finalized yet.
+    assert!(execution.get_proven_storage(&address, slot).await.is_err());
+
+    verified.finalize(verified_block(100)).await;
+
+    // The account proof is valid, but the mock RPC does not return the proof of the slot.
+    let err = execution
+        .get_proven_storage(&address, slot)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ExecutionError::MissingStorageProof(address, U256::zero()).to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_create_account_proofs() {
+    let execution = create_client(create_state());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
+
+    let proofs = execution
+        .create_account_proofs_at_block(&[address, address], &block)
+        .await
+        .unwrap();
+    assert_eq!(proofs.proofs.len(), 2);
+    assert_eq!(proofs.state_root, block.state_root);
+    proofs.verify().unwrap();
+
+    // A proof that does not match the state root fails the whole batch.
+    let proofs_res = execution
+        .create_account_proofs_at_block(&[address], &Block::default())
+        .await;
+    assert!(proofs_res.is_err());
+}
+
+#[tokio::test]
+async fn test_get_transaction_counts() {
+    let execution = create_client(create_state());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
+
+    // No block is finalized yet.
+    assert!(execution.get_transaction_counts(&[address]).await.is_err());
+
+    let counts = execution
+        .get_transaction_counts_at_block(&[address], &block)
+        .await
+        .unwrap();
+    assert_eq!(1, counts.nonces.len());
+    assert_eq!(Some(counts.nonces[0]), counts.nonce(&address));
+    assert_eq!(None, counts.nonce(&Address::zero()));
+
+    let bytes = counts.serialize_to_bytes().unwrap();
+    let mut decoded = TransactionCounts::deserialize_from_bytes(&bytes).unwrap();
+    decoded.verify().unwrap();
+
+    decoded.nonces[0] += 1;
+    assert!(decoded.verify().is_err());
+    decoded.nonces.clear();
+    assert!(decoded.verify().is_err());
+}
+
+#[tokio::test]
+async fn test_get_beacon_root_proof() {
+    let execution = create_client(create_state());
+
+    // Blocks before Deneb do not have a parent beacon block root.
+    let block = Block::default();
+    let proof_res = execution.get_beacon_root_proof_at_block(&block).await;
+    assert!(proof_res.is_err());
+
+    // The mock proof does not include the slots of the beacon roots contract.
+    let block = Block {
+        parent_beacon_block_root: Some(H256::repeat_byte(1)),
+        ..Default::default()
+    };
+    let proof_res = execution.get_beacon_root_proof_at_block(&block).await;
+    assert!(proof_res.is_err());
+}
+
+#[tokio::test]
+async fn test_compact_proof() {
+    let execution = create_client(create_state());
+
+    let address = Address::from_str("14f9D4aF749609c1438528C0Cce1cC3f6D411c47").unwrap();
+    let block = verified_block(0);
+
+    let proofs = execution
+        .create_account_proofs_at_block(&[address, address], &block)
+        .await
+        .unwrap();
+    let compact = CompactProof::from(&proofs);
+    assert_eq!(compact.nodes.len(), proofs.proofs[0].account_proof.len());
+
+    let decoded = CompactProof::decode(&compact.encode()).unwrap();
+    assert_eq!(decoded.to_proofs().unwrap(), proofs.proofs);
+    decoded.verify().unwrap();
+
+    let mut tampered = decoded;
+    tampered.accounts[0].balance += U256::one();
+    assert!(tampered.verify().is_err());
+}
+
 #[tokio::test]
 async fn test_get_account_bad_proof() {
     let state = create_state();
@@ -98,6 +406,131 @@ async fn test_get_tx_not_included() {
     assert!(tx_res.is_none());
 }
 
+#[tokio::test]
+async fn test_get_verified_tx_not_finalized() {
+    let verified = verified_state();
+
+    let tx = Transaction::decode(&Rlp::new(&hex::decode("02f8b20583623355849502f900849502f91082ea6094326c977e6efc84e512bb9c30f76e30c160ed06fb80b844a9059cbb0000000000000000000000007daccf9b3c1ae2fa5c55f1c978aeef700bc83be0000000000000000000000000000000000000000000000001158e460913d00000c080a0e1445466b058b6f883c0222f1b1f3e2ad9bee7b5f688813d86e3fa8f93aa868ca0786d6e7f3aefa8fe73857c65c32e4884d8ba38d0ecfb947fbffb82e8ee80c167").unwrap())).unwrap();
+    let hash = tx.hash();
+
+    verified
+        .finalize(Block {
+            number: 99.into(),
+            ..Default::default()
+        })
+        .await;
+    verified
+        .state
+        .push_block(Block {
+            number: 100.into(),
+            transactions: Transactions::Full(vec![tx]),
+            ..Default::default()
+        })
+        .await;
+
+    let execution = create_client(verified.state);
+    // The transaction is only in the optimistic block.
+    let err = execution.get_verified_transaction(hash).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ExecutionError::TransactionNotFinalized(hash).to_string()
+    );
+
+    let unknown = H256::repeat_byte(1);
+    assert!(execution.get_verified_transaction(unknown).await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_verified_block_receipts_not_finalized() {
+    let verified = verified_state();
+
+    verified
+        .finalize(Block {
+            number: 99.into(),
+            ..Default::default()
+        })
+        .await;
+    verified
+        .state
+        .push_block(Block {
+            number: 100.into(),
+            ..Default::default()
+        })
+        .await;
+
+    let execution = create_client(verified.state);
+    let err = execution
+        .get_verified_block_receipts(BlockTag::Number(100))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ExecutionError::BlockNotFinalized(100).to_string()
+    );
+}
+
+#[test]
+fn test_verify_empty_block_receipts() {
+    let empty_root = H256::from(keccak256([0x80]));
+    let proof = |gas_used: u64| {
+        let header = encode_header(&RpcBlock {
+            number: Some(100.into()),
+            receipts_root: empty_root,
+            gas_used: gas_used.into(),
+            ..Default::default()
+        });
+        BlockReceiptsProof {
+            block_number: 100,
+            block_hash: H256::from(keccak256(&header)),
+            header: header.into(),
+            receipts: Vec::new(),
+            receipt_proofs: Vec::new(),
+        }
+    };
+
+    proof(0).verify().unwrap();
+
+    // A block that used gas has receipts.
+    let err = proof(21_000).verify().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ExecutionError::BlockReceiptsRootMismatch(100).to_string()
+    );
+
+    let mut tampered = proof(0);
+    tampered.block_hash = H256::repeat_byte(1);
+    assert!(tampered.verify().is_err());
+}
+
+#[test]
+fn test_verify_block_receipts_without_status() {
+    let header = encode_header(&RpcBlock {
+        number: Some(100.into()),
+        gas_used: 21_000.into(),
+        ..Default::default()
+    });
+    // The receipts from before Byzantium have no status, and cannot be encoded.
+    let receipt = TransactionReceipt {
+        transaction_hash: H256::repeat_byte(1),
+        cumulative_gas_used: 21_000.into(),
+        transaction_type: Some(0.into()),
+        ..Default::default()
+    };
+    let proof = BlockReceiptsProof {
+        block_number: 100,
+        block_hash: H256::from(keccak256(&header)),
+        header: header.into(),
+        receipts: vec![receipt],
+        receipt_proofs: vec![Vec::new()],
+    };
+
+    let err = proof.verify().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ExecutionError::ReceiptRootMismatch(format!("{:?}", H256::repeat_byte(1))).to_string()
+    );
+}
+
 #[tokio::test]
 async fn test_get_logs() {
     let tx = Transaction::decode(&Rlp::new(&hex::decode("02f8b20583623355849502f900849502f91082ea6094326c977e6efc84e512bb9c30f76e30c160ed06fb80b844a9059cbb0000000000000000000000007daccf9b3c1ae2fa5c55f1c978aeef700bc83be0000000000000000000000000000000000000000000000001158e460913d00000c080a0e1445466b058b6f883c0222f1b1f3e2ad9bee7b5f688813d86e3fa8f93aa868ca0786d6e7f3aefa8fe73857c65c32e4884d8ba38d0ecfb947fbffb82e8ee80c167").unwrap())).unwrap();
@@ -127,6 +560,63 @@ async fn test_get_logs() {
     assert!(logs[0].transaction_hash.unwrap() == tx_hash);
 }
 
+#[tokio::test]
+async fn test_get_logs_at_block() {
+    let tx = Transaction::decode(&Rlp::new(&hex::decode("02f8b20583623355849502f900849502f91082ea6094326c977e6efc84e512bb9c30f76e30c160ed06fb80b844a9059cbb0000000000000000000000007daccf9b3c1ae2fa5c55f1c978aeef700bc83be0000000000000000000000000000000000000000000000001158e460913d00000c080a0e1445466b058b6f883c0222f1b1f3e2ad9bee7b5f688813d86e3fa8f93aa868ca0786d6e7f3aefa8fe73857c65c32e4884d8ba38d0ecfb947fbffb82e8ee80c167").unwrap())).unwrap();
+
+    let mut block = Block {
+        number: 7530933.into(),
+        receipts_root: H256::from_str(
+            "dd82a78eccb333854f0c99e5632906e092d8a49c27a21c25cae12b82ec2a113f",
+        )
+        .unwrap(),
+        transactions: Transactions::Full(vec![tx]),
+        ..Default::default()
+    };
+
+    // The block is not tracked by the state.
+    let execution = create_client(create_state());
+    let logs = execution
+        .get_logs_at_block(&Filter::new(), &block)
+        .await
+        .unwrap();
+
+    let tx_hash =
+        H256::from_str("2dac1b27ab58b493f902dda8b63979a112398d747f1761c0891777c0983e591f").unwrap();
+    assert!(!logs.is_empty());
+    assert_eq!(logs[0].transaction_hash, Some(tx_hash));
+
+    let logs = execution
+        .get_logs_at_blocks(&Filter::new(), &[block.clone(), block.clone()])
+        .await
+        .unwrap();
+    assert_eq!(2, logs.len());
+
+    // The RPC must return exactly the logs of the receipts that match the filter.
+    let filter = Filter::new().address(Address::zero());
+    let err = execution
+        .get_logs_at_block(&filter, &block)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ExecutionError::IncompleteLogs(7530933, 0, 1).to_string()
+    );
+
+    // A bloom without the address rules the block out, even with a wrong receipts root.
+    block.receipts_root = H256::default();
+    block.logs_bloom = vec![0; 256].into();
+    let filter = Filter::new().address(Address::zero());
+    let logs = execution
+        .get_logs_at_blocks(&filter, &[block.clone()])
+        .await;
+    assert!(logs.unwrap().is_empty());
+
+    block.logs_bloom = Default::default();
+    let logs_res = execution.get_logs_at_block(&Filter::new(), &block).await;
+    assert!(logs_res.is_err());
+}
+
 #[tokio::test]
 async fn test_get_receipt() {
     let tx = Transaction::decode(&Rlp::new(&hex::decode("02f8b20583623355849502f900849502f91082ea6094326c977e6efc84e512bb9c30f76e30c160ed06fb80b844a9059cbb0000000000000000000000007daccf9b3c1ae2fa5c55f1c978aeef700bc83be0000000000000000000000000000000000000000000000001158e460913d00000c080a0e1445466b058b6f883c0222f1b1f3e2ad9bee7b5f688813d86e3fa8f93aa868ca0786d6e7f3aefa8fe73857c65c32e4884d8ba38d0ecfb947fbffb82e8ee80c167").unwrap())).unwrap();
@@ -240,3 +730,44 @@ async fn test_get_tx_by_block_hash_and_index() {
 
     assert_eq!(tx.hash(), tx_hash);
 }
+
+#[tokio::test]
+async fn test_get_fee_history() {
+    let state = create_state();
+    for (number, gas_used) in [(100u64, 15_000_000u64), (101, 30_000_000), (102, 0)] {
+        let block = Block {
+            number: number.into(),
+            hash: H256::from_low_u64_be(number),
+            base_fee_per_gas: U256::from(1_000_000_000u64),
+            gas_used: gas_used.into(),
+            gas_limit: 30_000_000.into(),
+            ..Default::default()
+        };
+        state.push_block(block).await;
+    }
+    let execution = create_client(state);
+
+    let fee_history = execution
+        .get_fee_history(2, BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(U256::from(101), fee_history.oldest_block);
+    assert_eq!(vec![1.0, 0.0], fee_history.gas_used_ratio);
+    assert_eq!(
+        U256::from(875_000_000u64),
+        *fee_history.base_fee_per_gas.last().unwrap()
+    );
+
+    // The range is shortened to the verified blocks.
+    let fee_history = execution
+        .get_fee_history(10, BlockTag::Number(101))
+        .await
+        .unwrap();
+    assert_eq!(U256::from(100), fee_history.oldest_block);
+    assert_eq!(3, fee_history.base_fee_per_gas.len());
+
+    assert_eq!(
+        U256::from(875_000_000u64),
+        execution.suggest_base_fee().await.unwrap()
+    );
+}
@@ -0,0 +1,53 @@
+/*
+ * C ABI of the helios light client state, see helios-ffi/src/lib.rs.
+ *
+ * Functions returning an int32_t return HELIOS_OK on success and a negative error code otherwise.
+ * helios_last_error returns the message of the last error of the calling thread.
+ *
+ * HELIOS_ERR_PANIC is only returned by libraries built with the release-ffi profile, whose panics
+ * unwind: with the release profile of the workspace, a panic aborts the host process.
+ */
+
+#ifndef HELIOS_H
+#define HELIOS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define HELIOS_OK 0
+#define HELIOS_ERR_INVALID_ARGUMENT -1
+#define HELIOS_ERR_FAILED -2
+#define HELIOS_ERR_PANIC -3
+
+typedef struct HeliosState HeliosState;
+
+/* Bootstraps a state from a trusted hex encoded checkpoint. Returns NULL on failure. */
+HeliosState *helios_state_new(const char *checkpoint, const char *network, const char *consensus_rpc);
+
+void helios_state_free(HeliosState *state);
+
+/* Verifies and applies versioned BCS encoded updates. */
+int32_t helios_verify_updates(HeliosState *state, const uint8_t *updates, size_t len);
+
+/* Fetches the updates since the finalized header. Free the output with helios_bytes_free. */
+int32_t helios_get_updates(HeliosState *state, uint8_t **out, size_t *out_len);
+
+void helios_bytes_free(uint8_t *bytes, size_t len);
+
+/* Writes the 32 bytes of the finalized execution state root to out. */
+int32_t helios_get_finalized_state_root(const HeliosState *state, uint8_t *out);
+
+uint64_t helios_finalized_slot(const HeliosState *state);
+
+/* Owned by the library, valid until the next failing call on the same thread. */
+const char *helios_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HELIOS_H */
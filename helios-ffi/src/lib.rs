@@ -0,0 +1,269 @@
+//! A C ABI around the light client state, to embed it in mobile apps and services that are not
+//! written in Rust. See `include/helios.h` for the declarations.
+//!
+//! Functions that can fail return [`HELIOS_OK`] on success and a negative error code otherwise.
+//! The message of the last error of the calling thread is returned by [`helios_last_error`].
+//! Updates are passed as the versioned BCS encoding of
+//! [`consensus::types::encoding::UpdatesResponse`].
+//!
+//! Build the library with `cargo build -p helios-ffi --profile release-ffi`. The `release` profile
+//! of the workspace aborts on panic, so a panic would abort the host process instead of being
+//! returned as [`HELIOS_ERR_PANIC`].
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+    str::FromStr,
+};
+
+use config::Network;
+use consensus::{
+    rpc::nimbus_rpc::NimbusRpc, types::encoding::UpdatesResponse, ConsensusStateManager,
+};
+use eyre::{eyre, Result};
+use tokio::runtime::{Builder, Runtime};
+
+pub const HELIOS_OK: i32 = 0;
+pub const HELIOS_ERR_INVALID_ARGUMENT: i32 = -1;
+pub const HELIOS_ERR_FAILED: i32 = -2;
+pub const HELIOS_ERR_PANIC: i32 = -3;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, storing its error and converting it and any panic to an error code.
+fn ffi_call(f: impl FnOnce() -> Result<(), (i32, eyre::Report)>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => HELIOS_OK,
+        Ok(Err((code, err))) => {
+            set_last_error(err.to_string());
+            code
+        }
+        Err(_) => {
+            set_last_error("panic in the light client".to_string());
+            HELIOS_ERR_PANIC
+        }
+    }
+}
+
+fn invalid_argument(err: impl std::fmt::Display) -> (i32, eyre::Report) {
+    (HELIOS_ERR_INVALID_ARGUMENT, eyre!("{err}"))
+}
+
+fn failed(err: eyre::Report) -> (i32, eyre::Report) {
+    (HELIOS_ERR_FAILED, err)
+}
+
+unsafe fn read_str<'a>(value: *const c_char) -> Result<&'a str, (i32, eyre::Report)> {
+    if value.is_null() {
+        return Err(invalid_argument("null string"));
+    }
+
+    CStr::from_ptr(value).to_str().map_err(invalid_argument)
+}
+
+/// The light client state, owned by the caller from [`helios_state_new`] until
+/// [`helios_state_free`].
+pub struct HeliosState {
+    inner: ConsensusStateManager<NimbusRpc>,
+    runtime: Runtime,
+}
+
+/// Bootstraps a new state from a trusted hex encoded `checkpoint`, for `network` (e.g.
+/// `mainnet`), with the bootstrap fetched from `consensus_rpc`.
+/// Returns null on failure.
+#[no_mangle]
+pub unsafe extern "C" fn helios_state_new(
+    checkpoint: *const c_char,
+    network: *const c_char,
+    consensus_rpc: *const c_char,
+) -> *mut HeliosState {
+    let mut state = ptr::null_mut();
+
+    ffi_call(|| {
+        let checkpoint = read_str(checkpoint)?;
+        let checkpoint = hex::decode(checkpoint.strip_prefix("0x").unwrap_or(checkpoint))
+            .map_err(invalid_argument)?;
+        let network = Network::from_str(read_str(network)?).map_err(invalid_argument)?;
+        let consensus_rpc = read_str(consensus_rpc)?.to_string();
+
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| failed(err.into()))?;
+        let inner = runtime
+            .block_on(ConsensusStateManager::new_from_checkpoint(
+                checkpoint,
+                network,
+                consensus_rpc,
+            ))
+            .map_err(failed)?;
+
+        state = Box::into_raw(Box::new(HeliosState { inner, runtime }));
+        Ok(())
+    });
+
+    state
+}
+
+/// Frees a state created with [`helios_state_new`]. Passing null is a no-op.
+#[no_mangle]
+pub unsafe extern "C" fn helios_state_free(state: *mut HeliosState) {
+    if !state.is_null() {
+        drop(Box::from_raw(state));
+    }
+}
+
+/// Verifies the encoded `updates` of `len` bytes and applies them to `state`.
+#[no_mangle]
+pub unsafe extern "C" fn helios_verify_updates(
+    state: *mut HeliosState,
+    updates: *const u8,
+    len: usize,
+) -> i32 {
+    ffi_call(|| {
+        let state = state
+            .as_mut()
+            .ok_or_else(|| invalid_argument("null state"))?;
+        if updates.is_null() {
+            return Err(invalid_argument("null updates"));
+        }
+
+        let updates = slice::from_raw_parts(updates, len);
+        let updates = UpdatesResponse::deserialize_from_bytes(updates)
+            .map_err(|err| (HELIOS_ERR_INVALID_ARGUMENT, err))?;
+
+        state
+            .inner
+            .verify_and_apply_initial_updates(&updates.into())
+            .map_err(failed)
+    })
+}
+
+/// Fetches the updates since the finalized header of `state`. On success, `*out` points to
+/// `*out_len` encoded bytes, which must be freed with [`helios_bytes_free`].
+#[no_mangle]
+pub unsafe extern "C" fn helios_get_updates(
+    state: *mut HeliosState,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    ffi_call(|| {
+        let state = state
+            .as_mut()
+            .ok_or_else(|| invalid_argument("null state"))?;
+        if out.is_null() || out_len.is_null() {
+            return Err(invalid_argument("null output"));
+        }
+
+        let updates = state
+            .runtime
+            .block_on(state.inner.get_updates_since_finalized())
+            .map_err(failed)?;
+        let bytes = UpdatesResponse::from(&updates)
+            .serialize_to_bytes()
+            .map_err(failed)?
+            .into_boxed_slice();
+
+        *out_len = bytes.len();
+        *out = Box::into_raw(bytes) as *mut u8;
+        Ok(())
+    })
+}
+
+/// Frees bytes returned by [`helios_get_updates`].
+#[no_mangle]
+pub unsafe extern "C" fn helios_bytes_free(bytes: *mut u8, len: usize) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)));
+    }
+}
+
+/// Writes the execution state root of the finalized block of `state` to the 32 bytes at `out`.
+/// The execution payload is fetched from the consensus RPC, and verified against the finalized
+/// header.
+#[no_mangle]
+pub unsafe extern "C" fn helios_get_finalized_state_root(
+    state: *const HeliosState,
+    out: *mut u8,
+) -> i32 {
+    ffi_call(|| {
+        let state = state
+            .as_ref()
+            .ok_or_else(|| invalid_argument("null state"))?;
+        if out.is_null() {
+            return Err(invalid_argument("null output"));
+        }
+
+        let state_root = state
+            .runtime
+            .block_on(state.inner.get_finalized_state_root())
+            .map_err(failed)?;
+
+        slice::from_raw_parts_mut(out, 32).copy_from_slice(state_root.as_slice());
+        Ok(())
+    })
+}
+
+/// Returns the slot of the finalized header of `state`, or 0 if `state` is null.
+#[no_mangle]
+pub unsafe extern "C" fn helios_finalized_slot(state: *const HeliosState) -> u64 {
+    state
+        .as_ref()
+        .map(|state| state.inner.get_latest_slot().as_u64())
+        .unwrap_or_default()
+}
+
+/// Returns the message of the last error of the calling thread, or null if there was none.
+/// The string is owned by the library, and is valid until the next failing call on the thread.
+#[no_mangle]
+pub extern "C" fn helios_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|message| message.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+
+    #[test]
+    fn test_state_new_invalid_arguments() {
+        let checkpoint = CString::new("0xzz").unwrap();
+        let network = CString::new("mainnet").unwrap();
+        let rpc = CString::new("http://localhost:5052").unwrap();
+
+        let state =
+            unsafe { helios_state_new(checkpoint.as_ptr(), network.as_ptr(), rpc.as_ptr()) };
+        assert!(state.is_null());
+
+        let message = unsafe { CStr::from_ptr(helios_last_error()) };
+        assert!(!message.to_bytes().is_empty());
+
+        let state = unsafe { helios_state_new(ptr::null(), network.as_ptr(), rpc.as_ptr()) };
+        assert!(state.is_null());
+    }
+
+    #[test]
+    fn test_verify_updates_null_state() {
+        let updates = [1u8];
+        let code = unsafe { helios_verify_updates(ptr::null_mut(), updates.as_ptr(), 1) };
+        assert_eq!(HELIOS_ERR_INVALID_ARGUMENT, code);
+        assert_eq!(0, unsafe { helios_finalized_slot(ptr::null()) });
+    }
+}
@@ -0,0 +1,199 @@
+//! Python bindings of the light client, to produce dWallet-ready payloads from Python tooling.
+//!
+//! Every value that is passed to dWallet is exchanged as the versioned BCS encoding of the
+//! `consensus` crate, as Python `bytes`.
+
+use std::str::FromStr;
+
+use config::Network;
+use consensus::{
+    rpc::nimbus_rpc::NimbusRpc,
+    types::{
+        encoding::{decode_versioned, encode_versioned, UpdatesResponse},
+        AggregateUpdates,
+    },
+};
+use ethers::types::{Address, H256};
+use execution::{
+    block::header_hash,
+    rpc::{http_rpc::HttpRpc, ExecutionRpc},
+    types::ProofBundle,
+    verify_account_proof,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+use tokio::runtime::Runtime;
+
+fn to_py_err(err: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn parse_hex(value: &str) -> PyResult<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(to_py_err)
+}
+
+fn parse_network(network: &str) -> PyResult<Network> {
+    Network::from_str(network).map_err(to_py_err)
+}
+
+/// A light client state, advanced with verified updates.
+#[pyclass(name = "ConsensusStateManager")]
+struct PyConsensusStateManager {
+    inner: consensus::ConsensusStateManager<NimbusRpc>,
+    runtime: Runtime,
+}
+
+#[pymethods]
+impl PyConsensusStateManager {
+    /// Bootstraps the state from a trusted `checkpoint`, a hex encoded beacon block root.
+    #[new]
+    fn new(checkpoint: &str, network: &str, consensus_rpc: String) -> PyResult<Self> {
+        let runtime = Runtime::new().map_err(to_py_err)?;
+        let inner = runtime
+            .block_on(consensus::ConsensusStateManager::new_from_checkpoint(
+                parse_hex(checkpoint)?,
+                parse_network(network)?,
+                consensus_rpc,
+            ))
+            .map_err(to_py_err)?;
+
+        Ok(Self { inner, runtime })
+    }
+
+    fn set_rpc(&mut self, consensus_rpc: &str) {
+        self.inner.set_rpc(consensus_rpc);
+    }
+
+    /// Fetches the updates since the finalized header, encoded as a versioned `UpdatesResponse`.
+    fn get_updates<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let updates = self
+            .runtime
+            .block_on(self.inner.get_updates_since_finalized())
+            .map_err(to_py_err)?;
+        let bytes = UpdatesResponse::from(&updates)
+            .serialize_to_bytes()
+            .map_err(to_py_err)?;
+
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Verifies encoded updates and applies them to the state.
+    fn verify_updates(&mut self, updates: &[u8]) -> PyResult<()> {
+        let updates: AggregateUpdates = UpdatesResponse::deserialize_from_bytes(updates)
+            .map_err(to_py_err)?
+            .into();
+
+        self.inner
+            .verify_and_apply_initial_updates(&updates)
+            .map_err(to_py_err)
+    }
+
+    fn finalized_slot(&self) -> u64 {
+        self.inner.get_latest_slot().as_u64()
+    }
+
+    /// Returns the execution state root of the finalized block, as a hex string.
+    fn get_finalized_state_root(&self) -> PyResult<String> {
+        let state_root = self
+            .runtime
+            .block_on(self.inner.get_finalized_state_root())
+            .map_err(to_py_err)?;
+
+        Ok(format!("0x{}", hex::encode(state_root.as_slice())))
+    }
+
+    fn state_digest<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let digest = self.inner.state_digest().map_err(to_py_err)?;
+        Ok(PyBytes::new_bound(py, &digest))
+    }
+}
+
+/// Fetches the proof of `address` and `slots` at `block_number` from `execution_rpc`, verifies it
+/// against `state_root` and returns the versioned BCS encoding of the proof bundle. The block hash
+/// of the bundle is the hash of the header of the block, which must have `state_root`.
+#[pyfunction]
+fn get_proof<'py>(
+    py: Python<'py>,
+    execution_rpc: &str,
+    address: &str,
+    slots: Vec<String>,
+    block_number: u64,
+    state_root: &str,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let address = Address::from_str(address).map_err(to_py_err)?;
+    let state_root = H256::from_str(state_root).map_err(to_py_err)?;
+    let slots = slots
+        .iter()
+        .map(|slot| H256::from_str(slot).map_err(to_py_err))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let rpc = HttpRpc::new(execution_rpc).map_err(to_py_err)?;
+    let runtime = Runtime::new().map_err(to_py_err)?;
+    let (proof, block) = runtime
+        .block_on(async {
+            tokio::try_join!(
+                rpc.get_proof(&address, &slots, block_number),
+                rpc.get_block(block_number)
+            )
+        })
+        .map_err(to_py_err)?;
+
+    verify_account_proof(&address, &slots, &proof, &state_root).map_err(to_py_err)?;
+    if block.state_root != state_root {
+        return Err(to_py_err(format!(
+            "block {block_number} has state root {:?}",
+            block.state_root
+        )));
+    }
+
+    let bundle = ProofBundle {
+        block_number,
+        block_hash: header_hash(&block),
+        state_root,
+        address,
+        slots,
+        proof,
+    };
+    let bytes = encode_versioned(&bundle).map_err(to_py_err)?;
+
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Verifies an encoded proof bundle against its state root.
+#[pyfunction]
+fn verify_proof_bundle(bundle: &[u8]) -> PyResult<()> {
+    let bundle: ProofBundle = decode_versioned(bundle).map_err(to_py_err)?;
+    bundle.verify().map_err(to_py_err)
+}
+
+/// Decodes a versioned `UpdatesResponse` into its JSON representation.
+#[pyfunction]
+fn decode_updates(updates: &[u8]) -> PyResult<String> {
+    let updates = UpdatesResponse::deserialize_from_bytes(updates).map_err(to_py_err)?;
+    serde_json::to_string(&updates).map_err(to_py_err)
+}
+
+/// Encodes the JSON representation of an `UpdatesResponse` as versioned BCS.
+#[pyfunction]
+fn encode_updates<'py>(py: Python<'py>, updates: &str) -> PyResult<Bound<'py, PyBytes>> {
+    let updates: UpdatesResponse = serde_json::from_str(updates).map_err(to_py_err)?;
+    let bytes = updates.serialize_to_bytes().map_err(to_py_err)?;
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Decodes a versioned proof bundle into its JSON representation.
+#[pyfunction]
+fn decode_proof_bundle(bundle: &[u8]) -> PyResult<String> {
+    let bundle: ProofBundle = decode_versioned(bundle).map_err(to_py_err)?;
+    serde_json::to_string(&bundle).map_err(to_py_err)
+}
+
+#[pymodule]
+fn helios_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyConsensusStateManager>()?;
+    m.add_function(wrap_pyfunction!(get_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_proof_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_updates, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_updates, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_proof_bundle, m)?)?;
+    Ok(())
+}
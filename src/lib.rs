@@ -63,7 +63,8 @@
 //! of the dWallet network.
 //!
 //! ### `execution`
-//! Expose the `verify_proof` function to verify the state proof offline.
+//! Expose the `verify_proof` function to verify the state proof offline, and the `proofs::mpt`
+//! module to verify inclusion and exclusion proofs of any Merkle-Patricia trie.
 //!
 //! ### `consensus`
 //! Expose the `ConsensusStateManager` and the RPC types for offline consensus state verification.
@@ -98,9 +99,12 @@ pub mod dwallet {
 }
 
 pub mod execution {
-    pub use execution::{get_message_storage_slot, verify_proof};
+    pub use execution::{get_message_storage_slot, proofs, verify_proof};
 }
 
 pub mod consensus {
-    pub use consensus::{rpc::*, types::*, ConsensusStateManager};
+    pub use consensus::{
+        multi_chain::MultiChainManager, rpc::*, shared::SharedConsensusState, types::*,
+        ConsensusStateManager,
+    };
 }